@@ -57,6 +57,23 @@ impl Chain {
     }
   }
 
+  pub(crate) fn default_postage(self) -> Amount {
+    match self {
+      Self::Mainnet | Self::Testnet => Amount::from_sat(100_000),
+      Self::Regtest | Self::Signet => Amount::from_sat(10_000),
+    }
+  }
+
+  /// Craftcoin nodes refuse to relay outputs below the hard dust limit of
+  /// 0.001 CRAFT, regardless of script type. Regtest and signet keep the
+  /// script-dependent limits so tests can use small values.
+  pub(crate) fn dust_value(self, script_pubkey: &Script) -> Amount {
+    match self {
+      Self::Mainnet | Self::Testnet => Amount::from_sat(100_000),
+      Self::Regtest | Self::Signet => script_pubkey.dust_value(),
+    }
+  }
+
   pub(crate) fn genesis_block(self) -> Block {
     let genesis_hex: &str = match self {
       Self::Mainnet => "010000000000000000000000000000000000000000000000000000000000000000000000696ad20e2dd4365c7459b4a4a5af743d5e92c6da3229e6532cd605f6533f2a5b24a6a152f0ff0f1e678601000101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff1004ffff001d0104084e696e746f6e646fffffffff010058850c020000004341040184710fa689ad5023690c80f3a49c8f13f8d45b8c857fbcbc8bc4a8e4d3eb4b10f4d4604fa08dce601aaf0f470216fe1b51850b4acf21b179c45070ac7b03a9ac00000000",
@@ -96,3 +113,37 @@ impl Display for Chain {
     )
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn mainnet_and_testnet_use_hard_dust_limit() {
+    let script = Script::new_p2pkh(&bitcoin::PubkeyHash::all_zeros());
+
+    assert_eq!(
+      Chain::Mainnet.dust_value(&script),
+      Amount::from_sat(100_000)
+    );
+    assert_eq!(
+      Chain::Testnet.dust_value(&script),
+      Amount::from_sat(100_000)
+    );
+    assert_eq!(Chain::Regtest.dust_value(&script), script.dust_value());
+    assert_eq!(Chain::Signet.dust_value(&script), script.dust_value());
+  }
+
+  #[test]
+  fn default_postage_is_not_below_dust() {
+    let script = Script::new_p2pkh(&bitcoin::PubkeyHash::all_zeros());
+
+    assert_eq!(Chain::Mainnet.default_postage(), Amount::from_sat(100_000));
+    assert_eq!(Chain::Testnet.default_postage(), Amount::from_sat(100_000));
+    assert_eq!(Chain::Regtest.default_postage(), Amount::from_sat(10_000));
+
+    for chain in Chain::value_variants() {
+      assert!(chain.default_postage() >= chain.dust_value(&script));
+    }
+  }
+}
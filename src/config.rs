@@ -3,6 +3,51 @@ use super::*;
 #[derive(Deserialize, Default, PartialEq, Debug)]
 pub(crate) struct Config {
   pub(crate) hidden: HashSet<InscriptionId>,
+  #[serde(default)]
+  pub(crate) api_keys: Vec<ApiKey>,
+  #[serde(default)]
+  pub(crate) rate_limit: RateLimitConfig,
+}
+
+/// A key that clients present in the `X-API-Key` header to be rate limited
+/// under their own budgets, rather than those of their IP address.
+#[derive(Deserialize, PartialEq, Debug)]
+pub(crate) struct ApiKey {
+  pub(crate) name: String,
+  pub(crate) key: String,
+  /// Overrides `rate_limit.keyed` for this key.
+  pub(crate) budgets: Option<Budgets>,
+  /// Allows reading the usage of every key at `/api/usage`.
+  #[serde(default)]
+  pub(crate) admin: bool,
+}
+
+#[derive(Deserialize, Default, PartialEq, Debug)]
+pub(crate) struct RateLimitConfig {
+  /// Budgets of each IP address making requests without an API key.
+  pub(crate) anonymous: Option<Budgets>,
+  /// Budgets of each API key.
+  pub(crate) keyed: Option<Budgets>,
+  /// Take client IP addresses from the `X-Forwarded-For` header, for servers
+  /// running behind a reverse proxy.
+  #[serde(default)]
+  pub(crate) trust_forwarded_for: bool,
+}
+
+/// Separate budgets for ordinary routes and for expensive ones, like block
+/// ranges and address scans.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) struct Budgets {
+  pub(crate) default: Budget,
+  pub(crate) expensive: Budget,
+}
+
+/// A token bucket holding up to `burst` requests, refilled at `per_minute`
+/// requests per minute.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub(crate) struct Budget {
+  pub(crate) burst: u32,
+  pub(crate) per_minute: u32,
 }
 
 impl Config {
@@ -27,9 +72,77 @@ mod tests {
 
     let config = Config {
       hidden: iter::once(a).collect(),
+      ..Default::default()
     };
 
     assert!(config.is_hidden(a));
     assert!(!config.is_hidden(b));
   }
+
+  #[test]
+  fn rate_limits_can_be_configured() {
+    let config = serde_yaml::from_str::<Config>(
+      "
+hidden: []
+api_keys:
+  - name: indexer
+    key: secret
+    budgets:
+      default: { burst: 100, per_minute: 1000 }
+      expensive: { burst: 10, per_minute: 100 }
+    admin: true
+rate_limit:
+  anonymous:
+    default: { burst: 10, per_minute: 60 }
+    expensive: { burst: 1, per_minute: 6 }
+  trust_forwarded_for: true
+",
+    )
+    .unwrap();
+
+    assert_eq!(
+      config.api_keys,
+      [ApiKey {
+        name: "indexer".into(),
+        key: "secret".into(),
+        budgets: Some(Budgets {
+          default: Budget {
+            burst: 100,
+            per_minute: 1000,
+          },
+          expensive: Budget {
+            burst: 10,
+            per_minute: 100,
+          },
+        }),
+        admin: true,
+      }]
+    );
+
+    assert_eq!(
+      config.rate_limit,
+      RateLimitConfig {
+        anonymous: Some(Budgets {
+          default: Budget {
+            burst: 10,
+            per_minute: 60,
+          },
+          expensive: Budget {
+            burst: 1,
+            per_minute: 6,
+          },
+        }),
+        keyed: None,
+        trust_forwarded_for: true,
+      }
+    );
+  }
+
+  #[test]
+  fn rate_limits_are_optional() {
+    assert_eq!(
+      serde_yaml::from_str::<Config>("hidden: []").unwrap(),
+      Config::default()
+    );
+  }
 }
@@ -1,13 +1,49 @@
 use super::*;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, PartialOrd, Clone, Copy)]
 pub struct FeeRate(f64);
 
 impl FromStr for FeeRate {
   type Err = Error;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
-    Self::try_from(f64::from_str(s)?)
+    lazy_static! {
+      static ref RATE: Regex = Regex::new(
+        r"(?xi)
+        ^
+        (
+          \d+
+          |
+          \.\d+
+          |
+          \d+\.\d+
+        )
+        \ *
+        (sat/vb|sats/vb|sat/kb|sats/kb|craft/kb)?
+        $
+        "
+      )
+      .unwrap();
+    }
+
+    let captures = RATE
+      .captures(s)
+      .ok_or_else(|| anyhow!("invalid fee rate: {s}"))?;
+
+    let rate = f64::from_str(&captures[1])?;
+
+    Self::try_from(
+      match captures
+        .get(2)
+        .map(|unit| unit.as_str().to_lowercase())
+        .as_deref()
+      {
+        None | Some("sat/vb" | "sats/vb") => rate,
+        Some("sat/kb" | "sats/kb") => rate / 1000.0,
+        Some("craft/kb") => Self::from_amount_per_kb(Amount::from_btc(rate)?).0,
+        Some(unit) => unreachable!("unexpected fee rate unit {unit}"),
+      },
+    )
   }
 }
 
@@ -23,6 +59,16 @@ impl TryFrom<f64> for FeeRate {
 }
 
 impl FeeRate {
+  /// No fee, for parts of a transaction whose fee is paid separately.
+  pub(crate) const ZERO: Self = Self(0.0);
+
+  /// Convert a per-kB rate, the unit used by the node's fee estimation and
+  /// relay policy, to sats/vB.
+  #[allow(clippy::cast_precision_loss)]
+  pub(crate) fn from_amount_per_kb(amount: Amount) -> Self {
+    Self(amount.to_sat() as f64 / 1000.0)
+  }
+
   pub(crate) fn fee(&self, vsize: usize) -> Amount {
     #[allow(clippy::cast_possible_truncation)]
     #[allow(clippy::cast_sign_loss)]
@@ -34,6 +80,98 @@ impl FeeRate {
   }
 }
 
+/// A fee rate given on the command line, either fixed or estimated by the
+/// node for confirmation within a number of blocks.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum FeeRateSource {
+  Auto(u16),
+  Fixed(FeeRate),
+}
+
+impl FromStr for FeeRateSource {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s == "auto" {
+      return Ok(Self::Auto(Self::DEFAULT_CONFIRMATION_TARGET));
+    }
+
+    if let Some(blocks) = s.strip_prefix("auto:") {
+      let blocks = blocks.parse::<u16>()?;
+      ensure!(blocks > 0, "confirmation target must be at least one block");
+      return Ok(Self::Auto(blocks));
+    }
+
+    Ok(Self::Fixed(s.parse()?))
+  }
+}
+
+impl FeeRateSource {
+  const DEFAULT_CONFIRMATION_TARGET: u16 = 6;
+
+  /// Resolve to a fee rate that the node will relay. Explicit rates below the
+  /// node's minimum relay fee rate are an error, while estimates are raised to
+  /// it, and the minimum is used if the node has no estimate.
+  pub(crate) fn resolve(self, client: &Client) -> Result<FeeRate> {
+    let minimum = Self::minimum(client)?;
+
+    match self {
+      Self::Fixed(fee_rate) => {
+        ensure!(
+          fee_rate >= minimum,
+          "fee rate of {} sats/vB is below the node's minimum relay fee rate of {} sats/vB",
+          fee_rate.n(),
+          minimum.n(),
+        );
+        Ok(fee_rate)
+      }
+      Self::Auto(blocks) => Ok(match Self::estimate(client, blocks)? {
+        Some(estimate) if estimate > minimum => estimate,
+        _ => minimum,
+      }),
+    }
+  }
+
+  /// The fee rate used by commands when none is given, 1 sat/vB, or the
+  /// node's minimum relay fee rate if that is higher.
+  pub(crate) fn resolve_default(client: &Client) -> Result<FeeRate> {
+    let minimum = Self::minimum(client)?;
+    let default = FeeRate(1.0);
+
+    Ok(if minimum > default { minimum } else { default })
+  }
+
+  fn minimum(client: &Client) -> Result<FeeRate> {
+    Ok(FeeRate::from_amount_per_kb(
+      client.get_network_info()?.relay_fee,
+    ))
+  }
+
+  fn estimate(client: &Client, blocks: u16) -> Result<Option<FeeRate>> {
+    // Older nodes only support `estimatefee`, and `estimatesmartfee` may also
+    // fail on nodes that have it, so either way fall back to `estimatefee`
+    let smart_estimate =
+      match client.call::<serde_json::Value>("estimatesmartfee", &[blocks.into()]) {
+        Ok(estimate) => estimate.get("feerate").and_then(serde_json::Value::as_f64),
+        Err(err) => {
+          log::debug!("estimatesmartfee failed, falling back to estimatefee: {err}");
+          None
+        }
+      };
+
+    let rate = match smart_estimate {
+      Some(rate) => rate,
+      None => client.call::<f64>("estimatefee", &[blocks.into()])?,
+    };
+
+    if rate <= 0.0 {
+      return Ok(None);
+    }
+
+    Ok(Some(FeeRate::from_amount_per_kb(Amount::from_btc(rate)?)))
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -48,6 +186,35 @@ mod tests {
     assert!(FeeRate::try_from(f64::NAN).is_err());
   }
 
+  #[test]
+  fn parse_units() {
+    assert_eq!("2 sat/vB".parse::<FeeRate>().unwrap().0, 2.0);
+    assert_eq!("2sats/vb".parse::<FeeRate>().unwrap().0, 2.0);
+    assert_eq!("1500 sat/kB".parse::<FeeRate>().unwrap().0, 1.5);
+    assert_eq!("0.01 CRAFT/kB".parse::<FeeRate>().unwrap().0, 1000.0);
+    assert_eq!("0.00001craft/kb".parse::<FeeRate>().unwrap().0, 1.0);
+    assert!("1 btc/kB".parse::<FeeRate>().is_err());
+    assert!("sat/vB".parse::<FeeRate>().is_err());
+  }
+
+  #[test]
+  fn parse_source() {
+    assert_eq!(
+      "auto".parse::<FeeRateSource>().unwrap(),
+      FeeRateSource::Auto(6)
+    );
+    assert_eq!(
+      "auto:2".parse::<FeeRateSource>().unwrap(),
+      FeeRateSource::Auto(2)
+    );
+    assert_eq!(
+      "1.5".parse::<FeeRateSource>().unwrap(),
+      FeeRateSource::Fixed(FeeRate(1.5))
+    );
+    assert!("auto:0".parse::<FeeRateSource>().is_err());
+    assert!("auto:".parse::<FeeRateSource>().is_err());
+  }
+
   #[test]
   fn fee() {
     assert_eq!(
@@ -0,0 +1,217 @@
+use super::*;
+
+/// Words longer than this are not indexed, which keeps long runs of hex, base64
+/// and the like out of the index.
+pub(crate) const MAX_WORD_LENGTH: usize = 40;
+
+/// Terms shorter than this are neither indexed nor searched for.
+pub(crate) const MIN_TERM_LENGTH: usize = 2;
+
+/// Terms so common that their postings would cover most text inscriptions,
+/// like English function words and the keys of CRC-20 operations, which are
+/// neither indexed nor searched for.
+const STOP_WORDS: &[&str] = &[
+  "amt", "an", "and", "are", "as", "at", "be", "by", "crc", "for", "from", "in", "is", "it", "of",
+  "on", "op", "or", "that", "the", "this", "tick", "to", "was", "with",
+];
+
+const SNIPPET_CONTEXT: usize = 60;
+
+/// Whether inscriptions with `content_type` are added to the full-text index.
+pub(crate) fn is_indexable(content_type: &str) -> bool {
+  content_type.starts_with("text/") || content_type.starts_with("application/json")
+}
+
+/// Whether `content_type` satisfies a search's content type `filter`, which
+/// is either a full content type, a content type without parameters like
+/// `text/plain`, or a wildcard like `text/*`.
+pub(crate) fn content_type_matches(content_type: &str, filter: &str) -> bool {
+  match filter.strip_suffix("/*") {
+    Some(top_level) => content_type
+      .strip_prefix(top_level)
+      .map_or(false, |rest| rest.starts_with('/')),
+    None => {
+      content_type == filter || content_type.split(';').next().unwrap_or_default().trim() == filter
+    }
+  }
+}
+
+/// Indexable words in `text` with their byte offsets. Words are runs of
+/// alphanumeric characters.
+pub(crate) fn words(text: &str) -> Vec<(usize, &str)> {
+  let mut words = Vec::new();
+  let mut start = None;
+
+  for (i, c) in text
+    .char_indices()
+    .chain(std::iter::once((text.len(), ' ')))
+  {
+    match (start, c.is_alphanumeric()) {
+      (None, true) => start = Some(i),
+      (Some(s), false) => {
+        let word = &text[s..i];
+        if word.chars().count() <= MAX_WORD_LENGTH {
+          words.push((s, word));
+        }
+        start = None;
+      }
+      _ => {}
+    }
+  }
+
+  words
+}
+
+/// The distinct, lowercased words of `text`, as stored in the index, without
+/// short terms and stop words.
+pub(crate) fn terms(text: &str) -> BTreeSet<String> {
+  words(text)
+    .into_iter()
+    .map(|(_offset, word)| word.to_lowercase())
+    .filter(|term| term.chars().count() >= MIN_TERM_LENGTH)
+    .filter(|term| STOP_WORDS.binary_search(&term.as_str()).is_err())
+    .collect()
+}
+
+/// A single line excerpt of `text` around the first occurrence of any of
+/// `terms`.
+pub(crate) fn snippet(text: &str, terms: &BTreeSet<String>) -> String {
+  let (offset, len) = words(text)
+    .into_iter()
+    .find(|(_offset, word)| terms.contains(&word.to_lowercase()))
+    .map(|(offset, word)| (offset, word.len()))
+    .unwrap_or_default();
+
+  let mut start = offset.saturating_sub(SNIPPET_CONTEXT);
+  while !text.is_char_boundary(start) {
+    start += 1;
+  }
+
+  let mut end = (offset + len + SNIPPET_CONTEXT).min(text.len());
+  while !text.is_char_boundary(end) {
+    end += 1;
+  }
+
+  let mut snippet = text[start..end]
+    .split_whitespace()
+    .collect::<Vec<&str>>()
+    .join(" ");
+
+  if start > 0 {
+    snippet.insert(0, '…');
+  }
+
+  if end < text.len() {
+    snippet.push('…');
+  }
+
+  snippet
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn indexable_content_types() {
+    assert!(is_indexable("text/plain"));
+    assert!(is_indexable("text/html;charset=utf-8"));
+    assert!(is_indexable("application/json"));
+    assert!(!is_indexable("image/png"));
+    assert!(!is_indexable("application/pdf"));
+  }
+
+  #[test]
+  fn content_type_filters() {
+    assert!(content_type_matches("text/plain", "text/plain"));
+    assert!(content_type_matches(
+      "text/plain;charset=utf-8",
+      "text/plain"
+    ));
+    assert!(content_type_matches(
+      "text/plain;charset=utf-8",
+      "text/plain;charset=utf-8"
+    ));
+    assert!(content_type_matches("text/html", "text/*"));
+    assert!(!content_type_matches("text/html", "text/plain"));
+    assert!(!content_type_matches("application/json", "text/*"));
+    assert!(!content_type_matches("textual/plain", "text/*"));
+  }
+
+  #[test]
+  fn words_are_runs_of_alphanumeric_characters() {
+    assert_eq!(
+      words("Hello, wörld! {\"p\":\"crc-20\"}"),
+      [
+        (0, "Hello"),
+        (7, "wörld"),
+        (17, "p"),
+        (21, "crc"),
+        (25, "20")
+      ]
+    );
+  }
+
+  #[test]
+  fn long_words_are_skipped() {
+    assert_eq!(words(&"a".repeat(MAX_WORD_LENGTH)).len(), 1);
+    assert!(words(&"a".repeat(MAX_WORD_LENGTH + 1)).is_empty());
+  }
+
+  #[test]
+  fn terms_are_lowercased_and_deduplicated() {
+    assert_eq!(
+      terms("Foo foo FOO bar"),
+      ["bar".to_string(), "foo".to_string()].into()
+    );
+  }
+
+  #[test]
+  fn short_terms_and_stop_words_are_dropped() {
+    assert_eq!(
+      terms("A needle in the haystack"),
+      ["haystack".to_string(), "needle".to_string()].into()
+    );
+
+    assert_eq!(
+      terms(r#"{"p":"crc-20","op":"mint","tick":"doge","amt":"1000"}"#),
+      [
+        "1000".to_string(),
+        "20".to_string(),
+        "doge".to_string(),
+        "mint".to_string()
+      ]
+      .into()
+    );
+  }
+
+  #[test]
+  fn stop_words_are_sorted() {
+    assert!(STOP_WORDS.windows(2).all(|pair| pair[0] < pair[1]));
+  }
+
+  #[test]
+  fn snippets_surround_first_match() {
+    let text = format!("{} needle\n{}", "a ".repeat(50), "b ".repeat(50));
+
+    let snippet = snippet(&text, &terms("NEEDLE"));
+
+    assert!(snippet.starts_with('…'));
+    assert!(snippet.ends_with('…'));
+    assert!(snippet.contains("a needle b"));
+  }
+
+  #[test]
+  fn short_texts_are_not_truncated() {
+    assert_eq!(snippet("hello world", &terms("world")), "hello world");
+  }
+
+  #[test]
+  fn snippets_respect_char_boundaries() {
+    let text = format!("x{}needle", "€".repeat(30));
+    assert!(snippet(&text, &terms("needle")).ends_with("needle"));
+
+    let text = format!("needle {}", "€".repeat(30));
+    assert!(snippet(&text, &terms("needle")).starts_with("needle"));
+  }
+}
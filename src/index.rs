@@ -3,7 +3,8 @@ use {
     cunes::{Cune, CuneId},
     entry::{
       BlockHashValue, CuneEntryValue, CuneIdValue, Entry, InscriptionEntry, InscriptionEntryValue,
-      InscriptionIdValue, OutPointMapValue, OutPointValue, SatPointValue, SatRange, TxidValue,
+      InscriptionIdValue, InscriptionLocation, InscriptionMove, OutPointMapValue, OutPointValue,
+      SatPointValue, SatRange, TxidValue,
     },
     reorg::*,
     updater::Updater,
@@ -11,25 +12,25 @@ use {
   super::*,
   crate::inscription::ParsedInscription,
   crate::wallet::Wallet,
-  bitcoin::BlockHeader,
+  bitcoin::{hashes::sha256, BlockHeader},
   bitcoincore_rpc::{json::GetBlockHeaderResult, Auth, Client},
   chrono::SubsecRound,
   indicatif::{ProgressBar, ProgressStyle},
   log::log_enabled,
   redb::{
     Database, DatabaseError, MultimapTable, MultimapTableDefinition, ReadableMultimapTable,
-    ReadableTable, StorageError, Table, TableDefinition, WriteTransaction,
+    ReadTransaction, ReadableTable, StorageError, Table, TableDefinition, WriteTransaction,
   },
   std::collections::HashMap,
   std::io::Cursor,
-  std::sync::atomic::{self, AtomicBool},
+  std::sync::atomic::{self, AtomicBool, AtomicU64},
   url::Url,
 };
 
 use crate::crc20::script_key::ScriptKey;
 use crate::crc20::{
   max_script_tick_id_key, max_script_tick_key, min_script_tick_id_key, min_script_tick_key,
-  script_tick_key, Balance, Tick, TokenInfo, TransferableLog,
+  script_tick_key, Balance, Receipt, Tick, TokenInfo, TransferableLog,
 };
 use crate::sat::Sat;
 use crate::sat_point::SatPoint;
@@ -37,13 +38,20 @@ use crate::templates::BlockHashAndConfirmations;
 
 pub(crate) use self::entry::CuneEntry;
 
+mod batch;
 pub(crate) mod entry;
 mod fetcher;
 mod reorg;
 mod rtx;
 mod updater;
 
-const SCHEMA_VERSION: u64 = 6;
+const SCHEMA_VERSION: u64 = 14;
+
+/// Postings read per term by `Index::search_text`.
+const MAX_TEXT_SEARCH_CANDIDATES: usize = 10_000;
+
+/// Inscriptions examined per query by `Index::search_inscriptions`.
+const MAX_SEARCH_CANDIDATES: usize = 100_000;
 
 macro_rules! define_table {
   ($name:ident, $key:ty, $value:ty) => {
@@ -58,10 +66,17 @@ macro_rules! define_multimap_table {
   };
 }
 
+define_multimap_table! { CONTENT_HASH_TO_INSCRIPTION_NUMBER, &[u8; 32], u64 }
+define_multimap_table! { CONTENT_TYPE_TO_INSCRIPTION_NUMBER, &str, u64 }
 define_table! { HEIGHT_TO_BLOCK_HASH, u32, &BlockHashValue }
+define_multimap_table! { HEIGHT_TO_INSCRIPTION_NUMBER, u32, u64 }
 define_table! { INSCRIPTION_ID_TO_INSCRIPTION_ENTRY, &InscriptionIdValue, InscriptionEntryValue }
 define_table! { INSCRIPTION_ID_TO_CUNE, &InscriptionIdValue, u128 }
+define_table! { INSCRIPTION_ID_TO_LOCATIONS, (&InscriptionIdValue, u32), &[u8] }
 define_table! { INSCRIPTION_ID_TO_SATPOINT, &InscriptionIdValue, &SatPointValue }
+define_table! { INSCRIPTION_NUMBER_TO_CONTENT_LENGTH, u64, u64 }
+define_table! { INSCRIPTION_NUMBER_TO_CONTENT_TYPE, u64, &str }
+define_table! { INSCRIPTION_NUMBER_TO_CRC20_OPERATION, u64, &str }
 define_table! { INSCRIPTION_NUMBER_TO_INSCRIPTION_ID, u64, &InscriptionIdValue }
 define_table! { OUTPOINT_TO_CUNE_BALANCES, &OutPointValue, &[u8] }
 define_table! { INSCRIPTION_ID_TO_TXIDS, &InscriptionIdValue, &[u8] }
@@ -72,17 +87,22 @@ define_table! { OUTPOINT_TO_VALUE, &OutPointValue, u64}
 define_multimap_table! { ADDRESS_TO_OUTPOINT, &[u8], &OutPointValue}
 define_table! { CUNE_ID_TO_CUNE_ENTRY, CuneIdValue, CuneEntryValue }
 define_table! { CUNE_TO_CUNE_ID, u128, CuneIdValue }
+define_multimap_table! { RARITY_TO_INSCRIPTION_NUMBER, u8, u64 }
 define_table! { SATPOINT_TO_INSCRIPTION_ID, &SatPointValue, &InscriptionIdValue }
+define_table! { SPENT_OUTPOINT_TO_CUNE_BALANCES, &OutPointValue, &[u8] }
 define_table! { SAT_TO_INSCRIPTION_ID, u64, &InscriptionIdValue }
 define_table! { SAT_TO_SATPOINT, u64, &SatPointValue }
 define_table! { STATISTIC_TO_COUNT, u64, u64 }
 define_table! { TRANSACTION_ID_TO_CUNE, &TxidValue, u128 }
+define_table! { TRANSACTION_ID_TO_INSCRIPTION_MOVES, &TxidValue, &[u8] }
 define_table! { TRANSACTION_ID_TO_TRANSACTION, &TxidValue, &[u8] }
+define_multimap_table! { WORD_TO_INSCRIPTION_NUMBER, &str, u64 }
 define_table! { WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP, u32, u128 }
 define_table! { CRC20_BALANCES, &str, &[u8] }
 define_table! { CRC20_TOKEN, &str, &[u8] }
 define_table! { CRC20_INSCRIBE_TRANSFER, &InscriptionIdValue, &[u8] }
 define_table! { CRC20_TRANSFERABLELOG, &str, &[u8] }
+define_table! { CRC20_TRANSACTION_ID_TO_RECEIPTS, &TxidValue, &[u8] }
 define_multimap_table! { CRC20_TOKEN_HOLDER, &str, &str}
 
 pub(crate) struct Index {
@@ -97,9 +117,15 @@ pub(crate) struct Index {
   height_limit: Option<u32>,
   index_crc20: bool,
   index_cunes: bool,
+  index_history: bool,
+  index_locations: bool,
   index_sats: bool,
+  index_search: bool,
+  index_text: bool,
   index_transactions: bool,
   unrecoverably_reorged: AtomicBool,
+  commits: AtomicU64,
+  reorgs: AtomicU64,
   rpc_url: String,
   nr_parallel_requests: usize,
   chain: Chain,
@@ -125,6 +151,10 @@ pub(crate) enum Statistic {
   SatRanges,
   Schema,
   IndexTransactions,
+  IndexText,
+  IndexSearch,
+  IndexHistory,
+  IndexLocations,
 }
 
 impl Statistic {
@@ -163,6 +193,22 @@ pub(crate) struct TransactionInfo {
   pub(crate) starting_timestamp: u128,
 }
 
+/// Filters for `Index::search_inscriptions`. Each filter which is set narrows
+/// the results, and ranges are inclusive.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct InscriptionSearch {
+  pub(crate) address: Option<Address>,
+  pub(crate) content_length: Option<(u64, u64)>,
+  pub(crate) content_type: Option<String>,
+  pub(crate) crc20: Option<bool>,
+  pub(crate) from: Option<u64>,
+  pub(crate) height: Option<(u32, u32)>,
+  pub(crate) limit: usize,
+  pub(crate) media: Option<Media>,
+  pub(crate) rarity: Option<Rarity>,
+  pub(crate) timestamp: Option<(u32, u32)>,
+}
+
 trait BitcoinCoreRpcResultExt<T> {
   fn into_option(self) -> Result<Option<T>>;
 }
@@ -227,7 +273,11 @@ impl Index {
 
     let index_crc20;
     let index_cunes;
+    let index_history;
+    let index_locations;
     let index_sats;
+    let index_search;
+    let index_text;
     let index_transactions;
 
     let database = match unsafe { Database::builder().open(&path) } {
@@ -261,11 +311,31 @@ impl Index {
             .unwrap()
             .value()
             != 0;
+          index_history = statistics
+            .get(&Statistic::IndexHistory.key())?
+            .unwrap()
+            .value()
+            != 0;
+          index_locations = statistics
+            .get(&Statistic::IndexLocations.key())?
+            .unwrap()
+            .value()
+            != 0;
           index_sats = statistics
             .get(&Statistic::IndexSats.key())?
             .unwrap()
             .value()
             != 0;
+          index_search = statistics
+            .get(&Statistic::IndexSearch.key())?
+            .unwrap()
+            .value()
+            != 0;
+          index_text = statistics
+            .get(&Statistic::IndexText.key())?
+            .unwrap()
+            .value()
+            != 0;
           index_transactions = statistics
             .get(&Statistic::IndexTransactions.key())?
             .unwrap()
@@ -305,19 +375,29 @@ impl Index {
           tx
         };
 
+        tx.open_multimap_table(CONTENT_HASH_TO_INSCRIPTION_NUMBER)?;
+        tx.open_multimap_table(CONTENT_TYPE_TO_INSCRIPTION_NUMBER)?;
         tx.open_table(HEIGHT_TO_BLOCK_HASH)?;
+        tx.open_multimap_table(HEIGHT_TO_INSCRIPTION_NUMBER)?;
         tx.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?;
         tx.open_table(INSCRIPTION_ID_TO_CUNE)?;
+        tx.open_table(INSCRIPTION_ID_TO_LOCATIONS)?;
         tx.open_table(INSCRIPTION_ID_TO_SATPOINT)?;
+        tx.open_table(INSCRIPTION_NUMBER_TO_CONTENT_LENGTH)?;
+        tx.open_table(INSCRIPTION_NUMBER_TO_CONTENT_TYPE)?;
+        tx.open_table(INSCRIPTION_NUMBER_TO_CRC20_OPERATION)?;
         tx.open_table(INSCRIPTION_NUMBER_TO_INSCRIPTION_ID)?;
         tx.open_table(INSCRIPTION_ID_TO_TXIDS)?;
         tx.open_table(INSCRIPTION_TXID_TO_TX)?;
         tx.open_table(PARTIAL_TXID_TO_INSCRIPTION_TXIDS)?;
         tx.open_table(OUTPOINT_TO_VALUE)?;
         tx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+        tx.open_multimap_table(RARITY_TO_INSCRIPTION_NUMBER)?;
         tx.open_table(SATPOINT_TO_INSCRIPTION_ID)?;
         tx.open_table(SAT_TO_INSCRIPTION_ID)?;
         tx.open_table(SAT_TO_SATPOINT)?;
+        tx.open_table(TRANSACTION_ID_TO_INSCRIPTION_MOVES)?;
+        tx.open_multimap_table(WORD_TO_INSCRIPTION_NUMBER)?;
         tx.open_table(WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP)?;
 
         {
@@ -330,15 +410,30 @@ impl Index {
 
           index_crc20 = options.index_cunes();
           index_cunes = options.index_cunes();
+          index_history = options.index_history;
+          index_locations = options.index_locations;
           index_sats = options.index_sats;
+          index_search = options.index_search;
+          index_text = options.index_text;
           index_transactions = options.index_transactions;
 
           statistics.insert(&Statistic::IndexCrc20.key(), &u64::from(index_crc20))?;
 
           statistics.insert(&Statistic::IndexCunes.key(), &u64::from(index_cunes))?;
 
+          statistics.insert(&Statistic::IndexHistory.key(), &u64::from(index_history))?;
+
+          statistics.insert(
+            &Statistic::IndexLocations.key(),
+            &u64::from(index_locations),
+          )?;
+
           statistics.insert(&Statistic::IndexSats.key(), &u64::from(index_sats))?;
 
+          statistics.insert(&Statistic::IndexSearch.key(), &u64::from(index_search))?;
+
+          statistics.insert(&Statistic::IndexText.key(), &u64::from(index_text))?;
+
           statistics.insert(
             &Statistic::IndexTransactions.key(),
             &u64::from(index_transactions),
@@ -369,16 +464,26 @@ impl Index {
       height_limit: options.height_limit,
       index_crc20,
       index_cunes,
+      index_history,
+      index_locations,
       index_sats,
+      index_search,
+      index_text,
       index_transactions,
       unrecoverably_reorged: AtomicBool::new(false),
+      commits: AtomicU64::new(0),
+      reorgs: AtomicU64::new(0),
       rpc_url,
       nr_parallel_requests,
       chain: options.chain_argument,
     })
   }
 
-  pub(crate) fn get_unspent_outputs(&self, _wallet: Wallet) -> Result<BTreeMap<OutPoint, Amount>> {
+  pub(crate) fn get_unspent_outputs(&self, wallet: Wallet) -> Result<BTreeMap<OutPoint, Amount>> {
+    if let Some(addresses) = wallet.watch_only() {
+      return self.get_watch_only_outputs(addresses);
+    }
+
     let mut utxos = BTreeMap::new();
     utxos.extend(
       self
@@ -421,6 +526,32 @@ impl Index {
     Ok(utxos)
   }
 
+  /// Unspent outputs paying to `addresses`, taken from the address index. The
+  /// address index only drops spent outputs when transactions are indexed, so
+  /// outputs are also checked against the outpoint index.
+  pub(crate) fn get_watch_only_outputs(
+    &self,
+    addresses: &BTreeSet<Address>,
+  ) -> Result<BTreeMap<OutPoint, Amount>> {
+    let rtx = self.database.begin_read()?;
+    let address_to_outpoint = rtx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+    let outpoint_to_value = rtx.open_table(OUTPOINT_TO_VALUE)?;
+
+    let mut utxos = BTreeMap::new();
+
+    for address in addresses {
+      for outpoint in address_to_outpoint.get(address.to_string().as_bytes())? {
+        let outpoint = OutPoint::load(*outpoint?.value());
+
+        if let Some(value) = outpoint_to_value.get(&outpoint.store())? {
+          utxos.insert(outpoint, Amount::from_sat(value.value()));
+        }
+      }
+    }
+
+    Ok(utxos)
+  }
+
   pub(crate) fn get_unspent_output_ranges(
     &self,
     wallet: Wallet,
@@ -440,10 +571,30 @@ impl Index {
     self.index_cunes
   }
 
+  pub(crate) fn has_history_index(&self) -> bool {
+    self.index_history
+  }
+
+  pub(crate) fn has_locations_index(&self) -> bool {
+    self.index_locations
+  }
+
   pub(crate) fn has_sat_index(&self) -> bool {
     self.index_sats
   }
 
+  pub(crate) fn has_search_index(&self) -> bool {
+    self.index_search
+  }
+
+  pub(crate) fn has_text_index(&self) -> bool {
+    self.index_text
+  }
+
+  pub(crate) fn has_transaction_index(&self) -> bool {
+    self.index_transactions
+  }
+
   pub(crate) fn info(&self) -> Result<Info> {
     let wtx = self.begin_write()?;
 
@@ -531,6 +682,19 @@ impl Index {
     self.unrecoverably_reorged.load(atomic::Ordering::Relaxed)
   }
 
+  /// Number of updater commits and reorg rollbacks made by this process. Any
+  /// change to the index bumps it, so data read at one generation stays valid
+  /// until the next.
+  pub(crate) fn commit_generation(&self) -> u64 {
+    self.commits.load(atomic::Ordering::Acquire)
+  }
+
+  /// Number of reorg rollbacks made by this process. Data that only changes
+  /// when blocks are rolled back stays valid until this is bumped.
+  pub(crate) fn reorg_generation(&self) -> u64 {
+    self.reorgs.load(atomic::Ordering::Acquire)
+  }
+
   fn begin_read(&self) -> Result<rtx::Rtx> {
     Ok(rtx::Rtx(self.database.begin_read()?))
   }
@@ -714,35 +878,70 @@ impl Index {
         return Ok(Vec::new());
       };
 
-      let balances_buffer = balances.value();
+      Self::load_cune_balances(&id_to_cune_entries, balances.value())
+    } else {
+      Ok(Vec::new())
+    }
+  }
 
-      let mut balances = Vec::new();
-      let mut i = 0;
-      while i < balances_buffer.len() {
-        let (id, length) = cunes::varint::decode(&balances_buffer[i..]);
-        i += length;
-        let (amount, length) = cunes::varint::decode(&balances_buffer[i..]);
-        i += length;
+  /// Cune balances of `outpoint` as of when it was created, including
+  /// outputs which have since been spent.
+  pub(crate) fn get_historical_cune_balances_for_outpoint(
+    &self,
+    outpoint: OutPoint,
+  ) -> Result<Vec<(SpacedCune, Pile)>> {
+    if self.block_count()? >= self.first_cune_height && self.index_cunes {
+      let rtx = &self.database.begin_read()?;
 
-        let id = CuneId::try_from(id).unwrap();
+      let id_to_cune_entries = rtx.open_table(CUNE_ID_TO_CUNE_ENTRY)?;
 
-        let entry = CuneEntry::load(id_to_cune_entries.get(id.store())?.unwrap().value());
+      if let Some(balances) = rtx
+        .open_table(OUTPOINT_TO_CUNE_BALANCES)?
+        .get(&outpoint.store())?
+      {
+        return Self::load_cune_balances(&id_to_cune_entries, balances.value());
+      }
 
-        balances.push((
-          entry.spaced_cune(),
-          Pile {
-            amount,
-            divisibility: entry.divisibility,
-            symbol: entry.symbol,
-          },
-        ));
+      match rtx
+        .open_table(SPENT_OUTPOINT_TO_CUNE_BALANCES)?
+        .get(&outpoint.store())?
+      {
+        Some(balances) => Self::load_cune_balances(&id_to_cune_entries, balances.value()),
+        None => Ok(Vec::new()),
       }
-      Ok(balances)
     } else {
       Ok(Vec::new())
     }
   }
 
+  fn load_cune_balances(
+    id_to_cune_entries: &impl ReadableTable<CuneIdValue, CuneEntryValue>,
+    buffer: &[u8],
+  ) -> Result<Vec<(SpacedCune, Pile)>> {
+    let mut balances = Vec::new();
+    let mut i = 0;
+    while i < buffer.len() {
+      let (id, length) = cunes::varint::decode(&buffer[i..]);
+      i += length;
+      let (amount, length) = cunes::varint::decode(&buffer[i..]);
+      i += length;
+
+      let id = CuneId::try_from(id).unwrap();
+
+      let entry = CuneEntry::load(id_to_cune_entries.get(id.store())?.unwrap().value());
+
+      balances.push((
+        entry.spaced_cune(),
+        Pile {
+          amount,
+          divisibility: entry.divisibility,
+          symbol: entry.symbol,
+        },
+      ));
+    }
+    Ok(balances)
+  }
+
   pub(crate) fn get_dunic_outputs(&self, outpoints: &[OutPoint]) -> Result<BTreeSet<OutPoint>> {
     if self.block_count()? >= self.first_cune_height && self.index_cunes {
       let rtx = self.database.begin_read()?;
@@ -763,6 +962,41 @@ impl Index {
     }
   }
 
+  pub(crate) fn get_cune_balances_for_outpoints(
+    &self,
+    outpoints: &[OutPoint],
+  ) -> Result<BTreeMap<OutPoint, BTreeMap<CuneId, u128>>> {
+    let mut result = BTreeMap::new();
+
+    if self.block_count()? >= self.first_cune_height && self.index_cunes {
+      let rtx = self.database.begin_read()?;
+
+      let outpoint_to_balances = rtx.open_table(OUTPOINT_TO_CUNE_BALANCES)?;
+
+      for outpoint in outpoints {
+        let Some(balances) = outpoint_to_balances.get(&outpoint.store())? else {
+          continue;
+        };
+
+        let balances_buffer = balances.value();
+
+        let mut balances = BTreeMap::new();
+        let mut i = 0;
+        while i < balances_buffer.len() {
+          let (id, length) = cunes::varint::decode(&balances_buffer[i..]);
+          i += length;
+          let (balance, length) = cunes::varint::decode(&balances_buffer[i..]);
+          i += length;
+          *balances.entry(CuneId::try_from(id)?).or_default() += balance;
+        }
+
+        result.insert(*outpoint, balances);
+      }
+    }
+
+    Ok(result)
+  }
+
   pub(crate) fn get_cune_balance_map(
     &self,
   ) -> Result<BTreeMap<SpacedCune, BTreeMap<OutPoint, u128>>> {
@@ -1060,6 +1294,22 @@ impl Index {
     }
   }
 
+  pub(crate) fn get_crc20_receipts(&self, txid: Txid) -> Result<Vec<Receipt>> {
+    if self.index_crc20 && self.block_count()? >= self.first_inscription_height {
+      Ok(
+        self
+          .database
+          .begin_read()?
+          .open_table(CRC20_TRANSACTION_ID_TO_RECEIPTS)?
+          .get(&txid.store())?
+          .map(|receipts| rmp_serde::from_slice::<Vec<Receipt>>(receipts.value()).unwrap())
+          .unwrap_or_default(),
+      )
+    } else {
+      Ok(Vec::new())
+    }
+  }
+
   pub(crate) fn get_etching(&self, txid: Txid) -> Result<Option<SpacedCune>> {
     if self.block_count().unwrap() >= self.first_cune_height {
       let rtx = self.database.begin_read()?;
@@ -1145,9 +1395,14 @@ impl Index {
     &self,
     inscription_id: InscriptionId,
   ) -> Result<Option<Inscription>> {
-    if self
-      .database
-      .begin_read()?
+    Self::read_inscription(&self.database.begin_read()?, inscription_id)
+  }
+
+  fn read_inscription(
+    rtx: &ReadTransaction,
+    inscription_id: InscriptionId,
+  ) -> Result<Option<Inscription>> {
+    if rtx
       .open_table(INSCRIPTION_ID_TO_SATPOINT)?
       .get(&inscription_id.store())?
       .is_none()
@@ -1155,9 +1410,7 @@ impl Index {
       return Ok(None);
     }
 
-    let reader = self.database.begin_read()?;
-
-    let table = reader.open_table(INSCRIPTION_ID_TO_TXIDS)?;
+    let table = rtx.open_table(INSCRIPTION_ID_TO_TXIDS)?;
     let txids_result = table.get(&inscription_id.store())?;
 
     match txids_result {
@@ -1168,7 +1421,7 @@ impl Index {
 
         for i in 0..txids.len() / 32 {
           let txid_buf = &txids[i * 32..i * 32 + 32];
-          let table = reader.open_table(INSCRIPTION_TXID_TO_TX)?;
+          let table = rtx.open_table(INSCRIPTION_TXID_TO_TX)?;
           let tx_result = table.get(txid_buf)?;
 
           match tx_result {
@@ -1245,6 +1498,74 @@ impl Index {
     .collect()
   }
 
+  /// Inscriptions created or moved by transaction `txid`, in the order they
+  /// were assigned to its outputs.
+  pub(crate) fn get_inscription_moves(&self, txid: Txid) -> Result<Vec<InscriptionMove>> {
+    Ok(
+      self
+        .database
+        .begin_read()?
+        .open_table(TRANSACTION_ID_TO_INSCRIPTION_MOVES)?
+        .get(&txid.store())?
+        .map(|moves| {
+          moves
+            .value()
+            .chunks_exact(125)
+            .map(|chunk| InscriptionMove::load(chunk.try_into().unwrap()))
+            .collect()
+        })
+        .unwrap_or_default(),
+    )
+  }
+
+  /// Every location `inscription_id` has occupied, oldest first, starting
+  /// with the output of its reveal transaction.
+  pub(crate) fn get_inscription_history(
+    &self,
+    inscription_id: InscriptionId,
+  ) -> Result<Vec<InscriptionLocation>> {
+    let inscription_id = inscription_id.store();
+
+    self
+      .database
+      .begin_read()?
+      .open_table(INSCRIPTION_ID_TO_LOCATIONS)?
+      .range((&inscription_id, 0)..=(&inscription_id, u32::MAX))?
+      .map(|result| {
+        result
+          .map(|(_key, location)| InscriptionLocation::load(location.value().to_vec()))
+          .map_err(|err| err.into())
+      })
+      .collect()
+  }
+
+  /// Numbers and IDs of the inscriptions whose content hashes to
+  /// `content_hash`, in inscription number order, so the first is the
+  /// original and the rest are duplicates.
+  pub(crate) fn get_inscriptions_by_content_hash(
+    &self,
+    content_hash: sha256::Hash,
+  ) -> Result<Vec<(u64, InscriptionId)>> {
+    let rtx = self.database.begin_read()?;
+
+    let number_to_id = rtx.open_table(INSCRIPTION_NUMBER_TO_INSCRIPTION_ID)?;
+
+    let mut inscriptions = Vec::new();
+
+    for number in rtx
+      .open_multimap_table(CONTENT_HASH_TO_INSCRIPTION_NUMBER)?
+      .get(&content_hash.into_inner())?
+    {
+      let number = number?.value();
+
+      if let Some(id) = number_to_id.get(&number)? {
+        inscriptions.push((number, Entry::load(*id.value())));
+      }
+    }
+
+    Ok(inscriptions)
+  }
+
   pub(crate) fn get_transaction(&self, txid: Txid) -> Result<Option<Transaction>> {
     if txid == self.genesis_block_coinbase_txid {
       return Ok(Some(self.genesis_block_coinbase_transaction.clone()));
@@ -1490,6 +1811,291 @@ impl Index {
     Ok((inscriptions, prev, next))
   }
 
+  /// Inscriptions matching `search`, newest first, starting at inscription
+  /// number `search.from`, along with the inscription number to continue from
+  /// if there are more results.
+  ///
+  /// The most selective filter that can be read newest first drives the
+  /// search, and the remaining filters are checked against each candidate
+  /// using the tables written when the index is created with
+  /// `--index-search`. At most `MAX_SEARCH_CANDIDATES` inscriptions are
+  /// examined per query, so the number to continue from may be returned with
+  /// fewer than `search.limit` results.
+  pub(crate) fn search_inscriptions(
+    &self,
+    search: &InscriptionSearch,
+  ) -> Result<(Vec<InscriptionId>, Option<u64>)> {
+    let rtx = self.database.begin_read()?;
+
+    let content_type_to_number = rtx.open_multimap_table(CONTENT_TYPE_TO_INSCRIPTION_NUMBER)?;
+    let id_to_entry = rtx.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?;
+    let number_to_content_length = rtx.open_table(INSCRIPTION_NUMBER_TO_CONTENT_LENGTH)?;
+    let number_to_content_type = rtx.open_table(INSCRIPTION_NUMBER_TO_CONTENT_TYPE)?;
+    let number_to_crc20_operation = rtx.open_table(INSCRIPTION_NUMBER_TO_CRC20_OPERATION)?;
+    let number_to_id = rtx.open_table(INSCRIPTION_NUMBER_TO_INSCRIPTION_ID)?;
+    let rarity_to_number = rtx.open_multimap_table(RARITY_TO_INSCRIPTION_NUMBER)?;
+
+    // Inscriptions are numbered in the order they are indexed, so a height
+    // range is a range of inscription numbers
+    let (lowest, highest) = match search.height {
+      Some((min, max)) => {
+        let height_to_number = rtx.open_multimap_table(HEIGHT_TO_INSCRIPTION_NUMBER)?;
+
+        let lowest = match height_to_number.range(min..=max)?.next() {
+          Some(result) => result?.1.next().transpose()?.map(|number| number.value()),
+          None => None,
+        };
+
+        let highest = match height_to_number.range(min..=max)?.next_back() {
+          Some(result) => result?
+            .1
+            .next_back()
+            .transpose()?
+            .map(|number| number.value()),
+          None => None,
+        };
+
+        match lowest.zip(highest) {
+          Some(range) => range,
+          None => return Ok((Vec::new(), None)),
+        }
+      }
+      None => (0, u64::MAX),
+    };
+
+    let from = search.from.unwrap_or(u64::MAX).min(highest);
+
+    let numbers: Box<dyn Iterator<Item = Result<u64>> + '_> = if let Some(address) = &search.address
+    {
+      Box::new(
+        Self::address_inscription_numbers(&rtx, address)?
+          .into_iter()
+          .rev()
+          .map(Ok),
+      )
+    } else if let Some(content_type) = &search.content_type {
+      Box::new(
+        content_type_to_number
+          .get(content_type.as_str())?
+          .rev()
+          .map(|number| Ok(number?.value())),
+      )
+    } else if let Some(rarity) = search.rarity {
+      Box::new(
+        rarity_to_number
+          .get(u8::from(rarity))?
+          .rev()
+          .map(|number| Ok(number?.value())),
+      )
+    } else if search.crc20 == Some(true) {
+      Box::new(
+        number_to_crc20_operation
+          .range(..=from)?
+          .rev()
+          .map(|result| Ok(result?.0.value())),
+      )
+    } else {
+      Box::new(
+        number_to_id
+          .range(..=from)?
+          .rev()
+          .map(|result| Ok(result?.0.value())),
+      )
+    };
+
+    let numbers = numbers
+      .skip_while(|number| matches!(number, Ok(number) if *number > from))
+      .take_while(|number| !matches!(number, Ok(number) if *number < lowest));
+
+    let mut inscriptions = Vec::new();
+
+    for (examined, number) in numbers.enumerate() {
+      let number = number?;
+
+      if inscriptions.len() == search.limit || examined == MAX_SEARCH_CANDIDATES {
+        return Ok((inscriptions, Some(number)));
+      }
+
+      if search.content_type.is_some() || search.media.is_some() {
+        let Some(content_type) = number_to_content_type.get(number)? else {
+          continue;
+        };
+
+        let content_type = content_type.value();
+
+        if let Some(expected) = &search.content_type {
+          if content_type != expected {
+            continue;
+          }
+        }
+
+        if let Some(media) = search.media {
+          let content_media = content_type
+            .replace("; ", ";")
+            .replace(" ;", ";")
+            .parse()
+            .unwrap_or(Media::Unknown);
+
+          if content_media != media {
+            continue;
+          }
+        }
+      }
+
+      if let Some((min, max)) = search.content_length {
+        let Some(content_length) = number_to_content_length.get(number)? else {
+          continue;
+        };
+
+        if !(min..=max).contains(&content_length.value()) {
+          continue;
+        }
+      }
+
+      if let Some(crc20) = search.crc20 {
+        if number_to_crc20_operation.get(number)?.is_some() != crc20 {
+          continue;
+        }
+      }
+
+      let Some(inscription_id) = number_to_id.get(number)? else {
+        continue;
+      };
+
+      let inscription_id = InscriptionId::load(*inscription_id.value());
+
+      if search.timestamp.is_some() || search.rarity.is_some() {
+        let Some(entry) = id_to_entry.get(&inscription_id.store())? else {
+          continue;
+        };
+
+        let entry = InscriptionEntry::load(entry.value());
+
+        if let Some((min, max)) = search.timestamp {
+          if !(min..=max).contains(&entry.timestamp) {
+            continue;
+          }
+        }
+
+        if let Some(rarity) = search.rarity {
+          if entry.sat.map(|sat| sat.rarity()) != Some(rarity) {
+            continue;
+          }
+        }
+      }
+
+      inscriptions.push(inscription_id);
+    }
+
+    Ok((inscriptions, None))
+  }
+
+  /// Numbers of the inscriptions on unspent outputs sent to `address`.
+  fn address_inscription_numbers(
+    rtx: &ReadTransaction,
+    address: &Address,
+  ) -> Result<BTreeSet<u64>> {
+    let address_to_outpoint = rtx.open_multimap_table(ADDRESS_TO_OUTPOINT)?;
+    let outpoint_to_value = rtx.open_table(OUTPOINT_TO_VALUE)?;
+    let satpoint_to_id = rtx.open_table(SATPOINT_TO_INSCRIPTION_ID)?;
+    let id_to_entry = rtx.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?;
+
+    let mut numbers = BTreeSet::new();
+
+    for outpoint in address_to_outpoint.get(address.to_string().as_bytes())? {
+      let outpoint = OutPoint::load(*outpoint?.value());
+
+      if outpoint_to_value.get(&outpoint.store())?.is_none() {
+        continue;
+      }
+
+      for result in Self::inscriptions_on_output(&satpoint_to_id, outpoint)? {
+        let (_satpoint, inscription_id) = result?;
+
+        if let Some(entry) = id_to_entry.get(&inscription_id.store())? {
+          numbers.insert(InscriptionEntry::load(entry.value()).inscription_number);
+        }
+      }
+    }
+
+    Ok(numbers)
+  }
+
+  /// Numbers and ids of the inscriptions numbered at most `from` which contain
+  /// every one of `terms`, which must already be normalized with
+  /// `full_text::terms`, newest first. Inscriptions whose content type does
+  /// not match the `content_type` filter are skipped.
+  ///
+  /// At most `MAX_TEXT_SEARCH_CANDIDATES` postings are read per term, so
+  /// searches for common words only cover the newest inscriptions. The number
+  /// to continue the search from is returned if older inscriptions remain.
+  pub(crate) fn search_text(
+    &self,
+    terms: &BTreeSet<String>,
+    content_type: Option<&str>,
+    from: u64,
+  ) -> Result<(Vec<(u64, InscriptionId)>, Option<u64>)> {
+    let rtx = self.database.begin_read()?;
+
+    let word_to_number = rtx.open_multimap_table(WORD_TO_INSCRIPTION_NUMBER)?;
+
+    let mut candidates = None;
+    let mut floor = 0;
+
+    for term in terms {
+      let mut numbers = BTreeSet::new();
+
+      for number in word_to_number.get(term.as_str())?.rev() {
+        let number = number?.value();
+
+        if number > from {
+          continue;
+        }
+
+        if numbers.len() == MAX_TEXT_SEARCH_CANDIDATES {
+          floor = floor.max(number + 1);
+          break;
+        }
+
+        numbers.insert(number);
+      }
+
+      Self::intersect(&mut candidates, numbers);
+    }
+
+    let number_to_content_type = rtx.open_table(INSCRIPTION_NUMBER_TO_CONTENT_TYPE)?;
+    let number_to_id = rtx.open_table(INSCRIPTION_NUMBER_TO_INSCRIPTION_ID)?;
+
+    let mut results = Vec::new();
+
+    for &number in candidates.unwrap_or_default().range(floor..).rev() {
+      if let Some(filter) = content_type {
+        let matches = number_to_content_type
+          .get(number)?
+          .map_or(false, |content_type| {
+            full_text::content_type_matches(content_type.value(), filter)
+          });
+
+        if !matches {
+          continue;
+        }
+      }
+
+      if let Some(inscription_id) = number_to_id.get(number)? {
+        results.push((number, Entry::load(*inscription_id.value())));
+      }
+    }
+
+    Ok((results, floor.checked_sub(1)))
+  }
+
+  fn intersect(candidates: &mut Option<BTreeSet<u64>>, numbers: BTreeSet<u64>) {
+    *candidates = Some(match candidates.take() {
+      Some(candidates) => candidates.intersection(&numbers).copied().collect(),
+      None => numbers,
+    });
+  }
+
   pub(crate) fn get_feed_inscriptions(&self, n: usize) -> Result<Vec<(u64, InscriptionId)>> {
     self
       .database
@@ -2726,6 +3332,162 @@ mod tests {
       format!("index at `{}{delimiter}regtest{delimiter}index.redb` appears to have been built with a newer, incompatible version of ord, consider updating ord: index schema {}, ord schema {SCHEMA_VERSION}", path.display(), u64::MAX));
   }
 
+  #[test]
+  fn inscription_moves_are_recorded() {
+    let context = Context::builder().arg("--index-history").build();
+
+    context.mine_blocks(1);
+
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      witness: inscription("text/plain", "hello").to_witness(),
+      ..Default::default()
+    });
+
+    let inscription_id = InscriptionId::from(txid);
+
+    context.mine_blocks(1);
+
+    let inscription_satpoint = SatPoint {
+      outpoint: OutPoint { txid, vout: 0 },
+      offset: 0,
+    };
+
+    let moves = context.index.get_inscription_moves(txid).unwrap();
+
+    assert_eq!(moves.len(), 1);
+    assert!(moves[0].created);
+    assert_eq!(moves[0].inscription_id, inscription_id);
+    assert_eq!(moves[0].new_satpoint, inscription_satpoint);
+
+    let send_id = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0)],
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      context.index.get_inscription_moves(send_id).unwrap(),
+      [InscriptionMove {
+        created: false,
+        inscription_id,
+        new_satpoint: SatPoint {
+          outpoint: OutPoint {
+            txid: send_id,
+            vout: 0,
+          },
+          offset: 0,
+        },
+        old_satpoint: inscription_satpoint,
+      }]
+    );
+
+    assert_eq!(
+      context.index.get_inscription_moves(txid).unwrap(),
+      moves,
+      "moves are kept after the outputs involved are spent"
+    );
+  }
+
+  #[test]
+  fn inscription_moves_are_only_recorded_with_history_index() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(1);
+
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      witness: inscription("text/plain", "hello").to_witness(),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    assert!(!context.index.has_history_index());
+    assert_eq!(context.index.get_inscription_moves(txid).unwrap(), []);
+  }
+
+  #[test]
+  fn inscription_locations_are_recorded_one_row_per_move() {
+    let context = Context::builder().arg("--index-locations").build();
+
+    context.mine_blocks(1);
+
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      witness: inscription("text/plain", "hello").to_witness(),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let mut transfers = Vec::new();
+    for block in 2..5 {
+      transfers.push(context.rpc_server.broadcast_tx(TransactionTemplate {
+        inputs: &[(block, 1, 0)],
+        ..Default::default()
+      }));
+
+      context.mine_blocks(1);
+    }
+
+    let inscription_id = InscriptionId::from(txid);
+
+    assert_eq!(
+      context
+        .index
+        .get_inscription_history(inscription_id)
+        .unwrap()
+        .into_iter()
+        .map(|location| (location.txid, location.height))
+        .collect::<Vec<(Txid, u32)>>(),
+      [
+        (txid, 2),
+        (transfers[0], 3),
+        (transfers[1], 4),
+        (transfers[2], 5)
+      ]
+    );
+
+    let rtx = context.index.database.begin_read().unwrap();
+    let table = rtx.open_table(INSCRIPTION_ID_TO_LOCATIONS).unwrap();
+    let inscription_id = inscription_id.store();
+
+    assert_eq!(
+      table
+        .range((&inscription_id, 0)..=(&inscription_id, u32::MAX))
+        .unwrap()
+        .map(|result| result.unwrap().0.value().1)
+        .collect::<Vec<u32>>(),
+      [0, 1, 2, 3]
+    );
+  }
+
+  #[test]
+  fn inscription_locations_are_only_recorded_with_locations_index() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(1);
+
+    let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      witness: inscription("text/plain", "hello").to_witness(),
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    assert!(!context.index.has_locations_index());
+    assert_eq!(
+      context
+        .index
+        .get_inscription_history(InscriptionId::from(txid))
+        .unwrap(),
+      []
+    );
+  }
+
   #[test]
   fn inscriptions_on_output() {
     for context in Context::configurations() {
@@ -0,0 +1,189 @@
+use super::*;
+
+/// Results of a batch lookup. All results are read in a single read
+/// transaction, and so are consistent with the index as of `height`.
+pub(crate) struct Batch<T> {
+  pub(crate) height: Option<Height>,
+  pub(crate) results: Vec<T>,
+}
+
+pub(crate) struct BatchOutput {
+  pub(crate) cunes: Vec<(SpacedCune, Pile)>,
+  pub(crate) inscriptions: Vec<InscriptionId>,
+  pub(crate) tx_out: TxOut,
+}
+
+pub(crate) struct BatchInscription {
+  pub(crate) entry: InscriptionEntry,
+  pub(crate) inscription: Inscription,
+  pub(crate) satpoint: SatPoint,
+}
+
+impl Index {
+  /// Outputs are read from the transactions stored by `--index-transactions`,
+  /// and are `None` if their transaction is not in the index.
+  pub(crate) fn batch_outputs(&self, outpoints: &[OutPoint]) -> Result<Batch<Option<BatchOutput>>> {
+    let rtx = self.begin_read()?;
+
+    let satpoint_to_id = rtx.0.open_table(SATPOINT_TO_INSCRIPTION_ID)?;
+    let transaction_id_to_transaction = rtx.0.open_table(TRANSACTION_ID_TO_TRANSACTION)?;
+
+    let cunes = self.batch_read_cune_balances(&rtx, outpoints)?;
+
+    let mut transactions = HashMap::new();
+    let mut results = Vec::new();
+
+    for (outpoint, cunes) in outpoints.iter().zip(cunes) {
+      if !transactions.contains_key(&outpoint.txid) {
+        let transaction = if outpoint.txid == self.genesis_block_coinbase_txid {
+          Some(self.genesis_block_coinbase_transaction.clone())
+        } else {
+          transaction_id_to_transaction
+            .get(&outpoint.txid.store())?
+            .map(|transaction| consensus::encode::deserialize::<Transaction>(transaction.value()))
+            .transpose()?
+        };
+
+        transactions.insert(outpoint.txid, transaction);
+      }
+
+      let vout = usize::try_from(outpoint.vout)?;
+
+      let Some(tx_out) = transactions[&outpoint.txid]
+        .as_ref()
+        .and_then(|transaction| transaction.output.get(vout))
+        .cloned()
+      else {
+        results.push(None);
+        continue;
+      };
+
+      results.push(Some(BatchOutput {
+        cunes,
+        inscriptions: Self::inscriptions_on_output(&satpoint_to_id, *outpoint)?
+          .map(|result| result.map(|(_satpoint, inscription_id)| inscription_id))
+          .collect::<Result<Vec<InscriptionId>, StorageError>>()?,
+        tx_out,
+      }));
+    }
+
+    Ok(Batch {
+      height: rtx.height()?,
+      results,
+    })
+  }
+
+  pub(crate) fn batch_inscriptions(
+    &self,
+    inscription_ids: &[InscriptionId],
+  ) -> Result<Batch<Option<BatchInscription>>> {
+    let rtx = self.begin_read()?;
+
+    let id_to_entry = rtx.0.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?;
+    let id_to_satpoint = rtx.0.open_table(INSCRIPTION_ID_TO_SATPOINT)?;
+
+    let mut results = Vec::new();
+
+    for inscription_id in inscription_ids {
+      let (Some(entry), Some(satpoint)) = (
+        id_to_entry.get(&inscription_id.store())?,
+        id_to_satpoint.get(&inscription_id.store())?,
+      ) else {
+        results.push(None);
+        continue;
+      };
+
+      results.push(
+        Self::read_inscription(&rtx.0, *inscription_id)?.map(|inscription| BatchInscription {
+          entry: InscriptionEntry::load(entry.value()),
+          inscription,
+          satpoint: Entry::load(*satpoint.value()),
+        }),
+      );
+    }
+
+    Ok(Batch {
+      height: rtx.height()?,
+      results,
+    })
+  }
+
+  pub(crate) fn batch_cune_balances(
+    &self,
+    outpoints: &[OutPoint],
+  ) -> Result<Batch<Vec<(SpacedCune, Pile)>>> {
+    let rtx = self.begin_read()?;
+
+    Ok(Batch {
+      results: self.batch_read_cune_balances(&rtx, outpoints)?,
+      height: rtx.height()?,
+    })
+  }
+
+  /// CRC-20 balances of each of `script_keys`, with the decimals of their
+  /// tick.
+  pub(crate) fn batch_crc20_balances(
+    &self,
+    script_keys: &[ScriptKey],
+  ) -> Result<Batch<Vec<(Balance, u8)>>> {
+    let rtx = self.begin_read()?;
+
+    let mut results = Vec::new();
+
+    if rtx.block_count()? >= self.first_inscription_height {
+      let balances = rtx.0.open_table(CRC20_BALANCES)?;
+      let tokens = rtx.0.open_table(CRC20_TOKEN)?;
+
+      for script_key in script_keys {
+        let mut result = Vec::new();
+
+        for entry in balances.range(
+          min_script_tick_key(script_key).as_str()..max_script_tick_key(script_key).as_str(),
+        )? {
+          let balance = bincode::deserialize::<Balance>(entry?.1.value())?;
+
+          let token = tokens
+            .get(balance.tick.to_lowercase().hex().as_str())?
+            .map(|token| bincode::deserialize::<TokenInfo>(token.value()))
+            .transpose()?
+            .ok_or_else(|| anyhow!("missing token info for tick {}", balance.tick))?;
+
+          result.push((balance, token.decimal));
+        }
+
+        results.push(result);
+      }
+    } else {
+      results.resize_with(script_keys.len(), Vec::new);
+    }
+
+    Ok(Batch {
+      height: rtx.height()?,
+      results,
+    })
+  }
+
+  fn batch_read_cune_balances(
+    &self,
+    rtx: &rtx::Rtx,
+    outpoints: &[OutPoint],
+  ) -> Result<Vec<Vec<(SpacedCune, Pile)>>> {
+    if rtx.block_count()? < self.first_cune_height || !self.index_cunes {
+      return Ok(outpoints.iter().map(|_| Vec::new()).collect());
+    }
+
+    let outpoint_to_balances = rtx.0.open_table(OUTPOINT_TO_CUNE_BALANCES)?;
+    let id_to_cune_entries = rtx.0.open_table(CUNE_ID_TO_CUNE_ENTRY)?;
+
+    let mut results = Vec::new();
+
+    for outpoint in outpoints {
+      results.push(match outpoint_to_balances.get(&outpoint.store())? {
+        Some(balances) => Self::load_cune_balances(&id_to_cune_entries, balances.value())?,
+        None => Vec::new(),
+      });
+    }
+
+    Ok(results)
+  }
+}
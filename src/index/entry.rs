@@ -429,6 +429,82 @@ impl Entry for SatPoint {
   }
 }
 
+/// An inscription moved, or created, by a transaction. Kept so that what a
+/// transaction did can be shown after its inputs and outputs are spent.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub(crate) struct InscriptionMove {
+  pub(crate) created: bool,
+  pub(crate) inscription_id: InscriptionId,
+  pub(crate) new_satpoint: SatPoint,
+  pub(crate) old_satpoint: SatPoint,
+}
+
+pub(super) type InscriptionMoveValue = [u8; 125];
+
+impl Entry for InscriptionMove {
+  type Value = InscriptionMoveValue;
+
+  fn load(value: Self::Value) -> Self {
+    Self {
+      inscription_id: InscriptionId::load(value[0..36].try_into().unwrap()),
+      old_satpoint: SatPoint::load(value[36..80].try_into().unwrap()),
+      new_satpoint: SatPoint::load(value[80..124].try_into().unwrap()),
+      created: value[124] != 0,
+    }
+  }
+
+  fn store(self) -> Self::Value {
+    let mut value = [0; 125];
+    value[0..36].copy_from_slice(&self.inscription_id.store());
+    value[36..80].copy_from_slice(&self.old_satpoint.store());
+    value[80..124].copy_from_slice(&self.new_satpoint.store());
+    value[124] = self.created.into();
+    value
+  }
+}
+
+/// A location an inscription has occupied. Each is stored in its own row of
+/// `INSCRIPTION_ID_TO_LOCATIONS`, keyed by inscription ID and the number of
+/// locations recorded before it, so recording a move doesn't rewrite the
+/// inscription's earlier history.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct InscriptionLocation {
+  pub(crate) address: Option<String>,
+  pub(crate) height: u32,
+  pub(crate) sale: bool,
+  pub(crate) satpoint: SatPoint,
+  pub(crate) timestamp: u32,
+  pub(crate) txid: Txid,
+}
+
+impl Entry for InscriptionLocation {
+  type Value = Vec<u8>;
+
+  fn load(value: Self::Value) -> Self {
+    Self {
+      satpoint: SatPoint::load(value[0..44].try_into().unwrap()),
+      txid: Txid::load(value[44..76].try_into().unwrap()),
+      height: u32::from_le_bytes(value[76..80].try_into().unwrap()),
+      timestamp: u32::from_le_bytes(value[80..84].try_into().unwrap()),
+      sale: value[84] != 0,
+      address: (value.len() > 85).then(|| String::from_utf8_lossy(&value[85..]).into_owned()),
+    }
+  }
+
+  fn store(self) -> Self::Value {
+    let mut value = Vec::with_capacity(85);
+    value.extend(self.satpoint.store());
+    value.extend(self.txid.store());
+    value.extend(self.height.to_le_bytes());
+    value.extend(self.timestamp.to_le_bytes());
+    value.push(self.sale.into());
+    if let Some(address) = self.address {
+      value.extend(address.as_bytes());
+    }
+    value
+  }
+}
+
 pub(super) type SatRange = (u64, u64);
 
 impl Entry for SatRange {
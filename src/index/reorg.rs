@@ -66,6 +66,9 @@ impl Reorg {
     Index::increment_statistic(&wtx, Statistic::Commits, 1)?;
     wtx.commit()?;
 
+    index.reorgs.fetch_add(1, atomic::Ordering::Release);
+    index.commits.fetch_add(1, atomic::Ordering::Release);
+
     log::info!(
       "successfully rolled back database to height {}",
       index.block_count()?
@@ -8,6 +8,7 @@ use {
   tokio::sync::mpsc::{error::TryRecvError, Receiver, Sender},
 };
 
+use crate::crc20::operation::Action;
 use crate::crc20::BlockContext;
 use crate::index::updater::crc20_updater::Crc20Updater;
 use crate::sat::Sat;
@@ -392,6 +393,7 @@ impl<'index> Updater<'_> {
     let mut inscription_id_to_inscription_entry =
       wtx.open_table(INSCRIPTION_ID_TO_INSCRIPTION_ENTRY)?;
     let mut inscription_id_to_satpoint = wtx.open_table(INSCRIPTION_ID_TO_SATPOINT)?;
+    let mut inscription_id_to_locations = wtx.open_table(INSCRIPTION_ID_TO_LOCATIONS)?;
     let mut inscription_id_to_txids = wtx.open_table(INSCRIPTION_ID_TO_TXIDS)?;
     let mut inscription_txid_to_tx = wtx.open_table(INSCRIPTION_TXID_TO_TX)?;
     let mut partial_txid_to_inscription_txids =
@@ -408,6 +410,22 @@ impl<'index> Updater<'_> {
     let mut crc20_token_balance = wtx.open_table(CRC20_BALANCES)?;
     let mut crc20_inscribe_transfer = wtx.open_table(CRC20_INSCRIBE_TRANSFER)?;
     let mut crc20_transferable_log = wtx.open_table(CRC20_TRANSFERABLELOG)?;
+    let mut crc20_transaction_id_to_receipts = wtx.open_table(CRC20_TRANSACTION_ID_TO_RECEIPTS)?;
+    let mut transaction_id_to_inscription_moves =
+      wtx.open_table(TRANSACTION_ID_TO_INSCRIPTION_MOVES)?;
+    let mut content_hash_to_inscription_number =
+      wtx.open_multimap_table(CONTENT_HASH_TO_INSCRIPTION_NUMBER)?;
+    let mut content_type_to_inscription_number =
+      wtx.open_multimap_table(CONTENT_TYPE_TO_INSCRIPTION_NUMBER)?;
+    let mut height_to_inscription_number = wtx.open_multimap_table(HEIGHT_TO_INSCRIPTION_NUMBER)?;
+    let mut inscription_number_to_content_length =
+      wtx.open_table(INSCRIPTION_NUMBER_TO_CONTENT_LENGTH)?;
+    let mut inscription_number_to_content_type =
+      wtx.open_table(INSCRIPTION_NUMBER_TO_CONTENT_TYPE)?;
+    let mut inscription_number_to_crc20_operation =
+      wtx.open_table(INSCRIPTION_NUMBER_TO_CRC20_OPERATION)?;
+    let mut rarity_to_inscription_number = wtx.open_multimap_table(RARITY_TO_INSCRIPTION_NUMBER)?;
+    let mut word_to_inscription_number = wtx.open_multimap_table(WORD_TO_INSCRIPTION_NUMBER)?;
 
     let mut lost_sats = statistic_to_count
       .get(&Statistic::LostSats.key())?
@@ -418,6 +436,8 @@ impl<'index> Updater<'_> {
       let mut inscription_updater = InscriptionUpdater::new(
         self.height,
         &mut inscription_id_to_satpoint,
+        &mut inscription_id_to_locations,
+        self.index.index_locations,
         &mut inscription_id_to_txids,
         &mut inscription_txid_to_tx,
         &mut partial_txid_to_inscription_txids,
@@ -428,6 +448,16 @@ impl<'index> Updater<'_> {
         &mut inscription_id_to_inscription_entry,
         lost_sats,
         &mut inscription_number_to_inscription_id,
+        &mut content_hash_to_inscription_number,
+        self.index.index_search,
+        &mut content_type_to_inscription_number,
+        &mut height_to_inscription_number,
+        &mut inscription_number_to_content_length,
+        &mut inscription_number_to_crc20_operation,
+        &mut rarity_to_inscription_number,
+        self.index.index_text,
+        &mut inscription_number_to_content_type,
+        &mut word_to_inscription_number,
         &mut outpoint_to_value,
         &mut address_to_outpoint,
         &mut sat_to_inscription_id,
@@ -535,6 +565,30 @@ impl<'index> Updater<'_> {
         }
       }
 
+      if index.index_history {
+        for (txid, operations) in &inscription_updater.operations {
+          let mut moves = Vec::new();
+
+          for operation in operations {
+            let Some(new_satpoint) = operation.new_satpoint else {
+              continue;
+            };
+
+            moves.extend(
+              InscriptionMove {
+                created: matches!(operation.action, Action::New { .. }),
+                inscription_id: operation.inscription_id,
+                new_satpoint,
+                old_satpoint: operation.old_satpoint,
+              }
+              .store(),
+            );
+          }
+
+          transaction_id_to_inscription_moves.insert(&txid.store(), moves.as_slice())?;
+        }
+      }
+
       if index.index_crc20 && self.height >= index.first_inscription_height {
         let operations = inscription_updater.operations.clone();
 
@@ -545,6 +599,7 @@ impl<'index> Updater<'_> {
           &mut crc20_token_balance,
           &mut crc20_inscribe_transfer,
           &mut crc20_transferable_log,
+          &mut crc20_transaction_id_to_receipts,
           &inscription_id_to_inscription_entry,
           &mut transaction_id_to_transaction,
         )?
@@ -564,12 +619,15 @@ impl<'index> Updater<'_> {
 
     if index.index_cunes && self.height >= self.index.first_cune_height {
       let mut outpoint_to_cune_balances = wtx.open_table(OUTPOINT_TO_CUNE_BALANCES)?;
+      let mut spent_outpoint_to_cune_balances = wtx.open_table(SPENT_OUTPOINT_TO_CUNE_BALANCES)?;
       let mut cune_id_to_cune_entry = wtx.open_table(CUNE_ID_TO_CUNE_ENTRY)?;
       let mut cune_to_cune_id = wtx.open_table(CUNE_TO_CUNE_ID)?;
       let mut inscription_id_to_cune = wtx.open_table(INSCRIPTION_ID_TO_CUNE)?;
       let mut cune_updater = CuneUpdater::new(
         self.height,
         &mut outpoint_to_cune_balances,
+        index.index_history,
+        &mut spent_outpoint_to_cune_balances,
         &mut cune_id_to_cune_entry,
         &inscription_id_to_inscription_entry,
         &mut inscription_id_to_cune,
@@ -712,6 +770,8 @@ impl<'index> Updater<'_> {
 
     wtx.commit()?;
 
+    self.index.commits.fetch_add(1, atomic::Ordering::Release);
+
     Reorg::update_savepoints(self.index, self.height)?;
 
     Ok(())
@@ -14,8 +14,8 @@ use crate::crc20::script_key::ScriptKey;
 use crate::crc20::{
   max_script_tick_id_key, max_script_tick_key, min_script_tick_id_key, min_script_tick_key,
   script_tick_id_key, script_tick_key, Balance, BlockContext, Deploy, DeployEvent, Event,
-  InscribeTransferEvent, CRC20Error, Message, Mint, MintEvent, Num, Tick, TokenInfo, Transfer,
-  TransferEvent, TransferInfo, TransferableLog,
+  InscribeTransferEvent, CRC20Error, Message, Mint, MintEvent, Num, Receipt, Tick, TokenInfo,
+  Transfer, TransferEvent, TransferInfo, TransferableLog,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -36,6 +36,7 @@ pub(super) struct Crc20Updater<'a, 'db, 'tx> {
   crc20_token_balance: &'a mut Table<'db, 'tx, &'static str, &'static [u8]>,
   crc20_inscribe_transfer: &'a mut Table<'db, 'tx, &'static [u8; 36], &'static [u8]>,
   crc20_transferable_log: &'a mut Table<'db, 'tx, &'static str, &'static [u8]>,
+  crc20_transaction_id_to_receipts: &'a mut Table<'db, 'tx, &'static TxidValue, &'static [u8]>,
   inscription_id_to_inscription_entry:
     &'a Table<'db, 'tx, &'static InscriptionIdValue, InscriptionEntryValue>,
   transaction_id_to_transaction: &'a mut Table<'db, 'tx, &'static TxidValue, &'static [u8]>,
@@ -48,6 +49,7 @@ impl<'a, 'db, 'tx> Crc20Updater<'a, 'db, 'tx> {
     crc20_token_balance: &'a mut Table<'db, 'tx, &'static str, &'static [u8]>,
     crc20_inscribe_transfer: &'a mut Table<'db, 'tx, &'static [u8; 36], &'static [u8]>,
     crc20_transferable_log: &'a mut Table<'db, 'tx, &'static str, &'static [u8]>,
+    crc20_transaction_id_to_receipts: &'a mut Table<'db, 'tx, &'static TxidValue, &'static [u8]>,
     inscription_id_to_inscription_entry: &'a Table<
       'db,
       'tx,
@@ -62,6 +64,7 @@ impl<'a, 'db, 'tx> Crc20Updater<'a, 'db, 'tx> {
       crc20_token_balance,
       crc20_inscribe_transfer,
       crc20_transferable_log,
+      crc20_transaction_id_to_receipts,
       inscription_id_to_inscription_entry,
       transaction_id_to_transaction,
     })
@@ -90,10 +93,18 @@ impl<'a, 'db, 'tx> Crc20Updater<'a, 'db, 'tx> {
       if let Some(tx_operations) = operations.get(txid) {
         // Resolve and execute messages.
         let messages = self.resolve_message(tx, tx_operations)?;
+        let mut receipts = Vec::new();
         for msg in messages.iter() {
-          self.execute_message(context, msg)?;
+          receipts.push(self.execute_message(context, msg)?);
         }
         messages_size += messages.len();
+
+        if !receipts.is_empty() {
+          self.crc20_transaction_id_to_receipts.insert(
+            &txid.store(),
+            rmp_serde::to_vec(&receipts).unwrap().as_slice(),
+          )?;
+        }
       }
     }
 
@@ -147,9 +158,9 @@ impl<'a, 'db, 'tx> Crc20Updater<'a, 'db, 'tx> {
     Ok(messages)
   }
 
-  pub fn execute_message(&mut self, context: BlockContext, msg: &Message) -> Result {
+  pub fn execute_message(&mut self, context: BlockContext, msg: &Message) -> Result<Receipt> {
     let exec_msg = self.create_execution_message(msg, context.network)?;
-    let _ = match &exec_msg.op {
+    let result = match &exec_msg.op {
       Operation::Deploy(deploy) => {
         Self::process_deploy(self, context.clone(), &exec_msg, deploy.clone())
       }
@@ -161,7 +172,20 @@ impl<'a, 'db, 'tx> Crc20Updater<'a, 'db, 'tx> {
       }
       Operation::Transfer(_) => Self::process_transfer(self, context.clone(), &exec_msg.clone()),
     };
-    Ok(())
+
+    Ok(Receipt {
+      inscription_id: exec_msg.inscription_id,
+      inscription_number: i64::try_from(exec_msg.inscription_number)?,
+      old_satpoint: exec_msg.old_satpoint,
+      new_satpoint: exec_msg.new_satpoint,
+      op: exec_msg.op.op_type(),
+      to: exec_msg.to.clone().unwrap_or_else(|| exec_msg.from.clone()),
+      from: exec_msg.from,
+      result: result.map_err(|err| match err {
+        errors::Error::CRC20Error(err) => err,
+        errors::Error::LedgerError(err) => CRC20Error::InternalError(err.to_string()),
+      }),
+    })
   }
 
   pub fn create_execution_message(
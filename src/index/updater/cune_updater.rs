@@ -30,6 +30,8 @@ pub(super) struct CuneUpdater<'a, 'db, 'tx> {
   outpoint_to_balances: &'a mut Table<'db, 'tx, &'static OutPointValue, &'static [u8]>,
   cune_to_id: &'a mut Table<'db, 'tx, u128, CuneIdValue>,
   cunes: u64,
+  index_history: bool,
+  spent_outpoint_to_balances: &'a mut Table<'db, 'tx, &'static OutPointValue, &'static [u8]>,
   statistic_to_count: &'a mut Table<'db, 'tx, u64, u64>,
   timestamp: u32,
 }
@@ -38,6 +40,8 @@ impl<'a, 'db, 'tx> CuneUpdater<'a, 'db, 'tx> {
   pub(super) fn new(
     height: u32,
     outpoint_to_balances: &'a mut Table<'db, 'tx, &'static OutPointValue, &'static [u8]>,
+    index_history: bool,
+    spent_outpoint_to_balances: &'a mut Table<'db, 'tx, &'static OutPointValue, &'static [u8]>,
     id_to_entry: &'a mut Table<'db, 'tx, CuneIdValue, CuneEntryValue>,
     inscription_id_to_inscription_entry: &'a Table<
       'db,
@@ -64,6 +68,8 @@ impl<'a, 'db, 'tx> CuneUpdater<'a, 'db, 'tx> {
       inscription_id_to_cune,
       cune_to_id,
       cunes,
+      index_history,
+      spent_outpoint_to_balances,
       statistic_to_count,
       timestamp,
     })
@@ -82,6 +88,15 @@ impl<'a, 'db, 'tx> CuneUpdater<'a, 'db, 'tx> {
         .remove(&input.previous_output.store())?
       {
         let buffer = guard.value();
+
+        // Keep the balances of spent outputs, so that wallet history can
+        // show which cunes a transaction moved
+        if self.index_history {
+          self
+            .spent_outpoint_to_balances
+            .insert(&input.previous_output.store(), buffer)?;
+        }
+
         let mut i = 0;
         while i < buffer.len() {
           let (id, len) = varint::decode(&buffer[i..]);
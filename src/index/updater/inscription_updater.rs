@@ -1,7 +1,10 @@
 use crate::inscription::ParsedInscription;
-use crate::crc20::operation::{Action, InscriptionOp};
+use crate::crc20::operation::{deserialize_crc20_operation, Action, InscriptionOp};
+use crate::crc20::OperationType;
+use crate::index::entry::InscriptionLocation;
 use crate::sat::Sat;
 use crate::sat_point::SatPoint;
+use bitcoin::EcdsaSighashType;
 
 use super::*;
 
@@ -24,6 +27,8 @@ pub(super) struct InscriptionUpdater<'a, 'db, 'tx> {
   pub(super) operations: HashMap<Txid, Vec<InscriptionOp>>,
   height: u32,
   id_to_satpoint: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, &'static SatPointValue>,
+  id_to_locations: &'a mut Table<'db, 'tx, (&'static InscriptionIdValue, u32), &'static [u8]>,
+  index_locations: bool,
   id_to_txids: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, &'static [u8]>,
   txid_to_tx: &'a mut Table<'db, 'tx, &'static [u8], &'static [u8]>,
   partial_txid_to_txids: &'a mut Table<'db, 'tx, &'static [u8], &'static [u8]>,
@@ -35,6 +40,16 @@ pub(super) struct InscriptionUpdater<'a, 'db, 'tx> {
   lost_sats: u64,
   next_number: u64,
   number_to_id: &'a mut Table<'db, 'tx, u64, &'static InscriptionIdValue>,
+  content_hash_to_number: &'a mut MultimapTable<'db, 'tx, &'static [u8; 32], u64>,
+  index_search: bool,
+  content_type_to_number: &'a mut MultimapTable<'db, 'tx, &'static str, u64>,
+  height_to_number: &'a mut MultimapTable<'db, 'tx, u32, u64>,
+  number_to_content_length: &'a mut Table<'db, 'tx, u64, u64>,
+  number_to_crc20_operation: &'a mut Table<'db, 'tx, u64, &'static str>,
+  rarity_to_number: &'a mut MultimapTable<'db, 'tx, u8, u64>,
+  index_text: bool,
+  number_to_content_type: &'a mut Table<'db, 'tx, u64, &'static str>,
+  word_to_number: &'a mut MultimapTable<'db, 'tx, &'static str, u64>,
   outpoint_to_value: &'a mut Table<'db, 'tx, &'static OutPointValue, u64>,
   address_to_outpoint: &'a mut MultimapTable<'db, 'tx, &'static [u8], &'static OutPointValue>,
   reward: u64,
@@ -49,6 +64,8 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
   pub(super) fn new(
     height: u32,
     id_to_satpoint: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, &'static SatPointValue>,
+    id_to_locations: &'a mut Table<'db, 'tx, (&'static InscriptionIdValue, u32), &'static [u8]>,
+    index_locations: bool,
     id_to_txids: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, &'static [u8]>,
     txid_to_tx: &'a mut Table<'db, 'tx, &'static [u8], &'static [u8]>,
     partial_txid_to_txids: &'a mut Table<'db, 'tx, &'static [u8], &'static [u8]>,
@@ -59,6 +76,16 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
     id_to_entry: &'a mut Table<'db, 'tx, &'static InscriptionIdValue, InscriptionEntryValue>,
     lost_sats: u64,
     number_to_id: &'a mut Table<'db, 'tx, u64, &'static InscriptionIdValue>,
+    content_hash_to_number: &'a mut MultimapTable<'db, 'tx, &'static [u8; 32], u64>,
+    index_search: bool,
+    content_type_to_number: &'a mut MultimapTable<'db, 'tx, &'static str, u64>,
+    height_to_number: &'a mut MultimapTable<'db, 'tx, u32, u64>,
+    number_to_content_length: &'a mut Table<'db, 'tx, u64, u64>,
+    number_to_crc20_operation: &'a mut Table<'db, 'tx, u64, &'static str>,
+    rarity_to_number: &'a mut MultimapTable<'db, 'tx, u8, u64>,
+    index_text: bool,
+    number_to_content_type: &'a mut Table<'db, 'tx, u64, &'static str>,
+    word_to_number: &'a mut MultimapTable<'db, 'tx, &'static str, u64>,
     outpoint_to_value: &'a mut Table<'db, 'tx, &'static OutPointValue, u64>,
     address_to_outpoint: &'a mut MultimapTable<'db, 'tx, &'static [u8], &'static OutPointValue>,
     sat_to_inscription_id: &'a mut Table<'db, 'tx, u64, &'static InscriptionIdValue>,
@@ -80,6 +107,8 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
       operations: HashMap::new(),
       height,
       id_to_satpoint,
+      id_to_locations,
+      index_locations,
       id_to_txids,
       txid_to_tx,
       partial_txid_to_txids,
@@ -91,6 +120,16 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
       lost_sats,
       next_number,
       number_to_id,
+      content_hash_to_number,
+      index_search,
+      content_type_to_number,
+      height_to_number,
+      number_to_content_length,
+      number_to_crc20_operation,
+      rarity_to_number,
+      index_text,
+      number_to_content_type,
+      word_to_number,
       outpoint_to_value,
       address_to_outpoint,
       reward: Height(height).subsidy(),
@@ -309,6 +348,7 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
         };
 
         self.update_inscription_location(
+          tx,
           input_sat_ranges,
           inscriptions.next().unwrap(),
           new_satpoint,
@@ -347,7 +387,7 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
           outpoint: OutPoint::null(),
           offset: self.lost_sats + flotsam.offset - output_value,
         };
-        self.update_inscription_location(input_sat_ranges, flotsam, new_satpoint)?;
+        self.update_inscription_location(tx, input_sat_ranges, flotsam, new_satpoint)?;
       }
 
       Ok(self.reward - output_value)
@@ -363,6 +403,7 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
 
   fn update_inscription_location(
     &mut self,
+    tx: &Transaction,
     input_sat_ranges: Option<&VecDeque<(u64, u64)>>,
     flotsam: Flotsam,
     new_satpoint: SatPoint,
@@ -375,7 +416,7 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
       }
       Origin::New {
         fee,
-        inscription: _,
+        ref inscription,
       } => {
         self
           .number_to_id
@@ -409,10 +450,47 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
           .store(),
         )?;
 
+        self.index_search_keys(inscription, self.next_number, sat)?;
+
         self.next_number += 1;
       }
     }
 
+    if self.index_locations {
+      let address = tx
+        .output
+        .get(usize::try_from(new_satpoint.outpoint.vout).unwrap())
+        .and_then(|output| self.chain.address_from_script(&output.script_pubkey).ok())
+        .map(|address| address.to_string());
+
+      let sale = match flotsam.origin {
+        Origin::Old(old_satpoint) => Self::is_sale(tx, old_satpoint.outpoint),
+        Origin::New { .. } => false,
+      };
+
+      let sequence = self
+        .id_to_locations
+        .range((&inscription_id, 0)..=(&inscription_id, u32::MAX))?
+        .next_back()
+        .transpose()?
+        .map(|(key, _location)| key.value().1 + 1)
+        .unwrap_or(0);
+
+      self.id_to_locations.insert(
+        (&inscription_id, sequence),
+        InscriptionLocation {
+          address,
+          height: self.height,
+          sale,
+          satpoint: new_satpoint,
+          timestamp: self.timestamp,
+          txid: flotsam.txid,
+        }
+        .store()
+        .as_slice(),
+      )?;
+    }
+
     let new_satpoint = new_satpoint.store();
 
     self
@@ -442,4 +520,181 @@ impl<'a, 'db, 'tx> InscriptionUpdater<'a, 'db, 'tx> {
 
     Ok(())
   }
+
+  /// Whether `tx` spends `outpoint` with a `SIGHASH_SINGLE|ANYONECANPAY`
+  /// signature, as the seller input of an offer does, in which case the
+  /// inscriptions on it were sold in exchange for a payment.
+  fn is_sale(tx: &Transaction, outpoint: OutPoint) -> bool {
+    let Some(input) = tx
+      .input
+      .iter()
+      .find(|input| input.previous_output == outpoint)
+    else {
+      return false;
+    };
+
+    let signature = match input.script_sig.instructions().next() {
+      Some(Ok(Instruction::PushBytes(signature))) => Some(signature),
+      _ => input.witness.iter().next(),
+    };
+
+    signature
+      .and_then(|signature| signature.last())
+      .map(|hash_ty| u32::from(*hash_ty))
+      == Some(EcdsaSighashType::SinglePlusAnyoneCanPay.to_u32())
+  }
+
+  /// Writes the secondary indexes used by
+  /// `Index::get_inscriptions_by_content_hash` and, if enabled,
+  /// `Index::search_inscriptions` and `Index::search_text` for a newly created
+  /// inscription.
+  fn index_search_keys(
+    &mut self,
+    inscription: &Inscription,
+    number: u64,
+    sat: Option<Sat>,
+  ) -> Result {
+    if let Some(content_hash) = inscription.content_sha256() {
+      self
+        .content_hash_to_number
+        .insert(&content_hash.into_inner(), &number)?;
+    }
+
+    if self.index_search {
+      let content_type = inscription.content_type().unwrap_or_default();
+
+      self.content_type_to_number.insert(content_type, &number)?;
+
+      self.number_to_content_type.insert(&number, content_type)?;
+
+      self.number_to_content_length.insert(
+        &number,
+        &u64::try_from(inscription.content_length().unwrap_or_default())?,
+      )?;
+
+      self.height_to_number.insert(&self.height, &number)?;
+
+      if let Some(sat) = sat {
+        self
+          .rarity_to_number
+          .insert(&u8::from(sat.rarity()), &number)?;
+      }
+
+      self.index_crc20_operation(inscription, number)?;
+    }
+
+    if self.index_text {
+      let text = inscription
+        .content_type()
+        .filter(|content_type| full_text::is_indexable(content_type))
+        .and_then(|content_type| {
+          Some((content_type, std::str::from_utf8(inscription.body()?).ok()?))
+        });
+
+      if let Some((content_type, text)) = text {
+        // Kept alongside the postings, so that searches can filter by content
+        // type without loading inscriptions
+        if !self.index_search {
+          self.number_to_content_type.insert(&number, content_type)?;
+        }
+
+        for term in full_text::terms(text) {
+          self.word_to_number.insert(term.as_str(), &number)?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  fn index_crc20_operation(&mut self, inscription: &Inscription, number: u64) -> Result {
+    let might_be_crc20 = inscription.content_type().map_or(false, |content_type| {
+      content_type.starts_with("text/plain") || content_type.starts_with("application/json")
+    });
+
+    if !might_be_crc20 {
+      return Ok(());
+    }
+
+    if let Ok(operation) = deserialize_crc20_operation(
+      inscription,
+      &Action::New {
+        inscription: inscription.clone(),
+      },
+    ) {
+      let operation = match operation.op_type() {
+        OperationType::Deploy => "deploy",
+        OperationType::Mint => "mint",
+        OperationType::InscribeTransfer | OperationType::Transfer => "transfer",
+      };
+
+      self.number_to_crc20_operation.insert(&number, operation)?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn spend(script_sig: Script, witness: Witness) -> Transaction {
+    Transaction {
+      version: 1,
+      lock_time: bitcoin::PackedLockTime::ZERO,
+      input: vec![TxIn {
+        previous_output: outpoint(1),
+        script_sig,
+        sequence: Sequence::MAX,
+        witness,
+      }],
+      output: Vec::new(),
+    }
+  }
+
+  fn signature(sighash_type: EcdsaSighashType) -> Vec<u8> {
+    let mut signature = vec![0x30; 71];
+    signature.push(sighash_type.to_u32().try_into().unwrap());
+    signature
+  }
+
+  #[test]
+  fn inputs_signed_with_sighash_single_anyonecanpay_are_sales() {
+    let script_sig = script::Builder::new()
+      .push_slice(&signature(EcdsaSighashType::SinglePlusAnyoneCanPay))
+      .push_slice(&[2; 33])
+      .into_script();
+
+    let transaction = spend(script_sig, Witness::new());
+
+    assert!(InscriptionUpdater::is_sale(&transaction, outpoint(1)));
+    assert!(!InscriptionUpdater::is_sale(&transaction, outpoint(2)));
+  }
+
+  #[test]
+  fn witness_signatures_are_checked() {
+    let transaction = spend(
+      Script::new(),
+      Witness::from_vec(vec![
+        signature(EcdsaSighashType::SinglePlusAnyoneCanPay),
+        vec![2; 33],
+      ]),
+    );
+
+    assert!(InscriptionUpdater::is_sale(&transaction, outpoint(1)));
+  }
+
+  #[test]
+  fn inputs_signed_with_sighash_all_are_not_sales() {
+    let script_sig = script::Builder::new()
+      .push_slice(&signature(EcdsaSighashType::All))
+      .push_slice(&[2; 33])
+      .into_script();
+
+    assert!(!InscriptionUpdater::is_sale(
+      &spend(script_sig, Witness::new()),
+      outpoint(1)
+    ));
+  }
 }
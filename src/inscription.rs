@@ -1,6 +1,7 @@
 use {
   bitcoin::{
     blockdata::{opcodes, script},
+    hashes::sha256,
     Script,
   },
   std::str,
@@ -114,6 +115,10 @@ impl Inscription {
     Some(self.body()?.len())
   }
 
+  pub(crate) fn content_sha256(&self) -> Option<sha256::Hash> {
+    Some(sha256::Hash::hash(self.body()?))
+  }
+
   pub(crate) fn delegate(&self) -> Option<InscriptionId> {
     Self::inscription_id_field(self.delegate.as_deref())
   }
@@ -113,6 +113,7 @@ mod decimal;
 mod deserialize_from_str;
 mod epoch;
 mod fee_rate;
+mod full_text;
 mod height;
 mod index;
 
@@ -122,7 +123,9 @@ mod decimal_sat;
 mod inscription;
 mod inscription_id;
 mod media;
+mod multisig;
 mod object;
+mod offer;
 mod options;
 mod outgoing;
 mod page_config;
@@ -130,6 +133,7 @@ mod rarity;
 mod representation;
 mod sat;
 mod sat_point;
+mod signed_message;
 pub mod subcommand;
 mod tag;
 mod tally;
@@ -144,8 +148,6 @@ static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
 static LISTENERS: Mutex<Vec<axum_server::Handle>> = Mutex::new(Vec::new());
 static INDEXER: Mutex<Option<thread::JoinHandle<()>>> = Mutex::new(Option::None);
 
-const TARGET_POSTAGE: Amount = Amount::from_sat(10_000);
-
 #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
 fn fund_raw_transaction(
   client: &Client,
@@ -161,6 +163,9 @@ fn fund_raw_transaction(
           // and *not* fee per vB. So, we multiply the fee rate given by the user
           // by 1000.
           fee_rate: Some(Amount::from_sat((fee_rate.n() * 1000.0).ceil() as u64)),
+          // Place the cardinal change output last, so that the output indices
+          // of edicts and inscriptions remain valid
+          change_position: Some(u32::try_from(unfunded_transaction.output.len())?),
           ..Default::default()
         }),
         Some(false),
@@ -50,6 +50,21 @@ impl Media {
     ("video/webm", Media::Video, &["webm"]),
   ];
 
+  /// Parses the name of a media class, such as `image` or `text`.
+  pub(crate) fn from_class(class: &str) -> Result<Self, Error> {
+    Ok(match class.to_lowercase().as_str() {
+      "audio" => Self::Audio,
+      "iframe" => Self::Iframe,
+      "image" => Self::Image,
+      "model" => Self::Model,
+      "pdf" => Self::Pdf,
+      "text" => Self::Text,
+      "unknown" => Self::Unknown,
+      "video" => Self::Video,
+      _ => return Err(anyhow!("unknown media class `{class}`")),
+    })
+  }
+
   pub(crate) fn content_type_for_path(path: &Path) -> Result<&'static str, Error> {
     let extension = path
       .extension()
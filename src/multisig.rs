@@ -0,0 +1,507 @@
+use {
+  super::*,
+  bitcoin::{
+    secp256k1::{Message, Secp256k1},
+    util::psbt::PartiallySignedTransaction,
+    EcdsaSig, PublicKey,
+  },
+};
+
+/// An m-of-n pay-to-script-hash multisig, whose redeem script requires
+/// signatures by `threshold` of `public_keys`, in key order.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Multisig {
+  pub(crate) threshold: usize,
+  pub(crate) public_keys: Vec<PublicKey>,
+}
+
+impl Multisig {
+  const MAX_PUBLIC_KEYS: usize = 15;
+  const SIGNATURE_SIZE: usize = 72;
+
+  pub(crate) fn new(threshold: usize, public_keys: Vec<PublicKey>) -> Result<Self> {
+    ensure!(
+      (2..=Self::MAX_PUBLIC_KEYS).contains(&public_keys.len()),
+      "multisig must have between 2 and {} public keys",
+      Self::MAX_PUBLIC_KEYS
+    );
+
+    ensure!(
+      (1..=public_keys.len()).contains(&threshold),
+      "multisig threshold must be between 1 and the number of public keys"
+    );
+
+    ensure!(
+      public_keys.iter().collect::<BTreeSet<&PublicKey>>().len() == public_keys.len(),
+      "multisig public keys must be unique"
+    );
+
+    Ok(Self {
+      threshold,
+      public_keys,
+    })
+  }
+
+  pub(crate) fn from_redeem_script(redeem_script: &Script) -> Result<Self> {
+    fn number(instruction: Option<Result<Instruction, script::Error>>) -> Result<usize> {
+      match instruction {
+        Some(Ok(Instruction::Op(op)))
+          if (opcodes::all::OP_PUSHNUM_1.to_u8()..=opcodes::all::OP_PUSHNUM_16.to_u8())
+            .contains(&op.to_u8()) =>
+        {
+          Ok(usize::from(
+            op.to_u8() - opcodes::all::OP_PUSHNUM_1.to_u8() + 1,
+          ))
+        }
+        _ => bail!("redeem script is not a multisig"),
+      }
+    }
+
+    let mut instructions = redeem_script.instructions().peekable();
+
+    let threshold = number(instructions.next())?;
+
+    let mut public_keys = Vec::new();
+    while let Some(Ok(Instruction::PushBytes(bytes))) = instructions.peek() {
+      public_keys.push(PublicKey::from_slice(bytes).context("redeem script has invalid key")?);
+      instructions.next();
+    }
+
+    ensure!(
+      number(instructions.next())? == public_keys.len(),
+      "redeem script key count does not match its keys"
+    );
+
+    let multisig = Self::new(threshold, public_keys)?;
+
+    ensure!(
+      multisig.redeem_script() == *redeem_script,
+      "redeem script is not a multisig"
+    );
+
+    Ok(multisig)
+  }
+
+  pub(crate) fn redeem_script(&self) -> Script {
+    let mut builder = script::Builder::new().push_int(self.threshold.try_into().unwrap());
+
+    for public_key in &self.public_keys {
+      builder = builder.push_key(public_key);
+    }
+
+    builder
+      .push_int(self.public_keys.len().try_into().unwrap())
+      .push_opcode(opcodes::all::OP_CHECKMULTISIG)
+      .into_script()
+  }
+
+  /// The multisig's address, which also receives change from its spends.
+  pub(crate) fn address(&self, network: Network) -> Result<Address> {
+    Ok(Address::p2sh(&self.redeem_script(), network)?)
+  }
+
+  /// The multisig's redeem script, if `script_pubkey` pays to its address.
+  pub(crate) fn redeem_script_for(&self, script_pubkey: &Script) -> Option<Script> {
+    let redeem_script = self.redeem_script();
+
+    (Script::new_p2sh(&redeem_script.script_hash()) == *script_pubkey).then_some(redeem_script)
+  }
+
+  /// A placeholder script sig of maximum size for a spend by `threshold`
+  /// cosigners, used for fee estimation.
+  pub(crate) fn dummy_script_sig(&self) -> Script {
+    let mut builder = script::Builder::new().push_int(0);
+
+    for _ in 0..self.threshold {
+      builder = builder.push_slice(&[0; Self::SIGNATURE_SIZE]);
+    }
+
+    builder
+      .push_slice(self.redeem_script().as_bytes())
+      .into_script()
+  }
+
+  fn verify(
+    transaction: &Transaction,
+    input: usize,
+    redeem_script: &Script,
+    public_key: &PublicKey,
+    signature: &EcdsaSig,
+  ) -> bool {
+    let sighash = transaction.signature_hash(input, redeem_script, signature.hash_ty.to_u32());
+
+    let Ok(message) = Message::from_slice(&sighash[..]) else {
+      return false;
+    };
+
+    Secp256k1::verification_only()
+      .verify_ecdsa(&message, &signature.sig, &public_key.inner)
+      .is_ok()
+  }
+
+  /// Adds the signatures in the script sigs of `signed`, a copy of the
+  /// PSBT's transaction signed by a cosigner, to the PSBT's inputs, returning
+  /// how many were added.
+  pub(crate) fn add_signatures(
+    psbt: &mut PartiallySignedTransaction,
+    signed: &Transaction,
+  ) -> Result<usize> {
+    let mut unsigned = signed.clone();
+    for input in &mut unsigned.input {
+      input.script_sig = Script::new();
+    }
+
+    ensure!(
+      unsigned == psbt.unsigned_tx,
+      "signed transaction does not match PSBT"
+    );
+
+    let mut added = 0;
+
+    for (i, (input, tx_in)) in psbt.inputs.iter_mut().zip(&signed.input).enumerate() {
+      let Some(redeem_script) = &input.redeem_script else {
+        continue;
+      };
+
+      let multisig = Self::from_redeem_script(redeem_script)?;
+
+      for instruction in tx_in.script_sig.instructions() {
+        let Ok(Instruction::PushBytes(bytes)) = instruction else {
+          continue;
+        };
+
+        let Ok(signature) = EcdsaSig::from_slice(bytes) else {
+          continue;
+        };
+
+        for public_key in &multisig.public_keys {
+          if Self::verify(&psbt.unsigned_tx, i, redeem_script, public_key, &signature)
+            && input.partial_sigs.insert(*public_key, signature).is_none()
+          {
+            added += 1;
+          }
+        }
+      }
+    }
+
+    Ok(added)
+  }
+
+  /// Number of valid signatures each input of `psbt` still needs.
+  pub(crate) fn missing_signatures(psbt: &PartiallySignedTransaction) -> Result<Vec<usize>> {
+    psbt
+      .inputs
+      .iter()
+      .enumerate()
+      .map(|(i, input)| {
+        let redeem_script = input
+          .redeem_script
+          .as_ref()
+          .ok_or_else(|| anyhow!("PSBT input {i} has no redeem script"))?;
+
+        let multisig = Self::from_redeem_script(redeem_script)?;
+
+        Ok(
+          multisig
+            .threshold
+            .saturating_sub(Self::signatures(psbt, i, &multisig, redeem_script).len()),
+        )
+      })
+      .collect()
+  }
+
+  fn signatures<'a>(
+    psbt: &'a PartiallySignedTransaction,
+    input: usize,
+    multisig: &Self,
+    redeem_script: &Script,
+  ) -> Vec<&'a EcdsaSig> {
+    multisig
+      .public_keys
+      .iter()
+      .filter_map(|public_key| {
+        psbt.inputs[input]
+          .partial_sigs
+          .get(public_key)
+          .filter(|signature| {
+            Self::verify(
+              &psbt.unsigned_tx,
+              input,
+              redeem_script,
+              public_key,
+              signature,
+            )
+          })
+      })
+      .take(multisig.threshold)
+      .collect()
+  }
+
+  /// The PSBT's transaction, with each input signed by `threshold` of its
+  /// multisig's keys.
+  pub(crate) fn finalize(psbt: &PartiallySignedTransaction) -> Result<Transaction> {
+    let missing = Self::missing_signatures(psbt)?;
+
+    if let Some((i, missing)) = missing
+      .iter()
+      .enumerate()
+      .find(|(_i, missing)| **missing > 0)
+    {
+      bail!("PSBT input {i} needs {missing} more signatures");
+    }
+
+    let mut transaction = psbt.unsigned_tx.clone();
+
+    for (i, tx_in) in transaction.input.iter_mut().enumerate() {
+      let redeem_script = psbt.inputs[i].redeem_script.as_ref().unwrap();
+
+      let multisig = Self::from_redeem_script(redeem_script)?;
+
+      let mut builder = script::Builder::new().push_int(0);
+
+      for signature in Self::signatures(psbt, i, &multisig, redeem_script) {
+        builder = builder.push_slice(&signature.to_vec());
+      }
+
+      tx_in.script_sig = builder.push_slice(redeem_script.as_bytes()).into_script();
+    }
+
+    Ok(transaction)
+  }
+
+  pub(crate) fn serialize_psbt(psbt: &PartiallySignedTransaction) -> String {
+    base64::encode(consensus::serialize(psbt))
+  }
+
+  pub(crate) fn deserialize_psbt(s: &str) -> Result<PartiallySignedTransaction> {
+    consensus::deserialize(&base64::decode(s).context("PSBT is not base64")?)
+      .context("PSBT is malformed")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    bitcoin::{EcdsaSighashType, PackedLockTime, PrivateKey},
+  };
+
+  fn key(n: u8) -> PrivateKey {
+    PrivateKey::from_slice(&[n; 32], Network::Bitcoin).unwrap()
+  }
+
+  fn multisig() -> Multisig {
+    Multisig::new(
+      2,
+      (1..=3)
+        .map(|n| key(n).public_key(&Secp256k1::new()))
+        .collect(),
+    )
+    .unwrap()
+  }
+
+  fn psbt() -> PartiallySignedTransaction {
+    let multisig = multisig();
+
+    let mut psbt = PartiallySignedTransaction::from_unsigned_tx(Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: vec![tx_in(outpoint(1)), tx_in(outpoint(2))],
+      output: vec![tx_out(10_000, recipient())],
+    })
+    .unwrap();
+
+    psbt.inputs[0].redeem_script = Some(multisig.redeem_script());
+    psbt.inputs[1].redeem_script = Some(multisig.redeem_script());
+
+    psbt
+  }
+
+  /// Signs `psbt`'s transaction with `key`, as craftcoind would.
+  fn sign(psbt: &PartiallySignedTransaction, key: &PrivateKey) -> Transaction {
+    let mut transaction = psbt.unsigned_tx.clone();
+
+    for (i, tx_in) in transaction.input.iter_mut().enumerate() {
+      let redeem_script = psbt.inputs[i].redeem_script.as_ref().unwrap();
+
+      let sighash =
+        psbt
+          .unsigned_tx
+          .signature_hash(i, redeem_script, EcdsaSighashType::All.to_u32());
+
+      let signature = EcdsaSig {
+        sig: Secp256k1::new().sign_ecdsa(&Message::from_slice(&sighash[..]).unwrap(), &key.inner),
+        hash_ty: EcdsaSighashType::All,
+      };
+
+      tx_in.script_sig = script::Builder::new()
+        .push_int(0)
+        .push_slice(&signature.to_vec())
+        .push_slice(redeem_script.as_bytes())
+        .into_script();
+    }
+
+    transaction
+  }
+
+  #[test]
+  fn redeem_script_round_trips() {
+    assert_eq!(
+      Multisig::from_redeem_script(&multisig().redeem_script()).unwrap(),
+      multisig()
+    );
+  }
+
+  #[test]
+  fn non_multisig_redeem_scripts_are_rejected() {
+    assert_eq!(
+      Multisig::from_redeem_script(&address().script_pubkey())
+        .unwrap_err()
+        .to_string(),
+      "redeem script is not a multisig"
+    );
+  }
+
+  #[test]
+  fn invalid_multisigs_are_rejected() {
+    let public_key = key(1).public_key(&Secp256k1::new());
+
+    assert_eq!(
+      Multisig::new(1, vec![public_key]).unwrap_err().to_string(),
+      "multisig must have between 2 and 15 public keys"
+    );
+
+    assert_eq!(
+      Multisig::new(3, multisig().public_keys[..2].to_vec())
+        .unwrap_err()
+        .to_string(),
+      "multisig threshold must be between 1 and the number of public keys"
+    );
+
+    assert_eq!(
+      Multisig::new(1, vec![public_key, public_key])
+        .unwrap_err()
+        .to_string(),
+      "multisig public keys must be unique"
+    );
+  }
+
+  #[test]
+  fn redeem_script_is_found_for_address() {
+    let address = multisig().address(Network::Bitcoin).unwrap();
+
+    assert_eq!(
+      multisig().redeem_script_for(&address.script_pubkey()),
+      Some(multisig().redeem_script())
+    );
+
+    assert_eq!(
+      multisig().redeem_script_for(&recipient().script_pubkey()),
+      None
+    );
+  }
+
+  #[test]
+  fn signatures_are_merged_until_threshold_is_met() {
+    let mut psbt = psbt();
+
+    assert_eq!(Multisig::missing_signatures(&psbt).unwrap(), [2, 2]);
+
+    assert_eq!(
+      Multisig::add_signatures(&mut psbt, &sign(&psbt, &key(3))).unwrap(),
+      2
+    );
+
+    assert_eq!(Multisig::missing_signatures(&psbt).unwrap(), [1, 1]);
+
+    assert_eq!(
+      Multisig::finalize(&psbt).unwrap_err().to_string(),
+      "PSBT input 0 needs 1 more signatures"
+    );
+
+    let mut other = self::psbt();
+
+    Multisig::add_signatures(&mut other, &sign(&other, &key(1))).unwrap();
+
+    psbt.combine(other).unwrap();
+
+    assert_eq!(Multisig::missing_signatures(&psbt).unwrap(), [0, 0]);
+
+    let transaction = Multisig::finalize(&psbt).unwrap();
+
+    for (i, tx_in) in transaction.input.iter().enumerate() {
+      let pushes = tx_in
+        .script_sig
+        .instructions()
+        .map(|instruction| match instruction.unwrap() {
+          Instruction::PushBytes(bytes) => bytes.to_vec(),
+          Instruction::Op(op) => panic!("unexpected opcode {op:?}"),
+        })
+        .collect::<Vec<Vec<u8>>>();
+
+      let redeem_script = psbt.inputs[i].redeem_script.as_ref().unwrap();
+
+      assert_eq!(pushes.len(), 4);
+      assert!(pushes[0].is_empty());
+      assert_eq!(pushes[3], redeem_script.as_bytes());
+
+      let multisig = Multisig::from_redeem_script(redeem_script).unwrap();
+
+      let signers = multisig
+        .public_keys
+        .iter()
+        .filter(|public_key| {
+          **public_key == key(1).public_key(&Secp256k1::new())
+            || **public_key == key(3).public_key(&Secp256k1::new())
+        })
+        .collect::<Vec<&PublicKey>>();
+
+      for (signature, public_key) in pushes[1..3].iter().zip(signers) {
+        assert!(Multisig::verify(
+          &psbt.unsigned_tx,
+          i,
+          redeem_script,
+          public_key,
+          &EcdsaSig::from_slice(signature).unwrap()
+        ));
+      }
+    }
+  }
+
+  #[test]
+  fn signatures_over_other_transactions_are_rejected() {
+    let mut psbt = psbt();
+
+    let mut signed = sign(&psbt, &key(1));
+    signed.output[0].value = 1;
+
+    assert_eq!(
+      Multisig::add_signatures(&mut psbt, &signed)
+        .unwrap_err()
+        .to_string(),
+      "signed transaction does not match PSBT"
+    );
+  }
+
+  #[test]
+  fn signatures_by_other_keys_are_ignored() {
+    let mut psbt = psbt();
+
+    assert_eq!(
+      Multisig::add_signatures(&mut psbt, &sign(&psbt, &key(4))).unwrap(),
+      0
+    );
+  }
+
+  #[test]
+  fn psbt_round_trips() {
+    let mut psbt = psbt();
+
+    Multisig::add_signatures(&mut psbt, &sign(&psbt, &key(2))).unwrap();
+
+    assert_eq!(
+      Multisig::deserialize_psbt(&Multisig::serialize_psbt(&psbt)).unwrap(),
+      psbt
+    );
+  }
+}
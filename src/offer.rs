@@ -0,0 +1,305 @@
+use {
+  super::*,
+  bitcoin::{
+    consensus::encode::{deserialize, serialize_hex},
+    secp256k1::{Message, Secp256k1},
+    EcdsaSig, EcdsaSighashType, PackedLockTime, PublicKey,
+  },
+};
+
+/// An offer to sell the sats of one output, in the form of a transaction
+/// fragment whose seller input is signed with `SIGHASH_SINGLE|ANYONECANPAY`.
+///
+/// That signature commits only to the seller input and the output paying the
+/// seller at the same index, so the buyer can add their own inputs and
+/// outputs without invalidating it. Index 0 is left for the buyer's padding
+/// input and the output receiving the offered sats, which puts the seller
+/// input and payment at index 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Offer {
+  transaction: Transaction,
+}
+
+impl Offer {
+  pub(crate) const SELLER: usize = 1;
+
+  /// The unsigned offer transaction, with placeholders at index 0 which the
+  /// buyer replaces.
+  pub(crate) fn template(outgoing: OutPoint, price: Amount, seller: &Address) -> Transaction {
+    Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: vec![
+        TxIn {
+          previous_output: OutPoint::null(),
+          script_sig: Script::new(),
+          sequence: Sequence::MAX,
+          witness: Witness::new(),
+        },
+        TxIn {
+          previous_output: outgoing,
+          script_sig: Script::new(),
+          sequence: Sequence::MAX,
+          witness: Witness::new(),
+        },
+      ],
+      output: vec![
+        TxOut {
+          value: 0,
+          script_pubkey: Script::new(),
+        },
+        TxOut {
+          value: price.to_sat(),
+          script_pubkey: seller.script_pubkey(),
+        },
+      ],
+    }
+  }
+
+  pub(crate) fn from_transaction(transaction: Transaction) -> Result<Self> {
+    ensure!(
+      transaction.input.len() == 2 && transaction.output.len() == 2,
+      "offer must have two inputs and two outputs"
+    );
+
+    let (signature, _public_key) = Self::script_sig_pushes(&transaction)?;
+
+    ensure!(
+      matches!(
+        signature
+          .last()
+          .map(|hash_ty| EcdsaSighashType::from_standard(u32::from(*hash_ty))),
+        Some(Ok(EcdsaSighashType::SinglePlusAnyoneCanPay))
+      ),
+      "offer is not signed with SIGHASH_SINGLE|ANYONECANPAY"
+    );
+
+    Ok(Self { transaction })
+  }
+
+  fn script_sig_pushes(transaction: &Transaction) -> Result<(&[u8], &[u8])> {
+    let pushes = transaction.input[Self::SELLER]
+      .script_sig
+      .instructions()
+      .map(|instruction| match instruction {
+        Ok(Instruction::PushBytes(bytes)) => Ok(bytes),
+        _ => Err(anyhow!(
+          "offer seller input script is not a signature and public key"
+        )),
+      })
+      .collect::<Result<Vec<&[u8]>>>()?;
+
+    let [signature, public_key] = pushes.as_slice() else {
+      bail!("offer seller input is not signed");
+    };
+
+    Ok((*signature, *public_key))
+  }
+
+  pub(crate) fn transaction(&self) -> &Transaction {
+    &self.transaction
+  }
+
+  pub(crate) fn outgoing(&self) -> OutPoint {
+    self.transaction.input[Self::SELLER].previous_output
+  }
+
+  pub(crate) fn payment(&self) -> &TxOut {
+    &self.transaction.output[Self::SELLER]
+  }
+
+  pub(crate) fn price(&self) -> Amount {
+    Amount::from_sat(self.payment().value)
+  }
+
+  /// Checks that the seller input is validly signed for `prevout`, the output
+  /// it spends, which must pay to the hash of the signing key.
+  pub(crate) fn verify(&self, prevout: &TxOut) -> Result {
+    let (signature, public_key) = Self::script_sig_pushes(&self.transaction)?;
+
+    let signature = EcdsaSig::from_slice(signature).context("offer signature is malformed")?;
+
+    let public_key = PublicKey::from_slice(public_key).context("offer public key is malformed")?;
+
+    ensure!(
+      prevout.script_pubkey == Script::new_p2pkh(&public_key.pubkey_hash()),
+      "offered output is not locked to the offer public key"
+    );
+
+    let sighash = self.transaction.signature_hash(
+      Self::SELLER,
+      &prevout.script_pubkey,
+      signature.hash_ty.to_u32(),
+    );
+
+    Secp256k1::verification_only()
+      .verify_ecdsa(
+        &Message::from_slice(&sighash[..])?,
+        &signature.sig,
+        &public_key.inner,
+      )
+      .context("offer signature is invalid")
+  }
+}
+
+impl Display for Offer {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", serialize_hex(&self.transaction))
+  }
+}
+
+impl FromStr for Offer {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::from_transaction(deserialize(&hex::decode(s)?).context("offer is not a transaction")?)
+  }
+}
+
+impl Serialize for Offer {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    serializer.collect_str(self)
+  }
+}
+
+impl<'de> Deserialize<'de> for Offer {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    Ok(DeserializeFromStr::deserialize(deserializer)?.0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, bitcoin::PrivateKey};
+
+  fn sign(
+    transaction: &mut Transaction,
+    key: &PrivateKey,
+    prevout: &TxOut,
+    hash_ty: EcdsaSighashType,
+  ) {
+    let secp = Secp256k1::new();
+
+    let sighash =
+      transaction.signature_hash(Offer::SELLER, &prevout.script_pubkey, hash_ty.to_u32());
+
+    let signature = EcdsaSig {
+      sig: secp.sign_ecdsa(&Message::from_slice(&sighash[..]).unwrap(), &key.inner),
+      hash_ty,
+    };
+
+    transaction.input[Offer::SELLER].script_sig = script::Builder::new()
+      .push_slice(&signature.to_vec())
+      .push_key(&key.public_key(&secp))
+      .into_script();
+  }
+
+  fn key() -> PrivateKey {
+    PrivateKey::from_slice(&[1; 32], Network::Bitcoin).unwrap()
+  }
+
+  fn prevout(key: &PrivateKey) -> TxOut {
+    TxOut {
+      value: 100_000,
+      script_pubkey: Script::new_p2pkh(&key.public_key(&Secp256k1::new()).pubkey_hash()),
+    }
+  }
+
+  fn offer(hash_ty: EcdsaSighashType) -> Transaction {
+    let key = key();
+    let mut transaction = Offer::template(outpoint(1), Amount::from_sat(500_000), &address());
+    sign(&mut transaction, &key, &prevout(&key), hash_ty);
+    transaction
+  }
+
+  #[test]
+  fn signed_offer_verifies() {
+    let offer = Offer::from_transaction(offer(EcdsaSighashType::SinglePlusAnyoneCanPay)).unwrap();
+
+    assert_eq!(offer.outgoing(), outpoint(1));
+    assert_eq!(offer.price(), Amount::from_sat(500_000));
+    assert_eq!(offer.payment().script_pubkey, address().script_pubkey());
+
+    offer.verify(&prevout(&key())).unwrap();
+  }
+
+  #[test]
+  fn signature_survives_buyer_inputs_and_outputs() {
+    let mut offer =
+      Offer::from_transaction(offer(EcdsaSighashType::SinglePlusAnyoneCanPay)).unwrap();
+
+    offer.transaction.input[0].previous_output = outpoint(2);
+    offer.transaction.input.push(tx_in(outpoint(3)));
+    offer.transaction.output[0] = tx_out(600_000, recipient());
+    offer.transaction.output.push(tx_out(1_000, change(0)));
+
+    offer.verify(&prevout(&key())).unwrap();
+  }
+
+  #[test]
+  fn changed_price_does_not_verify() {
+    let mut offer =
+      Offer::from_transaction(offer(EcdsaSighashType::SinglePlusAnyoneCanPay)).unwrap();
+
+    offer.transaction.output[Offer::SELLER].value = 1;
+
+    assert_eq!(
+      offer.verify(&prevout(&key())).unwrap_err().to_string(),
+      "offer signature is invalid"
+    );
+  }
+
+  #[test]
+  fn offer_for_other_output_does_not_verify() {
+    let offer = Offer::from_transaction(offer(EcdsaSighashType::SinglePlusAnyoneCanPay)).unwrap();
+
+    assert_eq!(
+      offer
+        .verify(&tx_out(100_000, recipient()))
+        .unwrap_err()
+        .to_string(),
+      "offered output is not locked to the offer public key"
+    );
+  }
+
+  #[test]
+  fn offer_must_be_signed_with_single_anyone_can_pay() {
+    assert_eq!(
+      Offer::from_transaction(offer(EcdsaSighashType::All))
+        .unwrap_err()
+        .to_string(),
+      "offer is not signed with SIGHASH_SINGLE|ANYONECANPAY"
+    );
+  }
+
+  #[test]
+  fn unsigned_offer_is_rejected() {
+    assert_eq!(
+      Offer::from_transaction(Offer::template(
+        outpoint(1),
+        Amount::from_sat(500_000),
+        &address()
+      ))
+      .unwrap_err()
+      .to_string(),
+      "offer seller input is not signed"
+    );
+  }
+
+  #[test]
+  fn round_trip() {
+    let offer = Offer::from_transaction(offer(EcdsaSighashType::SinglePlusAnyoneCanPay)).unwrap();
+
+    assert_eq!(offer.to_string().parse::<Offer>().unwrap(), offer);
+    assert_eq!(
+      serde_json::from_str::<Offer>(&serde_json::to_string(&offer).unwrap()).unwrap(),
+      offer
+    );
+  }
+}
@@ -52,8 +52,22 @@ pub(crate) struct Options {
     help = "Track location of cunes. CUNES ARE IN AN UNFINISHED PRE-ALPHA STATE AND SUBJECT TO CHANGE AT ANY TIME."
   )]
   pub(crate) index_cunes: bool,
+  #[arg(
+    long,
+    help = "Record inscription and cune movements for `ord wallet transactions`."
+  )]
+  pub(crate) index_history: bool,
+  #[arg(
+    long,
+    help = "Record every location of each inscription for `/inscription/<INSCRIPTION_ID>/history`."
+  )]
+  pub(crate) index_locations: bool,
   #[arg(long, help = "Track location of all satoshis.")]
   pub(crate) index_sats: bool,
+  #[arg(long, help = "Index inscriptions for `/inscriptions/search`.")]
+  pub(crate) index_search: bool,
+  #[arg(long, help = "Build a full-text index of text and JSON inscriptions.")]
+  pub(crate) index_text: bool,
   #[arg(long, help = "Store transactions in index.")]
   pub(crate) index_transactions: bool,
   #[arg(long, short, help = "Use regtest. Equivalent to `--chain regtest`.")]
@@ -68,6 +82,11 @@ pub(crate) struct Options {
   pub(crate) testnet: bool,
   #[arg(long, default_value = "ord", help = "Use wallet named <WALLET>.")]
   pub(crate) wallet: String,
+  #[arg(
+    long,
+    help = "Query outputs of watch-only address group <WATCH_ONLY> instead of wallet."
+  )]
+  pub(crate) watch_only: Option<String>,
 }
 
 impl Options {
@@ -609,6 +628,7 @@ mod tests {
         .unwrap(),
       Config {
         hidden: iter::once(id).collect(),
+        ..Default::default()
       }
     );
   }
@@ -640,6 +660,7 @@ mod tests {
       .unwrap(),
       Config {
         hidden: iter::once(id).collect(),
+        ..Default::default()
       }
     );
   }
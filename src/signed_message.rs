@@ -0,0 +1,143 @@
+use {
+  super::*,
+  bitcoin::{
+    hashes::sha256d, secp256k1::Secp256k1, util::address::AddressType,
+    util::misc::MessageSignature, PublicKey,
+  },
+};
+
+/// Prefix which craftcoind's `signmessage` and `verifymessage` hash messages
+/// with, so that a signed message can never be mistaken for a signed
+/// transaction.
+const MAGIC: &str = "Craftcoin Signed Message:\n";
+
+/// Hash of `message` as signed by craftcoind: the double SHA-256 of the magic
+/// prefix and the message, each preceded by its length.
+pub(crate) fn hash(message: &str) -> sha256d::Hash {
+  let mut data = consensus::serialize(&MAGIC.to_string());
+  data.extend(consensus::serialize(&message.to_string()));
+  sha256d::Hash::hash(&data)
+}
+
+/// Checks that the base64-encoded `signature` over `message` was made with
+/// the key of `address`, returning that key.
+pub(crate) fn verify(address: &Address, message: &str, signature: &str) -> Result<PublicKey> {
+  ensure!(
+    address.address_type() == Some(AddressType::P2pkh),
+    "address {address} cannot sign messages, only pay-to-pubkey-hash addresses can"
+  );
+
+  let signature = base64::decode(signature).context("signature is not base64")?;
+
+  let signature = MessageSignature::from_slice(&signature).context("signature is malformed")?;
+
+  let public_key = signature
+    .recover_pubkey(&Secp256k1::verification_only(), hash(message))
+    .context("signature is invalid")?;
+
+  ensure!(
+    Address::p2pkh(&public_key, address.network) == *address,
+    "message was not signed by {address}"
+  );
+
+  Ok(public_key)
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    bitcoin::{secp256k1::Message, PrivateKey},
+  };
+
+  fn key() -> PrivateKey {
+    PrivateKey::from_slice(&[1; 32], Network::Bitcoin).unwrap()
+  }
+
+  fn signer() -> Address {
+    Address::p2pkh(&key().public_key(&Secp256k1::new()), Network::Bitcoin)
+  }
+
+  fn sign(message: &str) -> String {
+    let signature = Secp256k1::new().sign_ecdsa_recoverable(
+      &Message::from_slice(&hash(message)[..]).unwrap(),
+      &key().inner,
+    );
+
+    base64::encode(MessageSignature::new(signature, true).serialize())
+  }
+
+  #[test]
+  fn hash_commits_to_magic_and_message_length() {
+    assert_ne!(hash("foo"), hash("bar"));
+    assert_ne!(hash(""), sha256d::Hash::hash(&[]));
+
+    let mut data = vec![u8::try_from(MAGIC.len()).unwrap()];
+    data.extend(MAGIC.as_bytes());
+    data.push(3);
+    data.extend(b"foo");
+
+    assert_eq!(hash("foo"), sha256d::Hash::hash(&data));
+  }
+
+  #[test]
+  fn signature_verifies() {
+    assert_eq!(
+      verify(&signer(), "foo", &sign("foo")).unwrap(),
+      key().public_key(&Secp256k1::new())
+    );
+  }
+
+  #[test]
+  fn signature_over_other_message_does_not_verify() {
+    assert_eq!(
+      verify(&signer(), "bar", &sign("foo"))
+        .unwrap_err()
+        .to_string(),
+      format!("message was not signed by {}", signer())
+    );
+  }
+
+  #[test]
+  fn signature_by_other_address_does_not_verify() {
+    let other = Address::p2pkh(
+      &PrivateKey::from_slice(&[2; 32], Network::Bitcoin)
+        .unwrap()
+        .public_key(&Secp256k1::new()),
+      Network::Bitcoin,
+    );
+
+    assert_eq!(
+      verify(&other, "foo", &sign("foo")).unwrap_err().to_string(),
+      format!("message was not signed by {other}")
+    );
+  }
+
+  #[test]
+  fn malformed_signatures_are_rejected() {
+    assert_eq!(
+      verify(&signer(), "foo", "!").unwrap_err().to_string(),
+      "signature is not base64"
+    );
+
+    assert_eq!(
+      verify(&signer(), "foo", &base64::encode([0; 64]))
+        .unwrap_err()
+        .to_string(),
+      "signature is malformed"
+    );
+  }
+
+  #[test]
+  fn only_pay_to_pubkey_hash_addresses_can_sign() {
+    assert_eq!(
+      verify(&address(), "foo", &sign("foo"))
+        .unwrap_err()
+        .to_string(),
+      format!(
+        "address {} cannot sign messages, only pay-to-pubkey-hash addresses can",
+        address()
+      )
+    );
+  }
+}
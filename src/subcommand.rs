@@ -12,6 +12,7 @@ mod preview;
 mod server;
 pub mod subsidy;
 pub mod traits;
+pub mod verify_message;
 pub mod wallet;
 
 fn print_json(output: impl Serialize) -> Result {
@@ -46,6 +47,8 @@ pub(crate) enum Subcommand {
   Subsidy(subsidy::Subsidy),
   #[command(about = "Display satoshi traits")]
   Traits(traits::Traits),
+  #[command(about = "Verify message signed with `ord wallet sign-message` or craftcoind")]
+  VerifyMessage(verify_message::VerifyMessage),
   #[command(subcommand, about = "Wallet commands")]
   Wallet(wallet::Wallet),
 }
@@ -70,6 +73,7 @@ impl Subcommand {
       }
       Self::Subsidy(subsidy) => subsidy.run(),
       Self::Traits(traits) => traits.run(),
+      Self::VerifyMessage(verify_message) => verify_message.run(),
       Self::Wallet(wallet) => wallet.run(options),
     }
   }
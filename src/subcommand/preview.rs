@@ -1,4 +1,4 @@
-use {super::*, fee_rate::FeeRate};
+use super::*;
 
 #[derive(Debug, Parser)]
 pub(crate) struct Preview {
@@ -78,7 +78,7 @@ impl Preview {
         options: options.clone(),
         subcommand: Subcommand::Wallet(super::wallet::Wallet::Inscribe(
           super::wallet::inscribe::Inscribe {
-            fee_rate: FeeRate::try_from(1.0).unwrap(),
+            fee_rate: None,
             commit_fee_rate: None,
             file,
             no_backup: true,
@@ -86,6 +86,8 @@ impl Preview {
             dry_run: false,
             no_limit: false,
             destination: None,
+            postage: None,
+            coin_control: Default::default(),
           },
         )),
       }
@@ -6,32 +6,43 @@ use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use serde_json::json;
 use {
   self::{
+    byte_range::ByteRange,
+    cache::ResponseCache,
     deserialize_from_str::DeserializeFromStr,
     error::{OptionExt, ServerError, ServerResult},
+    pagination::{next_link, Pagination},
+    rate_limit::{RateLimiter, UsageJson},
   },
   super::*,
   crate::{
     crc20::{script_key::ScriptKey, Tick},
+    index::InscriptionSearch,
+    offer::Offer,
     page_config::PageConfig,
+    signed_message,
     templates::{
-      AddressOutputJson, BlockHtml, BlockJson, CraftscriptionJson, CuneAddressJson, CuneBalance,
-      CuneBalancesHtml, CuneEntryJson, CuneHtml, CuneJson, CuneOutput, CuneOutputJson, CunesHtml,
-      HomeHtml, InputHtml, InscriptionByAddressJson, InscriptionHtml, InscriptionJson,
-      InscriptionsHtml, Operation, OutputHtml, OutputJson, PageContent, PageHtml, PreviewAudioHtml,
-      PreviewImageHtml, PreviewModelHtml, PreviewPdfHtml, PreviewTextHtml, PreviewUnknownHtml,
-      PreviewVideoHtml, RangeHtml, RareTxt, SatHtml, TransactionHtml, Utxo, CRC20,
+      AddressOutputJson, BatchCrc20BalanceJson, BatchInscriptionJson, BatchJson, BlockHtml,
+      BlockJson, CraftscriptionJson, CuneAddressJson, CuneBalance, CuneBalancesHtml, CuneEntryJson,
+      CuneHtml, CuneJson, CuneOutput, CuneOutputJson, CunesHtml, HomeHtml, InputHtml,
+      InscriptionByAddressJson, InscriptionHtml, InscriptionJson, InscriptionSearchJson,
+      InscriptionsByHashJson, InscriptionsHtml, Operation, OutputHtml, OutputJson, PageContent,
+      PageHtml, PreviewAudioHtml, PreviewImageHtml, PreviewModelHtml, PreviewPdfHtml,
+      PreviewTextHtml, PreviewUnknownHtml, PreviewVideoHtml, RangeHtml, RareTxt, SatHtml,
+      SearchHtml, SearchJson, SearchResult, TransactionHtml, Utxo, CRC20,
     },
   },
   axum::{
     body,
-    extract::{Extension, Json, Path, Query},
+    extract::{Extension, Json, OriginalUri, Path, Query},
     headers::UserAgent,
     http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    middleware,
     response::{IntoResponse, Redirect, Response},
-    routing::get,
+    routing::{get, post},
     Router, TypedHeader,
   },
   axum_server::Handle,
+  bitcoin::hashes::sha256,
   rust_embed::RustEmbed,
   rustls_acme::{
     acme::{LETS_ENCRYPT_PRODUCTION_DIRECTORY, LETS_ENCRYPT_STAGING_DIRECTORY},
@@ -41,17 +52,29 @@ use {
   },
   serde_json::to_string,
   std::collections::HashMap,
+  std::net::SocketAddr,
   std::{cmp::Ordering, str},
   tokio_stream::StreamExt,
   tower_http::{
-    compression::CompressionLayer,
+    compression::{
+      predicate::{DefaultPredicate, Predicate},
+      CompressionLayer,
+    },
     cors::{Any, CorsLayer},
     set_header::SetResponseHeaderLayer,
   },
 };
 
+mod api;
+mod byte_range;
+mod cache;
 mod error;
+mod pagination;
 mod query;
+mod rate_limit;
+
+/// Maximum number of items accepted by the `/api/batch` endpoints.
+const MAX_BATCH_SIZE: usize = 1000;
 
 // Helper function to get transaction details
 fn get_transaction_details(
@@ -105,6 +128,7 @@ fn get_transaction_details(
 pub(crate) struct InscriptionAddressJson {
   pub(crate) inscriptions: Vec<InscriptionByAddressJson>,
   pub(crate) total_inscriptions: usize,
+  pub(crate) next: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -113,10 +137,54 @@ pub(crate) struct UtxoAddressJson {
   pub(crate) total_utxos: usize,
   pub(crate) total_shibes: u128,
   pub(crate) total_inscription_shibes: u128,
+  pub(crate) next: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct OfferValidationJson {
+  pub(crate) valid: bool,
+  pub(crate) error: Option<String>,
+  pub(crate) satpoint: SatPoint,
+  pub(crate) price: Option<u64>,
+  pub(crate) inscriptions: Vec<InscriptionId>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct InscriptionHistoryJson {
+  pub(crate) inscription_id: InscriptionId,
+  pub(crate) locations: Vec<InscriptionLocationJson>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct InscriptionLocationJson {
+  pub(crate) satpoint: SatPoint,
+  pub(crate) address: Option<String>,
+  pub(crate) height: u32,
+  pub(crate) timestamp: u32,
+  pub(crate) txid: Txid,
+  pub(crate) sale: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Crc20HoldingJson {
+  pub(crate) tick: String,
+  pub(crate) overall: String,
+  pub(crate) transferable: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct VerifyJson {
+  pub(crate) valid: bool,
+  pub(crate) error: Option<String>,
+  pub(crate) address: String,
+  pub(crate) inscriptions: Vec<InscriptionId>,
+  pub(crate) cunes: BTreeMap<String, u128>,
+  pub(crate) crc20: Vec<Crc20HoldingJson>,
 }
 
 #[derive(Deserialize)]
 struct UtxoBalanceQuery {
+  cursor: Option<String>,
   limit: Option<usize>,
   show_all: Option<bool>,
   show_unsafe: Option<bool>,
@@ -130,6 +198,8 @@ struct Crc20TickInfoQuery {
 
 #[derive(Deserialize)]
 struct Crc20BalanceQuery {
+  cursor: Option<String>,
+  limit: Option<usize>,
   show_all: Option<bool>,
   show_utxos: Option<bool>,
   tick: Option<String>,
@@ -186,11 +256,120 @@ struct ValidityQuery {
   inscription_ids: String,
 }
 
+#[derive(Deserialize)]
+struct InscriptionSearchQuery {
+  address: Option<String>,
+  content_type: Option<String>,
+  crc20: Option<bool>,
+  from: Option<u64>,
+  limit: Option<usize>,
+  max_content_length: Option<u64>,
+  max_height: Option<u32>,
+  max_timestamp: Option<u32>,
+  media: Option<String>,
+  min_content_length: Option<u64>,
+  min_height: Option<u32>,
+  min_timestamp: Option<u32>,
+  rarity: Option<Rarity>,
+}
+
+impl InscriptionSearchQuery {
+  const DEFAULT_LIMIT: usize = 100;
+  const MAX_LIMIT: usize = 1000;
+
+  fn into_search(self, chain: Chain, index_sats: bool) -> Result<InscriptionSearch> {
+    fn range<T: PartialOrd + Display>(
+      name: &str,
+      min: Option<T>,
+      max: Option<T>,
+      lowest: T,
+      highest: T,
+    ) -> Result<Option<(T, T)>> {
+      if min.is_none() && max.is_none() {
+        return Ok(None);
+      }
+
+      let min = min.unwrap_or(lowest);
+      let max = max.unwrap_or(highest);
+
+      ensure!(
+        min <= max,
+        "min_{name} {min} is greater than max_{name} {max}"
+      );
+
+      Ok(Some((min, max)))
+    }
+
+    let address = self
+      .address
+      .map(|address| -> Result<Address> {
+        let address = Address::from_str(&address)?;
+        ensure!(
+          address.network == chain.network(),
+          "address {address} is not valid for {chain}"
+        );
+        Ok(address)
+      })
+      .transpose()?;
+
+    ensure!(
+      self.rarity.is_none() || index_sats,
+      "searching by rarity requires index created with `--index-sats` flag"
+    );
+
+    ensure!(
+      self.limit != Some(0) && self.limit.unwrap_or_default() <= Self::MAX_LIMIT,
+      "limit must be between 1 and {}",
+      Self::MAX_LIMIT
+    );
+
+    Ok(InscriptionSearch {
+      address,
+      content_length: range(
+        "content_length",
+        self.min_content_length,
+        self.max_content_length,
+        0,
+        u64::MAX,
+      )?,
+      content_type: self.content_type,
+      crc20: self.crc20,
+      from: self.from,
+      height: range("height", self.min_height, self.max_height, 0, u32::MAX)?,
+      limit: self.limit.unwrap_or(Self::DEFAULT_LIMIT),
+      media: self.media.as_deref().map(Media::from_class).transpose()?,
+      rarity: self.rarity,
+      timestamp: range(
+        "timestamp",
+        self.min_timestamp,
+        self.max_timestamp,
+        0,
+        u32::MAX,
+      )?,
+    })
+  }
+}
+
+#[derive(Deserialize)]
+struct OfferQuery {
+  offer: String,
+  satpoint: String,
+}
+
+#[derive(Deserialize)]
+struct VerifyQuery {
+  address: String,
+  message: String,
+  signature: String,
+}
+
 #[derive(Deserialize)]
 struct CunesBalanceQuery {
-  show_all: Option<bool>,
-  list_cunes: Option<bool>,
+  cursor: Option<String>,
   filter: Option<SpacedCune>,
+  limit: Option<usize>,
+  list_cunes: Option<bool>,
+  show_all: Option<bool>,
 }
 
 #[derive(Deserialize)]
@@ -198,6 +377,16 @@ struct Search {
   query: String,
 }
 
+#[derive(Deserialize)]
+struct SearchQuery {
+  content_type: Option<String>,
+  from: Option<u64>,
+  json: Option<bool>,
+  limit: Option<usize>,
+  q: Option<String>,
+  query: Option<String>,
+}
+
 #[derive(RustEmbed)]
 #[folder = "static"]
 struct StaticAssets;
@@ -253,6 +442,12 @@ pub(crate) struct Server {
   https: bool,
   #[clap(long, help = "Redirect HTTP traffic to HTTPS.")]
   redirect_http_to_https: bool,
+  #[clap(
+    long,
+    default_value = "64",
+    help = "Cache up to <RESPONSE_CACHE_SIZE> MiB of responses in memory. 0 disables the cache."
+  )]
+  response_cache_size: usize,
 }
 
 impl Server {
@@ -272,6 +467,11 @@ impl Server {
       INDEXER.lock().unwrap().replace(index_thread);
 
       let config = options.load_config()?;
+      let rate_limiter = Arc::new(RateLimiter::new(&config));
+      let response_cache = Arc::new(ResponseCache::new(
+        index.clone(),
+        self.response_cache_size.saturating_mul(1 << 20),
+      ));
       let acme_domains = self.acme_domains()?;
 
       let page_config = Arc::new(PageConfig {
@@ -293,9 +493,22 @@ impl Server {
         .route("/feed.xml", get(Self::feed))
         .route("/input/:block/:transaction/:input", get(Self::input))
         .route("/inscription/:inscription_id", get(Self::inscription))
+        .route(
+          "/inscription/:inscription_id/history",
+          get(Self::inscription_history),
+        )
         .route("/inscriptions", get(Self::inscriptions))
         .route("/inscriptions/:from", get(Self::inscriptions_from))
+        .route("/inscriptions/search", get(Self::inscriptions_search))
+        .route(
+          "/inscriptions/by-hash/:sha256",
+          get(Self::inscriptions_by_hash),
+        )
         .route("/craftscription/:inscription_id", get(Self::inscription))
+        .route(
+          "/craftscription/:inscription_id/history",
+          get(Self::inscription_history),
+        )
         .route("/craftscriptions", get(Self::inscriptions))
         .route("/craftscriptions/:from", get(Self::inscriptions_from))
         .route(
@@ -311,6 +524,14 @@ impl Server {
         .route("/output/:output", get(Self::output))
         .route("/outputs/:output_list", get(Self::outputs))
         .route("/address/:address", get(Self::outputs_by_address))
+        .nest("/api/v1", api::router())
+        .route("/api/batch/outputs", post(Self::batch_outputs))
+        .route("/api/batch/inscriptions", post(Self::batch_inscriptions))
+        .route("/api/batch/cune-balances", post(Self::batch_cune_balances))
+        .route(
+          "/api/batch/crc20-balances",
+          post(Self::batch_crc20_balances),
+        )
         .route("/preview/:inscription_id", get(Self::preview))
         .route("/range/:start/:end", get(Self::range))
         .route("/rare.txt", get(Self::rare_txt))
@@ -336,6 +557,7 @@ impl Server {
           get(Self::inscriptions_by_address),
         )
         .route("/inscriptions/validate", get(Self::inscriptions_validate))
+        .route("/offer/validate", get(Self::offer_validate))
         .route("/crc20/tick/:tick", get(Self::crc20_tick_info))
         .route("/crc20/tick", get(Self::crc20_all_tick_info))
         .route(
@@ -351,10 +573,18 @@ impl Server {
         .route("/search/*query", get(Self::search_by_path))
         .route("/static/*path", get(Self::static_asset))
         .route("/status", get(Self::status))
+        .route("/api/usage", get(Self::usage))
         .route("/tx/:txid", get(Self::transaction))
+        .route("/verify", get(Self::verify))
         .layer(Extension(index))
         .layer(Extension(page_config))
         .layer(Extension(Arc::new(config)))
+        .layer(Extension(rate_limiter.clone()))
+        .layer(middleware::from_fn_with_state(response_cache, cache::cache))
+        .layer(middleware::from_fn_with_state(
+          rate_limiter,
+          rate_limit::rate_limit,
+        ))
         .layer(SetResponseHeaderLayer::if_not_present(
           header::CONTENT_SECURITY_POLICY,
           HeaderValue::from_static("default-src 'self'"),
@@ -365,10 +595,17 @@ impl Server {
         ))
         .layer(
           CorsLayer::new()
-            .allow_methods([http::Method::GET])
+            .allow_headers([
+              header::CONTENT_TYPE,
+              HeaderName::from_static(rate_limit::API_KEY_HEADER),
+            ])
+            .allow_methods([http::Method::GET, http::Method::POST])
             .allow_origin(Any),
         )
-        .layer(CompressionLayer::new());
+        .layer(
+          CompressionLayer::new()
+            .compress_when(DefaultPredicate::new().and(Self::is_not_partial_content)),
+        );
 
       match (self.http_port(), self.https_port()) {
         (Some(http_port), None) => {
@@ -443,7 +680,7 @@ impl Server {
           axum_server::Server::bind(addr)
             .handle(handle)
             .acceptor(acceptor)
-            .serve(router.into_make_service())
+            .serve(router.into_make_service_with_connect_info::<SocketAddr>())
             .await
         }
         SpawnConfig::Redirect(destination) => {
@@ -460,7 +697,7 @@ impl Server {
         SpawnConfig::Http => {
           axum_server::Server::bind(addr)
             .handle(handle)
-            .serve(router.into_make_service())
+            .serve(router.into_make_service_with_connect_info::<SocketAddr>())
             .await
         }
       }
@@ -612,16 +849,18 @@ impl Server {
     Extension(index): Extension<Arc<Index>>,
     Path(params): Path<(String, u32)>,
     Query(query): Query<UtxoBalanceQuery>,
+    OriginalUri(uri): OriginalUri,
   ) -> ServerResult<Response> {
-    Self::get_utxos_by_address(index, params.0, Some(params.1), query).await
+    Self::get_utxos_by_address(index, params.0, Some(params.1), query, uri).await
   }
 
   async fn utxos_by_address_unpaginated(
     Extension(index): Extension<Arc<Index>>,
     Path(params): Path<String>,
     Query(query): Query<UtxoBalanceQuery>,
+    OriginalUri(uri): OriginalUri,
   ) -> ServerResult<Response> {
-    Self::get_utxos_by_address(index, params, None, query).await
+    Self::get_utxos_by_address(index, params, None, query, uri).await
   }
 
   async fn get_utxos_by_address(
@@ -629,46 +868,35 @@ impl Server {
     address: String,
     page: Option<u32>,
     query: UtxoBalanceQuery,
+    uri: Uri,
   ) -> ServerResult<Response> {
-    let (address, page) = (address, page.unwrap_or(0));
     let show_all = query.show_all.unwrap_or(false);
     let value_filter = query.value_filter.unwrap_or(0);
     let show_unsafe = query.show_unsafe.unwrap_or(false);
 
-    let items_per_page = query.limit.unwrap_or(10);
-    let page = page as usize;
-    let start_index = if page == 0 || page == 1 {
-      0
-    } else {
-      (page - 1) * items_per_page + 1
-    };
-    let mut element_counter = 0;
-
-    let outpoints: Vec<OutPoint> = index.get_account_outputs(address.clone())?;
+    let pagination = Pagination::for_index(
+      &index,
+      query.cursor.as_deref(),
+      page,
+      (!show_all).then(|| query.limit.unwrap_or(Pagination::DEFAULT_LIMIT)),
+    )?;
 
-    let mut utxos = Vec::new();
+    let mut outputs = Vec::new();
     let mut total_shibes = 0u128;
     let mut inscription_shibes = 0u128;
 
-    for outpoint in outpoints {
+    for outpoint in index.get_account_outputs(address)? {
       if !index.get_cune_balances_for_outpoint(outpoint)?.is_empty() {
         continue;
       }
-      if !show_all
-        && (element_counter < start_index || element_counter > start_index + items_per_page - 1)
-      {
-        continue;
-      }
 
-      let txid = outpoint.txid;
-      let vout = outpoint.vout;
       let output = index
-        .get_transaction(txid)?
-        .ok_or_not_found(|| format!("{txid} current transaction"))?
+        .get_transaction(outpoint.txid)?
+        .ok_or_not_found(|| format!("{} current transaction", outpoint.txid))?
         .output
         .into_iter()
-        .nth(vout.try_into().unwrap())
-        .ok_or_not_found(|| format!("{vout} current transaction output"))?;
+        .nth(outpoint.vout.try_into().unwrap())
+        .ok_or_not_found(|| format!("{} current transaction output", outpoint.vout))?;
 
       if value_filter > 0 && output.value <= value_filter {
         continue;
@@ -681,30 +909,39 @@ impl Server {
         }
       }
 
-      element_counter += 1;
-
       total_shibes += output.value as u128;
 
-      let confirmations = if let Some(block_hash_info) = index.get_transaction_blockhash(txid)? {
-        block_hash_info.confirmations
-      } else {
-        None
-      };
+      outputs.push((outpoint, output));
+    }
+
+    let page = pagination.paginate(outputs);
+
+    let mut utxos = Vec::new();
+
+    for (outpoint, output) in page.items {
+      let confirmations =
+        if let Some(block_hash_info) = index.get_transaction_blockhash(outpoint.txid)? {
+          block_hash_info.confirmations
+        } else {
+          None
+        };
 
       utxos.push(Utxo {
-        txid,
-        vout,
+        txid: outpoint.txid,
+        vout: outpoint.vout,
         script: output.script_pubkey,
         shibes: output.value,
         confirmations,
       });
     }
+
     Ok(
       Json(UtxoAddressJson {
         utxos,
         total_shibes,
-        total_utxos: element_counter,
+        total_utxos: page.total,
         total_inscription_shibes: inscription_shibes,
+        next: page.next.map(|cursor| next_link(&uri, cursor)),
       })
       .into_response(),
     )
@@ -714,16 +951,18 @@ impl Server {
     Extension(index): Extension<Arc<Index>>,
     Path(params): Path<(String, u32)>,
     Query(query): Query<Crc20BalanceQuery>,
+    OriginalUri(uri): OriginalUri,
   ) -> ServerResult<Response> {
-    Self::get_crc20_by_address(index, params.0, Some(params.1), query).await
+    Self::get_crc20_by_address(index, params.0, Some(params.1), query, uri).await
   }
 
   async fn crc20_by_address_unpaginated(
     Extension(index): Extension<Arc<Index>>,
     Path(params): Path<String>,
     Query(query): Query<Crc20BalanceQuery>,
+    OriginalUri(uri): OriginalUri,
   ) -> ServerResult<Response> {
-    Self::get_crc20_by_address(index, params, None, query).await
+    Self::get_crc20_by_address(index, params, None, query, uri).await
   }
 
   async fn get_crc20_by_address(
@@ -731,9 +970,19 @@ impl Server {
     address: String,
     page: Option<u32>,
     query: Crc20BalanceQuery,
+    uri: Uri,
   ) -> ServerResult<Response> {
     task::block_in_place(|| {
-      let (address, page) = (address.clone(), page.unwrap_or(0));
+      let pagination = Pagination::for_index(
+        &index,
+        query.cursor.as_deref(),
+        page,
+        if query.show_all.unwrap_or(false) {
+          None
+        } else {
+          query.limit.or(page.map(|_| Pagination::DEFAULT_LIMIT))
+        },
+      )?;
       let address_from_str =
         Address::from_str(&address).map_err(|err| ServerError::BadRequest(err.to_string()))?;
       let value_filter = query.value_filter.unwrap_or(0);
@@ -856,12 +1105,6 @@ impl Server {
           }
         }
       }
-      /*let show_all = query.show_all.unwrap_or(false);
-      let items_per_page = 10usize;
-      let page = page as usize;
-      let mut start_index = if page == 0 { 0 } else { (page - 1) * items_per_page };
-      let mut element_counter = 0;*/
-
       let mut crc20balances: Vec<CRC20Balance> = Vec::new();
 
       let balance = index
@@ -902,7 +1145,9 @@ impl Server {
             })
             .collect();
           let token_info = index.get_crc20_token_info(&entry.tick.clone())?;
-          let token_info_clone = token_info.clone().unwrap();
+          let token_info_clone = token_info
+            .clone()
+            .ok_or_not_found(|| format!("tick {}", entry.tick))?;
           let decimals = token_info_clone.decimal;
           let overall_balance = entry.overall_balance;
           let transferable_balance = entry.transferable_balance;
@@ -916,7 +1161,16 @@ impl Server {
           }
         }
       }
-      Ok(Json(json!({"crc20": crc20balances})).into_response())
+      let page = pagination.paginate(crc20balances);
+
+      Ok(
+        Json(json!({
+          "crc20": page.items,
+          "total": page.total,
+          "next": page.next.map(|cursor| next_link(&uri, cursor)),
+        }))
+        .into_response(),
+      )
     })
   }
 
@@ -924,16 +1178,18 @@ impl Server {
     Extension(index): Extension<Arc<Index>>,
     Path(params): Path<(String, u32)>,
     Query(query): Query<UtxoBalanceQuery>,
+    OriginalUri(uri): OriginalUri,
   ) -> ServerResult<Response> {
-    Self::get_inscriptions_by_address(index, params.0, Some(params.1), query).await
+    Self::get_inscriptions_by_address(index, params.0, Some(params.1), query, uri).await
   }
 
   async fn inscriptions_by_address_unpaginated(
     Extension(index): Extension<Arc<Index>>,
     Path(params): Path<String>,
     Query(query): Query<UtxoBalanceQuery>,
+    OriginalUri(uri): OriginalUri,
   ) -> ServerResult<Response> {
-    Self::get_inscriptions_by_address(index, params, None, query).await
+    Self::get_inscriptions_by_address(index, params, None, query, uri).await
   }
 
   async fn get_inscriptions_by_address(
@@ -941,140 +1197,135 @@ impl Server {
     address: String,
     page: Option<u32>,
     query: UtxoBalanceQuery,
+    uri: Uri,
   ) -> ServerResult<Response> {
-    let (address, page) = (address, page.unwrap_or(0));
     let show_all = query.show_all.unwrap_or(false);
     let value_filter = query.value_filter.unwrap_or(0);
 
-    let items_per_page = query.limit.unwrap_or(10);
-    let page = page as usize;
-    let start_index = if page == 0 || page == 1 {
-      0
-    } else {
-      (page - 1) * items_per_page + 1
-    };
-    let mut element_counter = 0;
-
-    let mut all_inscriptions_json = Vec::new();
-    let outpoints: Vec<OutPoint> = index.get_account_outputs(address)?;
+    let pagination = Pagination::for_index(
+      &index,
+      query.cursor.as_deref(),
+      page,
+      (!show_all).then(|| query.limit.unwrap_or(Pagination::DEFAULT_LIMIT)),
+    )?;
 
-    for outpoint in outpoints {
-      let inscriptions = index.get_inscriptions_on_output(outpoint)?;
+    let mut inscriptions = Vec::new();
 
-      if inscriptions.is_empty() {
-        continue;
-      }
+    for outpoint in index.get_account_outputs(address)? {
+      let inscription_ids = index.get_inscriptions_on_output(outpoint)?;
 
-      element_counter += 1;
-      if !show_all
-        && (element_counter < start_index || element_counter > start_index + items_per_page - 1)
-      {
+      if inscription_ids.is_empty() {
         continue;
       }
 
-      let txid = outpoint.txid;
-      let vout = outpoint.vout;
-
       let output = index
-        .get_transaction(txid)?
-        .ok_or_not_found(|| format!("cunes {txid} current transaction"))?
+        .get_transaction(outpoint.txid)?
+        .ok_or_not_found(|| format!("cunes {} current transaction", outpoint.txid))?
         .output
         .into_iter()
-        .nth(vout.try_into().unwrap())
-        .ok_or_not_found(|| format!("cunes {vout} current transaction output"))?;
-      let shibes = output.value;
-      let script = output.script_pubkey;
+        .nth(outpoint.vout.try_into().unwrap())
+        .ok_or_not_found(|| format!("cunes {} current transaction output", outpoint.vout))?;
 
-      if value_filter > 0 && shibes <= value_filter {
-        element_counter -= 1;
+      if value_filter > 0 && output.value <= value_filter {
         continue;
       }
 
-      for inscription_id in inscriptions {
+      for inscription_id in inscription_ids {
         let inscription = index
           .get_inscription_by_id(inscription_id)?
           .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
 
-        let entry = index
-          .get_inscription_entry(inscription_id)?
-          .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+        if Self::inscription_text(&inscription)
+          .and_then(|content| CRC20::from_json_string(&content))
+          .is_some()
+        {
+          continue;
+        }
 
-        let satpoint = index
-          .get_inscription_satpoint_by_id(inscription_id)?
-          .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+        inscriptions.push((outpoint, output.clone(), inscription_id, inscription));
+      }
+    }
 
-        let content_type = inscription.content_type().map(|s| s.to_string());
-        let content_length = inscription.content_length();
-        let content = inscription.into_body();
+    let page = pagination.paginate(inscriptions);
 
-        let str_content = match (content_type.clone(), content) {
-          (Some(ref ct), Some(c))
-            if ct.starts_with("application/json") || ct.starts_with("text") =>
-          {
-            Some(String::from_utf8_lossy(c.as_slice()).to_string())
-          }
-          (None, Some(c)) => Some(String::from_utf8_lossy(c.as_slice()).to_string()),
-          _ => None,
-        };
+    let mut all_inscriptions_json = Vec::new();
 
-        if let Some(content) = str_content.clone() {
-          let crc20 = CRC20::from_json_string(content.as_str());
-          if crc20.is_some() {
-            element_counter = element_counter.checked_sub(1).unwrap_or(0);
-            continue;
-          }
-        };
+    for (outpoint, output, inscription_id, inscription) in page.items {
+      let entry = index
+        .get_inscription_entry(inscription_id)?
+        .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+
+      let satpoint = index
+        .get_inscription_satpoint_by_id(inscription_id)?
+        .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
 
-        let confirmations = if let Some(block_hash_info) = index.get_transaction_blockhash(txid)? {
+      let confirmations =
+        if let Some(block_hash_info) = index.get_transaction_blockhash(outpoint.txid)? {
           block_hash_info.confirmations
         } else {
           None
         };
 
-        let inscription_json = InscriptionByAddressJson {
-          utxo: Utxo {
-            txid,
-            vout,
-            script: script.clone(),
-            shibes,
-            confirmations,
-          },
-          content: str_content,
-          content_length,
-          content_type,
-          genesis_height: entry.height,
-          inscription_id,
-          inscription_number: entry.inscription_number,
-          timestamp: entry.timestamp,
-          offset: satpoint.offset,
-        };
-
-        all_inscriptions_json.push(inscription_json);
-      }
+      all_inscriptions_json.push(InscriptionByAddressJson {
+        utxo: Utxo {
+          txid: outpoint.txid,
+          vout: outpoint.vout,
+          script: output.script_pubkey,
+          shibes: output.value,
+          confirmations,
+        },
+        content: Self::inscription_text(&inscription),
+        content_length: inscription.content_length(),
+        content_type: inscription.content_type().map(|s| s.to_string()),
+        genesis_height: entry.height,
+        inscription_id,
+        inscription_number: entry.inscription_number,
+        timestamp: entry.timestamp,
+        offset: satpoint.offset,
+      });
     }
+
     Ok(
       Json(InscriptionAddressJson {
         inscriptions: all_inscriptions_json,
-        total_inscriptions: element_counter,
+        total_inscriptions: page.total,
+        next: page.next.map(|cursor| next_link(&uri, cursor)),
       })
       .into_response(),
     )
   }
 
+  /// Content of text and JSON inscriptions, and of inscriptions without a
+  /// content type, as served by the address endpoints.
+  fn inscription_text(inscription: &Inscription) -> Option<String> {
+    match inscription.content_type() {
+      Some(content_type)
+        if !content_type.starts_with("application/json") && !content_type.starts_with("text") =>
+      {
+        None
+      }
+      _ => inscription
+        .body()
+        .map(|body| String::from_utf8_lossy(body).to_string()),
+    }
+  }
+
   async fn cunes_by_address(
     Extension(index): Extension<Arc<Index>>,
     Path(params): Path<(String, u32)>,
     Query(query): Query<CunesBalanceQuery>,
+    OriginalUri(uri): OriginalUri,
   ) -> ServerResult<Response> {
-    Self::get_cunes_by_address(index, params.0, Some(params.1), query).await
+    Self::get_cunes_by_address(index, params.0, Some(params.1), query, uri).await
   }
 
   async fn cunes_by_address_unpaginated(
     Extension(index): Extension<Arc<Index>>,
     Path(params): Path<String>,
     Query(query): Query<CunesBalanceQuery>,
+    OriginalUri(uri): OriginalUri,
   ) -> ServerResult<Response> {
-    Self::get_cunes_by_address(index, params, None, query).await
+    Self::get_cunes_by_address(index, params, None, query, uri).await
   }
 
   async fn get_cunes_by_address(
@@ -1082,25 +1333,23 @@ impl Server {
     address: String,
     page: Option<u32>,
     query: CunesBalanceQuery,
+    uri: Uri,
   ) -> ServerResult<Response> {
-    let (address, page) = (address, page.unwrap_or(0));
     let show_all = query.show_all.unwrap_or(false);
     let list_cunes = query.list_cunes.unwrap_or(false);
 
-    let outpoints = index.get_account_outputs(address)?;
+    let pagination = Pagination::for_index(
+      &index,
+      query.cursor.as_deref(),
+      page,
+      (!show_all).then(|| query.limit.unwrap_or(Pagination::DEFAULT_LIMIT)),
+    )?;
 
-    let items_per_page = 10usize;
-    let page = page as usize;
-    let mut start_index = if page == 0 {
-      0
-    } else {
-      (page - 1) * items_per_page
-    };
+    let mut cune_balances_map: LinkedHashMap<SpacedCune, (CuneBalance, Vec<(OutPoint, u128)>)> =
+      LinkedHashMap::new();
     let mut elements_counter = 0;
 
-    let mut cune_balances_map: LinkedHashMap<SpacedCune, CuneBalance> = LinkedHashMap::new();
-
-    for outpoint in outpoints {
+    for outpoint in index.get_account_outputs(address)? {
       let cunes = index.get_cune_balances_for_outpoint(outpoint)?;
       for (cune, balances) in cunes {
         if let Some(filter) = query.filter {
@@ -1108,36 +1357,21 @@ impl Server {
             continue;
           }
         }
-        let cune_balance = cune_balances_map
-          .entry(cune.clone())
-          .or_insert_with(|| CuneBalance {
-            cune: cune.clone(),
-            divisibility: balances.divisibility,
-            symbol: balances.symbol,
-            total_balance: 0,
-            total_outputs: 0,
-            balances: Vec::new(),
-          });
-
-        if !list_cunes {
-          let txid = outpoint.txid;
-          let vout = outpoint.vout;
-          let output = index
-            .get_transaction(txid)?
-            .ok_or_not_found(|| format!("cunes {txid} current transaction"))?
-            .output
-            .into_iter()
-            .nth(vout.try_into().unwrap())
-            .ok_or_not_found(|| format!("cunes {vout} current transaction output"))?;
+        let (cune_balance, outputs) = cune_balances_map.entry(cune.clone()).or_insert_with(|| {
+          (
+            CuneBalance {
+              cune: cune.clone(),
+              divisibility: balances.divisibility,
+              symbol: balances.symbol,
+              total_balance: 0,
+              total_outputs: 0,
+              balances: Vec::new(),
+            },
+            Vec::new(),
+          )
+        });
 
-          cune_balance.balances.push(CuneOutput {
-            txid,
-            vout,
-            script: output.script_pubkey,
-            shibes: output.value,
-            balance: balances.amount,
-          });
-        }
+        outputs.push((outpoint, balances.amount));
 
         cune_balance.total_balance += balances.amount;
         cune_balance.total_outputs += 1;
@@ -1145,52 +1379,59 @@ impl Server {
       }
     }
 
-    let cune_balances: Vec<CuneBalance> = if show_all {
-      cune_balances_map.values().cloned().collect()
-    } else if list_cunes {
-      cune_balances_map
-        .values()
-        .cloned()
-        .skip(start_index)
-        .take(items_per_page)
-        .collect()
+    let total_cunes = cune_balances_map.len();
+
+    let (cune_balances, next) = if list_cunes {
+      let page = pagination.paginate(
+        cune_balances_map
+          .into_iter()
+          .map(|(_cune, (cune_balance, _outputs))| cune_balance),
+      );
+
+      (page.items, page.next)
     } else {
-      let values: Vec<CuneBalance> = cune_balances_map.values().cloned().collect();
-      let mut items_collected = 0;
-      let mut result = Vec::new();
-      for value in values.iter() {
-        let balances: Vec<CuneOutput> = value
-          .balances
-          .iter()
-          .skip(start_index)
-          .take(items_per_page - items_collected)
-          .cloned()
-          .collect();
-        items_collected += balances.len();
-        start_index -= value.balances.len().min(start_index);
-        if balances.is_empty() {
-          continue;
+      let page = pagination.paginate(cune_balances_map.values().flat_map(
+        |(cune_balance, outputs)| {
+          outputs
+            .iter()
+            .map(move |(outpoint, balance)| (&cune_balance.cune, *outpoint, *balance))
+        },
+      ));
+
+      let mut result: Vec<CuneBalance> = Vec::new();
+
+      for (cune, outpoint, balance) in page.items {
+        let output = index
+          .get_transaction(outpoint.txid)?
+          .ok_or_not_found(|| format!("cunes {} current transaction", outpoint.txid))?
+          .output
+          .into_iter()
+          .nth(outpoint.vout.try_into().unwrap())
+          .ok_or_not_found(|| format!("cunes {} current transaction output", outpoint.vout))?;
+
+        if result.last().map(|last| &last.cune) != Some(cune) {
+          let (cune_balance, _outputs) = &cune_balances_map[cune];
+          result.push(cune_balance.clone());
         }
-        result.push(CuneBalance {
-          cune: value.cune.clone(),
-          divisibility: value.divisibility,
-          symbol: value.symbol.clone(),
-          total_balance: value.total_balance,
-          total_outputs: value.total_outputs,
-          balances,
+
+        result.last_mut().unwrap().balances.push(CuneOutput {
+          txid: outpoint.txid,
+          vout: outpoint.vout,
+          script: output.script_pubkey,
+          shibes: output.value,
+          balance,
         });
-        if items_collected >= items_per_page {
-          break;
-        }
       }
-      result
+
+      (result, page.next)
     };
 
     Ok(
       Json(CuneAddressJson {
         cunes: cune_balances,
-        total_cunes: cune_balances_map.len(),
+        total_cunes,
         total_elements: elements_counter,
+        next: next.map(|cursor| next_link(&uri, cursor)),
       })
       .into_response(),
     )
@@ -1199,26 +1440,24 @@ impl Server {
   async fn outputs_by_address(
     Extension(index): Extension<Arc<Index>>,
     Path(address): Path<String>,
-  ) -> Result<String, ServerError> {
-    let mut outputs = vec![];
+  ) -> ServerResult<Json<Vec<AddressOutputJson>>> {
     let outpoints = index.get_account_outputs(address)?;
 
-    outputs.push(AddressOutputJson::new(outpoints));
-
-    let outputs_json = to_string(&outputs).context("Failed to serialize outputs")?;
-
-    Ok(outputs_json)
+    Ok(Json(vec![AddressOutputJson::new(outpoints)]))
   }
 
   async fn outputs(
     Extension(server_config): Extension<Arc<PageConfig>>,
     Extension(index): Extension<Arc<Index>>,
     Path(outpoints_str): Path<String>,
-  ) -> Result<String, ServerError> {
-    let outpoints: Vec<OutPoint> = outpoints_str
+  ) -> ServerResult<Json<Vec<OutputJson>>> {
+    let outpoints = outpoints_str
       .split(',')
-      .map(|s| OutPoint::from_str(s).expect("Failed to parse OutPoint"))
-      .collect();
+      .map(|outpoint| {
+        OutPoint::from_str(outpoint)
+          .map_err(|err| ServerError::BadRequest(format!("invalid outpoint `{outpoint}`: {err}")))
+      })
+      .collect::<ServerResult<Vec<OutPoint>>>()?;
     let mut outputs = vec![];
     for outpoint in outpoints {
       let list = index.list(outpoint)?;
@@ -1259,43 +1498,183 @@ impl Server {
       ))
     }
 
-    let outputs_json = to_string(&outputs).context("Failed to serialize outputs")?;
+    Ok(Json(outputs))
+  }
+
+  fn check_batch_size(len: usize) -> ServerResult<()> {
+    if len > MAX_BATCH_SIZE {
+      return Err(ServerError::BadRequest(format!(
+        "batch of {len} items exceeds maximum of {MAX_BATCH_SIZE}"
+      )));
+    }
 
-    Ok(outputs_json)
+    Ok(())
   }
 
-  async fn crc20_tick_info(
+  async fn batch_outputs(
+    Extension(page_config): Extension<Arc<PageConfig>>,
     Extension(index): Extension<Arc<Index>>,
-    Path(tick): Path<String>,
-    Query(query): Query<Crc20TickInfoQuery>,
-  ) -> Result<Response, ServerError> {
-    let tick =
-      &Tick::from_str(tick.as_str()).map_err(|err| ServerError::BadRequest(err.to_string()))?;
-    let token_info = index.get_crc20_token_info(&tick.clone())?;
+    Json(outpoints): Json<Vec<OutPoint>>,
+  ) -> ServerResult<Json<BatchJson<Option<OutputJson>>>> {
+    Self::check_batch_size(outpoints.len())?;
 
-    if query.show_holder.unwrap_or(false) {
-      let holder = index.get_crc20_token_holder(&tick.clone())?;
+    if !index.has_transaction_index() {
+      return Err(ServerError::BadRequest(
+        "batch output lookups require index created with `--index-transactions` flag".into(),
+      ));
+    }
 
-      let mut holder_to_balance: HashMap<String, HolderBalanceForTick> = HashMap::new();
+    let batch = index.batch_outputs(&outpoints)?;
 
-      for script_key in holder.clone() {
-        if let Some(balance) = index
-          .get_crc20_balance(&script_key, &tick)
-          .map_err(|err| ServerError::BadRequest(err.to_string()))?
-        {
-          let token_info_clone = token_info.clone().unwrap();
-          let decimals = token_info_clone.decimal;
-          let overall_balance = balance.overall_balance;
-          let transferable_balance = balance.transferable_balance;
-          holder_to_balance.insert(
-            script_key.to_string(),
-            HolderBalanceForTick {
-              overall_balance: format_balance(overall_balance, decimals),
-              transferable_balance: format_balance(transferable_balance, decimals),
-              available_balance: format_balance(overall_balance - transferable_balance, decimals),
-            },
-          );
-        }
+    Ok(Json(BatchJson {
+      height: batch.height.map(|height| height.n()),
+      results: outpoints
+        .into_iter()
+        .zip(batch.results)
+        .map(|(outpoint, output)| {
+          output.map(|output| {
+            OutputJson::new(
+              page_config.chain,
+              output.inscriptions,
+              outpoint,
+              output.tx_out,
+              output.cunes,
+            )
+          })
+        })
+        .collect(),
+    }))
+  }
+
+  async fn batch_inscriptions(
+    Extension(index): Extension<Arc<Index>>,
+    Json(inscription_ids): Json<Vec<InscriptionId>>,
+  ) -> ServerResult<Json<BatchJson<Option<BatchInscriptionJson>>>> {
+    Self::check_batch_size(inscription_ids.len())?;
+
+    let batch = index.batch_inscriptions(&inscription_ids)?;
+
+    Ok(Json(BatchJson {
+      height: batch.height.map(|height| height.n()),
+      results: inscription_ids
+        .into_iter()
+        .zip(batch.results)
+        .map(|(inscription_id, result)| {
+          result.map(|result| BatchInscriptionJson {
+            content_length: result.inscription.content_length(),
+            content_type: result.inscription.content_type().map(|s| s.to_string()),
+            genesis_fee: result.entry.fee,
+            genesis_height: result.entry.height,
+            inscription_id,
+            inscription_number: result.entry.inscription_number,
+            satpoint: result.satpoint,
+            timestamp: result.entry.timestamp,
+          })
+        })
+        .collect(),
+    }))
+  }
+
+  async fn batch_cune_balances(
+    Extension(index): Extension<Arc<Index>>,
+    Json(outpoints): Json<Vec<OutPoint>>,
+  ) -> ServerResult<Json<BatchJson<Vec<CuneOutputJson>>>> {
+    Self::check_batch_size(outpoints.len())?;
+
+    let batch = index.batch_cune_balances(&outpoints)?;
+
+    Ok(Json(BatchJson {
+      height: batch.height.map(|height| height.n()),
+      results: batch
+        .results
+        .into_iter()
+        .map(|cunes| {
+          cunes
+            .into_iter()
+            .map(|(cune, balances)| CuneOutputJson { cune, balances })
+            .collect()
+        })
+        .collect(),
+    }))
+  }
+
+  async fn batch_crc20_balances(
+    Extension(index): Extension<Arc<Index>>,
+    Json(addresses): Json<Vec<String>>,
+  ) -> ServerResult<Json<BatchJson<Vec<BatchCrc20BalanceJson>>>> {
+    Self::check_batch_size(addresses.len())?;
+
+    let network = index.get_network()?;
+
+    let script_keys = addresses
+      .iter()
+      .map(|address| {
+        Address::from_str(address)
+          .map(|address| ScriptKey::from_address(address, network))
+          .map_err(|err| ServerError::BadRequest(format!("invalid address `{address}`: {err}")))
+      })
+      .collect::<ServerResult<Vec<ScriptKey>>>()?;
+
+    let batch = index.batch_crc20_balances(&script_keys)?;
+
+    Ok(Json(BatchJson {
+      height: batch.height.map(|height| height.n()),
+      results: batch
+        .results
+        .into_iter()
+        .map(|balances| {
+          balances
+            .into_iter()
+            .filter(|(balance, _decimals)| balance.overall_balance > 0)
+            .map(|(balance, decimals)| BatchCrc20BalanceJson {
+              tick: balance.tick.as_str().to_string(),
+              available: format_balance(
+                balance.overall_balance - balance.transferable_balance,
+                decimals,
+              ),
+              transferable: format_balance(balance.transferable_balance, decimals),
+              overall: format_balance(balance.overall_balance, decimals),
+            })
+            .collect()
+        })
+        .collect(),
+    }))
+  }
+
+  async fn crc20_tick_info(
+    Extension(index): Extension<Arc<Index>>,
+    Path(tick): Path<String>,
+    Query(query): Query<Crc20TickInfoQuery>,
+  ) -> Result<Response, ServerError> {
+    let tick =
+      &Tick::from_str(tick.as_str()).map_err(|err| ServerError::BadRequest(err.to_string()))?;
+    let token_info = index.get_crc20_token_info(&tick.clone())?;
+
+    if query.show_holder.unwrap_or(false) {
+      let holder = index.get_crc20_token_holder(&tick.clone())?;
+
+      let mut holder_to_balance: HashMap<String, HolderBalanceForTick> = HashMap::new();
+
+      for script_key in holder.clone() {
+        if let Some(balance) = index
+          .get_crc20_balance(&script_key, &tick)
+          .map_err(|err| ServerError::BadRequest(err.to_string()))?
+        {
+          let token_info_clone = token_info
+            .clone()
+            .ok_or_not_found(|| format!("tick {tick}"))?;
+          let decimals = token_info_clone.decimal;
+          let overall_balance = balance.overall_balance;
+          let transferable_balance = balance.transferable_balance;
+          holder_to_balance.insert(
+            script_key.to_string(),
+            HolderBalanceForTick {
+              overall_balance: format_balance(overall_balance, decimals),
+              transferable_balance: format_balance(transferable_balance, decimals),
+              available_balance: format_balance(overall_balance - transferable_balance, decimals),
+            },
+          );
+        }
       }
 
       let nr_of_holder = holder.len();
@@ -1336,7 +1715,9 @@ impl Server {
         .map_err(|err| ServerError::BadRequest(err.to_string()))
         .unwrap_or(None)
       {
-        let token_info_clone = token_info.clone().unwrap();
+        let token_info_clone = token_info
+          .clone()
+          .ok_or_not_found(|| format!("tick {tick}"))?;
         let decimals = token_info_clone.decimal;
         let overall_balance = balance.overall_balance;
         let transferable_balance = balance.transferable_balance;
@@ -1523,12 +1904,17 @@ impl Server {
       DeserializeFromStr<Sat>,
     )>,
   ) -> ServerResult<PageHtml<RangeHtml>> {
+    Self::check_range(start, end)?;
+    Ok(RangeHtml { start, end }.page(page_config))
+  }
+
+  fn check_range(start: Sat, end: Sat) -> ServerResult<()> {
     match start.cmp(&end) {
       Ordering::Equal => Err(ServerError::BadRequest("empty range".to_string())),
       Ordering::Greater => Err(ServerError::BadRequest(
         "range start greater than range end".to_string(),
       )),
-      Ordering::Less => Ok(RangeHtml { start, end }.page(page_config)),
+      Ordering::Less => Ok(()),
     }
   }
 
@@ -1581,18 +1967,7 @@ impl Server {
       .into_response()
     } else {
       Json(CuneJson {
-        entry: CuneEntryJson {
-          burned: entry.burned,
-          divisibility: entry.divisibility,
-          etching: entry.etching,
-          mint: entry.terms,
-          mints: entry.mints,
-          number: entry.number,
-          cune: entry.spaced_cune(),
-          supply: entry.supply,
-          symbol: entry.symbol,
-          timestamp: entry.timestamp,
-        },
+        entry: CuneEntryJson::new(&entry),
         id,
         mintable,
         inscription,
@@ -2072,10 +2447,109 @@ impl Server {
   }
 
   async fn search_by_query(
+    Extension(page_config): Extension<Arc<PageConfig>>,
     Extension(index): Extension<Arc<Index>>,
-    Query(search): Query<Search>,
-  ) -> ServerResult<Redirect> {
-    Self::search(&index, &search.query).await
+    Extension(config): Extension<Arc<Config>>,
+    Query(search): Query<SearchQuery>,
+  ) -> ServerResult<Response> {
+    match (&search.q, &search.query) {
+      (Some(_), _) => Self::search_text(page_config, &index, &config, search),
+      (None, Some(query)) => Ok(Self::search(&index, query).await?.into_response()),
+      (None, None) => Err(ServerError::BadRequest(
+        "search requires `q` or `query` parameter".into(),
+      )),
+    }
+  }
+
+  fn search_text(
+    page_config: Arc<PageConfig>,
+    index: &Index,
+    config: &Config,
+    search: SearchQuery,
+  ) -> ServerResult<Response> {
+    if !index.has_text_index() {
+      return Err(ServerError::BadRequest(
+        "full-text search requires index created with `--index-text` flag".into(),
+      ));
+    }
+
+    let query = search.q.unwrap_or_default();
+
+    let terms = full_text::terms(&query);
+
+    if terms.is_empty() {
+      return Err(ServerError::BadRequest(format!(
+        "search query `{query}` contains no searchable words"
+      )));
+    }
+
+    let limit = search
+      .limit
+      .unwrap_or(InscriptionSearchQuery::DEFAULT_LIMIT);
+
+    if limit == 0 || limit > InscriptionSearchQuery::MAX_LIMIT {
+      return Err(ServerError::BadRequest(format!(
+        "limit must be between 1 and {}",
+        InscriptionSearchQuery::MAX_LIMIT
+      )));
+    }
+
+    let (candidates, remaining) = index.search_text(
+      &terms,
+      search.content_type.as_deref(),
+      search.from.unwrap_or(u64::MAX),
+    )?;
+
+    let mut results = Vec::new();
+    let mut next = None;
+
+    for (number, inscription_id) in candidates {
+      if config.is_hidden(inscription_id) {
+        continue;
+      }
+
+      if results.len() == limit {
+        next = Some(number);
+        break;
+      }
+
+      let Some(inscription) = index.get_inscription_by_id(inscription_id)? else {
+        continue;
+      };
+
+      results.push(SearchResult {
+        inscription_id,
+        inscription_number: number,
+        content_type: inscription
+          .content_type()
+          .map(|content_type| content_type.into()),
+        snippet: inscription
+          .body()
+          .and_then(|body| str::from_utf8(body).ok())
+          .map(|text| full_text::snippet(text, &terms))
+          .unwrap_or_default(),
+      });
+    }
+
+    if next.is_none() {
+      next = remaining;
+    }
+
+    if search.json.unwrap_or_default() {
+      Ok(Json(SearchJson { results, next }).into_response())
+    } else {
+      Ok(
+        SearchHtml {
+          content_type: search.content_type,
+          limit,
+          next,
+          query,
+          results,
+        }
+        .page(page_config)
+        .into_response(),
+      )
+    }
   }
 
   async fn search_by_path(
@@ -2212,6 +2686,20 @@ impl Server {
     )
   }
 
+  async fn usage(
+    Extension(rate_limiter): Extension<Arc<RateLimiter>>,
+    headers: HeaderMap,
+  ) -> ServerResult<Json<UsageJson>> {
+    rate_limiter
+      .usage(
+        headers
+          .get(rate_limit::API_KEY_HEADER)
+          .and_then(|value| value.to_str().ok()),
+      )
+      .map(Json)
+      .ok_or_else(|| ServerError::Unauthorized("API key required".into()))
+  }
+
   async fn block_count(Extension(index): Extension<Arc<Index>>) -> ServerResult<String> {
     Ok(index.block_count()?.to_string())
   }
@@ -2221,6 +2709,16 @@ impl Server {
     Extension(index): Extension<Arc<Index>>,
     Path(path): Path<(u32, usize, usize)>,
   ) -> Result<PageHtml<InputHtml>, ServerError> {
+    Ok(
+      InputHtml {
+        path,
+        input: Self::input_at(&index, path)?,
+      }
+      .page(page_config),
+    )
+  }
+
+  fn input_at(index: &Index, path: (u32, usize, usize)) -> ServerResult<TxIn> {
     let not_found = || format!("input /{}/{}/{}", path.0, path.1, path.2);
 
     let block = index
@@ -2233,13 +2731,11 @@ impl Server {
       .nth(path.1)
       .ok_or_not_found(not_found)?;
 
-    let input = transaction
+    transaction
       .input
       .into_iter()
       .nth(path.2)
-      .ok_or_not_found(not_found)?;
-
-    Ok(InputHtml { path, input }.page(page_config))
+      .ok_or_not_found(not_found)
   }
 
   async fn faq() -> Redirect {
@@ -2255,6 +2751,7 @@ impl Server {
     Extension(config): Extension<Arc<Config>>,
     Path(inscription_id): Path<InscriptionId>,
     Extension(page_config): Extension<Arc<PageConfig>>,
+    headers: HeaderMap,
   ) -> ServerResult<Response> {
     if config.is_hidden(inscription_id) {
       return Ok(PreviewUnknownHtml.into_response());
@@ -2270,15 +2767,115 @@ impl Server {
         .ok_or_not_found(|| format!("delegate {inscription_id}"))?
     }
 
-    Ok(
-      Self::content_response(inscription, &page_config)
-        .ok_or_not_found(|| format!("inscription {inscription_id} content"))?
-        .into_response(),
-    )
+    Ok(Self::ranged_response(
+      &headers,
+      Self::content_response(inscription, inscription_id, &page_config)
+        .ok_or_not_found(|| format!("inscription {inscription_id} content"))?,
+    ))
+  }
+
+  /// Inscriptions are immutable, so their ID is a strong validator for both
+  /// their content and their preview.
+  fn etag(inscription_id: InscriptionId) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{inscription_id}\"")).unwrap()
+  }
+
+  /// Whether an `If-None-Match` request header matches `etag`, using weak
+  /// comparison as required by RFC 9110.
+  fn is_not_modified(request_headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    let Some(if_none_match) = request_headers
+      .get(header::IF_NONE_MATCH)
+      .and_then(|if_none_match| if_none_match.to_str().ok())
+    else {
+      return false;
+    };
+
+    if_none_match.split(',').any(|tag| {
+      let tag = tag.trim();
+      tag == "*" || tag.strip_prefix("W/").unwrap_or(tag).as_bytes() == etag.as_bytes()
+    })
+  }
+
+  /// Responds with `content` and a `Content-Length` header. Inscription
+  /// bodies are parsed out of the revealing transaction's witness, so the
+  /// whole body is in memory, but ranges of it are served without copying.
+  fn bytes_response(status: StatusCode, mut headers: HeaderMap, content: body::Bytes) -> Response {
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(content.len()));
+
+    (status, headers, content).into_response()
+  }
+
+  /// Partial content must not be compressed, since its `Content-Range` refers
+  /// to the uncompressed body.
+  fn is_not_partial_content(
+    _status: StatusCode,
+    _version: http::Version,
+    headers: &HeaderMap,
+    _extensions: &http::Extensions,
+  ) -> bool {
+    !headers.contains_key(header::CONTENT_RANGE)
+  }
+
+  /// Responds with `body`, or the part of it selected by the request's `Range`
+  /// header, honoring `If-None-Match` and `If-Range` against the ETag in
+  /// `headers`.
+  fn ranged_response(
+    request_headers: &HeaderMap,
+    (mut headers, body): (HeaderMap, Vec<u8>),
+  ) -> Response {
+    if let Some(etag) = headers.get(header::ETAG) {
+      if Self::is_not_modified(request_headers, etag) {
+        headers.remove(header::CONTENT_TYPE);
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+      }
+    }
+
+    let content = body::Bytes::from(body);
+
+    let Some(range) = request_headers
+      .get(header::RANGE)
+      .and_then(|range| range.to_str().ok())
+    else {
+      return Self::bytes_response(StatusCode::OK, headers, content);
+    };
+
+    if let Some(if_range) = request_headers.get(header::IF_RANGE) {
+      if headers.get(header::ETAG) != Some(if_range) {
+        return Self::bytes_response(StatusCode::OK, headers, content);
+      }
+    }
+
+    match ByteRange::parse(range, content.len()) {
+      ByteRange::Full => Self::bytes_response(StatusCode::OK, headers, content),
+      ByteRange::Partial(range) => {
+        headers.insert(
+          header::CONTENT_RANGE,
+          HeaderValue::from_str(&format!(
+            "bytes {}-{}/{}",
+            range.start,
+            range.end - 1,
+            content.len()
+          ))
+          .unwrap(),
+        );
+
+        Self::bytes_response(StatusCode::PARTIAL_CONTENT, headers, content.slice(range))
+      }
+      ByteRange::Unsatisfiable => {
+        headers.remove(header::CONTENT_TYPE);
+        headers.insert(
+          header::CONTENT_RANGE,
+          HeaderValue::from_str(&format!("bytes */{}", content.len())).unwrap(),
+        );
+
+        (StatusCode::RANGE_NOT_SATISFIABLE, headers).into_response()
+      }
+    }
   }
 
   fn content_response(
     inscription: Inscription,
+    inscription_id: InscriptionId,
     page_config: &PageConfig,
   ) -> Option<(HeaderMap, Vec<u8>)> {
     let mut headers = HeaderMap::new();
@@ -2307,6 +2904,8 @@ impl Server {
       header::CACHE_CONTROL,
       HeaderValue::from_static("max-age=31536000, immutable"),
     );
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    headers.insert(header::ETAG, Self::etag(inscription_id));
     headers.insert(
       header::CONTENT_TYPE,
       inscription
@@ -2350,6 +2949,7 @@ impl Server {
     Extension(config): Extension<Arc<Config>>,
     Extension(page_config): Extension<Arc<PageConfig>>,
     Path(inscription_id): Path<InscriptionId>,
+    headers: HeaderMap,
   ) -> ServerResult<Response> {
     if config.is_hidden(inscription_id) {
       return Ok(PreviewUnknownHtml.into_response());
@@ -2365,34 +2965,42 @@ impl Server {
         .ok_or_not_found(|| format!("delegate {inscription_id}"))?
     }
 
+    let etag = Self::etag(inscription_id);
+
+    if Self::is_not_modified(&headers, &etag) {
+      return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
     let media = inscription.media();
     let content_security_policy =
       Self::preview_content_security_policy(media, &page_config.csp_origin)?;
 
-    match media {
-      Media::Audio => Ok(PreviewAudioHtml { inscription_id }.into_response()),
-      Media::Iframe => Ok(
-        Self::content_response(inscription, &page_config)
-          .ok_or_not_found(|| format!("inscription {inscription_id} content"))?
-          .into_response(),
-      ),
+    let mut response = match media {
+      Media::Audio => PreviewAudioHtml { inscription_id }.into_response(),
+      Media::Iframe => {
+        return Ok(Self::ranged_response(
+          &headers,
+          Self::content_response(inscription, inscription_id, &page_config)
+            .ok_or_not_found(|| format!("inscription {inscription_id} content"))?,
+        ))
+      }
       Media::Model => {
-        Ok((content_security_policy, PreviewModelHtml { inscription_id }).into_response())
+        (content_security_policy, PreviewModelHtml { inscription_id }).into_response()
       }
       Media::Image => {
-        Ok((content_security_policy, PreviewImageHtml { inscription_id }).into_response())
-      }
-      Media::Pdf => {
-        Ok((content_security_policy, PreviewPdfHtml { inscription_id }).into_response())
+        (content_security_policy, PreviewImageHtml { inscription_id }).into_response()
       }
-      Media::Text => {
-        Ok((content_security_policy, PreviewTextHtml { inscription_id }).into_response())
-      }
-      Media::Unknown => Ok((content_security_policy, PreviewUnknownHtml).into_response()),
+      Media::Pdf => (content_security_policy, PreviewPdfHtml { inscription_id }).into_response(),
+      Media::Text => (content_security_policy, PreviewTextHtml { inscription_id }).into_response(),
+      Media::Unknown => (content_security_policy, PreviewUnknownHtml).into_response(),
       Media::Video => {
-        Ok((content_security_policy, PreviewVideoHtml { inscription_id }).into_response())
+        (content_security_policy, PreviewVideoHtml { inscription_id }).into_response()
       }
-    }
+    };
+
+    response.headers_mut().insert(header::ETAG, etag);
+
+    Ok(response)
   }
 
   async fn inscription(
@@ -2409,6 +3017,19 @@ impl Server {
       .get_inscription_by_id(inscription_id)?
       .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
 
+    let content_sha256 = inscription.content_sha256();
+
+    let (first_inscription_number, duplicates) = match content_sha256 {
+      Some(content_sha256) => {
+        let inscriptions = index.get_inscriptions_by_content_hash(content_sha256)?;
+        (
+          inscriptions.first().map(|(number, _id)| *number),
+          inscriptions.len().saturating_sub(1),
+        )
+      }
+      None => (None, 0),
+    };
+
     if let Some(delegate) = inscription.delegate() {
       let delegate_inscription = index
         .get_inscription_by_id(delegate)?
@@ -2451,6 +3072,9 @@ impl Server {
       Ok(
         InscriptionHtml {
           chain: page_config.chain,
+          content_sha256,
+          duplicates,
+          first_inscription_number,
           genesis_fee: entry.fee,
           genesis_height: entry.height,
           inscription,
@@ -2482,6 +3106,9 @@ impl Server {
       Ok(
         Json(CraftscriptionJson {
           chain: page_config.chain,
+          content_sha256,
+          duplicates,
+          first_inscription_number,
           genesis_fee: entry.fee,
           genesis_height: entry.height,
           inscription,
@@ -2508,6 +3135,85 @@ impl Server {
     Self::inscriptions_inner(page_config, index, None).await
   }
 
+  async fn inscription_history(
+    Extension(index): Extension<Arc<Index>>,
+    Path(inscription_id): Path<InscriptionId>,
+  ) -> ServerResult<Json<InscriptionHistoryJson>> {
+    if !index.has_locations_index() {
+      return Err(ServerError::BadRequest(
+        "inscription history requires index created with `--index-locations` flag".into(),
+      ));
+    }
+
+    index
+      .get_inscription_entry(inscription_id)?
+      .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+
+    let locations = index
+      .get_inscription_history(inscription_id)?
+      .into_iter()
+      .map(|location| InscriptionLocationJson {
+        satpoint: location.satpoint,
+        address: location.address,
+        height: location.height,
+        timestamp: location.timestamp,
+        txid: location.txid,
+        sale: location.sale,
+      })
+      .collect();
+
+    Ok(Json(InscriptionHistoryJson {
+      inscription_id,
+      locations,
+    }))
+  }
+
+  async fn inscriptions_search(
+    Extension(page_config): Extension<Arc<PageConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+    Query(query): Query<InscriptionSearchQuery>,
+  ) -> ServerResult<Json<InscriptionSearchJson>> {
+    if !index.has_search_index() {
+      return Err(ServerError::BadRequest(
+        "inscription search requires index created with `--index-search` flag".into(),
+      ));
+    }
+
+    let search = query
+      .into_search(page_config.chain, index.has_sat_index())
+      .map_err(|err| ServerError::BadRequest(err.to_string()))?;
+
+    let (inscriptions, next) = index.search_inscriptions(&search)?;
+
+    Ok(Json(InscriptionSearchJson { inscriptions, next }))
+  }
+
+  async fn inscriptions_by_hash(
+    Extension(index): Extension<Arc<Index>>,
+    Path(content_hash): Path<String>,
+  ) -> ServerResult<Json<InscriptionsByHashJson>> {
+    let sha256 = sha256::Hash::from_str(&content_hash).map_err(|err| {
+      ServerError::BadRequest(format!("invalid content hash `{content_hash}`: {err}"))
+    })?;
+
+    let inscriptions = index
+      .get_inscriptions_by_content_hash(sha256)?
+      .into_iter()
+      .map(|(_number, id)| id)
+      .collect::<Vec<InscriptionId>>();
+
+    if inscriptions.is_empty() {
+      return Err(ServerError::NotFound(format!(
+        "no inscriptions with content hash {sha256}"
+      )));
+    }
+
+    Ok(Json(InscriptionsByHashJson {
+      sha256,
+      inscriptions,
+    }))
+  }
+
   async fn inscriptions_validate(
     Extension(index): Extension<Arc<Index>>,
     Extension(server_config): Extension<Arc<PageConfig>>,
@@ -2555,44 +3261,169 @@ impl Server {
     Ok(Json(validate_response).into_response())
   }
 
-  async fn craftscriptions_by_outputs(
+  async fn offer_validate(
     Extension(index): Extension<Arc<Index>>,
-    Query(query): Query<OutputsQuery>,
-  ) -> ServerResult<Response> {
-    let mut all_inscription_jsons = Vec::new();
-
-    // Split the outputs string into individual outputs
-    let outputs = query.outputs.split(',');
+    Query(query): Query<OfferQuery>,
+  ) -> ServerResult<Json<OfferValidationJson>> {
+    let satpoint = SatPoint::from_str(&query.satpoint)
+      .map_err(|err| ServerError::BadRequest(err.to_string()))?;
 
-    for output in outputs {
-      // Split the output into tx_id and vout
-      let parts: Vec<&str> = output.split(':').collect();
-      if parts.len() != 2 {
-        return Err(ServerError::BadRequest("wrong output format".to_string()));
+    let (price, inscriptions, error) = match Offer::from_str(&query.offer) {
+      Ok(offer) => {
+        let inscriptions = index.get_inscriptions_on_output(offer.outgoing())?;
+        let error = Self::offer_error(&index, &offer, satpoint, &inscriptions)?;
+        (Some(offer.price().to_sat()), inscriptions, error)
       }
+      Err(err) => (None, Vec::new(), Some(err.to_string())),
+    };
 
-      let tx_id = Txid::from_str(parts[0])
-        .map_err(|_| ServerError::BadRequest("wrong tx id format".to_string()))?;
-      let vout = parts[1]
-        .parse::<u32>()
-        .map_err(|_| ServerError::BadRequest("wrong vout format".to_string()))?;
+    Ok(Json(OfferValidationJson {
+      valid: error.is_none(),
+      error,
+      satpoint,
+      price,
+      inscriptions,
+    }))
+  }
 
-      // Create OutPoint
-      let outpoint = OutPoint::new(tx_id, vout);
+  /// Why `offer` cannot be accepted, if it does not spend the output of
+  /// `satpoint`, that output no longer holds an inscription at `satpoint` or
+  /// any cunes, or the offer is not signed by the owner of that output.
+  fn offer_error(
+    index: &Index,
+    offer: &Offer,
+    satpoint: SatPoint,
+    inscriptions: &[InscriptionId],
+  ) -> ServerResult<Option<String>> {
+    let outgoing = offer.outgoing();
+
+    if outgoing != satpoint.outpoint {
+      return Ok(Some(format!(
+        "offer spends {outgoing}, not the output of {satpoint}"
+      )));
+    }
 
-      // Query the index for inscriptions on this OutPoint
-      let inscriptions = index.get_inscriptions_on_output(outpoint)?;
+    let mut inscribed = false;
+    for inscription_id in inscriptions {
+      inscribed |= index.get_inscription_satpoint_by_id(*inscription_id)? == Some(satpoint);
+    }
 
-      let output = index
-        .get_transaction(outpoint.txid)?
-        .ok_or_not_found(|| format!("inscription {tx_id} current transaction"))?
-        .output
-        .into_iter()
-        .nth(outpoint.vout.try_into().unwrap())
-        .ok_or_not_found(|| format!("inscription {vout} current transaction output"))?;
+    // Spent outputs are removed from the inscription and cune tables, so this
+    // also checks that the output is unspent
+    if !inscribed && index.get_cune_balances_for_outpoint(outgoing)?.is_empty() {
+      return Ok(Some(format!(
+        "no inscription at {satpoint} and no cunes in output {outgoing}"
+      )));
+    }
 
-      for inscription_id in inscriptions {
-        let inscription = index
+    let Some(prevout) = index
+      .get_transaction(outgoing.txid)?
+      .and_then(|transaction| {
+        transaction
+          .output
+          .into_iter()
+          .nth(outgoing.vout.try_into().unwrap())
+      })
+    else {
+      return Ok(Some(format!("output {outgoing} not found")));
+    };
+
+    Ok(offer.verify(&prevout).err().map(|err| err.to_string()))
+  }
+
+  async fn verify(
+    Extension(index): Extension<Arc<Index>>,
+    Query(query): Query<VerifyQuery>,
+  ) -> ServerResult<Json<VerifyJson>> {
+    let address =
+      Address::from_str(&query.address).map_err(|err| ServerError::BadRequest(err.to_string()))?;
+
+    let mut json = VerifyJson {
+      valid: false,
+      error: None,
+      address: query.address,
+      inscriptions: Vec::new(),
+      cunes: BTreeMap::new(),
+      crc20: Vec::new(),
+    };
+
+    if let Err(err) = signed_message::verify(&address, &query.message, &query.signature) {
+      json.error = Some(err.to_string());
+      return Ok(Json(json));
+    }
+
+    json.valid = true;
+
+    for outpoint in index.get_account_outputs(json.address.clone())? {
+      json
+        .inscriptions
+        .extend(index.get_inscriptions_on_output(outpoint)?);
+
+      for (cune, pile) in index.get_cune_balances_for_outpoint(outpoint)? {
+        *json.cunes.entry(cune.to_string()).or_default() += pile.amount;
+      }
+    }
+
+    for balance in
+      index.get_crc20_balances(&ScriptKey::from_address(address, index.get_network()?))?
+    {
+      if balance.overall_balance == 0 {
+        continue;
+      }
+
+      let decimals = index
+        .get_crc20_token_info(&balance.tick)?
+        .map(|token_info| token_info.decimal)
+        .unwrap_or_default();
+
+      json.crc20.push(Crc20HoldingJson {
+        tick: balance.tick.as_str().to_string(),
+        overall: format_balance(balance.overall_balance, decimals),
+        transferable: format_balance(balance.transferable_balance, decimals),
+      });
+    }
+
+    Ok(Json(json))
+  }
+
+  async fn craftscriptions_by_outputs(
+    Extension(index): Extension<Arc<Index>>,
+    Query(query): Query<OutputsQuery>,
+  ) -> ServerResult<Response> {
+    let mut all_inscription_jsons = Vec::new();
+
+    // Split the outputs string into individual outputs
+    let outputs = query.outputs.split(',');
+
+    for output in outputs {
+      // Split the output into tx_id and vout
+      let parts: Vec<&str> = output.split(':').collect();
+      if parts.len() != 2 {
+        return Err(ServerError::BadRequest("wrong output format".to_string()));
+      }
+
+      let tx_id = Txid::from_str(parts[0])
+        .map_err(|_| ServerError::BadRequest("wrong tx id format".to_string()))?;
+      let vout = parts[1]
+        .parse::<u32>()
+        .map_err(|_| ServerError::BadRequest("wrong vout format".to_string()))?;
+
+      // Create OutPoint
+      let outpoint = OutPoint::new(tx_id, vout);
+
+      // Query the index for inscriptions on this OutPoint
+      let inscriptions = index.get_inscriptions_on_output(outpoint)?;
+
+      let output = index
+        .get_transaction(outpoint.txid)?
+        .ok_or_not_found(|| format!("inscription {tx_id} current transaction"))?
+        .output
+        .into_iter()
+        .nth(outpoint.vout.try_into().unwrap())
+        .ok_or_not_found(|| format!("inscription {vout} current transaction output"))?;
+
+      for inscription_id in inscriptions {
+        let inscription = index
           .get_inscription_by_id(inscription_id)?
           .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
 
@@ -2841,7 +3672,13 @@ fn format_balance(balance: u128, decimal_places: u8) -> String {
 mod tests {
   use bitcoin::blockdata::constants::COIN_VALUE;
 
-  use {super::*, reqwest::Url, std::net::TcpListener};
+  use {
+    super::*,
+    api::{ApiError, ApiResponse},
+    error::ErrorCode,
+    reqwest::Url,
+    std::net::TcpListener,
+  };
 
   use crate::cunes::{Cunestone, Edict, Etching};
 
@@ -2960,6 +3797,36 @@ mod tests {
       reqwest::blocking::get(self.join_url(path.as_ref())).unwrap()
     }
 
+    fn get_with_headers(
+      &self,
+      path: impl AsRef<str>,
+      headers: &[(header::HeaderName, &str)],
+    ) -> reqwest::blocking::Response {
+      let mut request = reqwest::blocking::Client::new().get(self.join_url(path.as_ref()));
+
+      for (name, value) in headers {
+        request = request.header(name.as_str(), *value);
+      }
+
+      request.send().unwrap()
+    }
+
+    fn post_json(
+      &self,
+      path: impl AsRef<str>,
+      body: &impl Serialize,
+    ) -> reqwest::blocking::Response {
+      if let Err(error) = self.index.update() {
+        log::error!("{error}");
+      }
+      reqwest::blocking::Client::new()
+        .post(self.join_url(path.as_ref()))
+        .header(header::CONTENT_TYPE.as_str(), "application/json")
+        .body(serde_json::to_string(body).unwrap())
+        .send()
+        .unwrap()
+    }
+
     fn join_url(&self, url: &str) -> Url {
       self.url.join(url).unwrap()
     }
@@ -3026,6 +3893,46 @@ mod tests {
       self.index.update().unwrap();
       blocks
     }
+
+    fn validate_offer(&self, offer: &str, satpoint: SatPoint) -> OfferValidationJson {
+      let response = self.get(format!("/offer/validate?offer={offer}&satpoint={satpoint}"));
+      assert_eq!(response.status(), StatusCode::OK);
+      serde_json::from_str(&response.text().unwrap()).unwrap()
+    }
+
+    fn verify(&self, address: &str, message: &str, signature: &str) -> reqwest::blocking::Response {
+      self.get(format!(
+        "/verify?{}",
+        url::form_urlencoded::Serializer::new(String::new())
+          .append_pair("address", address)
+          .append_pair("message", message)
+          .append_pair("signature", signature)
+          .finish()
+      ))
+    }
+
+    fn search_inscriptions(&self, query: &str) -> InscriptionSearchJson {
+      let response = self.get(format!("/inscriptions/search?{query}"));
+      assert_eq!(response.status(), StatusCode::OK);
+      serde_json::from_str(&response.text().unwrap()).unwrap()
+    }
+
+    fn inscribe_at(
+      &self,
+      block: usize,
+      content_type: &str,
+      body: impl AsRef<[u8]>,
+    ) -> InscriptionId {
+      let txid = self.craftcoin_rpc_server.broadcast_tx(TransactionTemplate {
+        inputs: &[(block, 0, 0)],
+        witness: inscription(content_type, body).to_witness(),
+        ..Default::default()
+      });
+
+      self.mine_blocks(1);
+
+      InscriptionId::from(txid)
+    }
   }
 
   impl Drop for TestServer {
@@ -3945,33 +4852,50 @@ mod tests {
     );
   }
 
+  fn page_config() -> PageConfig {
+    PageConfig {
+      chain: Chain::Regtest,
+      domain: None,
+      index_sats: false,
+      csp_origin: None,
+    }
+  }
+
   #[test]
   fn content_response_no_content() {
     assert_eq!(
-      Server::content_response(Inscription::new(
-        Some("text/plain".as_bytes().to_vec()),
-        None
-      )),
+      Server::content_response(
+        Inscription::new(Some("text/plain".as_bytes().to_vec()), None),
+        inscription_id(1),
+        &page_config(),
+      ),
       None
     );
   }
 
   #[test]
   fn content_response_with_content() {
-    let (headers, body) = Server::content_response(Inscription::new(
-      Some("text/plain".as_bytes().to_vec()),
-      Some(vec![1, 2, 3]),
-    ))
+    let (headers, body) = Server::content_response(
+      Inscription::new(Some("text/plain".as_bytes().to_vec()), Some(vec![1, 2, 3])),
+      inscription_id(1),
+      &page_config(),
+    )
     .unwrap();
 
     assert_eq!(headers["content-type"], "text/plain");
+    assert_eq!(headers["accept-ranges"], "bytes");
+    assert_eq!(headers["etag"], format!("\"{}\"", inscription_id(1)));
     assert_eq!(body, vec![1, 2, 3]);
   }
 
   #[test]
   fn content_response_no_content_type() {
-    let (headers, body) =
-      Server::content_response(Inscription::new(None, Some(Vec::new()))).unwrap();
+    let (headers, body) = Server::content_response(
+      Inscription::new(None, Some(Vec::new())),
+      inscription_id(1),
+      &page_config(),
+    )
+    .unwrap();
 
     assert_eq!(headers["content-type"], "application/octet-stream");
     assert!(body.is_empty());
@@ -4361,133 +5285,1543 @@ mod tests {
   }
 
   #[test]
-  fn inscriptions_page_with_no_prev_or_next() {
-    TestServer::new_with_sat_index().assert_response_regex(
-      "/craftscriptions",
-      StatusCode::OK,
-      ".*prev\nnext.*",
+  fn content_supports_range_requests() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let inscription_id = server.inscribe_at(1, "video/mp4", "hello world");
+    let path = format!("/content/{inscription_id}");
+
+    let response = server.get_with_headers(&path, &[(header::RANGE, "bytes=6-")]);
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers()[header::ACCEPT_RANGES], "bytes");
+    assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 6-10/11");
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "video/mp4");
+    assert_eq!(response.text().unwrap(), "world");
+
+    let response = server.get_with_headers(&path, &[(header::RANGE, "bytes=0-4")]);
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 0-4/11");
+    assert_eq!(response.text().unwrap(), "hello");
+
+    let response = server.get_with_headers(&path, &[(header::RANGE, "bytes=11-")]);
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes */11");
+  }
+
+  #[test]
+  fn content_range_requests_respect_if_range() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let inscription_id = server.inscribe_at(1, "audio/mpeg", "hello world");
+    let path = format!("/content/{inscription_id}");
+    let etag = format!("\"{inscription_id}\"");
+
+    let response = server.get_with_headers(
+      &path,
+      &[(header::RANGE, "bytes=0-4"), (header::IF_RANGE, &etag)],
+    );
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.text().unwrap(), "hello");
+
+    let response = server.get_with_headers(
+      &path,
+      &[
+        (header::RANGE, "bytes=0-4"),
+        (header::IF_RANGE, "\"stale\""),
+      ],
     );
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().unwrap(), "hello world");
   }
 
   #[test]
-  fn inscriptions_page_with_no_next() {
-    let server = TestServer::new_with_sat_index();
+  fn content_and_preview_honor_if_none_match() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
 
-    for i in 0..101 {
-      server.mine_blocks(1);
-      server
-        .craftcoin_rpc_server
-        .broadcast_tx(TransactionTemplate {
-          inputs: &[(i + 1, 0, 0)],
-          witness: inscription("text/foo", "hello").to_witness(),
-          ..Default::default()
-        });
+    let inscription_id = server.inscribe_at(1, "image/png", [1; 100]);
+    let etag = format!("\"{inscription_id}\"");
+
+    for path in [
+      format!("/content/{inscription_id}"),
+      format!("/preview/{inscription_id}"),
+    ] {
+      let response = server.get(&path);
+      assert_eq!(response.status(), StatusCode::OK);
+      assert_eq!(response.headers()[header::ETAG], etag.as_str());
+
+      let response = server.get_with_headers(&path, &[(header::IF_NONE_MATCH, &etag)]);
+      assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+      assert_eq!(response.headers()[header::ETAG], etag.as_str());
+
+      let response = server.get_with_headers(
+        &path,
+        &[(header::IF_NONE_MATCH, &format!("\"other\", W/{etag}"))],
+      );
+      assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+      let response = server.get_with_headers(&path, &[(header::IF_NONE_MATCH, "\"other\"")]);
+      assert_eq!(response.status(), StatusCode::OK);
     }
+  }
 
-    server.mine_blocks(1);
+  #[test]
+  fn preview_of_unknown_inscription_is_not_found_even_if_etag_matches() {
+    let server = TestServer::new();
 
-    server.assert_response_regex(
-      "/craftscriptions",
-      StatusCode::OK,
-      ".*<a class=prev href=/craftscriptions/0>prev</a>\nnext.*",
+    let inscription_id = inscription_id(1);
+
+    let response = server.get_with_headers(
+      format!("/preview/{inscription_id}"),
+      &[(header::IF_NONE_MATCH, &format!("\"{inscription_id}\""))],
     );
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
   }
 
   #[test]
-  fn inscriptions_page_with_no_prev() {
-    let server = TestServer::new_with_sat_index();
+  fn ranges_of_large_content_are_served() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
 
-    for i in 0..101 {
-      server.mine_blocks(1);
-      server
-        .craftcoin_rpc_server
-        .broadcast_tx(TransactionTemplate {
-          inputs: &[(i + 1, 0, 0)],
-          witness: inscription("text/foo", "hello").to_witness(),
-          ..Default::default()
-        });
-    }
+    let content = (0..200_000)
+      .map(|i| u8::try_from(i % 251).unwrap())
+      .collect::<Vec<u8>>();
 
-    server.mine_blocks(1);
+    let inscription_id = server.inscribe_at(1, "video/mp4", &content);
+    let path = format!("/content/{inscription_id}");
 
-    server.assert_response_regex(
-      "/craftscriptions/0",
-      StatusCode::OK,
-      ".*prev\n<a class=next href=/craftscriptions/100>next</a>.*",
+    let response = server.get(&path);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.headers()[header::CONTENT_LENGTH],
+      content.len().to_string().as_str()
     );
+    assert_eq!(response.bytes().unwrap(), content);
+
+    let response = server.get_with_headers(&path, &[(header::RANGE, "bytes=100000-100020")]);
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers()[header::CONTENT_LENGTH], "21");
+    assert_eq!(response.bytes().unwrap(), content[100_000..=100_020]);
   }
 
   #[test]
-  fn resonses_are_gzipped() {
+  fn partial_content_is_not_compressed() {
     let server = TestServer::new();
+    server.mine_blocks(1);
 
-    let mut headers = HeaderMap::new();
-
-    headers.insert(header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+    let content = "hello world ".repeat(100);
 
-    let response = reqwest::blocking::Client::builder()
-      .default_headers(headers)
-      .build()
-      .unwrap()
-      .get(server.join_url("/"))
-      .send()
-      .unwrap();
+    let inscription_id = server.inscribe_at(1, "text/plain;charset=utf-8", content.as_bytes());
+    let path = format!("/content/{inscription_id}");
 
+    let response = server.get_with_headers(
+      &path,
+      &[
+        (header::ACCEPT_ENCODING, "gzip"),
+        (header::RANGE, "bytes=0-599"),
+      ],
+    );
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers().get(header::CONTENT_ENCODING), None);
     assert_eq!(
-      response.headers().get(header::CONTENT_ENCODING).unwrap(),
-      "gzip"
+      response.headers()[header::CONTENT_RANGE],
+      "bytes 0-599/1200"
     );
+    assert_eq!(response.text().unwrap(), content[..600]);
+
+    let response = server.get_with_headers(&path, &[(header::ACCEPT_ENCODING, "gzip")]);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
   }
 
   #[test]
-  fn resonses_are_brotlied() {
+  fn cached_responses_are_invalidated_by_new_blocks() {
     let server = TestServer::new();
+    server.mine_blocks(1);
 
-    let mut headers = HeaderMap::new();
+    let response = server.get("/block/1");
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()[header::ETAG]
+      .to_str()
+      .unwrap()
+      .to_string();
+    let body = response.text().unwrap();
 
-    headers.insert(header::ACCEPT_ENCODING, "br".parse().unwrap());
+    let response = server.get_with_headers("/block/1", &[(header::IF_NONE_MATCH, &etag)]);
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()[header::ETAG], etag.as_str());
 
-    let response = reqwest::blocking::Client::builder()
-      .default_headers(headers)
-      .build()
-      .unwrap()
-      .get(server.join_url("/"))
-      .send()
-      .unwrap();
+    assert_eq!(server.get("/block/1").text().unwrap(), body);
+
+    server.mine_blocks(1);
+
+    let response = server.get_with_headers("/block/1", &[(header::IF_NONE_MATCH, &etag)]);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_ne!(response.headers()[header::ETAG], etag.as_str());
+    assert_ne!(response.text().unwrap(), body);
+  }
+
+  #[test]
+  fn response_cache_can_be_disabled() {
+    let server = TestServer::new_with_args(&[], &["--response-cache-size", "0"]);
+    server.mine_blocks(1);
+
+    let response = server.get("/block/1");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(header::ETAG).is_none());
+  }
+
+  fn api_get(server: &TestServer, path: &str) -> (StatusCode, ApiResponse) {
+    let response = server.get(format!("/api/v1{path}"));
+    let status = response.status();
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    (
+      status,
+      serde_json::from_str(&response.text().unwrap()).unwrap(),
+    )
+  }
+
+  #[test]
+  fn api_v1_wraps_data_in_envelope() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let inscription_id = server.inscribe_at(1, "text/plain", "hello");
+
+    let (status, response) = api_get(&server, &format!("/inscription/{inscription_id}"));
+    assert_eq!(status, StatusCode::OK);
+    let ApiResponse::Data(data) = response else {
+      panic!("unexpected response: {response:?}");
+    };
+    assert_eq!(data["inscription_id"], inscription_id.to_string());
+    assert_eq!(data["inscription_number"], 0);
 
     assert_eq!(
-      response.headers().get(header::CONTENT_ENCODING).unwrap(),
-      "br"
+      api_get(&server, "/block-count"),
+      (StatusCode::OK, ApiResponse::Data(3.into()))
     );
   }
 
   #[test]
-  fn inscriptions_can_be_hidden_with_config() {
-    let craftcoin_rpc_server = test_bitcoincore_rpc::spawn();
-    craftcoin_rpc_server.mine_blocks(1);
-    let txid = craftcoin_rpc_server.broadcast_tx(TransactionTemplate {
-      inputs: &[(1, 0, 0)],
-      witness: inscription("text/plain;charset=utf-8", "hello").to_witness(),
-      ..Default::default()
-    });
-    let inscription = InscriptionId::from(txid);
-    craftcoin_rpc_server.mine_blocks(1);
+  fn api_v1_serves_json_for_data_routes() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
 
-    let server = TestServer::new_with_craftcoin_rpc_server_and_config(
-      craftcoin_rpc_server,
-      format!("\"hidden\":\n - {inscription}"),
-    );
+    let inscription_id = server.inscribe_at(1, "text/plain", "hello");
 
-    server.assert_response(
-      format!("/preview/{inscription}"),
-      StatusCode::OK,
-      &fs::read_to_string("templates/preview-unknown.html").unwrap(),
+    let (status, response) = api_get(&server, "/blockheight");
+    assert_eq!(status, StatusCode::OK);
+    let ApiResponse::Data(height) = response else {
+      panic!("unexpected response: {response:?}");
+    };
+
+    let (status, response) = api_get(&server, "/blockhash");
+    assert_eq!(status, StatusCode::OK);
+    let ApiResponse::Data(hash) = response else {
+      panic!("unexpected response: {response:?}");
+    };
+
+    assert_eq!(
+      api_get(&server, &format!("/blockhash/{height}")),
+      (StatusCode::OK, ApiResponse::Data(hash.clone()))
     );
 
+    let (status, response) = api_get(&server, &format!("/block/{}", hash.as_str().unwrap()));
+    assert_eq!(status, StatusCode::OK);
+    let ApiResponse::Data(block) = response else {
+      panic!("unexpected response: {response:?}");
+    };
+    assert_eq!(block["height"], height);
+
+    let (status, response) = api_get(&server, "/inscriptions");
+    assert_eq!(status, StatusCode::OK);
+    let ApiResponse::Data(inscriptions) = response else {
+      panic!("unexpected response: {response:?}");
+    };
+    assert_eq!(
+      inscriptions["inscriptions"],
+      serde_json::json!([inscription_id])
+    );
+
+    let (status, response) = api_get(&server, &format!("/output/{}:0", inscription_id.txid));
+    assert_eq!(status, StatusCode::OK);
+    let ApiResponse::Data(output) = response else {
+      panic!("unexpected response: {response:?}");
+    };
+    assert_eq!(output["inscriptions"], serde_json::json!([inscription_id]));
+
+    assert_eq!(
+      api_get(&server, "/range/0/100"),
+      (
+        StatusCode::OK,
+        ApiResponse::Data(serde_json::json!({"start": 0, "end": 100, "size": 100}))
+      )
+    );
+
+    for path in [
+      "/block/1",
+      "/blocktime",
+      "/cunes",
+      "/input/1/0/0",
+      "/inscriptions/0",
+      "/sat/0",
+    ] {
+      let (status, response) = api_get(&server, path);
+      assert_eq!(status, StatusCode::OK, "{path}");
+      assert!(matches!(response, ApiResponse::Data(_)), "{path}");
+    }
+
+    for path in [
+      format!("/content/{inscription_id}"),
+      format!("/preview/{inscription_id}"),
+    ] {
+      let (status, response) = api_get(&server, &path);
+      assert_eq!(status, StatusCode::NOT_FOUND, "{path}");
+      assert!(
+        matches!(
+          response,
+          ApiResponse::Error(ApiError {
+            code: ErrorCode::NotFound,
+            ..
+          })
+        ),
+        "{path}"
+      );
+    }
+  }
+
+  #[test]
+  fn api_v1_errors_have_codes() {
+    let server = TestServer::new();
+
+    assert_eq!(
+      api_get(&server, &format!("/inscription/{}", inscription_id(1))),
+      (
+        StatusCode::NOT_FOUND,
+        ApiResponse::Error(ApiError {
+          code: ErrorCode::NotFound,
+          message: format!("inscription {} not found", inscription_id(1)),
+        })
+      )
+    );
+
+    assert_eq!(
+      api_get(&server, "/nonexistent"),
+      (
+        StatusCode::NOT_FOUND,
+        ApiResponse::Error(ApiError {
+          code: ErrorCode::NotFound,
+          message: "route /api/v1/nonexistent not found".into(),
+        })
+      )
+    );
+
+    let (status, response) = api_get(&server, "/inscription/foo");
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(matches!(
+      response,
+      ApiResponse::Error(ApiError {
+        code: ErrorCode::BadRequest,
+        ..
+      })
+    ));
+  }
+
+  #[test]
+  fn invalid_outpoints_are_bad_requests() {
+    let server = TestServer::new();
+
+    server.assert_response_regex(
+      "/outputs/foo",
+      StatusCode::BAD_REQUEST,
+      "invalid outpoint `foo`: .*",
+    );
+
+    let (status, response) = api_get(&server, "/outputs/foo");
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let ApiResponse::Error(error) = response else {
+      panic!("unexpected response: {response:?}");
+    };
+    assert_eq!(error.code, ErrorCode::BadRequest);
+    assert!(error.message.starts_with("invalid outpoint `foo`: "));
+  }
+
+  #[test]
+  fn outputs_are_served_as_json() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let outpoint = OutPoint {
+      txid: server.craftcoin_rpc_server.tx(1, 0).txid(),
+      vout: 0,
+    };
+
+    let response = server.get(format!("/outputs/{outpoint}"));
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+
+    let (status, response) = api_get(&server, &format!("/outputs/{outpoint}"));
+    assert_eq!(status, StatusCode::OK);
+    let ApiResponse::Data(data) = response else {
+      panic!("unexpected response: {response:?}");
+    };
+    assert_eq!(data.as_array().unwrap().len(), 1);
+  }
+
+  #[test]
+  fn batch_outputs() {
+    let server = TestServer::new_with_args(&["--index-transactions"], &[]);
+    server.mine_blocks(1);
+
+    let inscription_id = server.inscribe_at(1, "text/plain", "hello");
+    let output = OutPoint {
+      txid: inscription_id.txid,
+      vout: 0,
+    };
+
+    let response = server.post_json("/api/batch/outputs", &[output, outpoint(9)]);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let batch: BatchJson<Option<OutputJson>> = response.json().unwrap();
+    assert_eq!(batch.height, Some(2));
+    assert_eq!(batch.results.len(), 2);
+    assert_eq!(
+      batch.results[0].as_ref().unwrap().inscriptions,
+      [inscription_id]
+    );
+    assert!(batch.results[0].as_ref().unwrap().cunes.is_empty());
+    assert_eq!(batch.results[1], None);
+  }
+
+  #[test]
+  fn batch_outputs_require_transaction_index() {
+    let server = TestServer::new();
+
+    let response = server.post_json("/api/batch/outputs", &[outpoint(1)]);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+      response.text().unwrap(),
+      "batch output lookups require index created with `--index-transactions` flag"
+    );
+  }
+
+  #[test]
+  fn cors_preflight_allows_json_posts() {
+    let server = TestServer::new();
+
+    let response = reqwest::blocking::Client::new()
+      .request(
+        reqwest::Method::OPTIONS,
+        server.join_url("/api/batch/inscriptions"),
+      )
+      .header(header::ORIGIN.as_str(), "https://example.com")
+      .header(header::ACCESS_CONTROL_REQUEST_METHOD.as_str(), "POST")
+      .header(
+        header::ACCESS_CONTROL_REQUEST_HEADERS.as_str(),
+        "content-type",
+      )
+      .send()
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let headers = response.headers();
+
+    assert!(headers[header::ACCESS_CONTROL_ALLOW_METHODS]
+      .to_str()
+      .unwrap()
+      .contains("POST"));
+    assert!(headers[header::ACCESS_CONTROL_ALLOW_HEADERS]
+      .to_str()
+      .unwrap()
+      .contains("content-type"));
+  }
+
+  #[test]
+  fn batch_inscriptions() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let inscription = server.inscribe_at(1, "text/plain", "hello");
+
+    let response = server.post_json("/api/batch/inscriptions", &[inscription, inscription_id(1)]);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let batch: BatchJson<Option<BatchInscriptionJson>> = response.json().unwrap();
+    assert_eq!(batch.height, Some(2));
+    assert_eq!(
+      batch.results,
+      [
+        Some(BatchInscriptionJson {
+          content_length: Some(5),
+          content_type: Some("text/plain".into()),
+          genesis_fee: 0,
+          genesis_height: 2,
+          inscription_id: inscription,
+          inscription_number: 0,
+          satpoint: SatPoint {
+            outpoint: OutPoint {
+              txid: inscription.txid,
+              vout: 0,
+            },
+            offset: 0,
+          },
+          timestamp: 2,
+        }),
+        None,
+      ]
+    );
+  }
+
+  #[test]
+  fn batch_balances() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let response = server.post_json("/api/batch/cune-balances", &[outpoint(1), outpoint(2)]);
+    assert_eq!(response.status(), StatusCode::OK);
+    let batch: BatchJson<Vec<CuneOutputJson>> = response.json().unwrap();
+    assert_eq!(batch.height, Some(1));
+    assert_eq!(batch.results, [Vec::new(), Vec::new()]);
+
+    let response = server.post_json("/api/batch/crc20-balances", &[address().to_string()]);
+    assert_eq!(response.status(), StatusCode::OK);
+    let batch: BatchJson<Vec<BatchCrc20BalanceJson>> = response.json().unwrap();
+    assert_eq!(batch.height, Some(1));
+    assert_eq!(batch.results, [Vec::new()]);
+
+    let response = server.post_json("/api/batch/crc20-balances", &["foo"]);
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(response
+      .text()
+      .unwrap()
+      .starts_with("invalid address `foo`: "));
+  }
+
+  #[test]
+  fn batches_are_limited_in_size() {
+    let server = TestServer::new();
+
+    let response = server.post_json(
+      "/api/batch/inscriptions",
+      &vec![inscription_id(1); MAX_BATCH_SIZE + 1],
+    );
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+      response.text().unwrap(),
+      format!(
+        "batch of {} items exceeds maximum of {MAX_BATCH_SIZE}",
+        MAX_BATCH_SIZE + 1
+      )
+    );
+  }
+
+  #[test]
+  fn address_listings_reject_stale_cursors() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let address = address();
+
+    let response = server.get(format!("/utxos/balance/{address}?limit=5"));
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.json::<UtxoAddressJson>().unwrap(),
+      UtxoAddressJson {
+        utxos: Vec::new(),
+        total_utxos: 0,
+        total_shibes: 0,
+        total_inscription_shibes: 0,
+        next: None,
+      }
+    );
+
+    for path in [
+      "utxos/balance",
+      "inscriptions/balance",
+      "cunes/balance",
+      "crc20/balance",
+    ] {
+      server.assert_response(
+        format!("/{path}/{address}?cursor=000000000000000000000005"),
+        StatusCode::BAD_REQUEST,
+        "cursor was issued at height 0 but the index is at height 1, restart from the first page",
+      );
+    }
+
+    server.assert_response_regex(
+      format!("/api/v1/utxos/balance/{address}/2?cursor=foo"),
+      StatusCode::BAD_REQUEST,
+      r#"\{"error":\{"code":"bad_request","message":"invalid cursor `foo`: .*"\}\}"#,
+    );
+  }
+
+  #[test]
+  fn expensive_routes_are_rate_limited() {
+    let server = TestServer::new_with_craftcoin_rpc_server_and_config(
+      test_bitcoincore_rpc::spawn(),
+      "
+hidden: []
+api_keys:
+  - name: indexer
+    key: secret
+  - name: operator
+    key: admin-secret
+    admin: true
+rate_limit:
+  anonymous:
+    default: { burst: 1000, per_minute: 1000 }
+    expensive: { burst: 1, per_minute: 1 }
+"
+      .into(),
+    );
+
+    let path = format!("/utxos/balance/{}", address());
+
+    assert_eq!(server.get(&path).status(), StatusCode::OK);
+
+    let response = server.get(&path);
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[header::RETRY_AFTER], "60");
+    assert_eq!(
+      response.text().unwrap(),
+      "rate limit exceeded, retry after 60 seconds"
+    );
+
+    let (status, response) = api_get(&server, &path);
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(matches!(
+      response,
+      ApiResponse::Error(ApiError {
+        code: ErrorCode::RateLimited,
+        ..
+      })
+    ));
+
+    assert_eq!(server.get("/status").status(), StatusCode::OK);
+
+    let api_key = HeaderName::from_static("x-api-key");
+
+    assert_eq!(
+      server
+        .get_with_headers(&path, &[(api_key.clone(), "secret")])
+        .status(),
+      StatusCode::OK
+    );
+
+    let response = server.get_with_headers(&path, &[(api_key.clone(), "wrong")]);
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.text().unwrap(), "unknown API key");
+
+    let response = server.get("/api/usage");
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.text().unwrap(), "API key required");
+
+    let usage = server
+      .get_with_headers("/api/usage", &[(api_key.clone(), "secret")])
+      .json::<UsageJson>()
+      .unwrap();
+    assert_eq!(usage.anonymous, None);
+    assert_eq!(usage.keys.keys().collect::<Vec<_>>(), ["indexer"]);
+
+    let usage = server
+      .get_with_headers("/api/usage", &[(api_key, "admin-secret")])
+      .json::<UsageJson>()
+      .unwrap();
+    assert_eq!(usage.anonymous.unwrap().limited, 2);
+    // The indexer's own request for its usage is counted too
+    assert_eq!(
+      usage.keys["indexer"],
+      rate_limit::Usage {
+        requests: 2,
+        limited: 0,
+      }
+    );
+  }
+
+  #[test]
+  fn inscriptions_page_with_no_prev_or_next() {
+    TestServer::new_with_sat_index().assert_response_regex(
+      "/craftscriptions",
+      StatusCode::OK,
+      ".*prev\nnext.*",
+    );
+  }
+
+  #[test]
+  fn inscriptions_page_with_no_next() {
+    let server = TestServer::new_with_sat_index();
+
+    for i in 0..101 {
+      server.mine_blocks(1);
+      server
+        .craftcoin_rpc_server
+        .broadcast_tx(TransactionTemplate {
+          inputs: &[(i + 1, 0, 0)],
+          witness: inscription("text/foo", "hello").to_witness(),
+          ..Default::default()
+        });
+    }
+
+    server.mine_blocks(1);
+
+    server.assert_response_regex(
+      "/craftscriptions",
+      StatusCode::OK,
+      ".*<a class=prev href=/craftscriptions/0>prev</a>\nnext.*",
+    );
+  }
+
+  #[test]
+  fn inscriptions_page_with_no_prev() {
+    let server = TestServer::new_with_sat_index();
+
+    for i in 0..101 {
+      server.mine_blocks(1);
+      server
+        .craftcoin_rpc_server
+        .broadcast_tx(TransactionTemplate {
+          inputs: &[(i + 1, 0, 0)],
+          witness: inscription("text/foo", "hello").to_witness(),
+          ..Default::default()
+        });
+    }
+
+    server.mine_blocks(1);
+
+    server.assert_response_regex(
+      "/craftscriptions/0",
+      StatusCode::OK,
+      ".*prev\n<a class=next href=/craftscriptions/100>next</a>.*",
+    );
+  }
+
+  #[test]
+  fn resonses_are_gzipped() {
+    let server = TestServer::new();
+
+    let mut headers = HeaderMap::new();
+
+    headers.insert(header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+
+    let response = reqwest::blocking::Client::builder()
+      .default_headers(headers)
+      .build()
+      .unwrap()
+      .get(server.join_url("/"))
+      .send()
+      .unwrap();
+
+    assert_eq!(
+      response.headers().get(header::CONTENT_ENCODING).unwrap(),
+      "gzip"
+    );
+  }
+
+  #[test]
+  fn resonses_are_brotlied() {
+    let server = TestServer::new();
+
+    let mut headers = HeaderMap::new();
+
+    headers.insert(header::ACCEPT_ENCODING, "br".parse().unwrap());
+
+    let response = reqwest::blocking::Client::builder()
+      .default_headers(headers)
+      .build()
+      .unwrap()
+      .get(server.join_url("/"))
+      .send()
+      .unwrap();
+
+    assert_eq!(
+      response.headers().get(header::CONTENT_ENCODING).unwrap(),
+      "br"
+    );
+  }
+
+  #[test]
+  fn inscriptions_can_be_hidden_with_config() {
+    let craftcoin_rpc_server = test_bitcoincore_rpc::spawn();
+    craftcoin_rpc_server.mine_blocks(1);
+    let txid = craftcoin_rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      witness: inscription("text/plain;charset=utf-8", "hello").to_witness(),
+      ..Default::default()
+    });
+    let inscription = InscriptionId::from(txid);
+    craftcoin_rpc_server.mine_blocks(1);
+
+    let server = TestServer::new_with_craftcoin_rpc_server_and_config(
+      craftcoin_rpc_server,
+      format!("\"hidden\":\n - {inscription}"),
+    );
+
+    server.assert_response(
+      format!("/preview/{inscription}"),
+      StatusCode::OK,
+      &fs::read_to_string("templates/preview-unknown.html").unwrap(),
+    );
+
+    server.assert_response(
+      format!("/content/{inscription}"),
+      StatusCode::OK,
+      &fs::read_to_string("templates/preview-unknown.html").unwrap(),
+    );
+  }
+
+  fn search_text(server: &TestServer, query: &str) -> SearchJson {
+    let response = server.get(format!("/search?json=true&{query}"));
+    assert_eq!(response.status(), StatusCode::OK);
+    serde_json::from_str(&response.text().unwrap()).unwrap()
+  }
+
+  #[test]
+  fn full_text_search_finds_text_and_json_inscriptions() {
+    let server = TestServer::new_with_args(&["--index-text"], &[]);
+    server.mine_blocks(3);
+
+    let text = server.inscribe_at(1, "text/plain;charset=utf-8", "Hello, world!");
+    let json = server.inscribe_at(2, "application/json", r#"{"greeting":"hello"}"#);
+    server.inscribe_at(3, "image/png", "hello");
+
+    assert_eq!(
+      search_text(&server, "q=HELLO"),
+      SearchJson {
+        results: vec![
+          SearchResult {
+            inscription_id: json,
+            inscription_number: 1,
+            content_type: Some("application/json".into()),
+            snippet: r#"{"greeting":"hello"}"#.into(),
+          },
+          SearchResult {
+            inscription_id: text,
+            inscription_number: 0,
+            content_type: Some("text/plain;charset=utf-8".into()),
+            snippet: "Hello, world!".into(),
+          },
+        ],
+        next: None,
+      }
+    );
+
+    assert_eq!(
+      search_text(&server, "q=hello+world")
+        .results
+        .into_iter()
+        .map(|result| result.inscription_id)
+        .collect::<Vec<InscriptionId>>(),
+      [text]
+    );
+
+    assert_eq!(
+      search_text(&server, "q=hello&content_type=text/plain")
+        .results
+        .into_iter()
+        .map(|result| result.inscription_id)
+        .collect::<Vec<InscriptionId>>(),
+      [text]
+    );
+
+    assert!(search_text(&server, "q=goodbye").results.is_empty());
+  }
+
+  #[test]
+  fn full_text_search_pages_with_cursor() {
+    let server = TestServer::new_with_args(&["--index-text"], &[]);
+    server.mine_blocks(2);
+
+    let first = server.inscribe_at(1, "text/plain", "hello");
+    let second = server.inscribe_at(2, "text/plain", "hello again");
+
+    let page = search_text(&server, "q=hello&limit=1");
+    assert_eq!(page.results[0].inscription_id, second);
+    assert_eq!(page.next, Some(0));
+
+    let page = search_text(&server, "q=hello&limit=1&from=0");
+    assert_eq!(page.results[0].inscription_id, first);
+    assert_eq!(page.next, None);
+
+    server.assert_response_regex(
+      "/search?q=hello&limit=1",
+      StatusCode::OK,
+      format!(
+        r".*<h1>Search results for “hello”</h1>.*<a href=/craftscription/{second}>Craftscription 1</a>.*<a class=next href=/search\?q=hello&amp;from=0&amp;limit=1>next</a>.*"
+      ),
+    );
+  }
+
+  #[test]
+  fn full_text_search_omits_hidden_inscriptions() {
+    let craftcoin_rpc_server = test_bitcoincore_rpc::spawn();
+    craftcoin_rpc_server.mine_blocks(1);
+    let txid = craftcoin_rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      witness: inscription("text/plain;charset=utf-8", "hello").to_witness(),
+      ..Default::default()
+    });
+    let inscription = InscriptionId::from(txid);
+    craftcoin_rpc_server.mine_blocks(1);
+
+    let server = TestServer::new_server(
+      craftcoin_rpc_server,
+      Some(format!("\"hidden\":\n - {inscription}")),
+      &["--index-text"],
+      &[],
+    );
+
+    assert!(search_text(&server, "q=hello").results.is_empty());
+  }
+
+  #[test]
+  fn full_text_search_errors() {
+    TestServer::new().assert_response(
+      "/search?q=hello",
+      StatusCode::BAD_REQUEST,
+      "full-text search requires index created with `--index-text` flag",
+    );
+
+    let server = TestServer::new_with_args(&["--index-text"], &[]);
+
+    server.assert_response(
+      "/search?q=%21%21",
+      StatusCode::BAD_REQUEST,
+      "search query `!!` contains no searchable words",
+    );
+
+    server.assert_response(
+      "/search?q=the+a",
+      StatusCode::BAD_REQUEST,
+      "search query `the a` contains no searchable words",
+    );
+
+    server.assert_response(
+      "/search?q=hello&limit=0",
+      StatusCode::BAD_REQUEST,
+      "limit must be between 1 and 1000",
+    );
+  }
+
+  fn offer(outgoing: OutPoint) -> String {
+    let mut transaction = Offer::template(outgoing, Amount::from_sat(1_000_000), &address());
+
+    let mut signature = [0; 72];
+    signature[71] = 0x83;
+
+    transaction.input[Offer::SELLER].script_sig = script::Builder::new()
+      .push_slice(&signature)
+      .push_slice(&[0; 33])
+      .into_script();
+
+    Offer::from_transaction(transaction).unwrap().to_string()
+  }
+
+  fn inscribe(server: &TestServer) -> SatPoint {
+    server.mine_blocks(1);
+
+    let txid = server
+      .craftcoin_rpc_server
+      .broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0)],
+        witness: inscription("text/plain", "hello").to_witness(),
+        ..Default::default()
+      });
+
+    server.mine_blocks(1);
+
+    SatPoint {
+      outpoint: OutPoint { txid, vout: 0 },
+      offset: 0,
+    }
+  }
+
+  #[test]
+  fn offer_validate_rejects_malformed_offer() {
+    let server = TestServer::new();
+    let satpoint = inscribe(&server);
+
+    assert_eq!(
+      server.validate_offer("00", satpoint),
+      OfferValidationJson {
+        valid: false,
+        error: Some("offer is not a transaction".into()),
+        satpoint,
+        price: None,
+        inscriptions: Vec::new(),
+      }
+    );
+  }
+
+  #[test]
+  fn offer_validate_rejects_offer_for_other_output() {
+    let server = TestServer::new();
+    let satpoint = inscribe(&server);
+
+    let outgoing = OutPoint {
+      txid: satpoint.outpoint.txid,
+      vout: 1,
+    };
+
+    assert_eq!(
+      server.validate_offer(&offer(outgoing), satpoint),
+      OfferValidationJson {
+        valid: false,
+        error: Some(format!(
+          "offer spends {outgoing}, not the output of {satpoint}"
+        )),
+        satpoint,
+        price: Some(1_000_000),
+        inscriptions: Vec::new(),
+      }
+    );
+  }
+
+  #[test]
+  fn offer_validate_rejects_offer_without_inscription_at_satpoint() {
+    let server = TestServer::new();
+    let inscribed = inscribe(&server);
+
+    let satpoint = SatPoint {
+      offset: 1,
+      ..inscribed
+    };
+
+    assert_eq!(
+      server.validate_offer(&offer(satpoint.outpoint), satpoint),
+      OfferValidationJson {
+        valid: false,
+        error: Some(format!(
+          "no inscription at {satpoint} and no cunes in output {}",
+          satpoint.outpoint
+        )),
+        satpoint,
+        price: Some(1_000_000),
+        inscriptions: vec![InscriptionId::from(inscribed.outpoint.txid)],
+      }
+    );
+  }
+
+  #[test]
+  fn offer_validate_rejects_spent_output() {
+    let server = TestServer::new();
+    let satpoint = inscribe(&server);
+
+    server
+      .craftcoin_rpc_server
+      .broadcast_tx(TransactionTemplate {
+        inputs: &[(2, 1, 0)],
+        ..Default::default()
+      });
+
+    server.mine_blocks(1);
+
+    assert_eq!(
+      server
+        .validate_offer(&offer(satpoint.outpoint), satpoint)
+        .error,
+      Some(format!(
+        "no inscription at {satpoint} and no cunes in output {}",
+        satpoint.outpoint
+      )),
+    );
+  }
+
+  #[test]
+  fn offer_validate_checks_seller_signature() {
+    let server = TestServer::new();
+    let satpoint = inscribe(&server);
+
+    assert_eq!(
+      server.validate_offer(&offer(satpoint.outpoint), satpoint),
+      OfferValidationJson {
+        valid: false,
+        error: Some("offer signature is malformed".into()),
+        satpoint,
+        price: Some(1_000_000),
+        inscriptions: vec![InscriptionId::from(satpoint.outpoint.txid)],
+      }
+    );
+  }
+
+  #[test]
+  fn offer_validate_accepts_signed_offer() {
+    use bitcoin::{
+      secp256k1::{Message, Secp256k1},
+      EcdsaSig, EcdsaSighashType, PrivateKey,
+    };
+
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let secp = Secp256k1::new();
+    let key = PrivateKey::from_slice(&[1; 32], Network::Bitcoin).unwrap();
+    let seller = Address::p2pkh(&key.public_key(&secp), Network::Bitcoin);
+
+    let txid = server.craftcoin_rpc_server.broadcast_tx_to(
+      TransactionTemplate {
+        inputs: &[(1, 0, 0)],
+        witness: inscription("text/plain", "hello").to_witness(),
+        ..Default::default()
+      },
+      &seller,
+    );
+
+    server.mine_blocks(1);
+
+    let satpoint = SatPoint {
+      outpoint: OutPoint { txid, vout: 0 },
+      offset: 0,
+    };
+
+    let mut transaction = Offer::template(satpoint.outpoint, Amount::from_sat(1_000_000), &seller);
+
+    let hash_ty = EcdsaSighashType::SinglePlusAnyoneCanPay;
+
+    let sighash =
+      transaction.signature_hash(Offer::SELLER, &seller.script_pubkey(), hash_ty.to_u32());
+
+    let signature = EcdsaSig {
+      sig: secp.sign_ecdsa(&Message::from_slice(&sighash[..]).unwrap(), &key.inner),
+      hash_ty,
+    };
+
+    transaction.input[Offer::SELLER].script_sig = script::Builder::new()
+      .push_slice(&signature.to_vec())
+      .push_key(&key.public_key(&secp))
+      .into_script();
+
+    let offer = Offer::from_transaction(transaction).unwrap().to_string();
+
+    assert_eq!(
+      server.validate_offer(&offer, satpoint),
+      OfferValidationJson {
+        valid: true,
+        error: None,
+        satpoint,
+        price: Some(1_000_000),
+        inscriptions: vec![InscriptionId::from(txid)],
+      }
+    );
+  }
+
+  fn sign_message(message: &str) -> (Address, String) {
+    use bitcoin::{
+      secp256k1::{Message, Secp256k1},
+      util::misc::MessageSignature,
+      PrivateKey,
+    };
+
+    let secp = Secp256k1::new();
+    let key = PrivateKey::from_slice(&[1; 32], Network::Bitcoin).unwrap();
+
+    let signature = secp.sign_ecdsa_recoverable(
+      &Message::from_slice(&signed_message::hash(message)[..]).unwrap(),
+      &key.inner,
+    );
+
+    (
+      Address::p2pkh(&key.public_key(&secp), Network::Bitcoin),
+      base64::encode(MessageSignature::new(signature, true).serialize()),
+    )
+  }
+
+  #[test]
+  fn verify_valid_signature() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let (address, signature) = sign_message("I own this");
+
+    let response = server.verify(&address.to_string(), "I own this", &signature);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(
+      serde_json::from_str::<VerifyJson>(&response.text().unwrap()).unwrap(),
+      VerifyJson {
+        valid: true,
+        error: None,
+        address: address.to_string(),
+        inscriptions: Vec::new(),
+        cunes: BTreeMap::new(),
+        crc20: Vec::new(),
+      }
+    );
+  }
+
+  #[test]
+  fn verify_signature_over_other_message() {
+    let server = TestServer::new();
+    server.mine_blocks(1);
+
+    let (address, signature) = sign_message("I own this");
+
+    let response = server.verify(&address.to_string(), "I own that", &signature);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(
+      serde_json::from_str::<VerifyJson>(&response.text().unwrap()).unwrap(),
+      VerifyJson {
+        valid: false,
+        error: Some(format!("message was not signed by {address}")),
+        address: address.to_string(),
+        inscriptions: Vec::new(),
+        cunes: BTreeMap::new(),
+        crc20: Vec::new(),
+      }
+    );
+  }
+
+  #[test]
+  fn verify_requires_valid_address() {
+    let server = TestServer::new();
+
+    assert_eq!(
+      server.verify("foo", "I own this", "").status(),
+      StatusCode::BAD_REQUEST
+    );
+  }
+
+  #[test]
+  fn search_inscriptions_by_content_type_and_media() {
+    let server = TestServer::new_with_args(&["--index-search"], &[]);
+    server.mine_blocks(2);
+
+    let text = server.inscribe_at(1, "text/plain", "hello");
+    let image = server.inscribe_at(2, "image/png", [1; 100]);
+
+    assert_eq!(
+      server.search_inscriptions(""),
+      InscriptionSearchJson {
+        inscriptions: vec![image, text],
+        next: None,
+      }
+    );
+
+    assert_eq!(
+      server
+        .search_inscriptions("content_type=text/plain")
+        .inscriptions,
+      [text]
+    );
+
+    assert_eq!(
+      server.search_inscriptions("media=image").inscriptions,
+      [image]
+    );
+
+    assert_eq!(
+      server
+        .search_inscriptions("media=text&content_type=image/png")
+        .inscriptions,
+      []
+    );
+  }
+
+  #[test]
+  fn search_inscriptions_by_height_and_content_length() {
+    let server = TestServer::new_with_args(&["--index-search"], &[]);
+    server.mine_blocks(2);
+
+    let text = server.inscribe_at(1, "text/plain", "hello");
+    let image = server.inscribe_at(2, "image/png", [1; 100]);
+
+    assert_eq!(
+      server.search_inscriptions("max_height=3").inscriptions,
+      [text]
+    );
+    assert_eq!(
+      server.search_inscriptions("min_height=4").inscriptions,
+      [image]
+    );
+
+    assert_eq!(
+      server
+        .search_inscriptions("min_content_length=10")
+        .inscriptions,
+      [image]
+    );
+
+    assert_eq!(
+      server
+        .search_inscriptions("min_content_length=5&max_content_length=5")
+        .inscriptions,
+      [text]
+    );
+  }
+
+  #[test]
+  fn search_inscriptions_pages_with_cursor() {
+    let server = TestServer::new_with_args(&["--index-search"], &[]);
+    server.mine_blocks(2);
+
+    let first = server.inscribe_at(1, "text/plain", "hello");
+    let second = server.inscribe_at(2, "text/plain", "world");
+
+    assert_eq!(
+      server.search_inscriptions("limit=1"),
+      InscriptionSearchJson {
+        inscriptions: vec![second],
+        next: Some(0),
+      }
+    );
+
+    assert_eq!(
+      server.search_inscriptions("limit=1&from=0"),
+      InscriptionSearchJson {
+        inscriptions: vec![first],
+        next: None,
+      }
+    );
+  }
+
+  #[test]
+  fn search_inscriptions_combines_filters() {
+    let server = TestServer::new_with_args(&["--index-search"], &[]);
+    server.mine_blocks(3);
+
+    let first = server.inscribe_at(1, "text/plain", "hello");
+    server.inscribe_at(2, "image/png", [1; 100]);
+    let third = server.inscribe_at(3, "text/plain", "world");
+
+    assert_eq!(
+      server
+        .search_inscriptions("content_type=text/plain&max_height=5")
+        .inscriptions,
+      [first]
+    );
+
+    assert_eq!(
+      server.search_inscriptions("content_type=text/plain&limit=1"),
+      InscriptionSearchJson {
+        inscriptions: vec![third],
+        next: Some(0),
+      }
+    );
+
+    assert_eq!(
+      server
+        .search_inscriptions("media=text&min_height=5&from=1")
+        .inscriptions,
+      []
+    );
+  }
+
+  #[test]
+  fn search_crc20_inscriptions() {
+    let server = TestServer::new_with_args(&["--index-search"], &[]);
+    server.mine_blocks(2);
+
+    let text = server.inscribe_at(1, "text/plain", "hello");
+    let deploy = server.inscribe_at(
+      2,
+      "text/plain",
+      r#"{"p":"crc-20","op":"deploy","tick":"abcd","max":"21000000","lim":"1000"}"#,
+    );
+
+    assert_eq!(
+      server.search_inscriptions("crc20=true").inscriptions,
+      [deploy]
+    );
+    assert_eq!(
+      server.search_inscriptions("crc20=false").inscriptions,
+      [text]
+    );
+  }
+
+  #[test]
+  fn search_inscriptions_by_rarity() {
+    let server = TestServer::new_with_args(&["--index-search", "--index-sats"], &[]);
+    server.mine_blocks(1);
+
+    let uncommon = server.inscribe_at(1, "text/plain", "hello");
+
+    assert_eq!(
+      server.search_inscriptions("rarity=uncommon").inscriptions,
+      [uncommon]
+    );
+
+    assert_eq!(server.search_inscriptions("rarity=common").inscriptions, []);
+  }
+
+  #[test]
+  fn search_inscriptions_rejects_invalid_queries() {
+    TestServer::new().assert_response(
+      "/inscriptions/search",
+      StatusCode::BAD_REQUEST,
+      "inscription search requires index created with `--index-search` flag",
+    );
+
+    let server = TestServer::new_with_args(&["--index-search"], &[]);
+
     server.assert_response(
-      format!("/content/{inscription}"),
+      "/inscriptions/search?rarity=uncommon",
+      StatusCode::BAD_REQUEST,
+      "searching by rarity requires index created with `--index-sats` flag",
+    );
+
+    server.assert_response(
+      "/inscriptions/search?min_height=5&max_height=4",
+      StatusCode::BAD_REQUEST,
+      "min_height 5 is greater than max_height 4",
+    );
+
+    server.assert_response(
+      "/inscriptions/search?media=sculpture",
+      StatusCode::BAD_REQUEST,
+      "unknown media class `sculpture`",
+    );
+
+    server.assert_response(
+      "/inscriptions/search?limit=0",
+      StatusCode::BAD_REQUEST,
+      "limit must be between 1 and 1000",
+    );
+  }
+
+  #[test]
+  fn inscription_history_lists_every_location() {
+    let server = TestServer::new_with_args(&["--index-locations"], &[]);
+    let genesis = inscribe(&server);
+    let inscription_id = InscriptionId::from(genesis.outpoint.txid);
+
+    let transfer = server
+      .craftcoin_rpc_server
+      .broadcast_tx(TransactionTemplate {
+        inputs: &[(2, 1, 0)],
+        ..Default::default()
+      });
+
+    server.mine_blocks(1);
+
+    let response = server.get(format!("/inscription/{inscription_id}/history"));
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let history =
+      serde_json::from_str::<InscriptionHistoryJson>(&response.text().unwrap()).unwrap();
+
+    assert_eq!(history.inscription_id, inscription_id);
+
+    assert_eq!(
+      history
+        .locations
+        .iter()
+        .map(|location| (
+          location.satpoint,
+          location.height,
+          location.txid,
+          location.sale
+        ))
+        .collect::<Vec<(SatPoint, u32, Txid, bool)>>(),
+      [
+        (genesis, 2, genesis.outpoint.txid, false),
+        (
+          SatPoint {
+            outpoint: OutPoint {
+              txid: transfer,
+              vout: 0,
+            },
+            offset: 0,
+          },
+          3,
+          transfer,
+          false,
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn inscription_history_of_unknown_inscription_is_not_found() {
+    let server = TestServer::new_with_args(&["--index-locations"], &[]);
+
+    server.assert_response(
+      format!("/inscription/{}/history", inscription_id(1)),
+      StatusCode::NOT_FOUND,
+      &format!("inscription {} not found", inscription_id(1)),
+    );
+  }
+
+  #[test]
+  fn inscription_history_requires_locations_index() {
+    let server = TestServer::new();
+
+    server.assert_response(
+      format!("/inscription/{}/history", inscription_id(1)),
+      StatusCode::BAD_REQUEST,
+      "inscription history requires index created with `--index-locations` flag",
+    );
+  }
+
+  const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+  #[test]
+  fn inscriptions_by_hash_lists_duplicates_in_inscription_order() {
+    let server = TestServer::new();
+    server.mine_blocks(3);
+
+    let original = server.inscribe_at(1, "text/plain", "hello");
+    let copy = server.inscribe_at(2, "text/plain;charset=utf-8", "hello");
+    server.inscribe_at(3, "text/plain", "world");
+
+    let response = server.get(format!("/inscriptions/by-hash/{HELLO_SHA256}"));
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(
+      serde_json::from_str::<InscriptionsByHashJson>(&response.text().unwrap()).unwrap(),
+      InscriptionsByHashJson {
+        sha256: HELLO_SHA256.parse().unwrap(),
+        inscriptions: vec![original, copy],
+      }
+    );
+  }
+
+  #[test]
+  fn inscription_shows_first_inscription_and_duplicate_count() {
+    let server = TestServer::new();
+    server.mine_blocks(2);
+
+    let original = server.inscribe_at(1, "text/plain", "hello");
+    let copy = server.inscribe_at(2, "text/plain", "hello");
+
+    server.assert_response_regex(
+      format!("/inscription/{copy}"),
       StatusCode::OK,
-      &fs::read_to_string("templates/preview-unknown.html").unwrap(),
+      format!(
+        r".*<dt>content hash</dt>\s*<dd><a class=monospace href=/inscriptions/by-hash/{HELLO_SHA256}>{HELLO_SHA256}</a></dd>\s*<dt>first inscribed as</dt>\s*<dd>#0</dd>\s*<dt>duplicates</dt>\s*<dd>1</dd>.*"
+      ),
+    );
+
+    let json = serde_json::from_str::<serde_json::Value>(
+      &server
+        .get(format!("/inscription/{original}?json=true"))
+        .text()
+        .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(json["content_sha256"], HELLO_SHA256);
+    assert_eq!(json["first_inscription_number"], 0);
+    assert_eq!(json["duplicates"], 1);
+  }
+
+  #[test]
+  fn inscriptions_by_hash_errors() {
+    let server = TestServer::new();
+
+    server.assert_response(
+      format!("/inscriptions/by-hash/{HELLO_SHA256}"),
+      StatusCode::NOT_FOUND,
+      &format!("no inscriptions with content hash {HELLO_SHA256}"),
+    );
+
+    server.assert_response_regex(
+      "/inscriptions/by-hash/hello",
+      StatusCode::BAD_REQUEST,
+      "invalid content hash `hello`: .*",
     );
   }
 }
@@ -0,0 +1,384 @@
+use {
+  super::error::ErrorCode,
+  super::*,
+  crate::templates::{CunesEntryJson, InputJson, InscriptionsPageJson, RangeJson, SatJson},
+  axum::{
+    body::Body,
+    http::Request,
+    middleware::{self, Next},
+  },
+  serde_json::Value,
+};
+
+/// Body of every `/api/v1` response: `{"data": ...}` on success, and
+/// `{"error": {"code": ..., "message": ...}}` on failure.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum ApiResponse {
+  Data(Value),
+  Error(ApiError),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(super) struct ApiError {
+  pub(super) code: ErrorCode,
+  pub(super) message: String,
+}
+
+/// Routes served under `/api/v1`. Each returns the same data as its
+/// unversioned counterpart, which remains available as a compatibility alias,
+/// wrapped in an `ApiResponse`. Pages which are only rendered as HTML get JSON
+/// handlers here, while inscription content and previews, which are not data
+/// about the chain, have no `/api/v1` equivalent.
+pub(super) fn router() -> Router {
+  Router::new()
+    .route("/address/:address", get(Server::outputs_by_address))
+    .route("/block/:query", get(block))
+    .route("/block-count", get(Server::block_count))
+    .route("/blockhash", get(blockhash))
+    .route("/blockhash/:height", get(blockhash_at_height))
+    .route("/blockheight", get(blockheight))
+    .route("/blocks/:query/:endquery", get(Server::blocks))
+    .route("/blocktime", get(blocktime))
+    .route(
+      "/craftscriptions_by_outputs",
+      get(Server::craftscriptions_by_outputs),
+    )
+    .route(
+      "/craftscriptions_on_outputs",
+      get(Server::inscriptions_by_outputs),
+    )
+    .route(
+      "/crc20/balance/:address",
+      get(Server::crc20_by_address_unpaginated),
+    )
+    .route("/crc20/tick", get(Server::crc20_all_tick_info))
+    .route("/crc20/tick/:tick", get(Server::crc20_tick_info))
+    .route("/crc20/tick/holder/:tick", get(Server::crc20_tick_holder))
+    .route("/crc20/ticks", get(Server::crc20_all_ticks))
+    .route("/crc20/validate", get(Server::crc20_validate))
+    .route("/cune/:cune", get(cune))
+    .route("/cunes", get(cunes))
+    .route(
+      "/cunes/balance/:address",
+      get(Server::cunes_by_address_unpaginated),
+    )
+    .route(
+      "/cunes/balance/:address/:page",
+      get(Server::cunes_by_address),
+    )
+    .route("/cunes_on_outputs", get(Server::cunes_by_outputs))
+    .route("/input/:block/:transaction/:input", get(input))
+    .route("/inscription/:inscription_id", get(inscription))
+    .route(
+      "/inscription/:inscription_id/history",
+      get(Server::inscription_history),
+    )
+    .route("/inscriptions", get(inscriptions))
+    .route("/inscriptions/:from", get(inscriptions_from))
+    .route(
+      "/inscriptions/balance/:address",
+      get(Server::inscriptions_by_address_unpaginated),
+    )
+    .route(
+      "/inscriptions/balance/:address/:page",
+      get(Server::inscriptions_by_address),
+    )
+    .route(
+      "/inscriptions/by-hash/:sha256",
+      get(Server::inscriptions_by_hash),
+    )
+    .route("/inscriptions/search", get(Server::inscriptions_search))
+    .route("/inscriptions/validate", get(Server::inscriptions_validate))
+    .route("/offer/validate", get(Server::offer_validate))
+    .route("/output/:output", get(output))
+    .route("/outputs/:output_list", get(Server::outputs))
+    .route("/range/:start/:end", get(range))
+    .route("/sat/:sat", get(sat))
+    .route("/search", get(search))
+    .route("/status", get(Server::status))
+    .route("/usage", get(Server::usage))
+    .route("/tx/:txid", get(transaction))
+    .route(
+      "/utxos/balance/:address",
+      get(Server::utxos_by_address_unpaginated),
+    )
+    .route(
+      "/utxos/balance/:address/:page",
+      get(Server::utxos_by_address),
+    )
+    .route("/verify", get(Server::verify))
+    .fallback(not_found)
+    .layer(middleware::from_fn(envelope))
+}
+
+async fn block(
+  page_config: Extension<Arc<PageConfig>>,
+  index: Extension<Arc<Index>>,
+  Path(DeserializeFromStr(query)): Path<DeserializeFromStr<query::Block>>,
+) -> ServerResult<Json<Value>> {
+  let height = match query {
+    query::Block::Height(height) => height,
+    query::Block::Hash(hash) => index
+      .block_header_info(hash)?
+      .ok_or_not_found(|| format!("block {hash}"))?
+      .height
+      .try_into()
+      .unwrap(),
+  };
+
+  let blocks = Server::blocks(
+    page_config,
+    index,
+    Path((height, height.saturating_add(1))),
+    Query(BlocksQuery {
+      no_inscriptions: None,
+      no_input_data: None,
+    }),
+  )
+  .await?;
+
+  serde_json::from_str::<Vec<Value>>(&blocks)
+    .context("failed to deserialize block")?
+    .into_iter()
+    .next()
+    .map(Json)
+    .ok_or_not_found(|| format!("block {height}"))
+}
+
+async fn blockhash(Extension(index): Extension<Arc<Index>>) -> ServerResult<Json<BlockHash>> {
+  Ok(Json(
+    index.block_hash(None)?.ok_or_not_found(|| "latest block")?,
+  ))
+}
+
+async fn blockhash_at_height(
+  Extension(index): Extension<Arc<Index>>,
+  Path(height): Path<u32>,
+) -> ServerResult<Json<BlockHash>> {
+  Ok(Json(
+    index
+      .block_hash(Some(height))?
+      .ok_or_not_found(|| format!("block {height}"))?,
+  ))
+}
+
+async fn blockheight(Extension(index): Extension<Arc<Index>>) -> ServerResult<Json<u32>> {
+  Ok(Json(latest_height(&index)?))
+}
+
+async fn blocktime(Extension(index): Extension<Arc<Index>>) -> ServerResult<Json<i64>> {
+  Ok(Json(
+    index
+      .blocktime(Height(latest_height(&index)?))?
+      .unix_timestamp(),
+  ))
+}
+
+fn latest_height(index: &Index) -> ServerResult<u32> {
+  index
+    .block_count()?
+    .checked_sub(1)
+    .ok_or_not_found(|| "latest block")
+}
+
+async fn cune(
+  page_config: Extension<Arc<PageConfig>>,
+  index: Extension<Arc<Index>>,
+  cune: Path<DeserializeFromStr<query::Cune>>,
+) -> ServerResult<Response> {
+  Server::cune(
+    page_config,
+    index,
+    cune,
+    Query(JsonQuery { json: Some(true) }),
+  )
+  .await
+}
+
+async fn cunes(Extension(index): Extension<Arc<Index>>) -> ServerResult<Json<Vec<CunesEntryJson>>> {
+  Ok(Json(
+    index
+      .cunes()?
+      .into_iter()
+      .map(|(id, entry)| CunesEntryJson {
+        id,
+        entry: CuneEntryJson::new(&entry),
+      })
+      .collect(),
+  ))
+}
+
+async fn input(
+  Extension(index): Extension<Arc<Index>>,
+  Path(path): Path<(u32, usize, usize)>,
+) -> ServerResult<Json<InputJson>> {
+  Ok(Json(InputJson::new(&Server::input_at(&index, path)?)))
+}
+
+async fn inscription(
+  page_config: Extension<Arc<PageConfig>>,
+  index: Extension<Arc<Index>>,
+  inscription_id: Path<InscriptionId>,
+) -> ServerResult<Response> {
+  Server::inscription(
+    page_config,
+    index,
+    inscription_id,
+    Query(JsonQuery { json: Some(true) }),
+  )
+  .await
+}
+
+async fn inscriptions(
+  Extension(index): Extension<Arc<Index>>,
+) -> ServerResult<Json<InscriptionsPageJson>> {
+  inscriptions_page(&index, None)
+}
+
+async fn inscriptions_from(
+  Extension(index): Extension<Arc<Index>>,
+  Path(from): Path<u64>,
+) -> ServerResult<Json<InscriptionsPageJson>> {
+  inscriptions_page(&index, Some(from))
+}
+
+fn inscriptions_page(index: &Index, from: Option<u64>) -> ServerResult<Json<InscriptionsPageJson>> {
+  let (inscriptions, prev, next) = index.get_latest_inscriptions_with_prev_and_next(100, from)?;
+
+  Ok(Json(InscriptionsPageJson {
+    inscriptions,
+    prev,
+    next,
+  }))
+}
+
+async fn output(
+  page_config: Extension<Arc<PageConfig>>,
+  index: Extension<Arc<Index>>,
+  Path(outpoint): Path<OutPoint>,
+) -> ServerResult<Json<OutputJson>> {
+  let Json(outputs) = Server::outputs(page_config, index, Path(outpoint.to_string())).await?;
+
+  outputs
+    .into_iter()
+    .next()
+    .map(Json)
+    .ok_or_not_found(|| format!("output {outpoint}"))
+}
+
+async fn range(
+  Path((DeserializeFromStr(start), DeserializeFromStr(end))): Path<(
+    DeserializeFromStr<Sat>,
+    DeserializeFromStr<Sat>,
+  )>,
+) -> ServerResult<Json<RangeJson>> {
+  Server::check_range(start, end)?;
+
+  Ok(Json(RangeJson {
+    start,
+    end,
+    size: end.n() - start.n(),
+  }))
+}
+
+async fn sat(
+  Extension(index): Extension<Arc<Index>>,
+  Path(DeserializeFromStr(sat)): Path<DeserializeFromStr<Sat>>,
+) -> ServerResult<Json<SatJson>> {
+  Ok(Json(SatJson {
+    number: sat.n(),
+    decimal: sat.decimal().to_string(),
+    height: sat.height().n(),
+    rarity: sat.rarity(),
+    satpoint: index.rare_sat_satpoint(sat)?,
+    timestamp: index.blocktime(sat.height())?.unix_timestamp(),
+    inscription: index.get_inscription_id_by_sat(sat)?,
+  }))
+}
+
+async fn search(
+  Extension(page_config): Extension<Arc<PageConfig>>,
+  Extension(index): Extension<Arc<Index>>,
+  Extension(config): Extension<Arc<Config>>,
+  Query(search): Query<SearchQuery>,
+) -> ServerResult<Response> {
+  if search.q.is_none() {
+    return Err(ServerError::BadRequest(
+      "search requires `q` parameter".into(),
+    ));
+  }
+
+  Server::search_text(
+    page_config,
+    &index,
+    &config,
+    SearchQuery {
+      json: Some(true),
+      ..search
+    },
+  )
+}
+
+async fn transaction(
+  page_config: Extension<Arc<PageConfig>>,
+  index: Extension<Arc<Index>>,
+  txid: Path<Txid>,
+) -> ServerResult<Response> {
+  Server::transaction(
+    page_config,
+    index,
+    txid,
+    Query(JsonQuery { json: Some(true) }),
+  )
+  .await
+}
+
+async fn not_found(OriginalUri(uri): OriginalUri) -> ServerError {
+  ServerError::NotFound(format!("route {} not found", uri.path()))
+}
+
+/// Wraps responses in an `ApiResponse`. Error codes come from the
+/// `ServerError` that produced the response, or, for failures like rejected
+/// path and query parameters, from the response status.
+async fn envelope(request: Request<Body>, next: Next<Body>) -> Response {
+  let (parts, body) = next.run(request).await.into_parts();
+
+  let body = match hyper::body::to_bytes(body).await {
+    Ok(body) => body,
+    Err(err) => return error_response(ErrorCode::Internal, err.to_string()),
+  };
+
+  if parts.status.is_success() {
+    let data = serde_json::from_slice(&body)
+      .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into()));
+
+    return (parts.status, Json(ApiResponse::Data(data))).into_response();
+  }
+
+  let code = parts
+    .extensions
+    .get::<ErrorCode>()
+    .copied()
+    .unwrap_or_else(|| ErrorCode::from_status(parts.status));
+
+  let message = if body.is_empty() {
+    parts
+      .status
+      .canonical_reason()
+      .unwrap_or_default()
+      .to_string()
+  } else {
+    String::from_utf8_lossy(&body).into()
+  };
+
+  error_response(code, message)
+}
+
+pub(super) fn error_response(code: ErrorCode, message: String) -> Response {
+  (
+    code.status(),
+    Json(ApiResponse::Error(ApiError { code, message })),
+  )
+    .into_response()
+}
@@ -0,0 +1,94 @@
+use {super::*, std::ops::Range};
+
+/// The part of a representation requested by a `Range` header.
+#[derive(Debug, PartialEq)]
+pub(super) enum ByteRange {
+  /// The whole representation, because no range was requested, the header
+  /// could not be parsed, or it requested multiple ranges.
+  Full,
+  Partial(Range<usize>),
+  Unsatisfiable,
+}
+
+impl ByteRange {
+  pub(super) fn parse(header: &str, len: usize) -> Self {
+    let Some(spec) = header.trim().strip_prefix("bytes=") else {
+      return Self::Full;
+    };
+
+    if spec.contains(',') {
+      return Self::Full;
+    }
+
+    let Some((start, end)) = spec.split_once('-') else {
+      return Self::Full;
+    };
+
+    match (start.trim(), end.trim()) {
+      ("", suffix) => match suffix.parse::<usize>() {
+        Ok(0) => Self::Unsatisfiable,
+        Ok(_) if len == 0 => Self::Unsatisfiable,
+        Ok(suffix) => Self::Partial(len.saturating_sub(suffix)..len),
+        Err(_) => Self::Full,
+      },
+      (start, "") => match start.parse::<usize>() {
+        Ok(start) if start >= len => Self::Unsatisfiable,
+        Ok(start) => Self::Partial(start..len),
+        Err(_) => Self::Full,
+      },
+      (start, end) => match (start.parse::<usize>(), end.parse::<usize>()) {
+        (Ok(start), Ok(end)) if end < start => Self::Full,
+        (Ok(start), Ok(_)) if start >= len => Self::Unsatisfiable,
+        (Ok(start), Ok(end)) => Self::Partial(start..end.min(len - 1) + 1),
+        _ => Self::Full,
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn bounded_ranges() {
+    assert_eq!(ByteRange::parse("bytes=0-0", 10), ByteRange::Partial(0..1));
+    assert_eq!(ByteRange::parse("bytes=2-5", 10), ByteRange::Partial(2..6));
+    assert_eq!(
+      ByteRange::parse("bytes=5-100", 10),
+      ByteRange::Partial(5..10)
+    );
+    assert_eq!(
+      ByteRange::parse(" bytes= 1 - 2 ", 10),
+      ByteRange::Partial(1..3)
+    );
+  }
+
+  #[test]
+  fn open_ranges() {
+    assert_eq!(ByteRange::parse("bytes=4-", 10), ByteRange::Partial(4..10));
+    assert_eq!(ByteRange::parse("bytes=-3", 10), ByteRange::Partial(7..10));
+    assert_eq!(ByteRange::parse("bytes=-30", 10), ByteRange::Partial(0..10));
+  }
+
+  #[test]
+  fn unsatisfiable_ranges() {
+    assert_eq!(ByteRange::parse("bytes=10-", 10), ByteRange::Unsatisfiable);
+    assert_eq!(
+      ByteRange::parse("bytes=10-20", 10),
+      ByteRange::Unsatisfiable
+    );
+    assert_eq!(ByteRange::parse("bytes=-0", 10), ByteRange::Unsatisfiable);
+    assert_eq!(ByteRange::parse("bytes=-1", 0), ByteRange::Unsatisfiable);
+    assert_eq!(ByteRange::parse("bytes=0-", 0), ByteRange::Unsatisfiable);
+  }
+
+  #[test]
+  fn ignored_ranges() {
+    assert_eq!(ByteRange::parse("items=0-1", 10), ByteRange::Full);
+    assert_eq!(ByteRange::parse("bytes=0-1,4-5", 10), ByteRange::Full);
+    assert_eq!(ByteRange::parse("bytes=5-1", 10), ByteRange::Full);
+    assert_eq!(ByteRange::parse("bytes=a-b", 10), ByteRange::Full);
+    assert_eq!(ByteRange::parse("bytes=", 10), ByteRange::Full);
+  }
+}
@@ -0,0 +1,387 @@
+use {
+  super::*,
+  axum::{
+    body::{Body, BoxBody, Bytes, HttpBody},
+    extract::State,
+    http::{Method, Request},
+    middleware::Next,
+  },
+};
+
+/// Routes whose responses only change when blocks are rolled back, like
+/// inscription content.
+const IMMUTABLE_ROUTES: &[&str] = &["/content/", "/preview/"];
+
+/// Routes whose responses change as blocks are indexed, but not in between,
+/// like address listings, or transactions and blocks, which show their
+/// confirmations and the best height.
+const HEIGHT_SCOPED_ROUTES: &[&str] = &[
+  "/address/",
+  "/block/",
+  "/blocks/",
+  "/crc20/balance/",
+  "/cunes/balance/",
+  "/inscriptions/balance/",
+  "/tx/",
+  "/utxos/balance/",
+];
+
+/// Rough per-entry overhead, counted against the cache size so that many
+/// small responses can't grow the cache far beyond its cap.
+const ENTRY_OVERHEAD: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Scope {
+  Immutable,
+  Height,
+}
+
+impl Scope {
+  fn of(path: &str) -> Option<Self> {
+    let path = path.strip_prefix("/api/v1").unwrap_or(path);
+
+    if IMMUTABLE_ROUTES
+      .iter()
+      .any(|prefix| path.starts_with(prefix))
+    {
+      Some(Self::Immutable)
+    } else if HEIGHT_SCOPED_ROUTES
+      .iter()
+      .any(|prefix| path.starts_with(prefix))
+    {
+      Some(Self::Height)
+    } else {
+      None
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct CachedResponse {
+  generation: u64,
+  headers: HeaderMap,
+  body: Bytes,
+}
+
+impl CachedResponse {
+  fn size(&self, uri: &str) -> usize {
+    ENTRY_OVERHEAD
+      + uri.len()
+      + self.body.len()
+      + self
+        .headers
+        .iter()
+        .map(|(name, value)| name.as_str().len() + value.len())
+        .sum::<usize>()
+  }
+}
+
+/// Least recently used responses by request URI, up to `capacity` bytes.
+#[derive(Debug)]
+struct Lru {
+  capacity: usize,
+  entries: LinkedHashMap<String, CachedResponse>,
+  size: usize,
+}
+
+impl Lru {
+  fn new(capacity: usize) -> Self {
+    Self {
+      capacity,
+      entries: LinkedHashMap::new(),
+      size: 0,
+    }
+  }
+
+  /// The response cached for `uri`, if it was cached at `generation`.
+  /// Responses cached at other generations are stale and are dropped.
+  fn get(&mut self, uri: &str, generation: u64) -> Option<CachedResponse> {
+    let cached = self.entries.get_refresh(uri)?;
+
+    if cached.generation == generation {
+      return Some(cached.clone());
+    }
+
+    self.remove(uri);
+
+    None
+  }
+
+  /// Whether a body of `length` bytes could be cached at all, checked before
+  /// buffering it.
+  fn admits(&self, length: u64) -> bool {
+    usize::try_from(length)
+      .ok()
+      .and_then(|length| length.checked_add(ENTRY_OVERHEAD))
+      .map(|size| size <= self.capacity)
+      .unwrap_or_default()
+  }
+
+  fn insert(&mut self, uri: String, response: CachedResponse) {
+    self.remove(&uri);
+
+    let size = response.size(&uri);
+
+    if size > self.capacity {
+      return;
+    }
+
+    while self.size + size > self.capacity {
+      let Some((uri, evicted)) = self.entries.pop_front() else {
+        break;
+      };
+      self.size -= evicted.size(&uri);
+    }
+
+    self.size += size;
+    self.entries.insert(uri, response);
+  }
+
+  fn remove(&mut self, uri: &str) {
+    if let Some(removed) = self.entries.remove(uri) {
+      self.size -= removed.size(uri);
+    }
+  }
+}
+
+/// In-memory cache of responses to routes that only change when the index
+/// does. Entries are tagged with the index generation they were served at,
+/// so updater commits and reorg rollbacks invalidate them.
+pub(super) struct ResponseCache {
+  enabled: bool,
+  index: Arc<Index>,
+  lru: Mutex<Lru>,
+}
+
+impl ResponseCache {
+  pub(super) fn new(index: Arc<Index>, capacity: usize) -> Self {
+    Self {
+      enabled: capacity > 0,
+      index,
+      lru: Mutex::new(Lru::new(capacity)),
+    }
+  }
+
+  fn generation(&self, scope: Scope) -> u64 {
+    match scope {
+      Scope::Immutable => self.index.reorg_generation(),
+      Scope::Height => self.index.commit_generation(),
+    }
+  }
+
+  /// The length of `body`, from its `Content-Length` header or its size hint,
+  /// or `None` if it is streamed without a known length.
+  fn body_length(headers: &HeaderMap, body: &BoxBody) -> Option<u64> {
+    headers
+      .get(header::CONTENT_LENGTH)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.parse().ok())
+      .or_else(|| body.size_hint().exact())
+  }
+
+  fn etag(body: &[u8]) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{}\"", sha256::Hash::hash(body))).unwrap()
+  }
+
+  fn response(request_headers: &HeaderMap, cached: CachedResponse) -> Response {
+    let mut headers = cached.headers;
+
+    if let Some(etag) = headers.get(header::ETAG) {
+      if Server::is_not_modified(request_headers, etag) {
+        headers.remove(header::CONTENT_LENGTH);
+        headers.remove(header::CONTENT_TYPE);
+        return (StatusCode::NOT_MODIFIED, headers).into_response();
+      }
+    }
+
+    let mut response = Response::new(body::boxed(body::Full::from(cached.body)));
+    *response.headers_mut() = headers;
+    response
+  }
+}
+
+/// Serves successful `GET` responses of cacheable routes from the cache,
+/// adding an `ETag` to those that don't have one and answering matching
+/// `If-None-Match` requests with `304 Not Modified`. Range requests are
+/// passed through, since their responses depend on the `Range` header, as are
+/// responses too large to cache or of unknown length, which are not buffered.
+pub(super) async fn cache(
+  State(cache): State<Arc<ResponseCache>>,
+  request: Request<Body>,
+  next: Next<Body>,
+) -> Response {
+  let Some(scope) = Scope::of(request.uri().path()) else {
+    return next.run(request).await;
+  };
+
+  if !cache.enabled
+    || request.method() != Method::GET
+    || request.headers().contains_key(header::RANGE)
+  {
+    return next.run(request).await;
+  }
+
+  // Read before running the handler, so a response that straddles an index
+  // update is tagged with the older generation and never served as current.
+  let generation = cache.generation(scope);
+  let uri = request.uri().to_string();
+  let request_headers = request.headers().clone();
+
+  let cached = cache.lru.lock().unwrap().get(&uri, generation);
+
+  if let Some(cached) = cached {
+    return ResponseCache::response(&request_headers, cached);
+  }
+
+  let response = next.run(request).await;
+
+  if response.status() != StatusCode::OK {
+    return response;
+  }
+
+  let (mut parts, body) = response.into_parts();
+
+  let admitted = ResponseCache::body_length(&parts.headers, &body)
+    .map(|length| cache.lru.lock().unwrap().admits(length))
+    .unwrap_or_default();
+
+  if !admitted {
+    return Response::from_parts(parts, body);
+  }
+
+  let body = match hyper::body::to_bytes(body).await {
+    Ok(body) => body,
+    Err(err) => return ServerError::Internal(anyhow!(err)).into_response(),
+  };
+
+  if !parts.headers.contains_key(header::ETAG) {
+    parts
+      .headers
+      .insert(header::ETAG, ResponseCache::etag(&body));
+  }
+
+  let cached = CachedResponse {
+    generation,
+    headers: parts.headers,
+    body,
+  };
+
+  cache.lru.lock().unwrap().insert(uri, cached.clone());
+
+  ResponseCache::response(&request_headers, cached)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn response(generation: u64, body: &'static str) -> CachedResponse {
+    CachedResponse {
+      generation,
+      headers: HeaderMap::new(),
+      body: Bytes::from_static(body.as_bytes()),
+    }
+  }
+
+  #[test]
+  fn routes_are_scoped() {
+    assert_eq!(Scope::of("/content/foo"), Some(Scope::Immutable));
+    assert_eq!(Scope::of("/api/v1/preview/foo"), Some(Scope::Immutable));
+    assert_eq!(Scope::of("/tx/foo"), Some(Scope::Height));
+    assert_eq!(Scope::of("/api/v1/utxos/balance/foo"), Some(Scope::Height));
+    assert_eq!(Scope::of("/status"), None);
+    assert_eq!(Scope::of("/api/v1/block-count"), None);
+  }
+
+  #[test]
+  fn stale_generations_are_misses() {
+    let mut lru = Lru::new(1 << 20);
+
+    lru.insert("/tx/foo".into(), response(1, "foo"));
+
+    assert_eq!(lru.get("/tx/foo", 1), Some(response(1, "foo")));
+    assert_eq!(lru.get("/tx/foo", 2), None);
+    assert_eq!(lru.get("/tx/foo", 1), None);
+    assert_eq!(lru.size, 0);
+  }
+
+  #[test]
+  fn least_recently_used_entries_are_evicted() {
+    let size = response(0, "foo").size("/tx/a");
+
+    let mut lru = Lru::new(2 * size);
+
+    lru.insert("/tx/a".into(), response(0, "foo"));
+    lru.insert("/tx/b".into(), response(0, "bar"));
+
+    assert!(lru.get("/tx/a", 0).is_some());
+
+    lru.insert("/tx/c".into(), response(0, "baz"));
+
+    assert!(lru.get("/tx/a", 0).is_some());
+    assert!(lru.get("/tx/b", 0).is_none());
+    assert!(lru.get("/tx/c", 0).is_some());
+    assert_eq!(lru.size, 2 * size);
+  }
+
+  #[test]
+  fn responses_larger_than_capacity_are_not_cached() {
+    let mut lru = Lru::new(ENTRY_OVERHEAD);
+
+    lru.insert("/tx/a".into(), response(0, "foo"));
+
+    assert!(lru.get("/tx/a", 0).is_none());
+    assert_eq!(lru.size, 0);
+
+    let mut lru = Lru::new(0);
+
+    lru.insert("/tx/a".into(), response(0, ""));
+
+    assert!(lru.entries.is_empty());
+  }
+
+  #[test]
+  fn oversized_bodies_are_not_admitted() {
+    let lru = Lru::new(ENTRY_OVERHEAD + 3);
+
+    assert!(lru.admits(0));
+    assert!(lru.admits(3));
+    assert!(!lru.admits(4));
+    assert!(!lru.admits(u64::MAX));
+  }
+
+  #[test]
+  fn body_length_is_known_unless_streamed() {
+    let full = body::boxed(body::Full::from("foo"));
+
+    assert_eq!(
+      ResponseCache::body_length(&HeaderMap::new(), &full),
+      Some(3)
+    );
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from_static("1000"));
+
+    assert_eq!(ResponseCache::body_length(&headers, &full), Some(1000));
+
+    let streamed = body::boxed(body::StreamBody::new(futures::stream::once(async {
+      Ok::<_, io::Error>(Bytes::from_static(b"foo"))
+    })));
+
+    assert_eq!(
+      ResponseCache::body_length(&HeaderMap::new(), &streamed),
+      None
+    );
+  }
+
+  #[test]
+  fn replacing_an_entry_updates_size() {
+    let mut lru = Lru::new(1 << 20);
+
+    lru.insert("/tx/a".into(), response(0, "foo"));
+    lru.insert("/tx/a".into(), response(1, "foobar"));
+
+    assert_eq!(lru.get("/tx/a", 1), Some(response(1, "foobar")));
+    assert_eq!(lru.size, response(1, "foobar").size("/tx/a"));
+  }
+}
@@ -4,13 +4,63 @@ pub(super) enum ServerError {
   Internal(Error),
   BadRequest(String),
   NotFound(String),
+  Unauthorized(String),
 }
 
 pub(super) type ServerResult<T> = Result<T, ServerError>;
 
+/// Machine readable error codes returned by the `/api/v1` routes. Responses
+/// produced from a `ServerError` carry their code as a response extension.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum ErrorCode {
+  BadRequest,
+  Internal,
+  NotFound,
+  RateLimited,
+  Unauthorized,
+}
+
+impl ErrorCode {
+  /// The code for error responses that were not produced from a
+  /// `ServerError`, such as extractor rejections.
+  pub(super) fn from_status(status: StatusCode) -> Self {
+    match status {
+      StatusCode::NOT_FOUND => Self::NotFound,
+      StatusCode::TOO_MANY_REQUESTS => Self::RateLimited,
+      StatusCode::UNAUTHORIZED => Self::Unauthorized,
+      status if status.is_client_error() => Self::BadRequest,
+      _ => Self::Internal,
+    }
+  }
+
+  pub(super) fn status(self) -> StatusCode {
+    match self {
+      Self::BadRequest => StatusCode::BAD_REQUEST,
+      Self::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+      Self::NotFound => StatusCode::NOT_FOUND,
+      Self::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+      Self::Unauthorized => StatusCode::UNAUTHORIZED,
+    }
+  }
+}
+
+impl ServerError {
+  pub(super) fn code(&self) -> ErrorCode {
+    match self {
+      Self::Internal(_) => ErrorCode::Internal,
+      Self::BadRequest(_) => ErrorCode::BadRequest,
+      Self::NotFound(_) => ErrorCode::NotFound,
+      Self::Unauthorized(_) => ErrorCode::Unauthorized,
+    }
+  }
+}
+
 impl IntoResponse for ServerError {
   fn into_response(self) -> Response {
-    match self {
+    let code = self.code();
+
+    let mut response = match self {
       Self::Internal(error) => {
         eprintln!("error serving request: {error}");
         (
@@ -23,7 +73,12 @@ impl IntoResponse for ServerError {
       }
       Self::NotFound(message) => (StatusCode::NOT_FOUND, message).into_response(),
       Self::BadRequest(message) => (StatusCode::BAD_REQUEST, message).into_response(),
-    }
+      Self::Unauthorized(message) => (StatusCode::UNAUTHORIZED, message).into_response(),
+    };
+
+    response.extensions_mut().insert(code);
+
+    response
   }
 }
 
@@ -45,3 +100,45 @@ impl From<Error> for ServerError {
     Self::Internal(error)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn error_responses_carry_their_code() {
+    for (error, code) in [
+      (ServerError::BadRequest("foo".into()), ErrorCode::BadRequest),
+      (ServerError::NotFound("foo".into()), ErrorCode::NotFound),
+      (ServerError::Internal(anyhow!("foo")), ErrorCode::Internal),
+    ] {
+      let response = error.into_response();
+      assert_eq!(response.status(), code.status());
+      assert_eq!(response.extensions().get::<ErrorCode>(), Some(&code));
+    }
+  }
+
+  #[test]
+  fn codes_from_status() {
+    assert_eq!(
+      ErrorCode::from_status(StatusCode::NOT_FOUND),
+      ErrorCode::NotFound
+    );
+    assert_eq!(
+      ErrorCode::from_status(StatusCode::UNPROCESSABLE_ENTITY),
+      ErrorCode::BadRequest
+    );
+    assert_eq!(
+      ErrorCode::from_status(StatusCode::BAD_GATEWAY),
+      ErrorCode::Internal
+    );
+    assert_eq!(
+      ErrorCode::from_status(StatusCode::TOO_MANY_REQUESTS),
+      ErrorCode::RateLimited
+    );
+    assert_eq!(
+      ErrorCode::from_status(StatusCode::UNAUTHORIZED),
+      ErrorCode::Unauthorized
+    );
+  }
+}
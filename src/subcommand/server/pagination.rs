@@ -0,0 +1,334 @@
+use {super::*, std::ops::Range, url::form_urlencoded};
+
+/// Position in a paginated listing. A cursor is only valid at the index height
+/// it was issued at, since the listing it points into may have changed since.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Cursor {
+  pub(super) height: u32,
+  pub(super) offset: usize,
+}
+
+impl Display for Cursor {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let mut bytes = self.height.to_be_bytes().to_vec();
+    bytes.extend_from_slice(&u64::try_from(self.offset).unwrap().to_be_bytes());
+    write!(f, "{}", hex::encode(bytes))
+  }
+}
+
+impl FromStr for Cursor {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let bytes = hex::decode(s)?;
+
+    if bytes.len() != 12 {
+      bail!("expected 12 bytes, got {}", bytes.len());
+    }
+
+    Ok(Self {
+      height: u32::from_be_bytes(bytes[..4].try_into().unwrap()),
+      offset: u64::from_be_bytes(bytes[4..].try_into().unwrap()).try_into()?,
+    })
+  }
+}
+
+/// A page of a listing, along with the total number of items in the listing
+/// and the cursor of the following page, if any.
+#[derive(Debug, PartialEq)]
+pub(super) struct Page<T> {
+  pub(super) items: Vec<T>,
+  pub(super) next: Option<Cursor>,
+  pub(super) total: usize,
+}
+
+/// Selects a page of a listing, either by cursor, or by the legacy one-based
+/// page numbers of the `/:page` routes.
+#[derive(Debug, PartialEq)]
+pub(super) struct Pagination {
+  height: u32,
+  offset: usize,
+  limit: Option<usize>,
+}
+
+impl Pagination {
+  pub(super) const DEFAULT_LIMIT: usize = 10;
+  pub(super) const MAX_LIMIT: usize = 1000;
+
+  /// Pagination of a listing read from `index` at its current height.
+  pub(super) fn for_index(
+    index: &Index,
+    cursor: Option<&str>,
+    page: Option<u32>,
+    limit: Option<usize>,
+  ) -> ServerResult<Self> {
+    Self::new(
+      index.height()?.map(|height| height.n()).unwrap_or_default(),
+      cursor,
+      page,
+      limit,
+    )
+  }
+
+  /// Pagination of a listing read at `height`. `cursor` takes precedence over
+  /// `page`, and `limit: None` selects the whole listing.
+  pub(super) fn new(
+    height: u32,
+    cursor: Option<&str>,
+    page: Option<u32>,
+    limit: Option<usize>,
+  ) -> ServerResult<Self> {
+    if matches!(limit, Some(limit) if limit == 0 || limit > Self::MAX_LIMIT) {
+      return Err(ServerError::BadRequest(format!(
+        "limit must be between 1 and {}",
+        Self::MAX_LIMIT
+      )));
+    }
+
+    let offset = match cursor {
+      Some(cursor) => {
+        let cursor = cursor
+          .parse::<Cursor>()
+          .map_err(|err| ServerError::BadRequest(format!("invalid cursor `{cursor}`: {err}")))?;
+
+        if cursor.height != height {
+          return Err(ServerError::BadRequest(format!(
+            "cursor was issued at height {} but the index is at height {height}, restart from the first page",
+            cursor.height
+          )));
+        }
+
+        cursor.offset
+      }
+      None => match limit {
+        Some(limit) => usize::try_from(page.unwrap_or(1).saturating_sub(1))
+          .ok()
+          .and_then(|skipped| skipped.checked_mul(limit))
+          .ok_or_else(|| {
+            ServerError::BadRequest(format!("page {} is out of range", page.unwrap_or(1)))
+          })?,
+        None => 0,
+      },
+    };
+
+    Ok(Self {
+      height,
+      offset,
+      limit,
+    })
+  }
+
+  fn range(&self) -> Range<usize> {
+    match self.limit {
+      Some(limit) => self.offset..self.offset.saturating_add(limit),
+      None => self.offset..usize::MAX,
+    }
+  }
+
+  pub(super) fn paginate<T>(&self, items: impl IntoIterator<Item = T>) -> Page<T> {
+    let range = self.range();
+
+    let mut total = 0;
+    let mut page = Vec::new();
+
+    for item in items {
+      if range.contains(&total) {
+        page.push(item);
+      }
+      total += 1;
+    }
+
+    Page {
+      items: page,
+      next: (total > range.end).then_some(Cursor {
+        height: self.height,
+        offset: range.end,
+      }),
+      total,
+    }
+  }
+}
+
+/// Link to the page at `cursor` of the listing served at `uri`, keeping all
+/// query parameters other than `cursor`.
+pub(super) fn next_link(uri: &Uri, cursor: Cursor) -> String {
+  let mut query = form_urlencoded::Serializer::new(String::new());
+
+  for (key, value) in form_urlencoded::parse(uri.query().unwrap_or_default().as_bytes()) {
+    if key != "cursor" {
+      query.append_pair(&key, &value);
+    }
+  }
+
+  query.append_pair("cursor", &cursor.to_string());
+
+  format!("{}?{}", uri.path(), query.finish())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cursors_round_trip() {
+    let cursor = Cursor {
+      height: 100,
+      offset: 20,
+    };
+
+    assert_eq!(cursor.to_string(), "000000640000000000000014");
+    assert_eq!(cursor.to_string().parse::<Cursor>().unwrap(), cursor);
+  }
+
+  #[test]
+  fn malformed_cursors_are_rejected() {
+    assert!("foo".parse::<Cursor>().is_err());
+    assert!("00".parse::<Cursor>().is_err());
+    assert!("00000064000000000000001400".parse::<Cursor>().is_err());
+  }
+
+  #[test]
+  fn pages_are_contiguous() {
+    let first = Pagination::new(5, None, None, Some(2))
+      .unwrap()
+      .paginate(0..5);
+
+    assert_eq!(
+      first,
+      Page {
+        items: vec![0, 1],
+        next: Some(Cursor {
+          height: 5,
+          offset: 2
+        }),
+        total: 5,
+      }
+    );
+
+    let second = Pagination::new(5, Some(&first.next.unwrap().to_string()), None, Some(2))
+      .unwrap()
+      .paginate(0..5);
+
+    assert_eq!(second.items, [2, 3]);
+
+    let third = Pagination::new(5, Some(&second.next.unwrap().to_string()), None, Some(2))
+      .unwrap()
+      .paginate(0..5);
+
+    assert_eq!(
+      third,
+      Page {
+        items: vec![4],
+        next: None,
+        total: 5,
+      }
+    );
+  }
+
+  #[test]
+  fn page_numbers_do_not_skip_items() {
+    for page in 1..=3 {
+      assert_eq!(
+        Pagination::new(0, None, Some(page), Some(2))
+          .unwrap()
+          .paginate(0..6)
+          .items,
+        [
+          2 * usize::try_from(page).unwrap() - 2,
+          2 * usize::try_from(page).unwrap() - 1
+        ]
+      );
+    }
+
+    assert_eq!(
+      Pagination::new(0, None, Some(0), Some(2))
+        .unwrap()
+        .paginate(0..6)
+        .items,
+      [0, 1]
+    );
+  }
+
+  #[test]
+  fn cursors_take_precedence_over_pages() {
+    let cursor = Cursor {
+      height: 0,
+      offset: 1,
+    };
+
+    assert_eq!(
+      Pagination::new(0, Some(&cursor.to_string()), Some(3), Some(2))
+        .unwrap()
+        .paginate(0..6)
+        .items,
+      [1, 2]
+    );
+  }
+
+  #[test]
+  fn without_limit_everything_is_returned() {
+    assert_eq!(
+      Pagination::new(0, None, Some(3), None)
+        .unwrap()
+        .paginate(0..6),
+      Page {
+        items: (0..6).collect(),
+        next: None,
+        total: 6,
+      }
+    );
+  }
+
+  #[test]
+  fn stale_and_invalid_cursors_are_bad_requests() {
+    let cursor = Cursor {
+      height: 1,
+      offset: 2,
+    }
+    .to_string();
+
+    assert!(matches!(
+      Pagination::new(2, Some(&cursor), None, Some(2)),
+      Err(ServerError::BadRequest(message))
+        if message == "cursor was issued at height 1 but the index is at height 2, restart from the first page"
+    ));
+
+    assert!(matches!(
+      Pagination::new(2, Some("foo"), None, Some(2)),
+      Err(ServerError::BadRequest(message)) if message.starts_with("invalid cursor `foo`: ")
+    ));
+
+    assert!(matches!(
+      Pagination::new(2, None, None, Some(0)),
+      Err(ServerError::BadRequest(message)) if message == "limit must be between 1 and 1000"
+    ));
+
+    assert!(matches!(
+      Pagination::new(2, None, None, Some(1001)),
+      Err(ServerError::BadRequest(message)) if message == "limit must be between 1 and 1000"
+    ));
+  }
+
+  #[test]
+  fn next_links_replace_cursor() {
+    let cursor = Cursor {
+      height: 1,
+      offset: 2,
+    };
+
+    assert_eq!(
+      next_link(&"/utxos/balance/foo".parse().unwrap(), cursor),
+      format!("/utxos/balance/foo?cursor={cursor}")
+    );
+
+    assert_eq!(
+      next_link(
+        &"/api/v1/utxos/balance/foo?limit=2&cursor=bar&show_all=false"
+          .parse()
+          .unwrap(),
+        cursor
+      ),
+      format!("/api/v1/utxos/balance/foo?limit=2&show_all=false&cursor={cursor}")
+    );
+  }
+}
@@ -0,0 +1,576 @@
+use {
+  super::error::ErrorCode,
+  super::*,
+  crate::config::{Budget, Budgets},
+  axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::Request,
+    middleware::Next,
+  },
+  std::{
+    collections::BTreeMap,
+    net::{IpAddr, SocketAddr},
+  },
+};
+
+pub(super) const API_KEY_HEADER: &str = "x-api-key";
+
+/// Least recently used buckets are dropped once there are this many, which
+/// bounds memory use under traffic from many addresses.
+const MAX_BUCKETS: usize = 100_000;
+
+/// Routes that scan large parts of the index, like block ranges, address
+/// listings and token holders, draw from a separate, smaller budget.
+const EXPENSIVE_ROUTES: &[&str] = &[
+  "/address/",
+  "/api/batch/",
+  "/blocks/",
+  "/crc20/balance/",
+  "/crc20/tick/holder/",
+  "/cunes/balance/",
+  "/inscriptions/balance/",
+  "/utxos/balance/",
+];
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub(super) enum RouteClass {
+  Default,
+  Expensive,
+}
+
+impl RouteClass {
+  pub(super) fn of(path: &str) -> Self {
+    let path = path.strip_prefix("/api/v1").unwrap_or(path);
+
+    if EXPENSIVE_ROUTES
+      .iter()
+      .any(|prefix| path.starts_with(prefix))
+    {
+      Self::Expensive
+    } else {
+      Self::Default
+    }
+  }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum Client {
+  Address(IpAddr),
+  Key(String),
+}
+
+struct Bucket {
+  budget: Budget,
+  tokens: f64,
+  updated: Instant,
+}
+
+impl Bucket {
+  fn new(budget: Budget, now: Instant) -> Self {
+    Self {
+      budget,
+      tokens: f64::from(budget.burst),
+      updated: now,
+    }
+  }
+
+  fn refill(&mut self, now: Instant) {
+    let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+
+    self.tokens = (self.tokens + elapsed * f64::from(self.budget.per_minute) / 60.0)
+      .min(f64::from(self.budget.burst));
+    self.updated = now;
+  }
+
+  /// Takes a token, or returns how long until one will be available.
+  fn take(&mut self, now: Instant) -> Result<(), Duration> {
+    self.refill(now);
+
+    if self.tokens >= 1.0 {
+      self.tokens -= 1.0;
+      return Ok(());
+    }
+
+    if self.budget.per_minute == 0 {
+      return Err(Duration::from_secs(60));
+    }
+
+    Err(Duration::from_secs_f64(
+      (1.0 - self.tokens) * 60.0 / f64::from(self.budget.per_minute),
+    ))
+  }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Usage {
+  pub(crate) requests: u64,
+  pub(crate) limited: u64,
+}
+
+/// Request counters served at `/api/usage`. Requests without an API key are
+/// counted together under `anonymous`, and keyed requests under the name of
+/// their key. Only admin keys see `anonymous` and the usage of other keys.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct UsageJson {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub(crate) anonymous: Option<Usage>,
+  pub(crate) keys: BTreeMap<String, Usage>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum Decision {
+  Allow,
+  Limited(Duration),
+  UnknownKey,
+}
+
+#[derive(Default)]
+struct Limits {
+  anonymous: Usage,
+  buckets: LinkedHashMap<(Client, RouteClass), Bucket>,
+  keys: BTreeMap<String, Usage>,
+}
+
+struct Key {
+  admin: bool,
+  budgets: Option<Budgets>,
+  name: String,
+}
+
+pub(super) struct RateLimiter {
+  anonymous: Option<Budgets>,
+  keys: HashMap<String, Key>,
+  limits: Mutex<Limits>,
+  trust_forwarded_for: bool,
+}
+
+impl RateLimiter {
+  pub(super) fn new(config: &Config) -> Self {
+    Self {
+      anonymous: config.rate_limit.anonymous,
+      keys: config
+        .api_keys
+        .iter()
+        .map(|api_key| {
+          (
+            api_key.key.clone(),
+            Key {
+              admin: api_key.admin,
+              budgets: api_key.budgets.or(config.rate_limit.keyed),
+              name: api_key.name.clone(),
+            },
+          )
+        })
+        .collect(),
+      limits: Default::default(),
+      trust_forwarded_for: config.rate_limit.trust_forwarded_for,
+    }
+  }
+
+  pub(super) fn check(
+    &self,
+    key: Option<&str>,
+    address: Option<IpAddr>,
+    class: RouteClass,
+    now: Instant,
+  ) -> Decision {
+    let (client, budgets) = match key {
+      Some(key) => match self.keys.get(key) {
+        Some(key) => (Some(Client::Key(key.name.clone())), key.budgets),
+        None => return Decision::UnknownKey,
+      },
+      None => (address.map(Client::Address), self.anonymous),
+    };
+
+    let mut limits = self.limits.lock().unwrap();
+
+    let decision = match (client.clone(), budgets) {
+      (Some(client), Some(budgets)) => {
+        let budget = match class {
+          RouteClass::Default => budgets.default,
+          RouteClass::Expensive => budgets.expensive,
+        };
+
+        let key = (client, class);
+
+        if limits.buckets.get_refresh(&key).is_none() {
+          if limits.buckets.len() >= MAX_BUCKETS {
+            limits.buckets.pop_front();
+          }
+
+          limits.buckets.insert(key.clone(), Bucket::new(budget, now));
+        }
+
+        match limits.buckets.get_mut(&key).unwrap().take(now) {
+          Ok(()) => Decision::Allow,
+          Err(retry_after) => Decision::Limited(retry_after),
+        }
+      }
+      _ => Decision::Allow,
+    };
+
+    let usage = match client {
+      Some(Client::Key(name)) => limits.keys.entry(name).or_default(),
+      _ => &mut limits.anonymous,
+    };
+
+    usage.requests += 1;
+
+    if let Decision::Limited(_) = decision {
+      usage.limited += 1;
+    }
+
+    decision
+  }
+
+  /// Usage visible to the holder of `key`, which is that of every client
+  /// for admin keys, and only their own otherwise.
+  pub(super) fn usage(&self, key: Option<&str>) -> Option<UsageJson> {
+    let key = self.keys.get(key?)?;
+
+    let limits = self.limits.lock().unwrap();
+
+    if key.admin {
+      return Some(UsageJson {
+        anonymous: Some(limits.anonymous),
+        keys: limits.keys.clone(),
+      });
+    }
+
+    Some(UsageJson {
+      anonymous: None,
+      keys: [(
+        key.name.clone(),
+        limits.keys.get(&key.name).copied().unwrap_or_default(),
+      )]
+      .into(),
+    })
+  }
+
+  /// The address of the client making `request`. Behind a trusted proxy,
+  /// this is the rightmost `X-Forwarded-For` entry, which was appended by the
+  /// proxy, since entries to its left are supplied by the client.
+  fn client_address(&self, request: &Request<Body>) -> Option<IpAddr> {
+    if self.trust_forwarded_for {
+      if let Some(address) = request
+        .headers()
+        .get_all("x-forwarded-for")
+        .iter()
+        .last()
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|address| address.trim().parse().ok())
+      {
+        return Some(address);
+      }
+    }
+
+    request
+      .extensions()
+      .get::<ConnectInfo<SocketAddr>>()
+      .map(|ConnectInfo(address)| address.ip())
+  }
+}
+
+pub(super) async fn rate_limit(
+  State(rate_limiter): State<Arc<RateLimiter>>,
+  request: Request<Body>,
+  next: Next<Body>,
+) -> Response {
+  let decision = rate_limiter.check(
+    request
+      .headers()
+      .get(API_KEY_HEADER)
+      .and_then(|value| value.to_str().ok()),
+    rate_limiter.client_address(&request),
+    RouteClass::of(request.uri().path()),
+    Instant::now(),
+  );
+
+  let api = request.uri().path().starts_with("/api/v1/");
+
+  let (code, message, retry_after) = match decision {
+    Decision::Allow => return next.run(request).await,
+    Decision::UnknownKey => (ErrorCode::Unauthorized, "unknown API key".to_string(), None),
+    Decision::Limited(retry_after) => {
+      let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+      (
+        ErrorCode::RateLimited,
+        format!("rate limit exceeded, retry after {seconds} seconds"),
+        Some(seconds),
+      )
+    }
+  };
+
+  let mut response = if api {
+    api::error_response(code, message)
+  } else {
+    (code.status(), message).into_response()
+  };
+
+  if let Some(seconds) = retry_after {
+    response
+      .headers_mut()
+      .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+  }
+
+  response
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::config::{ApiKey, RateLimitConfig},
+  };
+
+  fn budgets(burst: u32, per_minute: u32) -> Budgets {
+    Budgets {
+      default: Budget { burst, per_minute },
+      expensive: Budget {
+        burst: 1,
+        per_minute: 1,
+      },
+    }
+  }
+
+  fn rate_limiter() -> RateLimiter {
+    RateLimiter::new(&Config {
+      api_keys: vec![
+        ApiKey {
+          name: "indexer".into(),
+          key: "secret".into(),
+          budgets: None,
+          admin: false,
+        },
+        ApiKey {
+          name: "operator".into(),
+          key: "admin-secret".into(),
+          budgets: None,
+          admin: true,
+        },
+      ],
+      rate_limit: RateLimitConfig {
+        anonymous: Some(budgets(2, 60)),
+        keyed: Some(budgets(10, 600)),
+        trust_forwarded_for: false,
+      },
+      ..Default::default()
+    })
+  }
+
+  fn address(n: u8) -> Option<IpAddr> {
+    Some(IpAddr::from([10, 0, 0, n]))
+  }
+
+  #[test]
+  fn expensive_routes() {
+    assert_eq!(RouteClass::of("/blocks/1/10"), RouteClass::Expensive);
+    assert_eq!(RouteClass::of("/api/v1/blocks/1/10"), RouteClass::Expensive);
+    assert_eq!(
+      RouteClass::of("/crc20/tick/holder/doge"),
+      RouteClass::Expensive
+    );
+    assert_eq!(
+      RouteClass::of("/utxos/balance/foo/2"),
+      RouteClass::Expensive
+    );
+    assert_eq!(RouteClass::of("/api/batch/outputs"), RouteClass::Expensive);
+    assert_eq!(RouteClass::of("/block/1"), RouteClass::Default);
+    assert_eq!(RouteClass::of("/crc20/tick/doge"), RouteClass::Default);
+    assert_eq!(RouteClass::of("/api/v1/status"), RouteClass::Default);
+  }
+
+  #[test]
+  fn buckets_are_per_address_and_refill() {
+    let rate_limiter = rate_limiter();
+    let now = Instant::now();
+
+    for _ in 0..2 {
+      assert_eq!(
+        rate_limiter.check(None, address(1), RouteClass::Default, now),
+        Decision::Allow
+      );
+    }
+
+    assert_eq!(
+      rate_limiter.check(None, address(1), RouteClass::Default, now),
+      Decision::Limited(Duration::from_secs(1))
+    );
+
+    assert_eq!(
+      rate_limiter.check(None, address(2), RouteClass::Default, now),
+      Decision::Allow
+    );
+
+    assert_eq!(
+      rate_limiter.check(
+        None,
+        address(1),
+        RouteClass::Default,
+        now + Duration::from_secs(1)
+      ),
+      Decision::Allow
+    );
+  }
+
+  #[test]
+  fn expensive_routes_have_separate_budgets() {
+    let rate_limiter = rate_limiter();
+    let now = Instant::now();
+
+    assert_eq!(
+      rate_limiter.check(None, address(1), RouteClass::Expensive, now),
+      Decision::Allow
+    );
+
+    assert_eq!(
+      rate_limiter.check(None, address(1), RouteClass::Expensive, now),
+      Decision::Limited(Duration::from_secs(60))
+    );
+
+    assert_eq!(
+      rate_limiter.check(None, address(1), RouteClass::Default, now),
+      Decision::Allow
+    );
+  }
+
+  #[test]
+  fn keys_have_their_own_budgets() {
+    let rate_limiter = rate_limiter();
+    let now = Instant::now();
+
+    for _ in 0..10 {
+      assert_eq!(
+        rate_limiter.check(Some("secret"), address(1), RouteClass::Default, now),
+        Decision::Allow
+      );
+    }
+
+    assert_eq!(
+      rate_limiter.check(Some("secret"), address(1), RouteClass::Default, now),
+      Decision::Limited(Duration::from_millis(100))
+    );
+
+    assert_eq!(
+      rate_limiter.check(None, address(1), RouteClass::Default, now),
+      Decision::Allow
+    );
+
+    assert_eq!(
+      rate_limiter.check(Some("wrong"), address(1), RouteClass::Default, now),
+      Decision::UnknownKey
+    );
+  }
+
+  #[test]
+  fn unconfigured_limits_allow_everything() {
+    let rate_limiter = RateLimiter::new(&Config::default());
+    let now = Instant::now();
+
+    for _ in 0..100 {
+      assert_eq!(
+        rate_limiter.check(None, address(1), RouteClass::Expensive, now),
+        Decision::Allow
+      );
+    }
+  }
+
+  #[test]
+  fn least_recently_used_buckets_are_evicted() {
+    let rate_limiter = rate_limiter();
+    let now = Instant::now();
+
+    for _ in 0..3 {
+      rate_limiter.check(None, address(1), RouteClass::Default, now);
+    }
+
+    for n in 0..u32::try_from(MAX_BUCKETS).unwrap() {
+      rate_limiter.check(
+        None,
+        Some(IpAddr::from((1 << 24 | n).to_be_bytes())),
+        RouteClass::Default,
+        now,
+      );
+    }
+
+    assert_eq!(
+      rate_limiter.limits.lock().unwrap().buckets.len(),
+      MAX_BUCKETS
+    );
+
+    assert_eq!(
+      rate_limiter.check(None, address(1), RouteClass::Default, now),
+      Decision::Allow
+    );
+  }
+
+  #[test]
+  fn forwarded_for_uses_rightmost_address() {
+    let mut rate_limiter = rate_limiter();
+
+    let request = Request::builder()
+      .header("x-forwarded-for", "10.0.0.1, 10.0.0.2")
+      .header("x-forwarded-for", "10.0.0.3, 10.0.0.4")
+      .body(Body::empty())
+      .unwrap();
+
+    assert_eq!(rate_limiter.client_address(&request), None);
+
+    rate_limiter.trust_forwarded_for = true;
+
+    assert_eq!(rate_limiter.client_address(&request), address(4));
+  }
+
+  #[test]
+  fn usage_is_counted() {
+    let rate_limiter = rate_limiter();
+    let now = Instant::now();
+
+    for _ in 0..3 {
+      rate_limiter.check(None, address(1), RouteClass::Default, now);
+    }
+
+    rate_limiter.check(Some("secret"), address(1), RouteClass::Default, now);
+    rate_limiter.check(Some("wrong"), address(1), RouteClass::Default, now);
+
+    assert_eq!(
+      rate_limiter.usage(Some("admin-secret")),
+      Some(UsageJson {
+        anonymous: Some(Usage {
+          requests: 3,
+          limited: 1,
+        }),
+        keys: [(
+          "indexer".to_string(),
+          Usage {
+            requests: 1,
+            limited: 0,
+          }
+        )]
+        .into(),
+      })
+    );
+  }
+
+  #[test]
+  fn usage_is_only_visible_to_key_holders() {
+    let rate_limiter = rate_limiter();
+    let now = Instant::now();
+
+    rate_limiter.check(None, address(1), RouteClass::Default, now);
+    rate_limiter.check(Some("admin-secret"), address(1), RouteClass::Default, now);
+
+    assert_eq!(rate_limiter.usage(None), None);
+    assert_eq!(rate_limiter.usage(Some("wrong")), None);
+
+    assert_eq!(
+      rate_limiter.usage(Some("secret")),
+      Some(UsageJson {
+        anonymous: None,
+        keys: [("indexer".to_string(), Usage::default())].into(),
+      })
+    );
+  }
+}
@@ -0,0 +1,28 @@
+use {super::*, crate::signed_message};
+
+#[derive(Debug, Parser)]
+pub(crate) struct VerifyMessage {
+  #[arg(long, help = "Verify that <MESSAGE> was signed by <ADDRESS>.")]
+  address: Address,
+  #[arg(long, help = "Verify base64-encoded <SIGNATURE>.")]
+  signature: String,
+  #[arg(help = "Verify signature over <MESSAGE>.")]
+  message: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub address: Address,
+  pub public_key: String,
+}
+
+impl VerifyMessage {
+  pub(crate) fn run(self) -> SubcommandResult {
+    let public_key = signed_message::verify(&self.address, &self.message, &self.signature)?;
+
+    Ok(Box::new(Output {
+      address: self.address,
+      public_key: public_key.to_string(),
+    }))
+  }
+}
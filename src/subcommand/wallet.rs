@@ -11,36 +11,65 @@ use {
     Network,
   },
   bitcoincore_rpc::bitcoincore_rpc_json::{ImportDescriptors, Timestamp},
-  fee_rate::FeeRate,
+  coin_control::{CoinControl, LockedOutputs},
+  fee_rate::{FeeRate, FeeRateSource},
   miniscript::descriptor::{Descriptor, DescriptorSecretKey, DescriptorXKey, Wildcard},
   transaction_builder::TransactionBuilder,
 };
 
 pub mod balance;
+pub mod bump;
+mod coin_control;
+pub mod consolidate;
+pub mod crc20;
 pub mod create;
 pub mod etch;
+pub mod freeze;
 pub(crate) mod inscribe;
 pub mod inscriptions;
+pub mod multisig;
+pub mod offer;
 pub mod outputs;
 pub mod receive;
 mod restore;
 pub mod sats;
 pub mod send;
+pub mod send_cunes;
+pub mod send_inscriptions;
+pub mod sign_message;
+pub mod split;
 pub(crate) mod transaction_builder;
 pub mod transactions;
+pub mod unfreeze;
+pub mod watch_only;
 
 #[derive(Debug, Parser)]
 pub(crate) enum Wallet {
   #[clap(about = "Get wallet balance")]
   Balance,
+  #[clap(about = "Bump fee of unconfirmed transaction")]
+  Bump(bump::Bump),
+  #[clap(about = "Consolidate cardinal UTXOs")]
+  Consolidate(consolidate::Consolidate),
   #[clap(about = "Create new wallet")]
   Create(create::Create),
+  #[clap(subcommand, about = "CRC-20 commands")]
+  Crc20(crc20::Crc20),
   #[clap(about = "Create cune")]
   Etch(etch::Etch),
+  #[clap(about = "Freeze outputs so that no wallet command spends them")]
+  Freeze(freeze::Freeze),
   #[clap(about = "Create inscription")]
   Inscribe(inscribe::Inscribe),
   #[clap(about = "List wallet inscriptions")]
   Inscriptions,
+  #[clap(subcommand, about = "Spend from P2SH multisig treasuries")]
+  Multisig(multisig::Multisig),
+  #[clap(
+    subcommand,
+    about = "Create and accept offers to sell inscriptions and cunes"
+  )]
+  Offer(offer::Offer),
   #[clap(about = "Generate receive address")]
   Receive,
   #[clap(about = "Restore wallet")]
@@ -49,8 +78,20 @@ pub(crate) enum Wallet {
   Sats(sats::Sats),
   #[clap(about = "Send sat or inscription")]
   Send(send::Send),
+  #[clap(about = "Send cunes to many recipients")]
+  SendCunes(send_cunes::SendCunes),
+  #[clap(about = "Send many inscriptions to many recipients")]
+  SendInscriptions(send_inscriptions::SendInscriptions),
+  #[clap(about = "Sign message with the key of an address or inscription holder")]
+  SignMessage(sign_message::SignMessage),
+  #[clap(about = "Split cardinal UTXOs into outputs of equal value")]
+  Split(split::Split),
   #[clap(about = "See wallet transactions")]
   Transactions(transactions::Transactions),
+  #[clap(about = "Unfreeze outputs")]
+  Unfreeze(unfreeze::Unfreeze),
+  #[clap(about = "Edit watch-only address groups")]
+  WatchOnly(watch_only::WatchOnly),
   #[clap(about = "List wallet outputs")]
   Outputs,
 }
@@ -59,15 +100,27 @@ impl Wallet {
   pub(crate) fn run(self, options: Options) -> SubcommandResult {
     match self {
       Self::Balance => balance::run(options),
+      Self::Bump(bump) => bump.run(options),
+      Self::Consolidate(consolidate) => consolidate.run(options),
       Self::Create(create) => create.run(options),
+      Self::Crc20(crc20) => crc20.run(options),
       Self::Etch(etch) => etch.run(options),
+      Self::Freeze(freeze) => freeze.run(options),
       Self::Inscribe(inscribe) => inscribe.run(options),
       Self::Inscriptions => inscriptions::run(options),
+      Self::Multisig(multisig) => multisig.run(options),
+      Self::Offer(offer) => offer.run(options),
       Self::Receive => receive::run(options),
       Self::Restore(restore) => restore.run(options),
       Self::Sats(sats) => sats.run(options),
       Self::Send(send) => send.run(options),
+      Self::SendCunes(send_cunes) => send_cunes.run(options),
+      Self::SendInscriptions(send_inscriptions) => send_inscriptions.run(options),
+      Self::SignMessage(sign_message) => sign_message.run(options),
+      Self::Split(split) => split.run(options),
       Self::Transactions(transactions) => transactions.run(options),
+      Self::Unfreeze(unfreeze) => unfreeze.run(options),
+      Self::WatchOnly(watch_only) => watch_only.run(options),
       Self::Outputs => outputs::run(options),
     }
   }
@@ -79,6 +132,25 @@ fn get_change_address(client: &Client) -> Result<Address> {
     .context("could not get change addresses from wallet")
 }
 
+/// Returns the subset of `unspent_outputs` which carry neither inscriptions
+/// nor cunes, and can therefore be spent freely.
+fn get_cardinal_outputs(
+  index: &Index,
+  unspent_outputs: &BTreeMap<OutPoint, Amount>,
+) -> Result<BTreeMap<OutPoint, Amount>> {
+  let mut cardinal = BTreeMap::new();
+
+  for (outpoint, amount) in unspent_outputs {
+    if index.get_inscriptions_on_output(*outpoint)?.is_empty()
+      && index.get_cune_balances_for_outpoint(*outpoint)?.is_empty()
+    {
+      cardinal.insert(*outpoint, *amount);
+    }
+  }
+
+  Ok(cardinal)
+}
+
 pub(crate) fn initialize_wallet(options: &Options, seed: [u8; 64]) -> Result {
   let client = options.craftcoin_rpc_client_for_wallet_command(true)?;
   let network = options.chain().network();
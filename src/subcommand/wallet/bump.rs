@@ -0,0 +1,575 @@
+use {
+  super::*,
+  crate::{inscription::ParsedInscription, wallet::Wallet},
+  bitcoin::PackedLockTime,
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Bump {
+  #[arg(help = "Bump fee of unconfirmed transaction <TXID>.")]
+  txid: Txid,
+  #[arg(
+    long,
+    help = "Use fee rate of <FEE_RATE> sats/vB, or `sat/kB` and `CRAFT/kB` with units, or `auto[:<BLOCKS>]` for the node's estimate"
+  )]
+  fee_rate: FeeRateSource,
+  #[arg(
+    long,
+    help = "Spend an output of <TXID> in a child transaction paying for both, instead of replacing <TXID>."
+  )]
+  cpfp: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub original: Txid,
+  pub transaction: Txid,
+  pub cpfp: bool,
+  pub fee: u64,
+  pub fee_rate: f64,
+}
+
+impl Bump {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
+
+    let fee_rate = self.fee_rate.resolve(&client)?;
+
+    let entry = Self::mempool_entry(&client, self.txid)
+      .with_context(|| format!("transaction {} is not in the mempool", self.txid))?;
+
+    let transaction = client.get_raw_transaction(&self.txid)?;
+
+    let input_values = Self::input_values(&client, &transaction)?;
+
+    let inscribed = Self::inscribed_offsets(&index, &transaction, &input_values)?;
+
+    let owned = client
+      .list_unspent(Some(0), None, None, None, None)?
+      .into_iter()
+      .filter(|utxo| utxo.txid == self.txid)
+      .map(|utxo| utxo.vout)
+      .collect::<Vec<u32>>();
+
+    let unspent_outputs = CoinControl::default().spendable_outputs(
+      &Wallet::frozen_outputs(&options)?,
+      &index.get_unspent_outputs(Wallet::load(&options)?)?,
+      None,
+    )?;
+
+    let cardinal = get_cardinal_outputs(&index, &unspent_outputs)?
+      .into_iter()
+      .filter(|(outpoint, _amount)| {
+        !transaction
+          .input
+          .iter()
+          .any(|input| input.previous_output == *outpoint)
+      })
+      .max_by_key(|(_outpoint, amount)| *amount);
+
+    let change = get_change_address(&client)?;
+
+    let (unsigned_transaction, fee) = if self.cpfp {
+      Self::build_child(
+        &client,
+        &index,
+        options.chain(),
+        &transaction,
+        &inscribed,
+        &owned,
+        cardinal,
+        change,
+        fee_rate,
+      )?
+    } else {
+      ensure!(
+        entry
+          .get("descendantcount")
+          .and_then(serde_json::Value::as_u64)
+          .unwrap_or(1)
+          <= 1,
+        "transaction {} has unconfirmed descendants which would be evicted by a replacement, use `--cpfp` to bump it with a child transaction",
+        self.txid
+      );
+
+      Self::build_replacement(
+        &client,
+        options.chain(),
+        &transaction,
+        &input_values,
+        &inscribed,
+        &owned,
+        cardinal,
+        change,
+        fee_rate,
+      )?
+    };
+
+    let signed_transaction = client
+      .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
+      .hex;
+
+    let txid = client.send_raw_transaction(&signed_transaction)?;
+
+    Ok(Box::new(Output {
+      original: self.txid,
+      transaction: txid,
+      cpfp: self.cpfp,
+      fee: fee.to_sat(),
+      fee_rate: fee_rate.n(),
+    }))
+  }
+
+  /// Builds a replacement for `transaction` with the same inputs and outputs,
+  /// paying the additional fee from one of its wallet outputs, or failing
+  /// that, from an additional cardinal input with its own change output.
+  fn build_replacement(
+    client: &Client,
+    chain: Chain,
+    transaction: &Transaction,
+    input_values: &[u64],
+    inscribed: &[u64],
+    owned: &[u32],
+    cardinal: Option<(OutPoint, Amount)>,
+    change: Address,
+    fee_rate: FeeRate,
+  ) -> Result<(Transaction, Amount)> {
+    let txid = transaction.txid();
+
+    // Reveal transactions spend their commit output with a key the wallet
+    // does not hold, either through a script sig or a taproot script path
+    ensure!(
+      matches!(
+        Inscription::from_transactions(vec![transaction.clone()]),
+        ParsedInscription::None
+      ) && transaction.input.iter().all(|input| input.witness.len() <= 1),
+      "transaction {txid} reveals an inscription and cannot be re-signed by the wallet, use `--cpfp` to bump it with a child transaction"
+    );
+
+    ensure!(
+      transaction.input.iter().any(|input| input.sequence.is_rbf()),
+      "transaction {txid} does not signal replaceability, use `--cpfp` to bump it with a child transaction"
+    );
+
+    let original_fee = Self::fee(transaction, input_values);
+    let original_fee_rate = original_fee.to_sat() as f64 / transaction.vsize() as f64;
+
+    ensure!(
+      fee_rate.n() > original_fee_rate,
+      "fee rate of {} sats/vB does not exceed the original fee rate of {original_fee_rate:.2} sats/vB",
+      fee_rate.n(),
+    );
+
+    let incremental_fee_rate =
+      FeeRate::from_amount_per_kb(client.get_network_info()?.incremental_fee);
+
+    // A replacement must pay at least the original fee plus the incremental
+    // relay fee for its own size
+    let required_fee = |replacement: &Transaction| {
+      let vsize = Self::estimate_vsize(replacement);
+      cmp::max(
+        fee_rate.fee(vsize),
+        original_fee + incremental_fee_rate.fee(vsize),
+      )
+    };
+
+    for vout in owned.iter().rev().map(|vout| *vout as usize) {
+      let mut replacement = transaction.clone();
+      let fee = required_fee(&replacement);
+      let output = &mut replacement.output[vout];
+
+      let Some(value) = Amount::from_sat(output.value).checked_sub(fee - original_fee) else {
+        continue;
+      };
+
+      if value < chain.dust_value(&output.script_pubkey) {
+        continue;
+      }
+
+      output.value = value.to_sat();
+
+      if Self::preserves_inscriptions(transaction, &replacement, inscribed) {
+        return Ok((replacement, fee));
+      }
+    }
+
+    // Appending an output would change the allocation of edicts which split
+    // cunes across all outputs
+    ensure!(
+      Cunestone::from_transaction(transaction).is_none(),
+      "none of the wallet outputs of transaction {txid} can pay the additional fee without moving inscriptions or cunes"
+    );
+
+    let (outpoint, amount) = cardinal
+      .context("wallet does not contain enough cardinal UTXOs, please add additional funds")?;
+
+    let mut replacement = transaction.clone();
+
+    replacement.input.push(TxIn {
+      previous_output: outpoint,
+      script_sig: Script::new(),
+      sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+      witness: Witness::new(),
+    });
+
+    replacement.output.push(TxOut {
+      script_pubkey: change.script_pubkey(),
+      value: 0,
+    });
+
+    let fee = required_fee(&replacement);
+
+    let value = amount
+      .checked_sub(fee - original_fee)
+      .filter(|value| *value >= chain.dust_value(&change.script_pubkey()))
+      .context("wallet does not contain enough cardinal UTXOs, please add additional funds")?;
+
+    replacement.output.last_mut().unwrap().value = value.to_sat();
+
+    assert!(Self::preserves_inscriptions(
+      transaction,
+      &replacement,
+      inscribed
+    ));
+
+    Ok((replacement, fee))
+  }
+
+  /// Builds a child transaction spending a wallet output of `parent`, paying
+  /// enough fee to bring `parent` and its unconfirmed ancestors up to
+  /// `fee_rate`.
+  fn build_child(
+    client: &Client,
+    index: &Index,
+    chain: Chain,
+    parent: &Transaction,
+    inscribed: &[u64],
+    owned: &[u32],
+    cardinal: Option<(OutPoint, Amount)>,
+    change: Address,
+    fee_rate: FeeRate,
+  ) -> Result<(Transaction, Amount)> {
+    let txid = parent.txid();
+
+    ensure!(
+      !owned.is_empty(),
+      "transaction {txid} has no unspent outputs belonging to the wallet"
+    );
+
+    let (package_fee, package_vsize) = Self::unconfirmed_ancestors(client, parent)?;
+
+    let child_fee = |child: &Transaction| {
+      fee_rate
+        .fee(package_vsize + Self::estimate_vsize(child))
+        .checked_sub(package_fee)
+        .filter(|fee| *fee > Amount::ZERO)
+        .with_context(|| {
+          format!(
+            "transaction {txid} and its unconfirmed ancestors already pay a fee rate of at least {} sats/vB",
+            fee_rate.n()
+          )
+        })
+    };
+
+    let mut cunic = Cunestone::from_transaction(parent).is_some();
+    for input in &parent.input {
+      cunic |= !index
+        .get_cune_balances_for_outpoint(input.previous_output)?
+        .is_empty();
+    }
+
+    let inscribed_outputs = inscribed
+      .iter()
+      .filter_map(|offset| Self::output_containing(parent, *offset))
+      .collect::<BTreeSet<usize>>();
+
+    let input = |previous_output| TxIn {
+      previous_output,
+      script_sig: Script::new(),
+      sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+      witness: Witness::new(),
+    };
+
+    // Prefer spending a cardinal output of the parent on its own
+    if let Some(vout) = owned
+      .iter()
+      .copied()
+      .filter(|vout| !cunic && !inscribed_outputs.contains(&(*vout as usize)))
+      .max_by_key(|vout| parent.output[*vout as usize].value)
+    {
+      let mut child = Transaction {
+        version: 1,
+        lock_time: PackedLockTime::ZERO,
+        input: vec![input(OutPoint { txid, vout })],
+        output: vec![TxOut {
+          script_pubkey: change.script_pubkey(),
+          value: 0,
+        }],
+      };
+
+      let fee = child_fee(&child)?;
+
+      if let Some(value) = Amount::from_sat(parent.output[vout as usize].value)
+        .checked_sub(fee)
+        .filter(|value| *value >= chain.dust_value(&change.script_pubkey()))
+      {
+        child.output[0].value = value.to_sat();
+        return Ok((child, fee));
+      }
+    }
+
+    // Otherwise pass the parent output through unchanged as the first output,
+    // which keeps inscriptions at the same offsets and receives any cunes, and
+    // pay the fee from a cardinal input
+    let vout = owned[0];
+    let parent_output = &parent.output[vout as usize];
+
+    let (outpoint, amount) = cardinal
+      .context("wallet does not contain enough cardinal UTXOs, please add additional funds")?;
+
+    let mut child = Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: vec![input(OutPoint { txid, vout }), input(outpoint)],
+      output: vec![
+        parent_output.clone(),
+        TxOut {
+          script_pubkey: change.script_pubkey(),
+          value: 0,
+        },
+      ],
+    };
+
+    let fee = child_fee(&child)?;
+
+    let value = amount
+      .checked_sub(fee)
+      .filter(|value| *value >= chain.dust_value(&change.script_pubkey()))
+      .context("wallet does not contain enough cardinal UTXOs, please add additional funds")?;
+
+    child.output[1].value = value.to_sat();
+
+    Ok((child, fee))
+  }
+
+  fn mempool_entry(client: &Client, txid: Txid) -> Option<serde_json::Value> {
+    client
+      .call("getmempoolentry", &[txid.to_string().into()])
+      .ok()
+  }
+
+  fn input_values(client: &Client, transaction: &Transaction) -> Result<Vec<u64>> {
+    transaction
+      .input
+      .iter()
+      .map(|input| {
+        Ok(
+          client
+            .get_raw_transaction(&input.previous_output.txid)?
+            .output[input.previous_output.vout as usize]
+            .value,
+        )
+      })
+      .collect()
+  }
+
+  fn fee(transaction: &Transaction, input_values: &[u64]) -> Amount {
+    Amount::from_sat(
+      input_values.iter().sum::<u64>()
+        - transaction
+          .output
+          .iter()
+          .map(|output| output.value)
+          .sum::<u64>(),
+    )
+  }
+
+  /// Returns the total fee and size of `transaction` and its ancestors which
+  /// are still in the mempool, since miners evaluate them as a package.
+  fn unconfirmed_ancestors(client: &Client, transaction: &Transaction) -> Result<(Amount, usize)> {
+    let mut fee = Amount::ZERO;
+    let mut vsize = 0;
+    let mut seen = HashSet::new();
+    let mut pending = vec![transaction.clone()];
+
+    while let Some(transaction) = pending.pop() {
+      if !seen.insert(transaction.txid()) {
+        continue;
+      }
+
+      fee += Self::fee(&transaction, &Self::input_values(client, &transaction)?);
+      vsize += transaction.vsize();
+
+      for input in &transaction.input {
+        if Self::mempool_entry(client, input.previous_output.txid).is_some() {
+          pending.push(client.get_raw_transaction(&input.previous_output.txid)?);
+        }
+      }
+    }
+
+    Ok((fee, vsize))
+  }
+
+  /// Returns the offsets, relative to the first sat of the first input, of
+  /// the inscriptions carried by `transaction`, including any it reveals.
+  fn inscribed_offsets(
+    index: &Index,
+    transaction: &Transaction,
+    input_values: &[u64],
+  ) -> Result<Vec<u64>> {
+    let mut offsets = Vec::new();
+
+    // Treat a taproot script path spend of the first input as a reveal too,
+    // since that is how `wallet inscribe` reveals its inscriptions
+    if !matches!(
+      Inscription::from_transactions(vec![transaction.clone()]),
+      ParsedInscription::None
+    ) || transaction
+      .input
+      .first()
+      .map(|input| input.witness.len() > 1)
+      .unwrap_or_default()
+    {
+      offsets.push(0);
+    }
+
+    let inscriptions = index.get_inscriptions(None)?;
+
+    let mut start = 0;
+    for (input, value) in transaction.input.iter().zip(input_values) {
+      offsets.extend(
+        inscriptions
+          .keys()
+          .filter(|satpoint| satpoint.outpoint == input.previous_output)
+          .map(|satpoint| start + satpoint.offset),
+      );
+
+      start += value;
+    }
+
+    Ok(offsets)
+  }
+
+  fn output_containing(transaction: &Transaction, offset: u64) -> Option<usize> {
+    let mut end = 0;
+
+    for (vout, output) in transaction.output.iter().enumerate() {
+      end += output.value;
+
+      if offset < end {
+        return Some(vout);
+      }
+    }
+
+    None
+  }
+
+  /// Whether every inscribed sat ends up in the same output of `replacement`
+  /// as in `original`. Inputs are only ever appended, so offsets relative to
+  /// the first input are the same in both.
+  fn preserves_inscriptions(
+    original: &Transaction,
+    replacement: &Transaction,
+    inscribed: &[u64],
+  ) -> bool {
+    inscribed.iter().all(|offset| {
+      Self::output_containing(original, *offset) == Self::output_containing(replacement, *offset)
+    })
+  }
+
+  fn estimate_vsize(transaction: &Transaction) -> usize {
+    let mut transaction = transaction.clone();
+
+    for input in &mut transaction.input {
+      input.script_sig = TransactionBuilder::dummy_script_sig();
+      input.witness = Witness::new();
+    }
+
+    transaction.vsize()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn transaction(values: &[u64]) -> Transaction {
+    Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: Vec::new(),
+      output: values
+        .iter()
+        .map(|value| TxOut {
+          value: *value,
+          script_pubkey: Script::new(),
+        })
+        .collect(),
+    }
+  }
+
+  #[test]
+  fn output_containing() {
+    let transaction = transaction(&[100, 200]);
+
+    assert_eq!(Bump::output_containing(&transaction, 0), Some(0));
+    assert_eq!(Bump::output_containing(&transaction, 99), Some(0));
+    assert_eq!(Bump::output_containing(&transaction, 100), Some(1));
+    assert_eq!(Bump::output_containing(&transaction, 299), Some(1));
+    assert_eq!(Bump::output_containing(&transaction, 300), None);
+  }
+
+  #[test]
+  fn reducing_last_output_preserves_inscriptions_outside_fee() {
+    let original = transaction(&[100, 200]);
+    let replacement = transaction(&[100, 150]);
+
+    assert!(Bump::preserves_inscriptions(
+      &original,
+      &replacement,
+      &[0, 100, 249]
+    ));
+    assert!(!Bump::preserves_inscriptions(
+      &original,
+      &replacement,
+      &[250]
+    ));
+  }
+
+  #[test]
+  fn reducing_earlier_output_shifts_inscriptions() {
+    let original = transaction(&[100, 200]);
+    let replacement = transaction(&[50, 200]);
+
+    assert!(Bump::preserves_inscriptions(&original, &replacement, &[0]));
+    assert!(!Bump::preserves_inscriptions(
+      &original,
+      &replacement,
+      &[75]
+    ));
+    assert!(!Bump::preserves_inscriptions(
+      &original,
+      &replacement,
+      &[260]
+    ));
+  }
+
+  #[test]
+  fn estimate_vsize_uses_p2pkh_script_sigs() {
+    let mut transaction = transaction(&[100]);
+    transaction.input.push(TxIn {
+      previous_output: OutPoint::null(),
+      script_sig: Script::new(),
+      sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+      witness: Witness::new(),
+    });
+
+    assert_eq!(
+      Bump::estimate_vsize(&transaction),
+      transaction.vsize() + TransactionBuilder::dummy_script_sig().len()
+    );
+  }
+}
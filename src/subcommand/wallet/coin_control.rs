@@ -0,0 +1,236 @@
+use {super::*, bitcoincore_rpc::json::JsonOutPoint};
+
+#[derive(Debug, Default, Parser)]
+pub(crate) struct CoinControl {
+  #[arg(
+    long = "utxo",
+    value_name = "OUTPOINT",
+    help = "Spend <OUTPOINT>, and no other outputs besides any output being sent. May be given multiple times."
+  )]
+  pub(crate) utxos: Vec<OutPoint>,
+  #[arg(
+    long = "exclude-utxo",
+    value_name = "OUTPOINT",
+    help = "Never spend <OUTPOINT>. May be given multiple times."
+  )]
+  pub(crate) exclude_utxos: Vec<OutPoint>,
+}
+
+impl CoinControl {
+  /// Returns the subset of `unspent_outputs` which may be spent. `outgoing`,
+  /// the output being sent, if any, may be spent even if not selected with
+  /// `--utxo`, but not if it is frozen or excluded.
+  pub(crate) fn spendable_outputs(
+    &self,
+    frozen: &BTreeSet<OutPoint>,
+    unspent_outputs: &BTreeMap<OutPoint, Amount>,
+    outgoing: Option<OutPoint>,
+  ) -> Result<BTreeMap<OutPoint, Amount>> {
+    for outpoint in &self.utxos {
+      ensure!(
+        unspent_outputs.contains_key(outpoint),
+        "output {outpoint} is not in wallet"
+      );
+    }
+
+    for outpoint in self.utxos.iter().chain(&outgoing) {
+      ensure!(!frozen.contains(outpoint), "output {outpoint} is frozen");
+      ensure!(
+        !self.exclude_utxos.contains(outpoint),
+        "output {outpoint} is excluded"
+      );
+    }
+
+    Ok(
+      unspent_outputs
+        .iter()
+        .filter(|(outpoint, _amount)| Some(**outpoint) == outgoing || self.allows(frozen, outpoint))
+        .map(|(outpoint, amount)| (*outpoint, *amount))
+        .collect(),
+    )
+  }
+
+  /// Locks `also` and every output in `unspent_outputs` which may not be
+  /// spent, so that the node does not select them when funding a transaction.
+  /// The outputs stay locked until the returned guard is dropped.
+  pub(crate) fn lock_unspendable_outputs<'a>(
+    &self,
+    client: &'a Client,
+    frozen: &BTreeSet<OutPoint>,
+    unspent_outputs: &BTreeMap<OutPoint, Amount>,
+    also: impl IntoIterator<Item = OutPoint>,
+  ) -> Result<LockedOutputs<'a>> {
+    for outpoint in &self.utxos {
+      ensure!(
+        unspent_outputs.contains_key(outpoint),
+        "output {outpoint} is not in wallet"
+      );
+      ensure!(!frozen.contains(outpoint), "output {outpoint} is frozen");
+    }
+
+    let mut locked = also.into_iter().collect::<BTreeSet<OutPoint>>();
+
+    locked.extend(
+      unspent_outputs
+        .keys()
+        .filter(|outpoint| !self.allows(frozen, outpoint)),
+    );
+
+    LockedOutputs::lock(client, locked)
+  }
+
+  fn allows(&self, frozen: &BTreeSet<OutPoint>, outpoint: &OutPoint) -> bool {
+    !frozen.contains(outpoint)
+      && !self.exclude_utxos.contains(outpoint)
+      && (self.utxos.is_empty() || self.utxos.contains(outpoint))
+  }
+}
+
+/// Outputs locked in the node wallet for the duration of a funding call.
+/// They are unlocked when the guard is dropped, including on error paths, so
+/// that commands never leave the wallet's coins locked. Outputs which were
+/// already locked beforehand are left alone.
+#[must_use]
+pub(crate) struct LockedOutputs<'a> {
+  client: &'a Client,
+  outpoints: Vec<OutPoint>,
+}
+
+impl<'a> LockedOutputs<'a> {
+  pub(crate) fn lock(
+    client: &'a Client,
+    outpoints: impl IntoIterator<Item = OutPoint>,
+  ) -> Result<Self> {
+    let already_locked = Self::list(client)?;
+
+    let outpoints = outpoints
+      .into_iter()
+      .filter(|outpoint| !already_locked.contains(outpoint))
+      .collect::<BTreeSet<OutPoint>>()
+      .into_iter()
+      .collect::<Vec<OutPoint>>();
+
+    if !client.lock_unspent(&outpoints)? {
+      bail!("failed to lock ordinal UTXOs");
+    }
+
+    Ok(Self { client, outpoints })
+  }
+
+  /// Outputs currently locked in the node wallet.
+  pub(crate) fn list(client: &Client) -> Result<BTreeSet<OutPoint>> {
+    Ok(
+      client
+        .call::<Vec<JsonOutPoint>>("listlockunspent", &[])?
+        .into_iter()
+        .map(OutPoint::from)
+        .collect(),
+    )
+  }
+}
+
+impl Drop for LockedOutputs<'_> {
+  fn drop(&mut self) {
+    if self.outpoints.is_empty() {
+      return;
+    }
+
+    match self.client.unlock_unspent(&self.outpoints) {
+      Ok(true) => {}
+      Ok(false) => log::warn!("failed to unlock {} outputs", self.outpoints.len()),
+      Err(err) => log::warn!("failed to unlock {} outputs: {err}", self.outpoints.len()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn unspent_outputs() -> BTreeMap<OutPoint, Amount> {
+    (1..=3)
+      .map(|n| (outpoint(n), Amount::from_sat(n * 1_000)))
+      .collect()
+  }
+
+  #[test]
+  fn default_spends_everything_but_frozen_outputs() {
+    assert_eq!(
+      CoinControl::default()
+        .spendable_outputs(&[outpoint(2)].into(), &unspent_outputs(), None)
+        .unwrap()
+        .into_keys()
+        .collect::<Vec<OutPoint>>(),
+      [outpoint(1), outpoint(3)],
+    );
+  }
+
+  #[test]
+  fn utxos_restrict_spendable_outputs() {
+    assert_eq!(
+      CoinControl {
+        utxos: vec![outpoint(3)],
+        exclude_utxos: Vec::new(),
+      }
+      .spendable_outputs(&BTreeSet::new(), &unspent_outputs(), Some(outpoint(1)))
+      .unwrap()
+      .into_keys()
+      .collect::<Vec<OutPoint>>(),
+      [outpoint(1), outpoint(3)],
+    );
+  }
+
+  #[test]
+  fn excluded_utxos_are_not_spendable() {
+    assert_eq!(
+      CoinControl {
+        utxos: Vec::new(),
+        exclude_utxos: vec![outpoint(1)],
+      }
+      .spendable_outputs(&BTreeSet::new(), &unspent_outputs(), None)
+      .unwrap()
+      .into_keys()
+      .collect::<Vec<OutPoint>>(),
+      [outpoint(2), outpoint(3)],
+    );
+  }
+
+  #[test]
+  fn frozen_outgoing_output_is_an_error() {
+    assert_eq!(
+      CoinControl::default()
+        .spendable_outputs(&[outpoint(1)].into(), &unspent_outputs(), Some(outpoint(1)))
+        .unwrap_err()
+        .to_string(),
+      format!("output {} is frozen", outpoint(1)),
+    );
+  }
+
+  #[test]
+  fn required_utxo_must_be_in_wallet() {
+    assert_eq!(
+      CoinControl {
+        utxos: vec![outpoint(4)],
+        exclude_utxos: Vec::new(),
+      }
+      .spendable_outputs(&BTreeSet::new(), &unspent_outputs(), None)
+      .unwrap_err()
+      .to_string(),
+      format!("output {} is not in wallet", outpoint(4)),
+    );
+  }
+
+  #[test]
+  fn required_and_excluded_utxo_is_an_error() {
+    assert_eq!(
+      CoinControl {
+        utxos: vec![outpoint(2)],
+        exclude_utxos: vec![outpoint(2)],
+      }
+      .spendable_outputs(&BTreeSet::new(), &unspent_outputs(), None)
+      .unwrap_err()
+      .to_string(),
+      format!("output {} is excluded", outpoint(2)),
+    );
+  }
+}
@@ -0,0 +1,376 @@
+use {super::*, crate::wallet::Wallet, bitcoin::PackedLockTime};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Consolidate {
+  #[arg(
+    long,
+    default_value = "100",
+    help = "Spend at most <MAX_INPUTS> cardinal UTXOs."
+  )]
+  max_inputs: usize,
+  #[arg(
+    long,
+    help = "Use fee rate of <FEE_RATE> sats/vB, or `sat/kB` and `CRAFT/kB` with units, or `auto[:<BLOCKS>]` for the node's estimate"
+  )]
+  fee_rate: FeeRateSource,
+  #[arg(
+    long,
+    help = "Move inscriptions that share an output onto their own postage-sized outputs."
+  )]
+  separate_inscriptions: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Separated {
+  pub inscriptions: Vec<InscriptionId>,
+  pub output: OutPoint,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub transaction: Txid,
+  pub inputs: usize,
+  pub outputs: usize,
+  pub consolidated: u64,
+  pub fee: u64,
+  pub fee_rate: f64,
+  pub separated: Vec<Separated>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Segment {
+  start: u64,
+  end: u64,
+  inscriptions: Vec<InscriptionId>,
+}
+
+impl Consolidate {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
+
+    let fee_rate = self.fee_rate.resolve(&client)?;
+
+    let unspent_outputs = CoinControl::default().spendable_outputs(
+      &Wallet::frozen_outputs(&options)?,
+      &index.get_unspent_outputs(Wallet::load(&options)?)?,
+      None,
+    )?;
+
+    let mut cardinal = get_cardinal_outputs(&index, &unspent_outputs)?
+      .into_iter()
+      .collect::<Vec<(OutPoint, Amount)>>();
+
+    // Spend the smallest UTXOs first, since those are the most expensive to
+    // spend individually
+    cardinal.sort_by_key(|(_outpoint, amount)| *amount);
+    cardinal.truncate(self.max_inputs);
+
+    let mut inscribed: Vec<(OutPoint, Amount, Vec<(u64, InscriptionId)>)> = Vec::new();
+
+    if self.separate_inscriptions {
+      let mut inscriptions_by_output: BTreeMap<OutPoint, Vec<(u64, InscriptionId)>> =
+        BTreeMap::new();
+
+      for (satpoint, inscription_id) in index.get_inscriptions(None)? {
+        if unspent_outputs.contains_key(&satpoint.outpoint) {
+          inscriptions_by_output
+            .entry(satpoint.outpoint)
+            .or_default()
+            .push((satpoint.offset, inscription_id));
+        }
+      }
+
+      for (outpoint, inscriptions) in inscriptions_by_output {
+        if inscriptions.len() < 2 || !index.get_cune_balances_for_outpoint(outpoint)?.is_empty() {
+          continue;
+        }
+
+        inscribed.push((outpoint, unspent_outputs[&outpoint], inscriptions));
+      }
+    }
+
+    if inscribed.is_empty() {
+      ensure!(
+        cardinal.len() >= 2,
+        "wallet contains fewer than two cardinal UTXOs, nothing to consolidate"
+      );
+    }
+
+    // Inscribed inputs come first, so the offsets of their inscriptions in the
+    // transaction are simple to calculate
+    let mut inputs = Vec::new();
+    let mut inscriptions = Vec::new();
+    let mut total = 0;
+
+    for (outpoint, amount, output_inscriptions) in &inscribed {
+      inputs.push(*outpoint);
+      for (offset, inscription_id) in output_inscriptions {
+        inscriptions.push((total + offset, *inscription_id));
+      }
+      total += amount.to_sat();
+    }
+
+    for (outpoint, amount) in &cardinal {
+      inputs.push(*outpoint);
+      total += amount.to_sat();
+    }
+
+    inscriptions.sort_by_key(|(offset, _inscription_id)| *offset);
+
+    let change_address = get_change_address(&client)?;
+    let chain = options.chain();
+    let dust_value = chain.dust_value(&change_address.script_pubkey()).to_sat();
+
+    let segments = Self::separate(
+      &inscriptions,
+      total,
+      chain.default_postage().to_sat(),
+      dust_value,
+    );
+
+    let mut values = Vec::new();
+    let mut locations = Vec::new();
+    let mut cursor = 0;
+    for segment in &segments {
+      if segment.start > cursor {
+        values.push(segment.start - cursor);
+      }
+
+      ensure!(
+        segment.end - segment.start >= dust_value,
+        "not enough value to separate inscriptions {:?}",
+        segment.inscriptions
+      );
+
+      locations.push((values.len(), segment));
+      values.push(segment.end - segment.start);
+      cursor = segment.end;
+    }
+
+    let mut addresses = vec![change_address];
+    while addresses.len() < values.len() + 1 {
+      addresses.push(get_change_address(&client)?);
+    }
+
+    let fee = fee_rate
+      .fee(TransactionBuilder::estimate_vbytes_with(
+        inputs.len(),
+        addresses.clone(),
+      ))
+      .to_sat();
+
+    let consolidated = (total - cursor)
+      .checked_sub(fee)
+      .filter(|value| *value >= dust_value)
+      .context("consolidated UTXOs do not contain enough value to pay fee")?;
+
+    values.push(consolidated);
+
+    let unsigned_transaction = Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: inputs
+        .iter()
+        .map(|previous_output| TxIn {
+          previous_output: *previous_output,
+          script_sig: Script::new(),
+          sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+          witness: Witness::new(),
+        })
+        .collect(),
+      output: addresses
+        .iter()
+        .zip(&values)
+        .map(|(address, value)| TxOut {
+          script_pubkey: address.script_pubkey(),
+          value: *value,
+        })
+        .collect(),
+    };
+
+    let signed_transaction = client
+      .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
+      .hex;
+
+    let txid = client.send_raw_transaction(&signed_transaction)?;
+
+    Ok(Box::new(Output {
+      transaction: txid,
+      inputs: inputs.len(),
+      outputs: values.len(),
+      consolidated,
+      fee,
+      fee_rate: fee_rate.n(),
+      separated: locations
+        .into_iter()
+        .map(|(vout, segment)| Separated {
+          inscriptions: segment.inscriptions.clone(),
+          output: OutPoint {
+            txid,
+            vout: vout.try_into().unwrap(),
+          },
+        })
+        .collect(),
+    }))
+  }
+
+  /// Split the sats of a transaction into segments, one per inscription,
+  /// each at most `postage` sats. Inscriptions closer together than
+  /// `dust_value` cannot be separated and share a segment. Gaps between
+  /// segments that are smaller than `dust_value` are absorbed into the
+  /// preceding segment.
+  fn separate(
+    inscriptions: &[(u64, InscriptionId)],
+    total: u64,
+    postage: u64,
+    dust_value: u64,
+  ) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+
+    for (offset, inscription_id) in inscriptions {
+      if let Some(last) = segments.last_mut() {
+        if *offset < last.end {
+          if offset - last.start >= dust_value {
+            last.end = *offset;
+          } else {
+            last.inscriptions.push(*inscription_id);
+            continue;
+          }
+        }
+      }
+
+      segments.push(Segment {
+        start: *offset,
+        end: offset + postage,
+        inscriptions: vec![*inscription_id],
+      });
+    }
+
+    for segment in &mut segments {
+      segment.end = segment.end.min(total);
+    }
+
+    if let Some(first) = segments.first_mut() {
+      if first.start < dust_value {
+        first.start = 0;
+      }
+    }
+
+    for i in 1..segments.len() {
+      if segments[i].start - segments[i - 1].end < dust_value {
+        segments[i - 1].end = segments[i].start;
+      }
+    }
+
+    segments
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn separate_inscriptions_into_postage_sized_segments() {
+    assert_eq!(
+      Consolidate::separate(
+        &[(0, inscription_id(1)), (50_000, inscription_id(2))],
+        100_000,
+        10_000,
+        1_000,
+      ),
+      [
+        Segment {
+          start: 0,
+          end: 10_000,
+          inscriptions: vec![inscription_id(1)],
+        },
+        Segment {
+          start: 50_000,
+          end: 60_000,
+          inscriptions: vec![inscription_id(2)],
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn close_inscriptions_are_cut_at_next_inscription() {
+    assert_eq!(
+      Consolidate::separate(
+        &[(0, inscription_id(1)), (5_000, inscription_id(2))],
+        100_000,
+        10_000,
+        1_000,
+      ),
+      [
+        Segment {
+          start: 0,
+          end: 5_000,
+          inscriptions: vec![inscription_id(1)],
+        },
+        Segment {
+          start: 5_000,
+          end: 15_000,
+          inscriptions: vec![inscription_id(2)],
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn inscriptions_closer_than_dust_share_segment() {
+    assert_eq!(
+      Consolidate::separate(
+        &[(0, inscription_id(1)), (100, inscription_id(2))],
+        100_000,
+        10_000,
+        1_000,
+      ),
+      [Segment {
+        start: 0,
+        end: 10_000,
+        inscriptions: vec![inscription_id(1), inscription_id(2)],
+      }]
+    );
+  }
+
+  #[test]
+  fn gaps_below_dust_are_absorbed() {
+    assert_eq!(
+      Consolidate::separate(
+        &[(500, inscription_id(1)), (10_600, inscription_id(2))],
+        100_000,
+        10_000,
+        1_000,
+      ),
+      [
+        Segment {
+          start: 0,
+          end: 10_600,
+          inscriptions: vec![inscription_id(1)],
+        },
+        Segment {
+          start: 10_600,
+          end: 20_600,
+          inscriptions: vec![inscription_id(2)],
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn segments_are_clamped_to_total() {
+    assert_eq!(
+      Consolidate::separate(&[(5_000, inscription_id(1))], 8_000, 10_000, 1_000),
+      [Segment {
+        start: 5_000,
+        end: 8_000,
+        inscriptions: vec![inscription_id(1)],
+      }]
+    );
+  }
+}
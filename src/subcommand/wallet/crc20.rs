@@ -0,0 +1,157 @@
+use {
+  super::*,
+  crate::{
+    crc20::{script_key::ScriptKey, Tick},
+    wallet::Wallet,
+  },
+};
+
+#[derive(Debug, Parser)]
+pub(crate) enum Crc20 {
+  #[clap(about = "List CRC-20 balances and transferable inscriptions held by wallet")]
+  Balances,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct TickBalance {
+  pub tick: String,
+  pub overall: String,
+  pub transferable: String,
+  pub available: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Transferable {
+  pub inscription: InscriptionId,
+  pub number: u64,
+  pub tick: String,
+  pub amount: String,
+  pub owner: String,
+  pub location: Option<SatPoint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub balances: Vec<TickBalance>,
+  pub transferable: Vec<Transferable>,
+}
+
+impl Crc20 {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    match self {
+      Self::Balances => balances(options),
+    }
+  }
+}
+
+fn balances(options: Options) -> SubcommandResult {
+  let index = Index::open(&options)?;
+  index.update()?;
+
+  let mut totals: BTreeMap<Tick, (u128, u128)> = BTreeMap::new();
+  let mut logs = Vec::new();
+
+  for script_key in wallet_script_keys(&options)?.values() {
+    for balance in index.get_crc20_balances(script_key)? {
+      let (overall, transferable) = totals.entry(balance.tick).or_default();
+      *overall += balance.overall_balance;
+      *transferable += balance.transferable_balance;
+    }
+
+    logs.extend(index.get_crc20_transferable_by_range(script_key)?);
+  }
+
+  let mut decimals: BTreeMap<Tick, u8> = BTreeMap::new();
+  for tick in totals.keys().chain(logs.iter().map(|log| &log.tick)) {
+    if !decimals.contains_key(tick) {
+      let decimal = index
+        .get_crc20_token_info(tick)?
+        .map(|token_info| token_info.decimal)
+        .unwrap_or_default();
+      decimals.insert(tick.clone(), decimal);
+    }
+  }
+
+  let balances = totals
+    .into_iter()
+    .map(|(tick, (overall, transferable))| {
+      let decimal = decimals[&tick];
+      TickBalance {
+        tick: tick.to_string(),
+        overall: format_amount(overall, decimal),
+        transferable: format_amount(transferable, decimal),
+        available: format_amount(overall.saturating_sub(transferable), decimal),
+      }
+    })
+    .collect();
+
+  let mut transferable = Vec::new();
+  for log in logs {
+    transferable.push(Transferable {
+      inscription: log.inscription_id,
+      number: log.inscription_number,
+      amount: format_amount(log.amount, decimals[&log.tick]),
+      tick: log.tick.to_string(),
+      owner: log.owner.to_string(),
+      location: index.get_inscription_satpoint_by_id(log.inscription_id)?,
+    });
+  }
+
+  Ok(Box::new(Output {
+    balances,
+    transferable,
+  }))
+}
+
+/// Returns the script keys of every address in the wallet, keyed by their
+/// string representation. CRC-20 balances stay with an address after its
+/// outputs are spent, so these are the addresses of the watch-only group, or
+/// every address craftcoind has seen receive an output, rather than those
+/// which currently hold one.
+fn wallet_script_keys(options: &Options) -> Result<BTreeMap<String, ScriptKey>> {
+  let network = options.chain().network();
+
+  let addresses = match Wallet::load_for_query(options)?.watch_only() {
+    Some(addresses) => addresses.clone(),
+    None => wallet_addresses(&options.craftcoin_rpc_client_for_wallet_command(false)?)?,
+  };
+
+  Ok(
+    addresses
+      .into_iter()
+      .map(|address| {
+        let script_key = ScriptKey::from_address(address, network);
+        (script_key.to_string(), script_key)
+      })
+      .collect(),
+  )
+}
+
+/// Addresses of the craftcoind wallet which have received an output,
+/// including change addresses, as returned by `listaddressgroupings`.
+fn wallet_addresses(client: &Client) -> Result<BTreeSet<Address>> {
+  let groupings = client
+    .call::<Vec<Vec<Vec<serde_json::Value>>>>("listaddressgroupings", &[])
+    .context("could not list wallet addresses")?;
+
+  let mut addresses = BTreeSet::new();
+
+  for entry in groupings.into_iter().flatten() {
+    let address = entry
+      .first()
+      .and_then(|address| address.as_str())
+      .ok_or_else(|| anyhow!("unexpected address grouping entry: {entry:?}"))?;
+
+    addresses.insert(address.parse()?);
+  }
+
+  Ok(addresses)
+}
+
+pub(super) fn format_amount(amount: u128, decimal: u8) -> String {
+  Decimal {
+    value: amount,
+    scale: decimal,
+  }
+  .to_string()
+}
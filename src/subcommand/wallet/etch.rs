@@ -1,12 +1,14 @@
-use super::*;
-use bitcoin::PackedLockTime;
+use {super::*, crate::wallet::Wallet, bitcoin::PackedLockTime};
 
 #[derive(Debug, Parser)]
 pub(crate) struct Etch {
   #[clap(long, help = "Set divisibility to <DIVISIBILITY>.")]
   divisibility: u8,
-  #[clap(long, help = "Etch with fee rate of <FEE_RATE> sats/vB.")]
-  fee_rate: FeeRate,
+  #[clap(
+    long,
+    help = "Etch with fee rate of <FEE_RATE> sats/vB, or `sat/kB` and `CRAFT/kB` with units, or `auto[:<BLOCKS>]` for the node's estimate."
+  )]
+  fee_rate: FeeRateSource,
   #[clap(long, help = "Etch cune <CUNE>. May contain `.` or `•`as spacers.")]
   cune: SpacedCune,
   #[clap(long, help = "Set supply to <SUPPLY>.")]
@@ -18,6 +20,7 @@ pub(crate) struct Etch {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Output {
   pub transaction: Txid,
+  pub fee_rate: f64,
 }
 
 impl Etch {
@@ -35,6 +38,8 @@ impl Etch {
 
     let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
 
+    let fee_rate = self.fee_rate.resolve(&client)?;
+
     let count = client.get_block_count()?;
 
     ensure!(
@@ -99,22 +104,22 @@ impl Etch {
         },
         TxOut {
           script_pubkey: destination.script_pubkey(),
-          value: TARGET_POSTAGE.to_sat(),
+          value: options.chain().default_postage().to_sat(),
         },
       ],
     };
 
-    let inscriptions = index
+    let mut locked = index
       .get_inscriptions(None)?
       .keys()
       .map(|satpoint| satpoint.outpoint)
       .collect::<Vec<OutPoint>>();
 
-    if !client.lock_unspent(&inscriptions)? {
-      bail!("failed to lock UTXOs");
-    }
+    locked.extend(Wallet::frozen_outputs(&options)?);
+
+    let _locked = LockedOutputs::lock(&client, locked)?;
 
-    let unsigned_transaction = fund_raw_transaction(&client, self.fee_rate, &unfunded_transaction)?;
+    let unsigned_transaction = fund_raw_transaction(&client, fee_rate, &unfunded_transaction)?;
 
     let signed_transaction = client
       .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
@@ -122,6 +127,9 @@ impl Etch {
 
     let transaction = client.send_raw_transaction(&signed_transaction)?;
 
-    Ok(Box::new(Output { transaction }))
+    Ok(Box::new(Output {
+      transaction,
+      fee_rate: fee_rate.n(),
+    }))
   }
 }
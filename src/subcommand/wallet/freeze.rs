@@ -0,0 +1,40 @@
+use {super::*, crate::wallet::Wallet};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Freeze {
+  #[arg(help = "Freeze <OUTPOINTS>. Lists frozen outputs if none are given.")]
+  outpoints: Vec<OutPoint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub frozen: Vec<OutPoint>,
+}
+
+impl Freeze {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let mut frozen = Wallet::frozen_outputs(&options)?;
+
+    if !self.outpoints.is_empty() {
+      let index = Index::open(&options)?;
+      index.update()?;
+
+      let unspent_outputs = index.get_unspent_outputs(Wallet::load(&options)?)?;
+
+      for outpoint in &self.outpoints {
+        ensure!(
+          unspent_outputs.contains_key(outpoint),
+          "output {outpoint} is not in wallet"
+        );
+      }
+
+      frozen.extend(self.outpoints);
+
+      Wallet::save_frozen_outputs(&options, &frozen)?;
+    }
+
+    Ok(Box::new(Output {
+      frozen: frozen.into_iter().collect(),
+    }))
+  }
+}
@@ -24,6 +24,9 @@ pub struct Output {
   inscription: InscriptionId,
   reveal: Txid,
   fees: u64,
+  commit_fee_rate: f64,
+  reveal_fee_rate: f64,
+  reinscribed: Option<InscriptionId>,
 }
 
 #[derive(Debug, Parser)]
@@ -32,15 +35,20 @@ pub(crate) struct Inscribe {
   pub(crate) satpoint: Option<SatPoint>,
   #[clap(
     long,
-    default_value = "1.0",
-    help = "Use fee rate of <FEE_RATE> sats/vB"
+    requires = "satpoint",
+    help = "Allow inscribing <SATPOINT> even though it already carries an inscription."
   )]
-  pub(crate) fee_rate: FeeRate,
+  pub(crate) reinscribe: bool,
+  #[clap(
+    long,
+    help = "Use fee rate of <FEE_RATE> sats/vB, or `sat/kB` and `CRAFT/kB` with units, or `auto[:<BLOCKS>]` for the node's estimate.\nDefaults to 1 sat/vB, or the node's minimum relay fee rate if higher."
+  )]
+  pub(crate) fee_rate: Option<FeeRateSource>,
   #[clap(
     long,
     help = "Use <COMMIT_FEE_RATE> sats/vbyte for commit transaction.\nDefaults to <FEE_RATE> if unset."
   )]
-  pub(crate) commit_fee_rate: Option<FeeRate>,
+  pub(crate) commit_fee_rate: Option<FeeRateSource>,
   #[clap(help = "Shibescribe sat with contents of <FILE>")]
   pub(crate) file: PathBuf,
   #[clap(long, help = "Do not back up recovery key.")]
@@ -54,6 +62,13 @@ pub(crate) struct Inscribe {
   pub(crate) dry_run: bool,
   #[clap(long, help = "Send inscription to <DESTINATION>.")]
   pub(crate) destination: Option<Address>,
+  #[clap(
+    long,
+    help = "Amount of postage to include in the inscription. Defaults to the chain's postage."
+  )]
+  pub(crate) postage: Option<Amount>,
+  #[clap(flatten)]
+  pub(crate) coin_control: CoinControl,
 }
 
 impl Inscribe {
@@ -65,11 +80,45 @@ impl Inscribe {
 
     let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
 
+    let reveal_fee_rate = match self.fee_rate {
+      Some(fee_rate) => fee_rate.resolve(&client)?,
+      None => FeeRateSource::resolve_default(&client)?,
+    };
+
+    let commit_fee_rate = self
+      .commit_fee_rate
+      .map(|commit_fee_rate| commit_fee_rate.resolve(&client))
+      .transpose()?
+      .unwrap_or(reveal_fee_rate);
+
     let mut utxos = index.get_unspent_outputs(Wallet::load(&options)?)?;
 
-    let dunic_utxos = index.get_dunic_outputs(&utxos.keys().cloned().collect::<Vec<OutPoint>>())?;
+    let satpoint = self.satpoint.or_else(|| {
+      self.coin_control.utxos.first().map(|outpoint| SatPoint {
+        outpoint: *outpoint,
+        offset: 0,
+      })
+    });
+
+    let spendable_outputs = self.coin_control.spendable_outputs(
+      &Wallet::frozen_outputs(&options)?,
+      &utxos,
+      satpoint.map(|satpoint| satpoint.outpoint),
+    )?;
 
-    let inscriptions = index.get_inscriptions(None)?;
+    let cune_balances = index.get_cune_balances_for_outpoints(
+      &spendable_outputs.keys().cloned().collect::<Vec<OutPoint>>(),
+    )?;
+
+    let mut inscriptions = index.get_inscriptions(None)?;
+
+    if let Some(satpoint) = satpoint {
+      if let Some(inscription_id) = Self::sat_inscription(&index, satpoint)? {
+        inscriptions.entry(satpoint).or_insert(inscription_id);
+      }
+    }
+
+    let reinscribed = satpoint.and_then(|satpoint| inscriptions.get(&satpoint).copied());
 
     let commit_tx_change = [get_change_address(&client)?, get_change_address(&client)?];
 
@@ -80,19 +129,30 @@ impl Inscribe {
 
     let (unsigned_commit_tx, reveal_tx, recovery_key_pair) =
       Inscribe::create_inscription_transactions(
-        self.satpoint,
+        satpoint,
         inscription,
         inscriptions,
-        options.chain().network(),
-        utxos.clone(),
-        dunic_utxos,
+        options.chain(),
+        spendable_outputs,
+        cune_balances,
+        self.coin_control.utxos.clone(),
         commit_tx_change,
         reveal_tx_destination,
-        self.commit_fee_rate.unwrap_or(self.fee_rate),
-        self.fee_rate,
+        commit_fee_rate,
+        reveal_fee_rate,
+        self
+          .postage
+          .unwrap_or_else(|| options.chain().default_postage()),
         self.no_limit,
+        self.reinscribe,
       )?;
 
+    if let (Some(satpoint), Some(inscription_id)) = (satpoint, reinscribed) {
+      eprintln!(
+        "warning: reinscribing sat at {satpoint} which carries inscription {inscription_id}"
+      );
+    }
+
     utxos.insert(
       reveal_tx.input[0].previous_output,
       Amount::from_sat(
@@ -132,9 +192,35 @@ impl Inscribe {
       reveal,
       inscription: reveal.into(),
       fees,
+      commit_fee_rate: commit_fee_rate.n(),
+      reveal_fee_rate: reveal_fee_rate.n(),
+      reinscribed,
     }))
   }
 
+  /// The inscription on the sat at `satpoint` according to the sat index, if
+  /// there is one. This catches inscribed sats whose inscriptions are not
+  /// tracked by satpoint.
+  fn sat_inscription(index: &Index, satpoint: SatPoint) -> Result<Option<InscriptionId>> {
+    let Some(List::Unspent(ranges)) = index.list(satpoint.outpoint)? else {
+      return Ok(None);
+    };
+
+    let mut offset = satpoint.offset;
+
+    for (start, end) in ranges {
+      let size = end - start;
+
+      if offset < size {
+        return index.get_inscription_id_by_sat(Sat(start + offset));
+      }
+
+      offset -= size;
+    }
+
+    Ok(None)
+  }
+
   fn calculate_fee(tx: &Transaction, utxos: &BTreeMap<OutPoint, Amount>) -> u64 {
     tx.input
       .iter()
@@ -148,16 +234,36 @@ impl Inscribe {
     satpoint: Option<SatPoint>,
     inscription: Inscription,
     inscriptions: BTreeMap<SatPoint, InscriptionId>,
-    network: Network,
+    chain: Chain,
     utxos: BTreeMap<OutPoint, Amount>,
-    dunic_utxos: BTreeSet<OutPoint>,
+    cune_balances: BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
+    required_inputs: Vec<OutPoint>,
     change: [Address; 2],
     destination: Address,
     commit_fee_rate: FeeRate,
     reveal_fee_rate: FeeRate,
+    postage: Amount,
     no_limit: bool,
+    reinscribe: bool,
   ) -> Result<(Transaction, Transaction, TweakedKeyPair)> {
     let satpoint = if let Some(satpoint) = satpoint {
+      ensure!(
+        !cune_balances.contains_key(&satpoint.outpoint),
+        "refusing to inscribe onto output {} which holds cunes",
+        satpoint.outpoint,
+      );
+
+      match inscriptions.get(&satpoint) {
+        Some(inscription_id) => ensure!(
+          reinscribe,
+          "sat at {satpoint} already inscribed with inscription {inscription_id}, use --reinscribe to inscribe it again",
+        ),
+        None => ensure!(
+          !reinscribe,
+          "sat at {satpoint} is not inscribed and cannot be reinscribed"
+        ),
+      }
+
       satpoint
     } else {
       let inscribed_utxos = inscriptions
@@ -167,7 +273,9 @@ impl Inscribe {
 
       utxos
         .keys()
-        .find(|outpoint| !inscribed_utxos.contains(outpoint) && !dunic_utxos.contains(outpoint))
+        .find(|outpoint| {
+          !inscribed_utxos.contains(outpoint) && !cune_balances.contains_key(outpoint)
+        })
         .map(|outpoint| SatPoint {
           outpoint: *outpoint,
           offset: 0,
@@ -175,17 +283,22 @@ impl Inscribe {
         .ok_or_else(|| anyhow!("wallet contains no cardinal utxos"))?
     };
 
-    for (inscribed_satpoint, inscription_id) in &inscriptions {
-      if inscribed_satpoint == &satpoint {
-        return Err(anyhow!("sat at {} already inscribed", satpoint));
-      }
-
-      if inscribed_satpoint.outpoint == satpoint.outpoint {
-        return Err(anyhow!(
-          "utxo {} already inscribed with inscription {inscription_id} on sat {inscribed_satpoint}",
-          satpoint.outpoint,
-        ));
-      }
+    let additional_inscriptions = inscriptions
+      .iter()
+      .filter(|(inscribed_satpoint, _inscription_id)| {
+        inscribed_satpoint.outpoint == satpoint.outpoint && **inscribed_satpoint != satpoint
+      })
+      .map(|(inscribed_satpoint, inscription_id)| {
+        format!("inscription {inscription_id} on sat {inscribed_satpoint}")
+      })
+      .collect::<Vec<String>>();
+
+    if !additional_inscriptions.is_empty() {
+      bail!(
+        "utxo {} already inscribed with {}",
+        satpoint.outpoint,
+        additional_inscriptions.join(", "),
+      );
     }
 
     let secp256k1 = Secp256k1::new();
@@ -208,7 +321,7 @@ impl Inscribe {
       .control_block(&(reveal_script.clone(), LeafVersion::TapScript))
       .expect("should compute control block");
 
-    let commit_tx_address = Address::p2tr_tweaked(taproot_spend_info.output_key(), network);
+    let commit_tx_address = Address::p2tr_tweaked(taproot_spend_info.output_key(), chain.network());
 
     let (_, reveal_fee) = Self::build_reveal_transaction(
       &control_block,
@@ -221,16 +334,19 @@ impl Inscribe {
       &reveal_script,
     );
 
-    let unsigned_commit_tx = TransactionBuilder::build_transaction_with_value(
+    let unsigned_commit_tx = TransactionBuilder::new(
       satpoint,
       inscriptions,
       utxos,
+      cune_balances,
       commit_tx_address.clone(),
-      dunic_utxos,
       change,
       commit_fee_rate,
-      reveal_fee + TransactionBuilder::TARGET_POSTAGE,
-    )?;
+      chain,
+      Target::Value(reveal_fee + postage),
+    )?
+    .with_required_inputs(required_inputs)
+    .build_transaction()?;
 
     let (vout, output) = unsigned_commit_tx
       .output
@@ -258,7 +374,11 @@ impl Inscribe {
       .checked_sub(fee.to_sat())
       .context("commit transaction output value insufficient to pay transaction fee")?;
 
-    if reveal_tx.output[0].value < reveal_tx.output[0].script_pubkey.dust_value().to_sat() {
+    if reveal_tx.output[0].value
+      < chain
+        .dust_value(&reveal_tx.output[0].script_pubkey)
+        .to_sat()
+    {
       bail!("commit transaction output would be dust");
     }
 
@@ -292,7 +412,7 @@ impl Inscribe {
     assert_eq!(
       Address::p2tr_tweaked(
         TweakedPublicKey::dangerous_assume_tweaked(x_only_pub_key),
-        network,
+        chain.network(),
       ),
       commit_tx_address
     );
@@ -389,12 +509,16 @@ mod tests {
       Some(satpoint(1, 0)),
       inscription,
       BTreeMap::new(),
-      Network::Bitcoin,
+      Chain::Regtest,
       utxos.into_iter().collect(),
+      BTreeMap::new(),
+      Vec::new(),
       [commit_address, change(1)],
       reveal_address,
       FeeRate::try_from(1.0).unwrap(),
       FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest.default_postage(),
+      false,
       false,
     )
     .unwrap();
@@ -420,12 +544,16 @@ mod tests {
       Some(satpoint(1, 0)),
       inscription,
       BTreeMap::new(),
-      Network::Bitcoin,
+      Chain::Regtest,
       utxos.into_iter().collect(),
+      BTreeMap::new(),
+      Vec::new(),
       [commit_address, change(1)],
       reveal_address,
       FeeRate::try_from(1.0).unwrap(),
       FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest.default_postage(),
+      false,
       false,
     )
     .unwrap();
@@ -455,12 +583,16 @@ mod tests {
       satpoint,
       inscription,
       inscriptions,
-      Network::Bitcoin,
+      Chain::Regtest,
       utxos.into_iter().collect(),
+      BTreeMap::new(),
+      Vec::new(),
       [commit_address, change(1)],
       reveal_address,
       FeeRate::try_from(1.0).unwrap(),
       FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest.default_postage(),
+      false,
       false,
     )
     .unwrap_err()
@@ -497,12 +629,16 @@ mod tests {
       satpoint,
       inscription,
       inscriptions,
-      Network::Bitcoin,
+      Chain::Regtest,
       utxos.into_iter().collect(),
+      BTreeMap::new(),
+      Vec::new(),
       [commit_address, change(1)],
       reveal_address,
       FeeRate::try_from(1.0).unwrap(),
       FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest.default_postage(),
+      false,
       false,
     )
     .is_ok())
@@ -533,17 +669,21 @@ mod tests {
       satpoint,
       inscription,
       inscriptions,
-      bitcoin::Network::Signet,
+      Chain::Signet,
       utxos.into_iter().collect(),
+      BTreeMap::new(),
+      Vec::new(),
       [commit_address, change(1)],
       reveal_address,
       FeeRate::try_from(fee_rate).unwrap(),
       FeeRate::try_from(fee_rate).unwrap(),
+      Chain::Signet.default_postage(),
+      false,
       false,
     )
     .unwrap();
 
-    let sig_vbytes = 17;
+    let sig_vbytes = 107;
     let fee = FeeRate::try_from(fee_rate)
       .unwrap()
       .fee(commit_tx.vsize() + sig_vbytes)
@@ -595,17 +735,21 @@ mod tests {
       satpoint,
       inscription,
       inscriptions,
-      bitcoin::Network::Signet,
+      Chain::Signet,
       utxos.into_iter().collect(),
+      BTreeMap::new(),
+      Vec::new(),
       [commit_address, change(1)],
       reveal_address,
       FeeRate::try_from(commit_fee_rate).unwrap(),
       FeeRate::try_from(fee_rate).unwrap(),
+      Chain::Signet.default_postage(),
+      false,
       false,
     )
     .unwrap();
 
-    let sig_vbytes = 17;
+    let sig_vbytes = 107;
     let fee = FeeRate::try_from(commit_fee_rate)
       .unwrap()
       .fee(commit_tx.vsize() + sig_vbytes)
@@ -644,12 +788,16 @@ mod tests {
       satpoint,
       inscription,
       BTreeMap::new(),
-      Network::Bitcoin,
+      Chain::Regtest,
       utxos.into_iter().collect(),
+      BTreeMap::new(),
+      Vec::new(),
       [commit_address, change(1)],
       reveal_address,
       FeeRate::try_from(1.0).unwrap(),
       FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest.default_postage(),
+      false,
       false,
     )
     .unwrap_err()
@@ -675,16 +823,128 @@ mod tests {
       satpoint,
       inscription,
       BTreeMap::new(),
-      Network::Bitcoin,
+      Chain::Regtest,
       utxos.into_iter().collect(),
+      BTreeMap::new(),
+      Vec::new(),
       [commit_address, change(1)],
       reveal_address,
       FeeRate::try_from(1.0).unwrap(),
       FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest.default_postage(),
       true,
+      false,
     )
     .unwrap();
 
     assert!(reveal_tx.size() >= MAX_STANDARD_TX_WEIGHT as usize);
   }
+
+  fn create_with(
+    inscriptions: BTreeMap<SatPoint, InscriptionId>,
+    cune_balances: BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
+    reinscribe: bool,
+  ) -> Result<(Transaction, Transaction, TweakedKeyPair)> {
+    Inscribe::create_inscription_transactions(
+      Some(satpoint(1, 0)),
+      inscription("text/plain", "ord"),
+      inscriptions,
+      Chain::Regtest,
+      [
+        (outpoint(1), Amount::from_sat(20_000)),
+        (outpoint(2), Amount::from_sat(20_000)),
+      ]
+      .into(),
+      cune_balances,
+      Vec::new(),
+      [change(0), change(1)],
+      recipient(),
+      FeeRate::try_from(1.0).unwrap(),
+      FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest.default_postage(),
+      false,
+      reinscribe,
+    )
+  }
+
+  #[test]
+  fn reinscribing_requires_opt_in() {
+    let inscriptions = BTreeMap::from([(satpoint(1, 0), inscription_id(1))]);
+
+    assert_eq!(
+      create_with(inscriptions.clone(), BTreeMap::new(), false)
+        .unwrap_err()
+        .to_string(),
+      format!(
+        "sat at {} already inscribed with inscription {}, use --reinscribe to inscribe it again",
+        satpoint(1, 0),
+        inscription_id(1),
+      ),
+    );
+
+    let (commit_tx, _reveal_tx, _key_pair) =
+      create_with(inscriptions, BTreeMap::new(), true).unwrap();
+
+    assert_eq!(commit_tx.input[0].previous_output, outpoint(1));
+  }
+
+  #[test]
+  fn reinscribing_requires_inscribed_sat() {
+    assert_eq!(
+      create_with(BTreeMap::new(), BTreeMap::new(), true)
+        .unwrap_err()
+        .to_string(),
+      format!(
+        "sat at {} is not inscribed and cannot be reinscribed",
+        satpoint(1, 0)
+      ),
+    );
+  }
+
+  #[test]
+  fn reinscribing_refuses_outputs_with_additional_inscriptions() {
+    let inscriptions = BTreeMap::from([
+      (satpoint(1, 0), inscription_id(1)),
+      (satpoint(1, 100), inscription_id(2)),
+      (satpoint(1, 200), inscription_id(3)),
+    ]);
+
+    assert_eq!(
+      create_with(inscriptions, BTreeMap::new(), true)
+        .unwrap_err()
+        .to_string(),
+      format!(
+        "utxo {} already inscribed with inscription {} on sat {}, inscription {} on sat {}",
+        outpoint(1),
+        inscription_id(2),
+        satpoint(1, 100),
+        inscription_id(3),
+        satpoint(1, 200),
+      ),
+    );
+  }
+
+  #[test]
+  fn refuse_to_inscribe_onto_cune_outputs() {
+    let cune_balances = BTreeMap::from([(
+      outpoint(1),
+      BTreeMap::from([(
+        CuneId {
+          height: 2,
+          index: 1,
+        },
+        1000,
+      )]),
+    )]);
+
+    assert_eq!(
+      create_with(BTreeMap::new(), cune_balances, false)
+        .unwrap_err()
+        .to_string(),
+      format!(
+        "refusing to inscribe onto output {} which holds cunes",
+        outpoint(1)
+      ),
+    );
+  }
 }
@@ -13,7 +13,7 @@ pub(crate) fn run(options: Options) -> SubcommandResult {
   index.update()?;
 
   let inscriptions = index.get_inscriptions(None)?;
-  let unspent_outputs = index.get_unspent_outputs(Wallet::load(&options)?)?;
+  let unspent_outputs = index.get_unspent_outputs(Wallet::load_for_query(&options)?)?;
 
   let explorer = match options.chain() {
     Chain::Mainnet => "https://ordinals.com/craftscription/",
@@ -0,0 +1,53 @@
+use {super::*, bitcoin::util::psbt::PartiallySignedTransaction};
+
+pub mod broadcast;
+pub mod combine;
+pub mod register;
+pub mod send;
+pub mod sign;
+
+#[derive(Debug, Parser)]
+pub(crate) enum Multisig {
+  #[clap(about = "Broadcast a multisig transaction once it is signed by enough cosigners")]
+  Broadcast(broadcast::Broadcast),
+  #[clap(about = "Combine signatures of cosigners")]
+  Combine(combine::Combine),
+  #[clap(about = "Register a multisig redeem script")]
+  Register(register::Register),
+  #[clap(about = "Build a PSBT sending a sat, inscription, or cunes from a multisig")]
+  Send(send::Send),
+  #[clap(about = "Sign a multisig PSBT with keys in wallet")]
+  Sign(sign::Sign),
+}
+
+impl Multisig {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    match self {
+      Self::Broadcast(broadcast) => broadcast.run(options),
+      Self::Combine(combine) => combine.run(options),
+      Self::Register(register) => register.run(options),
+      Self::Send(send) => send.run(options),
+      Self::Sign(sign) => sign.run(options),
+    }
+  }
+}
+
+/// Merges the signatures of `psbts`, which must all be for the same
+/// transaction.
+fn combine_psbts(psbts: &[String]) -> Result<PartiallySignedTransaction> {
+  let mut psbts = psbts
+    .iter()
+    .map(|psbt| crate::multisig::Multisig::deserialize_psbt(psbt))
+    .collect::<Result<Vec<PartiallySignedTransaction>>>()?
+    .into_iter();
+
+  let mut combined = psbts.next().ok_or_else(|| anyhow!("no PSBTs given"))?;
+
+  for psbt in psbts {
+    combined
+      .combine(psbt)
+      .context("PSBTs are not for the same transaction")?;
+  }
+
+  Ok(combined)
+}
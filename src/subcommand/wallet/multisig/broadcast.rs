@@ -0,0 +1,27 @@
+use {super::*, crate::multisig::Multisig};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Broadcast {
+  #[arg(
+    required = true,
+    help = "Broadcast the transaction of base64-encoded <PSBTS>, combining their signatures."
+  )]
+  psbts: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub transaction: Txid,
+}
+
+impl Broadcast {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let transaction = Multisig::finalize(&combine_psbts(&self.psbts)?)?;
+
+    let client = options.craftcoin_rpc_client()?;
+
+    Ok(Box::new(Output {
+      transaction: client.send_raw_transaction(&transaction)?,
+    }))
+  }
+}
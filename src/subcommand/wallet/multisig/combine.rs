@@ -0,0 +1,31 @@
+use {super::*, crate::multisig::Multisig};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Combine {
+  #[arg(
+    required = true,
+    help = "Combine signatures of base64-encoded <PSBTS>."
+  )]
+  psbts: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub psbt: String,
+  pub missing_signatures: Vec<usize>,
+  pub complete: bool,
+}
+
+impl Combine {
+  pub(crate) fn run(self, _options: Options) -> SubcommandResult {
+    let psbt = combine_psbts(&self.psbts)?;
+
+    let missing_signatures = Multisig::missing_signatures(&psbt)?;
+
+    Ok(Box::new(Output {
+      psbt: Multisig::serialize_psbt(&psbt),
+      complete: missing_signatures.iter().all(|missing| *missing == 0),
+      missing_signatures,
+    }))
+  }
+}
@@ -0,0 +1,63 @@
+use {
+  super::*,
+  crate::{multisig::Multisig, wallet::Wallet},
+  bitcoin::PublicKey,
+};
+
+#[derive(Debug, Parser)]
+#[command(group(
+  ArgGroup::new("keys")
+    .required(true)
+    .args(&["public_key", "redeem_script"]),
+))]
+pub(crate) struct Register {
+  #[arg(help = "Register multisig under <NAME>.")]
+  name: String,
+  #[arg(
+    long,
+    requires = "public_key",
+    help = "Require signatures by <THRESHOLD> of the public keys."
+  )]
+  threshold: Option<usize>,
+  #[arg(long, requires = "threshold", help = "Add <PUBLIC_KEY> to multisig.")]
+  public_key: Vec<PublicKey>,
+  #[arg(
+    long,
+    conflicts_with = "threshold",
+    help = "Register multisig with hex-encoded <REDEEM_SCRIPT>."
+  )]
+  redeem_script: Option<String>,
+  #[arg(long, help = "Replace multisig already registered as <NAME>.")]
+  force: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub address: Address,
+  pub redeem_script: String,
+  pub threshold: usize,
+  pub public_keys: Vec<PublicKey>,
+}
+
+impl Register {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let multisig = match (self.threshold, self.redeem_script) {
+      (Some(threshold), None) => Multisig::new(threshold, self.public_key)?,
+      (None, Some(redeem_script)) => Multisig::from_redeem_script(&Script::from(
+        hex::decode(redeem_script).context("redeem script is not hex")?,
+      ))?,
+      _ => unreachable!(),
+    };
+
+    let address = multisig.address(options.chain().network())?;
+
+    Wallet::save_multisig(&options, &self.name, &multisig, self.force)?;
+
+    Ok(Box::new(Output {
+      address,
+      redeem_script: hex::encode(multisig.redeem_script().as_bytes()),
+      threshold: multisig.threshold,
+      public_keys: multisig.public_keys,
+    }))
+  }
+}
@@ -0,0 +1,330 @@
+use {
+  super::*,
+  crate::{multisig::Multisig, sat_point::SatPoint, wallet::Wallet},
+  bitcoin::PackedLockTime,
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Send {
+  #[arg(help = "Spend from multisig registered as <NAME>.")]
+  name: String,
+  address: Address,
+  outgoing: Outgoing,
+  #[arg(
+    long,
+    help = "Use fee rate of <FEE_RATE> sats/vB, or `sat/kB` and `CRAFT/kB` with units, or `auto[:<BLOCKS>]` for the node's estimate"
+  )]
+  fee_rate: FeeRateSource,
+  #[arg(
+    long,
+    help = "Target amount of postage to include with sent inscriptions. Defaults to the chain's postage."
+  )]
+  postage: Option<Amount>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub psbt: String,
+  pub fee: u64,
+}
+
+impl Send {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let multisig = Wallet::multisig(&options, &self.name)?;
+
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    let client = options.craftcoin_rpc_client()?;
+
+    let fee_rate = self.fee_rate.resolve(&client)?;
+
+    let chain = options.chain();
+
+    let postage = self.postage.unwrap_or_else(|| chain.default_postage());
+
+    let change = multisig.address(chain.network())?;
+
+    let unspent_outputs = index.get_watch_only_outputs(&[change.clone()].into_iter().collect())?;
+
+    let inscriptions = index.get_inscriptions(None)?;
+
+    let cune_balances = index.get_cune_balances_for_outpoints(
+      &unspent_outputs.keys().cloned().collect::<Vec<OutPoint>>(),
+    )?;
+
+    let (satpoint, target) = match self.outgoing {
+      Outgoing::Amount(amount) => {
+        let dust_value = chain.dust_value(&self.address.script_pubkey());
+
+        ensure!(
+          amount >= dust_value,
+          "output value is below dust value: {amount} < {dust_value}"
+        );
+
+        let inscribed = inscriptions
+          .keys()
+          .map(|satpoint| satpoint.outpoint)
+          .collect::<HashSet<OutPoint>>();
+
+        let (outpoint, _amount) = unspent_outputs
+          .iter()
+          .filter(|(outpoint, _amount)| {
+            !inscribed.contains(*outpoint) && !cune_balances.contains_key(*outpoint)
+          })
+          .max_by_key(|(_outpoint, amount)| **amount)
+          .ok_or_else(|| anyhow!("multisig contains no cardinal outputs"))?;
+
+        (
+          SatPoint {
+            outpoint: *outpoint,
+            offset: 0,
+          },
+          Target::Value(amount),
+        )
+      }
+      Outgoing::InscriptionId(id) => (
+        index
+          .get_inscription_satpoint_by_id(id)?
+          .ok_or_else(|| anyhow!("inscription {id} not found"))?,
+        Target::Postage(postage),
+      ),
+      Outgoing::SatPoint(satpoint) => {
+        for inscription_satpoint in inscriptions.keys() {
+          if satpoint == *inscription_satpoint {
+            bail!("inscriptions must be sent by inscription ID");
+          }
+        }
+
+        ensure!(
+          !cune_balances.contains_key(&satpoint.outpoint),
+          "dunic outpoints may not be sent by satpoint"
+        );
+
+        (satpoint, Target::Postage(postage))
+      }
+      Outgoing::Cune { decimal, cune } => {
+        let transaction = Self::build_cune_transaction(
+          &index,
+          &multisig,
+          chain,
+          fee_rate,
+          postage,
+          self.address,
+          decimal,
+          cune,
+          &inscriptions,
+          &unspent_outputs,
+          &cune_balances,
+          &change,
+        )?;
+
+        return Self::output(&index, &multisig, &unspent_outputs, transaction);
+      }
+    };
+
+    let transaction = TransactionBuilder::new_with_change_address(
+      satpoint,
+      inscriptions,
+      unspent_outputs.clone(),
+      cune_balances,
+      self.address,
+      change,
+      fee_rate,
+      chain,
+      target,
+    )?
+    .with_script_sig(multisig.dummy_script_sig())
+    .build_transaction()?;
+
+    Self::output(&index, &multisig, &unspent_outputs, transaction)
+  }
+
+  /// Builds a transaction sending `decimal` of `spaced_cune` to `recipient`,
+  /// laid out like `ord wallet send` lays out cune sends, with the remaining
+  /// cunes and cardinal change sent back to the multisig.
+  fn build_cune_transaction(
+    index: &Index,
+    multisig: &Multisig,
+    chain: Chain,
+    fee_rate: FeeRate,
+    postage: Amount,
+    recipient: Address,
+    decimal: Decimal,
+    spaced_cune: SpacedCune,
+    inscriptions: &BTreeMap<SatPoint, InscriptionId>,
+    unspent_outputs: &BTreeMap<OutPoint, Amount>,
+    cune_balances: &BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
+    change: &Address,
+  ) -> Result<Transaction> {
+    ensure!(
+      index.has_cune_index(),
+      "sending cunes with `ord wallet multisig send` requires index created with `--index-cunes` flag",
+    );
+
+    let (id, entry) = index
+      .cune(spaced_cune.cune)?
+      .with_context(|| format!("cune `{}` has not been etched", spaced_cune.cune))?;
+
+    let amount = decimal.to_amount(entry.divisibility)?;
+
+    let inscribed = inscriptions
+      .keys()
+      .map(|satpoint| satpoint.outpoint)
+      .collect::<HashSet<OutPoint>>();
+
+    let mut input_cunes = 0;
+    let mut input = Vec::new();
+
+    for (outpoint, balances) in cune_balances {
+      if inscribed.contains(outpoint) {
+        continue;
+      }
+
+      let balance = balances.get(&id).copied().unwrap_or_default();
+
+      if balance > 0 {
+        input_cunes += balance;
+        input.push(*outpoint);
+      }
+
+      if input_cunes >= amount {
+        break;
+      }
+    }
+
+    ensure! {
+      input_cunes >= amount,
+      "insufficient `{}` balance, only {} in multisig",
+      spaced_cune,
+      Pile {
+        amount: input_cunes,
+        divisibility: entry.divisibility,
+        symbol: entry.symbol
+      },
+    }
+
+    let mut cardinal = unspent_outputs
+      .iter()
+      .filter(|(outpoint, _amount)| {
+        !inscribed.contains(*outpoint) && !cune_balances.contains_key(*outpoint)
+      })
+      .map(|(outpoint, amount)| (*amount, *outpoint))
+      .collect::<Vec<(Amount, OutPoint)>>();
+
+    cardinal.sort();
+
+    let cunestone = Cunestone {
+      edicts: vec![Edict {
+        amount,
+        id: id.into(),
+        output: 2,
+      }],
+      ..Default::default()
+    };
+
+    let tx_in = |previous_output| TxIn {
+      previous_output,
+      script_sig: Script::new(),
+      sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+      witness: Witness::new(),
+    };
+
+    let mut transaction = Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: input.into_iter().map(tx_in).collect(),
+      output: vec![
+        TxOut {
+          script_pubkey: cunestone.encipher(),
+          value: 0,
+        },
+        TxOut {
+          script_pubkey: change.script_pubkey(),
+          value: 0,
+        },
+        TxOut {
+          script_pubkey: recipient.script_pubkey(),
+          value: postage.to_sat(),
+        },
+      ],
+    };
+
+    let change_dust_value = chain.dust_value(&change.script_pubkey());
+
+    loop {
+      let input_value = Amount::from_sat(
+        transaction
+          .input
+          .iter()
+          .map(|tx_in| unspent_outputs[&tx_in.previous_output].to_sat())
+          .sum(),
+      );
+
+      let mut estimate = transaction.clone();
+      for tx_in in &mut estimate.input {
+        tx_in.script_sig = multisig.dummy_script_sig();
+      }
+
+      let fee = fee_rate.fee(estimate.vsize());
+
+      if let Some(change_value) = input_value
+        .checked_sub(postage + fee)
+        .filter(|change_value| *change_value >= postage.max(change_dust_value))
+      {
+        transaction.output[1].value = change_value.to_sat();
+        return Ok(transaction);
+      }
+
+      let (_amount, outpoint) = cardinal.pop().ok_or_else(|| {
+        anyhow!("multisig does not contain enough cardinal outputs to pay for transaction")
+      })?;
+
+      transaction.input.push(tx_in(outpoint));
+    }
+  }
+
+  fn output(
+    index: &Index,
+    multisig: &Multisig,
+    unspent_outputs: &BTreeMap<OutPoint, Amount>,
+    transaction: Transaction,
+  ) -> SubcommandResult {
+    let fee = transaction
+      .input
+      .iter()
+      .map(|tx_in| unspent_outputs[&tx_in.previous_output].to_sat())
+      .sum::<u64>()
+      - transaction
+        .output
+        .iter()
+        .map(|tx_out| tx_out.value)
+        .sum::<u64>();
+
+    let mut psbt = PartiallySignedTransaction::from_unsigned_tx(transaction)?;
+
+    for (input, tx_in) in psbt.inputs.iter_mut().zip(&psbt.unsigned_tx.input) {
+      let previous_output = tx_in.previous_output;
+
+      let transaction = index
+        .get_transaction(previous_output.txid)?
+        .ok_or_else(|| anyhow!("transaction {} not found", previous_output.txid))?;
+
+      input.redeem_script = multisig.redeem_script_for(
+        &transaction.output[usize::try_from(previous_output.vout).unwrap()].script_pubkey,
+      );
+
+      ensure!(
+        input.redeem_script.is_some(),
+        "output {previous_output} does not belong to multisig"
+      );
+
+      input.non_witness_utxo = Some(transaction);
+    }
+
+    Ok(Box::new(Output {
+      psbt: Multisig::serialize_psbt(&psbt),
+      fee,
+    }))
+  }
+}
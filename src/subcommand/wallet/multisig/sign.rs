@@ -0,0 +1,68 @@
+use {super::*, crate::multisig::Multisig, bitcoincore_rpc::json::SignRawTransactionInput};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Sign {
+  #[arg(help = "Sign base64-encoded <PSBT>.")]
+  psbt: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub psbt: String,
+  pub signatures: usize,
+  pub complete: bool,
+}
+
+impl Sign {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let mut psbt = Multisig::deserialize_psbt(&self.psbt)?;
+
+    let mut prevouts = Vec::new();
+
+    for (i, (input, tx_in)) in psbt.inputs.iter().zip(&psbt.unsigned_tx.input).enumerate() {
+      let previous_output = tx_in.previous_output;
+
+      let tx_out = input
+        .non_witness_utxo
+        .as_ref()
+        .filter(|transaction| transaction.txid() == previous_output.txid)
+        .and_then(|transaction| {
+          transaction
+            .output
+            .get(usize::try_from(previous_output.vout).unwrap())
+        })
+        .ok_or_else(|| anyhow!("PSBT input {i} is missing the output it spends"))?;
+
+      prevouts.push(SignRawTransactionInput {
+        txid: previous_output.txid,
+        vout: previous_output.vout,
+        script_pub_key: tx_out.script_pubkey.clone(),
+        redeem_script: input.redeem_script.clone(),
+        amount: Some(Amount::from_sat(tx_out.value)),
+      });
+    }
+
+    let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
+
+    let signed = consensus::encode::deserialize(
+      &client
+        .sign_raw_transaction_with_wallet(&psbt.unsigned_tx, Some(&prevouts[..]), None)?
+        .hex,
+    )?;
+
+    let signatures = Multisig::add_signatures(&mut psbt, &signed)?;
+
+    ensure!(
+      signatures > 0,
+      "wallet holds none of the PSBT's multisig keys"
+    );
+
+    Ok(Box::new(Output {
+      psbt: Multisig::serialize_psbt(&psbt),
+      signatures,
+      complete: Multisig::missing_signatures(&psbt)?
+        .iter()
+        .all(|missing| *missing == 0),
+    }))
+  }
+}
@@ -0,0 +1,21 @@
+use super::*;
+
+pub mod accept;
+pub mod create;
+
+#[derive(Debug, Parser)]
+pub(crate) enum Offer {
+  #[clap(about = "Buy the output offered by <OFFER>")]
+  Accept(accept::Accept),
+  #[clap(about = "Offer an inscription or cune output for sale")]
+  Create(create::Create),
+}
+
+impl Offer {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    match self {
+      Self::Accept(accept) => accept.run(options),
+      Self::Create(create) => create.run(options),
+    }
+  }
+}
@@ -0,0 +1,319 @@
+use {
+  super::*,
+  crate::{offer::Offer, wallet::Wallet},
+};
+
+#[derive(Debug, Parser)]
+#[command(group(
+  ArgGroup::new("offered")
+    .required(true)
+    .args(&["inscription", "outpoint"]),
+))]
+pub(crate) struct Accept {
+  #[arg(long, help = "Accept hex-encoded <OFFER>.")]
+  offer: Offer,
+  #[arg(long, help = "Expect <OFFER> to sell <INSCRIPTION>.")]
+  inscription: Option<InscriptionId>,
+  #[arg(long, help = "Expect <OFFER> to sell the cunes in <OUTPOINT>.")]
+  outpoint: Option<OutPoint>,
+  #[arg(long, help = "Pay at most <MAX_PRICE> for the offered output.")]
+  max_price: Amount,
+  #[arg(
+    long,
+    help = "Use fee rate of <FEE_RATE> sats/vB, or `sat/kB` and `CRAFT/kB` with units, or `auto[:<BLOCKS>]` for the node's estimate"
+  )]
+  fee_rate: FeeRateSource,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub transaction: Txid,
+  pub outgoing: OutPoint,
+  pub received: OutPoint,
+  pub price: u64,
+  pub fee: u64,
+}
+
+impl Accept {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
+
+    let fee_rate = self.fee_rate.resolve(&client)?;
+
+    let outgoing = self.offer.outgoing();
+
+    ensure!(
+      self.offer.price() <= self.max_price,
+      "offer price of {} sats exceeds maximum price of {} sats",
+      self.offer.price().to_sat(),
+      self.max_price.to_sat(),
+    );
+
+    match (self.inscription, self.outpoint) {
+      (Some(inscription), None) => {
+        let satpoint = index
+          .get_inscription_satpoint_by_id(inscription)?
+          .ok_or_else(|| anyhow!("inscription {inscription} not found"))?;
+
+        ensure!(
+          satpoint.outpoint == outgoing,
+          "offer spends {outgoing}, but inscription {inscription} is at {satpoint}"
+        );
+      }
+      (None, Some(outpoint)) => {
+        ensure!(
+          outpoint == outgoing,
+          "offer spends {outgoing}, not {outpoint}"
+        );
+
+        ensure!(
+          !index.get_cune_balances_for_outpoint(outgoing)?.is_empty(),
+          "output {outgoing} contains no cunes"
+        );
+      }
+      _ => unreachable!(),
+    }
+
+    let prevout = client
+      .get_raw_transaction(&outgoing.txid)?
+      .output
+      .into_iter()
+      .nth(usize::try_from(outgoing.vout)?)
+      .ok_or_else(|| anyhow!("output {outgoing} not found"))?;
+
+    self.offer.verify(&prevout)?;
+
+    let offered_value = Amount::from_sat(prevout.value);
+
+    let unspent_outputs = CoinControl::default().spendable_outputs(
+      &Wallet::frozen_outputs(&options)?,
+      &index.get_unspent_outputs(Wallet::load(&options)?)?,
+      None,
+    )?;
+
+    let cardinals = get_cardinal_outputs(&index, &unspent_outputs)?
+      .into_iter()
+      .collect::<Vec<(OutPoint, Amount)>>();
+
+    let recipient =
+      client.get_new_address(None, Some(bitcoincore_rpc::json::AddressType::Bech32m))?;
+
+    let change = get_change_address(&client)?;
+
+    let (unsigned_transaction, fee) = Self::build_transaction(
+      &self.offer,
+      offered_value,
+      cardinals,
+      recipient,
+      change,
+      fee_rate,
+      options.chain(),
+    )?;
+
+    let signed_transaction = client
+      .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
+      .hex;
+
+    let txid = client.send_raw_transaction(&signed_transaction)?;
+
+    Ok(Box::new(Output {
+      transaction: txid,
+      outgoing,
+      received: OutPoint { txid, vout: 0 },
+      price: self.offer.price().to_sat(),
+      fee: fee.to_sat(),
+    }))
+  }
+
+  /// Completes `offer` with the smallest cardinal output as padding input,
+  /// followed by the seller input, and then the largest cardinal outputs
+  /// until they cover the price and fee. The padding and offered sats go to
+  /// `recipient` in the first output, so sats at any offset of the offered
+  /// output stay ahead of the seller payment.
+  fn build_transaction(
+    offer: &Offer,
+    offered_value: Amount,
+    mut cardinals: Vec<(OutPoint, Amount)>,
+    recipient: Address,
+    change: Address,
+    fee_rate: FeeRate,
+    chain: Chain,
+  ) -> Result<(Transaction, Amount)> {
+    ensure!(
+      cardinals.len() >= 2,
+      "wallet must contain at least two cardinal UTXOs to accept an offer, use `ord wallet split` to create them"
+    );
+
+    cardinals.sort_by_key(|(_outpoint, amount)| cmp::Reverse(*amount));
+
+    let (padding, padding_value) = cardinals.pop().unwrap();
+
+    let input = |previous_output| TxIn {
+      previous_output,
+      script_sig: Script::new(),
+      sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+      witness: Witness::new(),
+    };
+
+    let mut transaction = offer.transaction().clone();
+
+    transaction.input[0] = input(padding);
+
+    transaction.output[0] = TxOut {
+      value: (padding_value + offered_value).to_sat(),
+      script_pubkey: recipient.script_pubkey(),
+    };
+
+    transaction.output.push(TxOut {
+      value: 0,
+      script_pubkey: change.script_pubkey(),
+    });
+
+    let price = offer.price();
+
+    let mut funding = Amount::ZERO;
+
+    for (outpoint, amount) in cardinals {
+      transaction.input.push(input(outpoint));
+      funding += amount;
+
+      let fee = fee_rate.fee(Self::estimate_vsize(&transaction));
+
+      if let Some(value) = funding
+        .checked_sub(price + fee)
+        .filter(|value| *value >= chain.dust_value(&change.script_pubkey()))
+      {
+        transaction.output[2].value = value.to_sat();
+        return Ok((transaction, fee));
+      }
+    }
+
+    bail!("wallet does not contain enough cardinal UTXOs, please add additional funds")
+  }
+
+  /// Size of `transaction` once the buyer inputs are signed. The seller input
+  /// is already signed.
+  fn estimate_vsize(transaction: &Transaction) -> usize {
+    let mut transaction = transaction.clone();
+
+    for (i, input) in transaction.input.iter_mut().enumerate() {
+      if i != Offer::SELLER {
+        input.script_sig = TransactionBuilder::dummy_script_sig();
+      }
+    }
+
+    transaction.vsize()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, bitcoin::PackedLockTime};
+
+  fn offer(price: u64) -> Offer {
+    let mut transaction = Offer::template(outpoint(1), Amount::from_sat(price), &address());
+
+    let mut signature = [0; 72];
+    signature[71] = 0x83;
+
+    transaction.input[Offer::SELLER].script_sig = script::Builder::new()
+      .push_slice(&signature)
+      .push_slice(&[0; 33])
+      .into_script();
+
+    Offer::from_transaction(transaction).unwrap()
+  }
+
+  fn build(cardinals: &[(u64, u64)]) -> Result<(Transaction, Amount)> {
+    Accept::build_transaction(
+      &offer(20_000),
+      Amount::from_sat(10_000),
+      cardinals
+        .iter()
+        .map(|(n, value)| (outpoint(*n), Amount::from_sat(*value)))
+        .collect(),
+      recipient(),
+      change(0),
+      FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest,
+    )
+  }
+
+  #[test]
+  fn smallest_cardinal_pads_offered_output() {
+    let (transaction, fee) = build(&[(2, 50_000), (3, 1_000)]).unwrap();
+
+    assert_eq!(
+      transaction
+        .input
+        .iter()
+        .map(|input| input.previous_output)
+        .collect::<Vec<OutPoint>>(),
+      [outpoint(3), outpoint(1), outpoint(2)]
+    );
+
+    assert_eq!(transaction.output[0], tx_out(11_000, recipient()));
+    assert_eq!(transaction.output[1], tx_out(20_000, address()));
+    assert_eq!(
+      transaction.output[2],
+      tx_out(50_000 - 20_000 - fee.to_sat(), change(0))
+    );
+
+    assert_eq!(transaction.version, 1);
+    assert_eq!(transaction.lock_time, PackedLockTime::ZERO);
+  }
+
+  #[test]
+  fn seller_input_and_payment_are_unchanged() {
+    let offer = offer(20_000);
+    let (transaction, _fee) = build(&[(2, 50_000), (3, 1_000)]).unwrap();
+
+    assert_eq!(
+      transaction.input[Offer::SELLER],
+      offer.transaction().input[Offer::SELLER]
+    );
+    assert_eq!(
+      transaction.output[Offer::SELLER],
+      offer.transaction().output[Offer::SELLER]
+    );
+  }
+
+  #[test]
+  fn fee_covers_signed_buyer_inputs() {
+    let (transaction, fee) = build(&[(2, 50_000), (3, 1_000)]).unwrap();
+
+    assert_eq!(
+      fee,
+      Amount::from_sat(u64::try_from(Accept::estimate_vsize(&transaction)).unwrap())
+    );
+    assert!(Accept::estimate_vsize(&transaction) > transaction.vsize());
+  }
+
+  #[test]
+  fn funding_inputs_are_added_until_price_is_covered() {
+    let (transaction, _fee) = build(&[(2, 15_000), (3, 1_000), (4, 10_000)]).unwrap();
+
+    assert_eq!(transaction.input.len(), 4);
+    assert_eq!(transaction.input[2].previous_output, outpoint(2));
+    assert_eq!(transaction.input[3].previous_output, outpoint(4));
+  }
+
+  #[test]
+  fn padding_input_is_required() {
+    assert_eq!(
+      build(&[(2, 50_000)]).unwrap_err().to_string(),
+      "wallet must contain at least two cardinal UTXOs to accept an offer, use `ord wallet split` to create them"
+    );
+  }
+
+  #[test]
+  fn insufficient_funds() {
+    assert_eq!(
+      build(&[(2, 15_000), (3, 1_000)]).unwrap_err().to_string(),
+      "wallet does not contain enough cardinal UTXOs, please add additional funds"
+    );
+  }
+}
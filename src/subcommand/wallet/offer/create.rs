@@ -0,0 +1,114 @@
+use {
+  super::*,
+  crate::{offer::Offer, wallet::Wallet},
+  bitcoin::EcdsaSighashType,
+};
+
+#[derive(Debug, Parser)]
+#[command(group(
+  ArgGroup::new("offered")
+    .required(true)
+    .args(&["inscription", "outpoint"]),
+))]
+pub(crate) struct Create {
+  #[arg(long, help = "Offer the output containing <INSCRIPTION>.")]
+  inscription: Option<InscriptionId>,
+  #[arg(long, help = "Offer the cunes in <OUTPOINT>.")]
+  outpoint: Option<OutPoint>,
+  #[arg(long, help = "Ask <PRICE> for the offered output.")]
+  price: Amount,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub offer: Offer,
+  pub outgoing: OutPoint,
+  pub price: u64,
+  pub inscriptions: Vec<InscriptionId>,
+}
+
+impl Create {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
+
+    let outgoing = match (self.inscription, self.outpoint) {
+      (Some(inscription), None) => {
+        index
+          .get_inscription_satpoint_by_id(inscription)?
+          .ok_or_else(|| anyhow!("inscription {inscription} not found"))?
+          .outpoint
+      }
+      (None, Some(outpoint)) => outpoint,
+      _ => unreachable!(),
+    };
+
+    let unspent_outputs = index.get_unspent_outputs(Wallet::load(&options)?)?;
+
+    ensure!(
+      unspent_outputs.contains_key(&outgoing),
+      "output {outgoing} is not in wallet"
+    );
+
+    ensure!(
+      !Wallet::frozen_outputs(&options)?.contains(&outgoing),
+      "output {outgoing} is frozen"
+    );
+
+    let inscriptions = index.get_inscriptions_on_output(outgoing)?;
+
+    let cunic = !index.get_cune_balances_for_outpoint(outgoing)?.is_empty();
+
+    // Everything in the offered output goes to the buyer, so refuse to offer
+    // anything besides what was asked for
+    if let Some(inscription) = self.inscription {
+      ensure!(
+        inscriptions == [inscription],
+        "output {outgoing} contains other inscriptions besides {inscription}, which would be sold with it"
+      );
+
+      ensure!(
+        !cunic,
+        "output {outgoing} contains cunes, which would be sold with inscription {inscription}"
+      );
+    } else {
+      ensure!(cunic, "output {outgoing} contains no cunes");
+
+      ensure!(
+        inscriptions.is_empty(),
+        "output {outgoing} contains inscriptions, which would be sold with its cunes"
+      );
+    }
+
+    let seller = client.get_new_address(None, Some(bitcoincore_rpc::json::AddressType::Bech32m))?;
+
+    let dust_value = options.chain().dust_value(&seller.script_pubkey());
+
+    ensure!(
+      self.price >= dust_value,
+      "price of {} sats is below the dust limit of {} sats",
+      self.price.to_sat(),
+      dust_value.to_sat(),
+    );
+
+    let signed_transaction = client
+      .sign_raw_transaction_with_wallet(
+        &Offer::template(outgoing, self.price, &seller),
+        None,
+        Some(EcdsaSighashType::SinglePlusAnyoneCanPay.into()),
+      )?
+      .hex;
+
+    let offer = Offer::from_transaction(consensus::deserialize(&signed_transaction)?)
+      .with_context(|| format!("wallet could not sign offer for output {outgoing}"))?;
+
+    Ok(Box::new(Output {
+      offer,
+      outgoing,
+      price: self.price.to_sat(),
+      inscriptions,
+    }))
+  }
+}
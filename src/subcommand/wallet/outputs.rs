@@ -11,7 +11,7 @@ pub(crate) fn run(options: Options) -> SubcommandResult {
   index.update()?;
 
   let mut outputs = Vec::new();
-  for (output, amount) in index.get_unspent_outputs(Wallet::load(&options)?)? {
+  for (output, amount) in index.get_unspent_outputs(Wallet::load_for_query(&options)?)? {
     outputs.push(Output {
       output,
       amount: amount.to_sat(),
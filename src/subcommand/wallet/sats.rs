@@ -33,7 +33,7 @@ impl Sats {
 
     index.update()?;
 
-    let utxos = index.get_unspent_output_ranges(Wallet::load(&options)?)?;
+    let utxos = index.get_unspent_output_ranges(Wallet::load_for_query(&options)?)?;
 
     if let Some(path) = &self.tsv {
       let mut output = Vec::new();
@@ -5,13 +5,24 @@ use {super::*, crate::wallet::Wallet};
 pub(crate) struct Send {
   address: Address,
   outgoing: Outgoing,
-  #[arg(long, help = "Use fee rate of <FEE_RATE> sats/vB")]
-  fee_rate: FeeRate,
+  #[arg(
+    long,
+    help = "Use fee rate of <FEE_RATE> sats/vB, or `sat/kB` and `CRAFT/kB` with units, or `auto[:<BLOCKS>]` for the node's estimate"
+  )]
+  fee_rate: FeeRateSource,
+  #[arg(
+    long,
+    help = "Target amount of postage to include with sent inscriptions. Defaults to the chain's postage."
+  )]
+  postage: Option<Amount>,
+  #[command(flatten)]
+  coin_control: CoinControl,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Output {
   pub transaction: Txid,
+  pub fee_rate: f64,
 }
 
 impl Send {
@@ -23,34 +34,90 @@ impl Send {
 
     let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
 
+    let fee_rate = self.fee_rate.resolve(&client)?;
+
+    let chain = options.chain();
+
+    let postage = self.postage.unwrap_or_else(|| chain.default_postage());
+
     let unspent_outputs = index.get_unspent_outputs(Wallet::load(&options)?)?;
 
+    let frozen = Wallet::frozen_outputs(&options)?;
+
     let inscriptions = index.get_inscriptions(None)?;
 
-    let dunic_outputs =
-      index.get_dunic_outputs(&unspent_outputs.keys().cloned().collect::<Vec<OutPoint>>())?;
+    let mut cune_balances = index.get_cune_balances_for_outpoints(
+      &unspent_outputs.keys().cloned().collect::<Vec<OutPoint>>(),
+    )?;
+
+    // Inscribed outputs are locked so that the node never uses them to fund
+    // amount and cune sends
+    let inscribed_outputs = inscriptions
+      .keys()
+      .map(|satpoint| satpoint.outpoint)
+      .filter(|outpoint| unspent_outputs.contains_key(outpoint))
+      .collect::<Vec<OutPoint>>();
 
     let satpoint = match self.outgoing {
       Outgoing::Amount(amount) => {
-        let transaction = Self::send_amount(&client, amount, address, self.fee_rate)?;
-        return Ok(Box::new(Output { transaction }));
+        let _locked = self.coin_control.lock_unspendable_outputs(
+          &client,
+          &frozen,
+          &unspent_outputs,
+          inscribed_outputs.clone(),
+        )?;
+
+        let transaction = if self.coin_control.utxos.is_empty() {
+          Self::send_amount(&client, amount, address, fee_rate)?
+        } else {
+          for outpoint in &self.coin_control.utxos {
+            ensure!(
+              !inscribed_outputs.contains(outpoint) && !cune_balances.contains_key(outpoint),
+              "output {outpoint} carries inscriptions or cunes"
+            );
+          }
+
+          Self::send_amount_from(&client, &self.coin_control.utxos, amount, address, fee_rate)?
+        };
+        return Ok(Box::new(Output {
+          transaction,
+          fee_rate: fee_rate.n(),
+        }));
       }
       Outgoing::InscriptionId(id) => index
         .get_inscription_satpoint_by_id(id)?
         .ok_or_else(|| anyhow!("inscription {id} not found"))?,
       Outgoing::Cune { decimal, cune } => {
+        let spendable_outputs =
+          self
+            .coin_control
+            .spendable_outputs(&frozen, &unspent_outputs, None)?;
+
+        cune_balances.retain(|outpoint, _balances| spendable_outputs.contains_key(outpoint));
+
+        let _locked = self.coin_control.lock_unspendable_outputs(
+          &client,
+          &frozen,
+          &unspent_outputs,
+          inscribed_outputs,
+        )?;
+
         let transaction = Self::send_cunes(
           address,
           &client,
           decimal,
-          self.fee_rate,
+          fee_rate,
+          postage,
           &index,
           inscriptions,
           cune,
-          dunic_outputs,
-          unspent_outputs,
+          cune_balances,
+          &self.coin_control.utxos,
         )?;
-        return Ok(Box::new(Output { transaction }));
+        return Ok(Box::new(Output {
+          transaction,
+          fee_rate: fee_rate.n(),
+        }));
       }
       Outgoing::SatPoint(satpoint) => {
         for inscription_satpoint in inscriptions.keys() {
@@ -60,7 +127,7 @@ impl Send {
         }
 
         ensure!(
-          !dunic_outputs.contains(&satpoint.outpoint),
+          !cune_balances.contains_key(&satpoint.outpoint),
           "dunic outpoints may not be sent by satpoint"
         );
 
@@ -68,17 +135,28 @@ impl Send {
       }
     };
 
+    let spendable_outputs =
+      self
+        .coin_control
+        .spendable_outputs(&frozen, &unspent_outputs, Some(satpoint.outpoint))?;
+
+    cune_balances.retain(|outpoint, _balances| spendable_outputs.contains_key(outpoint));
+
     let change = [get_change_address(&client)?, get_change_address(&client)?];
 
-    let unsigned_transaction = TransactionBuilder::build_transaction_with_postage(
+    let unsigned_transaction = TransactionBuilder::new(
       satpoint,
       inscriptions,
-      unspent_outputs,
-      dunic_outputs,
+      spendable_outputs,
+      cune_balances,
       self.address,
       change,
-      self.fee_rate,
-    )?;
+      fee_rate,
+      chain,
+      Target::Postage(postage),
+    )?
+    .with_required_inputs(self.coin_control.utxos)
+    .build_transaction()?;
 
     let signed_tx = client
       .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
@@ -88,7 +166,10 @@ impl Send {
 
     println!("{txid}");
 
-    Ok(Box::new(Output { transaction: txid }))
+    Ok(Box::new(Output {
+      transaction: txid,
+      fee_rate: fee_rate.n(),
+    }))
   }
 
   fn send_amount(
@@ -114,16 +195,53 @@ impl Send {
     )?)
   }
 
+  /// Send `amount` to `address` in a transaction spending every output in
+  /// `utxos`, funded by the node if they are not enough.
+  fn send_amount_from(
+    client: &Client,
+    utxos: &[OutPoint],
+    amount: Amount,
+    address: Address,
+    fee_rate: FeeRate,
+  ) -> Result<Txid> {
+    let unfunded_transaction = Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: utxos
+        .iter()
+        .map(|previous_output| TxIn {
+          previous_output: *previous_output,
+          script_sig: Script::new(),
+          sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+          witness: Witness::new(),
+        })
+        .collect(),
+      output: vec![TxOut {
+        script_pubkey: address.script_pubkey(),
+        value: amount.to_sat(),
+      }],
+    };
+
+    let unsigned_transaction = fund_raw_transaction(client, fee_rate, &unfunded_transaction)?;
+
+    let signed_transaction = client
+      .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
+      .hex;
+
+    Ok(client.send_raw_transaction(&signed_transaction)?)
+  }
+
   fn send_cunes(
     address: Address,
     client: &Client,
     decimal: Decimal,
     fee_rate: FeeRate,
+    postage: Amount,
     index: &Index,
     inscriptions: BTreeMap<SatPoint, InscriptionId>,
     spaced_cune: SpacedCune,
-    dunic_outputs: BTreeSet<OutPoint>,
-    unspent_outputs: BTreeMap<OutPoint, Amount>,
+    cune_balances: BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
+    required_inputs: &[OutPoint],
   ) -> Result<Txid> {
     ensure!(
       index.has_cune_index(),
@@ -144,12 +262,12 @@ impl Send {
     let mut input_cunes = 0;
     let mut input = Vec::new();
 
-    for output in dunic_outputs {
+    for (output, balances) in cune_balances {
       if inscribed_outputs.contains(&output) {
         continue;
       }
 
-      let balance = index.get_cune_balance(output, id)?;
+      let balance = balances.get(&id).copied().unwrap_or_default();
 
       if balance > 0 {
         input_cunes += balance;
@@ -172,6 +290,19 @@ impl Send {
       },
     }
 
+    // Outputs selected with `--utxo` are always spent, and any cunes they carry
+    // go to the change output along with the unsent balance
+    for outpoint in required_inputs {
+      ensure!(
+        !inscribed_outputs.contains(outpoint),
+        "output {outpoint} carries inscriptions"
+      );
+
+      if !input.contains(outpoint) {
+        input.push(*outpoint);
+      }
+    }
+
     let cunestone = Cunestone {
       edicts: vec![Edict {
         amount,
@@ -189,7 +320,7 @@ impl Send {
         .map(|previous_output| TxIn {
           previous_output,
           script_sig: Script::new(),
-          sequence: Sequence::MAX,
+          sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
           witness: Witness::new(),
         })
         .collect(),
@@ -200,11 +331,11 @@ impl Send {
         },
         TxOut {
           script_pubkey: get_change_address(client)?.script_pubkey(),
-          value: TARGET_POSTAGE.to_sat(),
+          value: postage.to_sat(),
         },
         TxOut {
           script_pubkey: address.script_pubkey(),
-          value: TARGET_POSTAGE.to_sat(),
+          value: postage.to_sat(),
         },
       ],
     };
@@ -0,0 +1,549 @@
+use {super::*, crate::wallet::Wallet, bitcoin::PackedLockTime};
+
+const MAX_CUNESTONE_SIZE: usize = 82;
+
+#[derive(Debug, Parser)]
+pub(crate) struct SendCunes {
+  #[arg(
+    long,
+    help = "Send cunes to recipients listed in <CSV>, one `ADDRESS,AMOUNT,CUNE` per line"
+  )]
+  csv: PathBuf,
+  #[arg(
+    long,
+    help = "Use fee rate of <FEE_RATE> sats/vB, or `sat/kB` and `CRAFT/kB` with units, or `auto[:<BLOCKS>]` for the node's estimate"
+  )]
+  fee_rate: FeeRateSource,
+  #[arg(
+    long,
+    help = "Write `ADDRESS,AMOUNT,CUNE,TXID` report of sent batches to <REPORT>"
+  )]
+  report: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Recipient {
+  pub address: Address,
+  pub amount: String,
+  pub cune: SpacedCune,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Batch {
+  pub transaction: Txid,
+  pub recipients: Vec<Recipient>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub batches: Vec<Batch>,
+  pub fee_rate: f64,
+}
+
+struct Transfer {
+  address: Address,
+  decimal: Decimal,
+  spaced_cune: SpacedCune,
+  id: CuneId,
+  amount: u128,
+}
+
+impl SendCunes {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    ensure!(
+      index.has_cune_index(),
+      "sending cunes with `ord wallet send-cunes` requires index created with `--index-cunes` flag",
+    );
+
+    let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
+
+    let fee_rate = self.fee_rate.resolve(&client)?;
+
+    let postage = options.chain().default_postage();
+
+    let transfers = Self::parse_csv(&fs::read_to_string(&self.csv)?, &index)?;
+
+    ensure!(
+      !transfers.is_empty(),
+      "no recipients in {}",
+      self.csv.display()
+    );
+
+    let unspent_outputs = index.get_unspent_outputs(Wallet::load(&options)?)?;
+
+    let frozen = Wallet::frozen_outputs(&options)?;
+
+    let inscribed_outputs = index
+      .get_inscriptions(None)?
+      .keys()
+      .map(|satpoint| satpoint.outpoint)
+      .collect::<HashSet<OutPoint>>();
+
+    let ids = transfers
+      .iter()
+      .map(|transfer| transfer.id)
+      .collect::<BTreeSet<CuneId>>();
+
+    // Dunic outputs spendable by this command, together with their balances of
+    // the cunes being sent
+    let mut available: Vec<(OutPoint, BTreeMap<CuneId, u128>)> = Vec::new();
+
+    let dunic_outputs =
+      index.get_dunic_outputs(&unspent_outputs.keys().cloned().collect::<Vec<OutPoint>>())?;
+
+    for &outpoint in &dunic_outputs {
+      if inscribed_outputs.contains(&outpoint) || frozen.contains(&outpoint) {
+        continue;
+      }
+
+      let mut balances = BTreeMap::new();
+      for id in &ids {
+        let balance = index.get_cune_balance(outpoint, *id)?;
+        if balance > 0 {
+          balances.insert(*id, balance);
+        }
+      }
+
+      if !balances.is_empty() {
+        available.push((outpoint, balances));
+      }
+    }
+
+    let mut wallet_balances: BTreeMap<CuneId, u128> = BTreeMap::new();
+    for (_, balances) in &available {
+      for (id, balance) in balances {
+        *wallet_balances.entry(*id).or_default() += balance;
+      }
+    }
+
+    let mut required: BTreeMap<CuneId, (u128, SpacedCune)> = BTreeMap::new();
+    for transfer in &transfers {
+      required
+        .entry(transfer.id)
+        .or_insert((0, transfer.spaced_cune))
+        .0 += transfer.amount;
+    }
+
+    for (id, (amount, spaced_cune)) in &required {
+      let balance = wallet_balances.get(id).copied().unwrap_or_default();
+      ensure!(
+        balance >= *amount,
+        "insufficient `{spaced_cune}` balance, {} required but only {} in wallet",
+        amount,
+        balance,
+      );
+    }
+
+    // Inscribed and dunic outputs of the wallet, which must never be used to
+    // pay fees
+    let non_cardinal_outputs = inscribed_outputs
+      .iter()
+      .filter(|outpoint| unspent_outputs.contains_key(outpoint))
+      .chain(&dunic_outputs)
+      .copied()
+      .collect::<BTreeSet<OutPoint>>();
+
+    // Postage outputs of earlier batches, which may carry leftover cunes
+    let mut pending = Vec::new();
+
+    let mut batches = Vec::new();
+    let mut remaining = transfers.as_slice();
+
+    while !remaining.is_empty() {
+      let mut plan = Self::plan(&remaining[..1], &available)?.with_context(|| {
+        format!(
+          "cunestone for transfer to {} exceeds maximum OP_RETURN size of {MAX_CUNESTONE_SIZE} bytes",
+          remaining[0].address
+        )
+      })?;
+
+      let mut count = 1;
+      while count < remaining.len() {
+        match Self::plan(&remaining[..count + 1], &available)? {
+          Some(next) => {
+            plan = next;
+            count += 1;
+          }
+          None => break,
+        }
+      }
+
+      let (batch, rest) = remaining.split_at(count);
+
+      let change_address = get_change_address(&client)?;
+
+      let mut output = vec![
+        TxOut {
+          script_pubkey: plan.cunestone.encipher(),
+          value: 0,
+        },
+        TxOut {
+          script_pubkey: change_address.script_pubkey(),
+          value: postage.to_sat(),
+        },
+      ];
+
+      output.extend(batch.iter().map(|transfer| TxOut {
+        script_pubkey: transfer.address.script_pubkey(),
+        value: postage.to_sat(),
+      }));
+
+      let unfunded_transaction = Transaction {
+        version: 1,
+        lock_time: PackedLockTime::ZERO,
+        input: plan
+          .inputs
+          .iter()
+          .map(|previous_output| TxIn {
+            previous_output: *previous_output,
+            script_sig: Script::new(),
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            witness: Witness::new(),
+          })
+          .collect(),
+        output,
+      };
+
+      let unsigned_transaction = {
+        let _locked = CoinControl::default().lock_unspendable_outputs(
+          &client,
+          &frozen,
+          &unspent_outputs,
+          non_cardinal_outputs
+            .iter()
+            .chain(&pending)
+            .filter(|outpoint| !plan.inputs.contains(outpoint))
+            .copied(),
+        )?;
+
+        fund_raw_transaction(&client, fee_rate, &unfunded_transaction)?
+      };
+
+      let signed_transaction = client
+        .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
+        .hex;
+
+      let txid = client.send_raw_transaction(&signed_transaction)?;
+
+      // Spent inputs are no longer available, but the leftover balances on the
+      // change output can fund subsequent batches
+      available.retain(|(outpoint, _)| !plan.inputs.contains(outpoint));
+
+      let change = OutPoint { txid, vout: 1 };
+
+      pending.push(change);
+
+      if !plan.change.is_empty() {
+        available.push((change, plan.change));
+      }
+
+      batches.push(Batch {
+        transaction: txid,
+        recipients: batch
+          .iter()
+          .map(|transfer| Recipient {
+            address: transfer.address.clone(),
+            amount: transfer.decimal.to_string(),
+            cune: transfer.spaced_cune,
+          })
+          .collect(),
+      });
+
+      remaining = rest;
+    }
+
+    if let Some(report) = &self.report {
+      let mut csv = String::new();
+      for batch in &batches {
+        for recipient in &batch.recipients {
+          csv.push_str(&format!(
+            "{},{},{},{}\n",
+            recipient.address, recipient.amount, recipient.cune, batch.transaction
+          ));
+        }
+      }
+      fs::write(report, csv)
+        .with_context(|| format!("failed to write report to {}", report.display()))?;
+    }
+
+    Ok(Box::new(Output {
+      batches,
+      fee_rate: fee_rate.n(),
+    }))
+  }
+
+  fn parse_csv(csv: &str, index: &Index) -> Result<Vec<Transfer>> {
+    let mut entries = HashMap::new();
+    let mut transfers = Vec::new();
+
+    for (i, line) in csv.lines().enumerate() {
+      let line = line.trim();
+
+      if line.is_empty() || line.starts_with('#') || (i == 0 && line.starts_with("address")) {
+        continue;
+      }
+
+      let fields = line.split(',').map(str::trim).collect::<Vec<&str>>();
+
+      let [address, decimal, spaced_cune] = fields.as_slice() else {
+        bail!(
+          "line {}: expected `ADDRESS,AMOUNT,CUNE`, found `{line}`",
+          i + 1
+        );
+      };
+
+      let address = address
+        .parse::<Address>()
+        .with_context(|| format!("line {}: invalid address `{address}`", i + 1))?;
+
+      let decimal = decimal
+        .parse::<Decimal>()
+        .with_context(|| format!("line {}: invalid amount `{decimal}`", i + 1))?;
+
+      let spaced_cune = spaced_cune
+        .parse::<SpacedCune>()
+        .with_context(|| format!("line {}: invalid cune `{spaced_cune}`", i + 1))?;
+
+      if !entries.contains_key(&spaced_cune.cune) {
+        let entry = index
+          .cune(spaced_cune.cune)?
+          .with_context(|| format!("cune `{}` has not been etched", spaced_cune.cune))?;
+        entries.insert(spaced_cune.cune, entry);
+      }
+
+      let (id, entry) = &entries[&spaced_cune.cune];
+
+      let amount = decimal
+        .to_amount(entry.divisibility)
+        .with_context(|| format!("line {}: invalid amount for `{spaced_cune}`", i + 1))?;
+
+      ensure!(
+        amount > 0,
+        "line {}: amount must be greater than zero",
+        i + 1
+      );
+
+      transfers.push(Transfer {
+        address,
+        decimal,
+        spaced_cune,
+        id: *id,
+        amount,
+      });
+    }
+
+    Ok(transfers)
+  }
+
+  /// Select inputs for `transfers` and build the cunestone paying them,
+  /// returning `None` if the cunestone would not fit in an OP_RETURN output.
+  fn plan(
+    transfers: &[Transfer],
+    available: &[(OutPoint, BTreeMap<CuneId, u128>)],
+  ) -> Result<Option<Plan>> {
+    let mut required: BTreeMap<CuneId, u128> = BTreeMap::new();
+    for transfer in transfers {
+      *required.entry(transfer.id).or_default() += transfer.amount;
+    }
+
+    let mut inputs = Vec::new();
+    let mut selected: BTreeMap<CuneId, u128> = BTreeMap::new();
+
+    for (outpoint, balances) in available {
+      let needed = required
+        .iter()
+        .any(|(id, amount)| selected.get(id).copied().unwrap_or_default() < *amount);
+
+      if !needed {
+        break;
+      }
+
+      if balances.iter().any(|(id, balance)| {
+        *balance > 0
+          && required
+            .get(id)
+            .map(|amount| selected.get(id).copied().unwrap_or_default() < *amount)
+            .unwrap_or_default()
+      }) {
+        inputs.push(*outpoint);
+        for (id, balance) in balances {
+          *selected.entry(*id).or_default() += balance;
+        }
+      }
+    }
+
+    for (id, amount) in &required {
+      ensure!(
+        selected.get(id).copied().unwrap_or_default() >= *amount,
+        "insufficient balance of cune {id} in wallet"
+      );
+    }
+
+    let mut edicts = transfers
+      .iter()
+      .enumerate()
+      .map(|(i, transfer)| Edict {
+        id: transfer.id.into(),
+        amount: transfer.amount,
+        output: u128::try_from(i + 2).unwrap(),
+      })
+      .collect::<Vec<Edict>>();
+
+    let mut change = BTreeMap::new();
+
+    for (id, balance) in selected {
+      let leftover = balance - required.get(&id).copied().unwrap_or_default();
+      if leftover > 0 {
+        edicts.push(Edict {
+          id: id.into(),
+          amount: leftover,
+          output: 1,
+        });
+        change.insert(id, leftover);
+      }
+    }
+
+    // Any other cunes held by the selected inputs are swept to the change
+    // output by the pointer
+    let cunestone = Cunestone {
+      edicts,
+      pointer: Some(1),
+      ..Default::default()
+    };
+
+    if cunestone.encipher().len() > MAX_CUNESTONE_SIZE {
+      return Ok(None);
+    }
+
+    Ok(Some(Plan {
+      cunestone,
+      inputs,
+      change,
+    }))
+  }
+}
+
+struct Plan {
+  cunestone: Cunestone,
+  inputs: Vec<OutPoint>,
+  change: BTreeMap<CuneId, u128>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn transfer(n: u32, amount: u128) -> Transfer {
+    Transfer {
+      address: recipient(),
+      decimal: Decimal {
+        value: amount,
+        scale: 0,
+      },
+      spaced_cune: SpacedCune {
+        cune: Cune(u128::from(n)),
+        spacers: 0,
+      },
+      id: CuneId {
+        height: 1,
+        index: n,
+      },
+      amount,
+    }
+  }
+
+  #[test]
+  fn plan_adds_change_edicts_for_leftover_balance() {
+    let id = CuneId {
+      height: 1,
+      index: 1,
+    };
+
+    let plan = SendCunes::plan(
+      &[transfer(1, 600)],
+      &[(outpoint(1), [(id, 1000)].into_iter().collect())],
+    )
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(plan.inputs, vec![outpoint(1)]);
+    assert_eq!(plan.change, [(id, 400)].into_iter().collect());
+    assert_eq!(
+      plan.cunestone,
+      Cunestone {
+        edicts: vec![
+          Edict {
+            id: id.into(),
+            amount: 600,
+            output: 2,
+          },
+          Edict {
+            id: id.into(),
+            amount: 400,
+            output: 1,
+          },
+        ],
+        pointer: Some(1),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn plan_only_selects_needed_inputs() {
+    let id = CuneId {
+      height: 1,
+      index: 1,
+    };
+
+    let plan = SendCunes::plan(
+      &[transfer(1, 100)],
+      &[
+        (outpoint(1), [(id, 100)].into_iter().collect()),
+        (outpoint(2), [(id, 100)].into_iter().collect()),
+      ],
+    )
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(plan.inputs, vec![outpoint(1)]);
+    assert!(plan.change.is_empty());
+  }
+
+  #[test]
+  fn plan_fails_when_cunestone_is_too_large() {
+    let id = CuneId {
+      height: 1,
+      index: 1,
+    };
+
+    let available = [(outpoint(1), [(id, u128::MAX)].into_iter().collect())];
+
+    let transfers = (0..100)
+      .map(|_| transfer(1, 1 << 60))
+      .collect::<Vec<Transfer>>();
+
+    assert!(SendCunes::plan(&transfers[..1], &available)
+      .unwrap()
+      .is_some());
+
+    assert!(SendCunes::plan(&transfers, &available).unwrap().is_none());
+  }
+
+  #[test]
+  fn plan_fails_on_insufficient_balance() {
+    let id = CuneId {
+      height: 1,
+      index: 1,
+    };
+
+    assert!(SendCunes::plan(
+      &[transfer(1, 101)],
+      &[(outpoint(1), [(id, 100)].into_iter().collect())],
+    )
+    .is_err());
+  }
+}
@@ -0,0 +1,348 @@
+use {
+  super::*,
+  crate::wallet::Wallet,
+  bitcoin::policy::MAX_STANDARD_TX_WEIGHT,
+  transaction_builder::batch::{BatchTransactionBuilder, Transfer},
+};
+
+// Each transfer adds at most three inputs and three outputs, so batches of
+// this size stay well below the standard transaction weight limit
+const MAX_BATCH_SIZE: usize = 100;
+
+#[derive(Debug, Parser)]
+pub(crate) struct SendInscriptions {
+  #[arg(
+    long,
+    help = "Send inscriptions to recipients listed in <CSV>, one `INSCRIPTION_ID,ADDRESS` per line"
+  )]
+  csv: PathBuf,
+  #[arg(
+    long,
+    help = "Use fee rate of <FEE_RATE> sats/vB, or `sat/kB` and `CRAFT/kB` with units, or `auto[:<BLOCKS>]` for the node's estimate"
+  )]
+  fee_rate: FeeRateSource,
+  #[arg(
+    long,
+    help = "Target amount of postage to include with each sent inscription. Defaults to the chain's postage."
+  )]
+  postage: Option<Amount>,
+  #[arg(
+    long,
+    help = "Don't sign or broadcast transactions, and print where each inscription would be moved."
+  )]
+  dry_run: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Recipient {
+  pub inscription: InscriptionId,
+  pub address: Address,
+  pub input: SatPoint,
+  pub output: SatPoint,
+  pub value: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Batch {
+  pub transaction: Txid,
+  pub fee: u64,
+  pub recipients: Vec<Recipient>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub batches: Vec<Batch>,
+  pub fee_rate: f64,
+}
+
+impl SendInscriptions {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
+
+    let fee_rate = self.fee_rate.resolve(&client)?;
+
+    let chain = options.chain();
+
+    let postage = self.postage.unwrap_or_else(|| chain.default_postage());
+
+    let entries = Self::parse_csv(&fs::read_to_string(&self.csv)?)?;
+
+    ensure!(
+      !entries.is_empty(),
+      "no recipients in {}",
+      self.csv.display()
+    );
+
+    let unspent_outputs = index.get_unspent_outputs(Wallet::load(&options)?)?;
+
+    let frozen = Wallet::frozen_outputs(&options)?;
+
+    let mut transfers = Vec::new();
+    for (inscription, address) in entries {
+      let satpoint = index
+        .get_inscription_satpoint_by_id(inscription)?
+        .ok_or_else(|| anyhow!("inscription {inscription} not found"))?;
+
+      ensure!(
+        unspent_outputs.contains_key(&satpoint.outpoint),
+        "inscription {inscription} is not in wallet"
+      );
+
+      ensure!(
+        !frozen.contains(&satpoint.outpoint),
+        "output {} is frozen",
+        satpoint.outpoint
+      );
+
+      transfers.push((
+        inscription,
+        Transfer {
+          outgoing: satpoint,
+          recipient: address,
+        },
+      ));
+    }
+
+    let inscriptions = index.get_inscriptions(None)?;
+
+    let mut amounts = CoinControl::default().spendable_outputs(&frozen, &unspent_outputs, None)?;
+
+    let cune_balances =
+      index.get_cune_balances_for_outpoints(&amounts.keys().cloned().collect::<Vec<OutPoint>>())?;
+
+    let mut unsigned_transactions = Vec::new();
+
+    for batch in transfers.chunks(MAX_BATCH_SIZE) {
+      let mut change = Vec::new();
+      for _ in 0..BatchTransactionBuilder::change_addresses_required(batch.len()) {
+        change.push(get_change_address(&client)?);
+      }
+
+      let unsigned_transaction = BatchTransactionBuilder::build_transaction(
+        batch
+          .iter()
+          .map(|(_id, transfer)| transfer.clone())
+          .collect(),
+        inscriptions.clone(),
+        amounts.clone(),
+        cune_balances.clone(),
+        change,
+        fee_rate,
+        chain,
+        postage,
+      )?;
+
+      ensure!(
+        unsigned_transaction.weight() <= MAX_STANDARD_TX_WEIGHT.try_into().unwrap(),
+        "batch transaction weight greater than {MAX_STANDARD_TX_WEIGHT} (MAX_STANDARD_TX_WEIGHT)"
+      );
+
+      let fee = unsigned_transaction
+        .input
+        .iter()
+        .map(|tx_in| amounts[&tx_in.previous_output].to_sat())
+        .sum::<u64>()
+        - unsigned_transaction
+          .output
+          .iter()
+          .map(|tx_out| tx_out.value)
+          .sum::<u64>();
+
+      let vouts = batch
+        .iter()
+        .map(|(_id, transfer)| {
+          Self::output_containing(&unsigned_transaction, &amounts, transfer.outgoing)
+        })
+        .collect::<Vec<u32>>();
+
+      // Later batches must not spend inputs of earlier ones
+      for tx_in in &unsigned_transaction.input {
+        amounts.remove(&tx_in.previous_output);
+      }
+
+      unsigned_transactions.push((batch, vouts, unsigned_transaction, fee));
+    }
+
+    let mut batches = Vec::new();
+
+    for (batch, vouts, unsigned_transaction, fee) in unsigned_transactions {
+      let txid = if self.dry_run {
+        unsigned_transaction.txid()
+      } else {
+        let signed_transaction = client
+          .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
+          .hex;
+
+        client.send_raw_transaction(&signed_transaction)?
+      };
+
+      batches.push(Batch {
+        transaction: txid,
+        fee,
+        recipients: batch
+          .iter()
+          .zip(vouts)
+          .map(|((inscription, transfer), vout)| Recipient {
+            inscription: *inscription,
+            address: transfer.recipient.clone(),
+            input: transfer.outgoing,
+            output: SatPoint {
+              outpoint: OutPoint { txid, vout },
+              offset: 0,
+            },
+            value: unsigned_transaction.output[usize::try_from(vout).unwrap()].value,
+          })
+          .collect(),
+      });
+    }
+
+    // The table goes to stderr so that stdout is only the JSON output
+    if self.dry_run {
+      eprint!("{}", Self::table(&batches));
+    }
+
+    Ok(Box::new(Output {
+      batches,
+      fee_rate: fee_rate.n(),
+    }))
+  }
+
+  fn parse_csv(csv: &str) -> Result<Vec<(InscriptionId, Address)>> {
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+
+    for (i, line) in csv.lines().enumerate() {
+      let line = line.trim();
+
+      if line.is_empty()
+        || line.starts_with('#')
+        || (i == 0 && (line.starts_with("id") || line.starts_with("inscription")))
+      {
+        continue;
+      }
+
+      let fields = line.split(',').map(str::trim).collect::<Vec<&str>>();
+
+      let [inscription, address] = fields.as_slice() else {
+        bail!(
+          "line {}: expected `INSCRIPTION_ID,ADDRESS`, found `{line}`",
+          i + 1
+        );
+      };
+
+      let inscription = inscription
+        .parse::<InscriptionId>()
+        .with_context(|| format!("line {}: invalid inscription ID `{inscription}`", i + 1))?;
+
+      let address = address
+        .parse::<Address>()
+        .with_context(|| format!("line {}: invalid address `{address}`", i + 1))?;
+
+      ensure!(
+        seen.insert(inscription),
+        "line {}: inscription {inscription} is listed more than once",
+        i + 1
+      );
+
+      entries.push((inscription, address));
+    }
+
+    Ok(entries)
+  }
+
+  /// Index of the output of `transaction` which receives the sat at
+  /// `satpoint`, found by walking input and output values.
+  fn output_containing(
+    transaction: &Transaction,
+    amounts: &BTreeMap<OutPoint, Amount>,
+    satpoint: SatPoint,
+  ) -> u32 {
+    let mut offset = satpoint.offset;
+    for tx_in in &transaction.input {
+      if tx_in.previous_output == satpoint.outpoint {
+        break;
+      }
+      offset += amounts[&tx_in.previous_output].to_sat();
+    }
+
+    let mut end = 0;
+    for (vout, tx_out) in transaction.output.iter().enumerate() {
+      end += tx_out.value;
+      if end > offset {
+        return u32::try_from(vout).unwrap();
+      }
+    }
+
+    panic!("outgoing sat is not found in outputs");
+  }
+
+  fn table(batches: &[Batch]) -> String {
+    let mut table = String::new();
+
+    for batch in batches {
+      table.push_str(&format!(
+        "transaction {} fee {} sats\n",
+        batch.transaction, batch.fee
+      ));
+
+      for recipient in &batch.recipients {
+        table.push_str(&format!(
+          "  {}  {} -> {}  {} sats  {}\n",
+          recipient.inscription,
+          recipient.input,
+          recipient.output.outpoint.vout,
+          recipient.value,
+          recipient.address,
+        ));
+      }
+    }
+
+    table
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_csv() {
+    assert_eq!(
+      SendInscriptions::parse_csv(&format!(
+        "id,address\n# comment\n\n{},bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4\n",
+        inscription_id(1)
+      ))
+      .unwrap(),
+      [(inscription_id(1), address())],
+    );
+  }
+
+  #[test]
+  fn parse_csv_rejects_malformed_line() {
+    assert_eq!(
+      SendInscriptions::parse_csv("foo").unwrap_err().to_string(),
+      "line 1: expected `INSCRIPTION_ID,ADDRESS`, found `foo`",
+    );
+  }
+
+  #[test]
+  fn parse_csv_rejects_duplicate_inscription() {
+    let line = format!(
+      "{},bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+      inscription_id(1)
+    );
+
+    assert_eq!(
+      SendInscriptions::parse_csv(&format!("{line}\n{line}"))
+        .unwrap_err()
+        .to_string(),
+      format!(
+        "line 2: inscription {} is listed more than once",
+        inscription_id(1)
+      ),
+    );
+  }
+}
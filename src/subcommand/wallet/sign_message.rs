@@ -0,0 +1,79 @@
+use {super::*, crate::wallet::Wallet};
+
+#[derive(Debug, Parser)]
+#[command(group(
+  ArgGroup::new("signer")
+    .required(true)
+    .args(&["address", "inscription"]),
+))]
+pub(crate) struct SignMessage {
+  #[arg(long, help = "Sign with the key of <ADDRESS>.")]
+  address: Option<Address>,
+  #[arg(long, help = "Sign with the key of the address holding <INSCRIPTION>.")]
+  inscription: Option<InscriptionId>,
+  #[arg(help = "Sign <MESSAGE>.")]
+  message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub address: Address,
+  pub message: String,
+  pub signature: String,
+}
+
+impl SignMessage {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
+
+    let address = match (self.address, self.inscription) {
+      (Some(address), None) => address,
+      (None, Some(inscription)) => {
+        let index = Index::open(&options)?;
+        index.update()?;
+
+        let satpoint = index
+          .get_inscription_satpoint_by_id(inscription)?
+          .ok_or_else(|| anyhow!("inscription {inscription} not found"))?;
+
+        ensure!(
+          index
+            .get_unspent_outputs(Wallet::load(&options)?)?
+            .contains_key(&satpoint.outpoint),
+          "inscription {inscription} is not in wallet"
+        );
+
+        let output = index
+          .get_transaction(satpoint.outpoint.txid)?
+          .and_then(|transaction| {
+            transaction
+              .output
+              .into_iter()
+              .nth(satpoint.outpoint.vout.try_into().unwrap())
+          })
+          .ok_or_else(|| anyhow!("output {} not found", satpoint.outpoint))?;
+
+        Address::from_script(&output.script_pubkey, options.chain().network()).map_err(|_| {
+          anyhow!(
+            "inscription {inscription} is held by output {} which has no address",
+            satpoint.outpoint
+          )
+        })?
+      }
+      _ => unreachable!(),
+    };
+
+    let signature = client
+      .call::<String>(
+        "signmessage",
+        &[address.to_string().into(), self.message.clone().into()],
+      )
+      .with_context(|| format!("wallet could not sign message with {address}"))?;
+
+    Ok(Box::new(Output {
+      address,
+      message: self.message,
+      signature,
+    }))
+  }
+}
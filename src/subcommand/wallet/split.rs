@@ -0,0 +1,163 @@
+use {super::*, crate::wallet::Wallet, bitcoin::PackedLockTime};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Split {
+  #[arg(long, help = "Create <OUTPUTS> outputs.")]
+  outputs: usize,
+  #[arg(long, help = "Create outputs with value <VALUE>.")]
+  value: Amount,
+  #[arg(
+    long,
+    help = "Use fee rate of <FEE_RATE> sats/vB, or `sat/kB` and `CRAFT/kB` with units, or `auto[:<BLOCKS>]` for the node's estimate"
+  )]
+  fee_rate: FeeRateSource,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub transaction: Txid,
+  pub inputs: usize,
+  pub outputs: Vec<OutPoint>,
+  pub change: Option<u64>,
+  pub fee: u64,
+  pub fee_rate: f64,
+}
+
+impl Split {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    ensure!(
+      self.outputs > 0,
+      "number of outputs must be greater than zero"
+    );
+
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
+
+    let fee_rate = self.fee_rate.resolve(&client)?;
+
+    let unspent_outputs = CoinControl::default().spendable_outputs(
+      &Wallet::frozen_outputs(&options)?,
+      &index.get_unspent_outputs(Wallet::load(&options)?)?,
+      None,
+    )?;
+
+    let mut addresses = Vec::new();
+    for _ in 0..self.outputs {
+      addresses.push(get_change_address(&client)?);
+    }
+
+    let dust_value = options.chain().dust_value(&addresses[0].script_pubkey());
+
+    ensure!(
+      self.value >= dust_value,
+      "output value is below dust value: {} < {dust_value}",
+      self.value
+    );
+
+    let target = self
+      .value
+      .checked_mul(self.outputs.try_into()?)
+      .context("arithmetic overflow calculating value")?;
+
+    let mut cardinal = get_cardinal_outputs(&index, &unspent_outputs)?
+      .into_iter()
+      .collect::<Vec<(OutPoint, Amount)>>();
+
+    // Spend the largest UTXOs first, to keep the number of inputs down
+    cardinal.sort_by_key(|(_outpoint, amount)| cmp::Reverse(*amount));
+
+    let change_address = get_change_address(&client)?;
+
+    let mut inputs = Vec::new();
+    let mut total = Amount::ZERO;
+    let mut selected = None;
+
+    for (outpoint, amount) in cardinal {
+      inputs.push(outpoint);
+      total += amount;
+
+      let fee = fee_rate.fee(TransactionBuilder::estimate_vbytes_with(
+        inputs.len(),
+        addresses.clone(),
+      ));
+
+      if total < target + fee {
+        continue;
+      }
+
+      let mut with_change = addresses.clone();
+      with_change.push(change_address.clone());
+
+      let fee_with_change = fee_rate.fee(TransactionBuilder::estimate_vbytes_with(
+        inputs.len(),
+        with_change,
+      ));
+
+      let change = total
+        .checked_sub(target + fee_with_change)
+        .filter(|change| *change >= dust_value);
+
+      selected = Some(match change {
+        Some(change) => (fee_with_change, Some(change)),
+        None => (total - target, None),
+      });
+
+      break;
+    }
+
+    let (fee, change) = selected
+      .context("wallet does not contain enough cardinal UTXOs, please add additional funds")?;
+
+    let mut output = addresses
+      .iter()
+      .map(|address| TxOut {
+        script_pubkey: address.script_pubkey(),
+        value: self.value.to_sat(),
+      })
+      .collect::<Vec<TxOut>>();
+
+    if let Some(change) = change {
+      output.push(TxOut {
+        script_pubkey: change_address.script_pubkey(),
+        value: change.to_sat(),
+      });
+    }
+
+    let unsigned_transaction = Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: inputs
+        .iter()
+        .map(|previous_output| TxIn {
+          previous_output: *previous_output,
+          script_sig: Script::new(),
+          sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+          witness: Witness::new(),
+        })
+        .collect(),
+      output,
+    };
+
+    let signed_transaction = client
+      .sign_raw_transaction_with_wallet(&unsigned_transaction, None, None)?
+      .hex;
+
+    let txid = client.send_raw_transaction(&signed_transaction)?;
+
+    Ok(Box::new(Output {
+      transaction: txid,
+      inputs: inputs.len(),
+      outputs: (0..self.outputs)
+        .map(|vout| OutPoint {
+          txid,
+          vout: vout.try_into().unwrap(),
+        })
+        .collect(),
+      change: change.map(|change| change.to_sat()),
+      fee: fee.to_sat(),
+      fee_rate: fee_rate.n(),
+    }))
+  }
+}
@@ -16,16 +16,32 @@
 //! recipient's address.
 //!
 //! `TransactionBuilder::build_transaction_with_postage` ensures that the
-//! outgoing value is at most 20,000 sats, reducing it to 10,000 sats if coin
-//! selection requires adding excess value.
+//! outgoing value is at most twice the requested postage, reducing it to the
+//! postage if coin selection requires adding excess value. Default postage and
+//! dust limits are chain policy, see `Chain::default_postage` and
+//! `Chain::dust_value`.
 //!
 //! `TransactionBuilder::build_transaction_with_value` ensures that the
 //! outgoing value is exactly the requested amount,
 //!
+//! `batch::BatchTransactionBuilder` builds a single transaction sending many
+//! inscriptions, laying out the inputs and outputs for each of them with a
+//! `TransactionBuilder` sending it alone.
+//!
 //! Internally, `TransactionBuilder` calls multiple methods that implement
 //! transformations responsible for individual concerns, such as ensuring that
 //! the transaction fee is paid, and that outgoing outputs aren't too large.
 //!
+//! Cunes carried by the outgoing UTXO are never sent to the recipient. Since
+//! unallocated cunes are assigned to the first output of a transaction
+//! without a cunestone, an alignment output paying to a change address is
+//! always created in that case, padded with a cardinal input if necessary.
+//!
+//! Inputs given with `TransactionBuilder::with_required_inputs` are always
+//! spent, after the outgoing input and any alignment padding. Their value is
+//! added to the recipient output, and stripped to change if excessive. Since
+//! they are spent regardless of their position, they must be cardinal.
+//!
 //! This module is heavily tested. For all features of transaction
 //! construction, there should be a positive test that checks that the feature
 //! is implemented correctly, an assertion in the final `Transaction::build`
@@ -36,15 +52,18 @@ use crate::sat_point::SatPoint;
 use {
   super::*,
   bitcoin::{
-    blockdata::{locktime::PackedLockTime, witness::Witness},
+    blockdata::{locktime::PackedLockTime, script, witness::Witness},
     Amount,
   },
   std::collections::{BTreeMap, BTreeSet},
 };
 
+pub(crate) mod batch;
+
 #[derive(Debug, PartialEq)]
 pub enum Error {
   DuplicateAddress(Address),
+  DuplicateOutgoing(SatPoint),
   Dust {
     output_value: Amount,
     dust_value: Amount,
@@ -52,6 +71,8 @@ pub enum Error {
   NotEnoughCardinalUtxos,
   NotInWallet(SatPoint),
   OutOfRange(SatPoint, u64),
+  RequiredInputNotCardinal(OutPoint),
+  RequiredInputNotInWallet(OutPoint),
   UtxoContainsAdditionalInscription {
     outgoing_satpoint: SatPoint,
     inscribed_satpoint: SatPoint,
@@ -63,7 +84,7 @@ pub enum Error {
 #[derive(Debug, PartialEq)]
 pub enum Target {
   Value(Amount),
-  Postage,
+  Postage(Amount),
 }
 
 impl fmt::Display for Error {
@@ -75,6 +96,8 @@ impl fmt::Display for Error {
       } => write!(f, "output value is below dust value: {output_value} < {dust_value}"),
       Error::NotInWallet(outgoing_satpoint) => write!(f, "outgoing satpoint {outgoing_satpoint} not in wallet"),
       Error::OutOfRange(outgoing_satpoint, maximum) => write!(f, "outgoing satpoint {outgoing_satpoint} offset higher than maximum {maximum}"),
+      Error::RequiredInputNotCardinal(outpoint) => write!(f, "required input {outpoint} carries inscriptions or cunes"),
+      Error::RequiredInputNotInWallet(outpoint) => write!(f, "required input {outpoint} not in wallet"),
       Error::NotEnoughCardinalUtxos => write!(
         f,
         "wallet does not contain enough cardinal UTXOs, please add additional funds to wallet."
@@ -89,6 +112,9 @@ impl fmt::Display for Error {
       ),
       Error::ValueOverflow => write!(f, "arithmetic overflow calculating value"),
       Error::DuplicateAddress(address) => write!(f, "duplicate input address: {address}"),
+      Error::DuplicateOutgoing(outgoing_satpoint) => {
+        write!(f, "utxo of outgoing satpoint {outgoing_satpoint} is sent more than once")
+      }
     }
   }
 }
@@ -98,6 +124,7 @@ impl std::error::Error for Error {}
 #[derive(Debug)]
 pub struct TransactionBuilder {
   amounts: BTreeMap<OutPoint, Amount>,
+  chain: Chain,
   change_addresses: BTreeSet<Address>,
   fee_rate: FeeRate,
   inputs: Vec<OutPoint>,
@@ -105,7 +132,9 @@ pub struct TransactionBuilder {
   outgoing: SatPoint,
   outputs: Vec<(Address, Amount)>,
   recipient: Address,
-  dunic_utxos: BTreeSet<OutPoint>,
+  cune_balances: BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
+  required_inputs: Vec<OutPoint>,
+  script_sig: Script,
   unused_change_addresses: Vec<Address>,
   utxos: BTreeSet<OutPoint>,
   target: Target,
@@ -114,45 +143,47 @@ pub struct TransactionBuilder {
 type Result<T> = std::result::Result<T, Error>;
 
 impl TransactionBuilder {
-  const ADDITIONAL_INPUT_VBYTES: usize = 58;
   const ADDITIONAL_OUTPUT_VBYTES: usize = 43;
-  const MAX_POSTAGE: Amount = Amount::from_sat(2 * 10_000);
-  const SCHNORR_SIGNATURE_SIZE: usize = 64;
-  pub(crate) const TARGET_POSTAGE: Amount = Amount::from_sat(10_000);
+  const P2PKH_PUBLIC_KEY_SIZE: usize = 33;
+  const P2PKH_SIGNATURE_SIZE: usize = 72;
 
-  pub fn build_transaction_with_postage(
+  pub(crate) fn build_transaction_with_postage(
     outgoing: SatPoint,
     inscriptions: BTreeMap<SatPoint, InscriptionId>,
     amounts: BTreeMap<OutPoint, Amount>,
-    dunic_utxos: BTreeSet<OutPoint>,
+    cune_balances: BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
     recipient: Address,
     change: [Address; 2],
     fee_rate: FeeRate,
+    chain: Chain,
+    postage: Amount,
   ) -> Result<Transaction> {
     Self::new(
       outgoing,
       inscriptions,
       amounts,
-      dunic_utxos,
+      cune_balances,
       recipient,
       change,
       fee_rate,
-      Target::Postage,
+      chain,
+      Target::Postage(postage),
     )?
     .build_transaction()
   }
 
-  pub fn build_transaction_with_value(
+  pub(crate) fn build_transaction_with_value(
     outgoing: SatPoint,
     inscriptions: BTreeMap<SatPoint, InscriptionId>,
     amounts: BTreeMap<OutPoint, Amount>,
+    cune_balances: BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
     recipient: Address,
-    dunic_utxos: BTreeSet<OutPoint>,
     change: [Address; 2],
     fee_rate: FeeRate,
+    chain: Chain,
     output_value: Amount,
   ) -> Result<Transaction> {
-    let dust_value = recipient.script_pubkey().dust_value();
+    let dust_value = chain.dust_value(&recipient.script_pubkey());
 
     if output_value < dust_value {
       return Err(Error::Dust {
@@ -165,10 +196,11 @@ impl TransactionBuilder {
       outgoing,
       inscriptions,
       amounts,
-      dunic_utxos,
+      cune_balances,
       recipient,
       change,
       fee_rate,
+      chain,
       Target::Value(output_value),
     )?
     .build_transaction()
@@ -179,20 +211,22 @@ impl TransactionBuilder {
       .select_outgoing()?
       .align_outgoing()
       .pad_alignment_output()?
+      .add_required_inputs()?
       .add_value()?
       .strip_value()
       .deduct_fee()
       .build()
   }
 
-  pub fn new(
+  pub(crate) fn new(
     outgoing: SatPoint,
     inscriptions: BTreeMap<SatPoint, InscriptionId>,
     amounts: BTreeMap<OutPoint, Amount>,
-    dunic_utxos: BTreeSet<OutPoint>,
+    cune_balances: BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
     recipient: Address,
     change: [Address; 2],
     fee_rate: FeeRate,
+    chain: Chain,
     target: Target,
   ) -> Result<Self> {
     if change.contains(&recipient) {
@@ -206,19 +240,73 @@ impl TransactionBuilder {
     Ok(Self {
       utxos: amounts.keys().cloned().collect(),
       amounts,
+      chain,
       change_addresses: change.iter().cloned().collect(),
       fee_rate,
       inputs: Vec::new(),
       inscriptions,
       outgoing,
       outputs: Vec::new(),
-      dunic_utxos,
+      cune_balances,
       recipient,
+      required_inputs: Vec::new(),
+      script_sig: Self::dummy_script_sig(),
       unused_change_addresses: change.to_vec(),
       target,
     })
   }
 
+  /// Like `new`, but for wallets with a single address, like multisigs, which
+  /// receive both the alignment output and excess value at `change`.
+  pub(crate) fn new_with_change_address(
+    outgoing: SatPoint,
+    inscriptions: BTreeMap<SatPoint, InscriptionId>,
+    amounts: BTreeMap<OutPoint, Amount>,
+    cune_balances: BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
+    recipient: Address,
+    change: Address,
+    fee_rate: FeeRate,
+    chain: Chain,
+    target: Target,
+  ) -> Result<Self> {
+    if change == recipient {
+      return Err(Error::DuplicateAddress(recipient));
+    }
+
+    Ok(Self {
+      utxos: amounts.keys().cloned().collect(),
+      amounts,
+      chain,
+      change_addresses: [change.clone()].into_iter().collect(),
+      fee_rate,
+      inputs: Vec::new(),
+      inscriptions,
+      outgoing,
+      outputs: Vec::new(),
+      cune_balances,
+      recipient,
+      required_inputs: Vec::new(),
+      script_sig: Self::dummy_script_sig(),
+      unused_change_addresses: vec![change.clone(), change],
+      target,
+    })
+  }
+
+  /// Estimate fees for inputs signed with script sigs of the same size as
+  /// `script_sig`, instead of P2PKH script sigs.
+  pub(crate) fn with_script_sig(self, script_sig: Script) -> Self {
+    Self { script_sig, ..self }
+  }
+
+  /// Always spend `required_inputs`, even if the transaction could be funded
+  /// without them.
+  pub(crate) fn with_required_inputs(self, required_inputs: Vec<OutPoint>) -> Self {
+    Self {
+      required_inputs,
+      ..self
+    }
+  }
+
   fn select_outgoing(mut self) -> Result<Self> {
     for (inscribed_satpoint, inscription_id) in &self.inscriptions {
       if self.outgoing.outpoint == inscribed_satpoint.outpoint
@@ -263,9 +351,12 @@ impl TransactionBuilder {
     );
 
     let sat_offset = self.calculate_sat_offset();
-    if sat_offset == 0 {
+    if sat_offset == 0 && !self.cune_balances.contains_key(&self.outgoing.outpoint) {
       tprintln!("outgoing is aligned");
     } else {
+      // Cunes without a cunestone are assigned to the first output, so if the
+      // outgoing UTXO carries cunes we always create an alignment output to
+      // act as cune change, padding it with a cardinal input if necessary
       tprintln!("aligned outgoing with {sat_offset} sat padding output");
       self.outputs.insert(
         0,
@@ -287,7 +378,7 @@ impl TransactionBuilder {
     if self.outputs[0].0 == self.recipient {
       tprintln!("no alignment output");
     } else {
-      let dust_limit = self.recipient.script_pubkey().dust_value();
+      let dust_limit = self.chain.dust_value(&self.recipient.script_pubkey());
       if self.outputs[0].1 >= dust_limit {
         tprintln!("no padding needed");
       } else {
@@ -304,11 +395,43 @@ impl TransactionBuilder {
     Ok(self)
   }
 
+  fn add_required_inputs(mut self) -> Result<Self> {
+    let inscribed_utxos = self
+      .inscriptions
+      .keys()
+      .map(|satpoint| satpoint.outpoint)
+      .collect::<BTreeSet<OutPoint>>();
+
+    for outpoint in self.required_inputs.clone() {
+      if self.inputs.contains(&outpoint) {
+        continue;
+      }
+
+      let value = *self
+        .amounts
+        .get(&outpoint)
+        .ok_or(Error::RequiredInputNotInWallet(outpoint))?;
+
+      if inscribed_utxos.contains(&outpoint) || self.cune_balances.contains_key(&outpoint) {
+        return Err(Error::RequiredInputNotCardinal(outpoint));
+      }
+
+      self.utxos.remove(&outpoint);
+      self.inputs.push(outpoint);
+      self.outputs.last_mut().unwrap().1 += value;
+      tprintln!("added required {value} sat input {outpoint}");
+    }
+
+    Ok(self)
+  }
+
   fn add_value(mut self) -> Result<Self> {
     let estimated_fee = self.estimate_fee();
 
     let min_value = match self.target {
-      Target::Postage => self.outputs.last().unwrap().0.script_pubkey().dust_value(),
+      Target::Postage(_) => self
+        .chain
+        .dust_value(&self.outputs.last().unwrap().0.script_pubkey()),
       Target::Value(value) => value,
     };
 
@@ -319,7 +442,11 @@ impl TransactionBuilder {
     if let Some(deficit) = total.checked_sub(self.outputs.last().unwrap().1) {
       if deficit > Amount::ZERO {
         let needed = deficit
-          .checked_add(self.fee_rate.fee(Self::ADDITIONAL_INPUT_VBYTES))
+          .checked_add(
+            self
+              .fee_rate
+              .fee(Self::additional_input_vbytes(&self.script_sig)),
+          )
           .ok_or(Error::ValueOverflow)?;
         let (utxo, value) = self.select_cardinal_utxo(needed)?;
         self.inputs.push(utxo);
@@ -350,18 +477,15 @@ impl TransactionBuilder {
 
     if let Some(excess) = value.checked_sub(self.fee_rate.fee(self.estimate_vbytes())) {
       let (max, target) = match self.target {
-        Target::Postage => (Self::MAX_POSTAGE, Self::TARGET_POSTAGE),
+        Target::Postage(postage) => (postage * 2, postage),
         Target::Value(value) => (value, value),
       };
 
       if excess > max
         && value.checked_sub(target).unwrap()
           > self
-            .unused_change_addresses
-            .last()
-            .unwrap()
-            .script_pubkey()
-            .dust_value()
+            .chain
+            .dust_value(&self.unused_change_addresses.last().unwrap().script_pubkey())
             + self
               .fee_rate
               .fee(self.estimate_vbytes() + Self::ADDITIONAL_OUTPUT_VBYTES)
@@ -415,11 +539,12 @@ impl TransactionBuilder {
   }
 
   /// Estimate the size in virtual bytes of the transaction under construction.
-  /// We initialize wallets with taproot descriptors only, so we know that all
-  /// inputs are taproot key path spends, which allows us to know that witnesses
-  /// will all consist of single Schnorr signatures.
+  /// Craftcoin wallets hold legacy P2PKH outputs, so unless another script sig
+  /// is given with `with_script_sig`, we assume that every input will be
+  /// signed with a script sig containing a DER signature and a compressed
+  /// public key, and that transactions carry no witness data.
   fn estimate_vbytes(&self) -> usize {
-    Self::estimate_vbytes_with(
+    Self::estimate_vbytes_with_script_sig(
       self.inputs.len(),
       self
         .outputs
@@ -427,10 +552,24 @@ impl TransactionBuilder {
         .map(|(address, _amount)| address)
         .cloned()
         .collect(),
+      &self.script_sig,
     )
   }
 
-  fn estimate_vbytes_with(inputs: usize, outputs: Vec<Address>) -> usize {
+  pub(crate) fn estimate_vbytes_with(inputs: usize, outputs: Vec<Address>) -> usize {
+    Self::estimate_vbytes_with_script_sig(inputs, outputs, &Self::dummy_script_sig())
+  }
+
+  fn additional_input_vbytes(script_sig: &Script) -> usize {
+    Self::estimate_vbytes_with_script_sig(1, Vec::new(), script_sig)
+      - Self::estimate_vbytes_with_script_sig(0, Vec::new(), script_sig)
+  }
+
+  fn estimate_vbytes_with_script_sig(
+    inputs: usize,
+    outputs: Vec<Address>,
+    script_sig: &Script,
+  ) -> usize {
     Transaction {
       version: 1,
       lock_time: PackedLockTime::ZERO,
@@ -438,9 +577,9 @@ impl TransactionBuilder {
         .into_iter()
         .map(|_| TxIn {
           previous_output: OutPoint::null(),
-          script_sig: Script::new(),
+          script_sig: script_sig.clone(),
           sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
-          witness: Witness::from_vec([[0; Self::SCHNORR_SIGNATURE_SIZE].to_vec()].to_vec()),
+          witness: Witness::new(),
         })
         .collect(),
       output: outputs
@@ -454,6 +593,15 @@ impl TransactionBuilder {
     .vsize()
   }
 
+  /// A placeholder script sig of maximum size for a P2PKH spend, used for
+  /// fee estimation.
+  pub(crate) fn dummy_script_sig() -> Script {
+    script::Builder::new()
+      .push_slice(&[0; Self::P2PKH_SIGNATURE_SIZE])
+      .push_slice(&[0; Self::P2PKH_PUBLIC_KEY_SIZE])
+      .into_script()
+  }
+
   fn estimate_fee(&self) -> Amount {
     self.fee_rate.fee(self.estimate_vbytes())
   }
@@ -504,6 +652,56 @@ impl TransactionBuilder {
       "invariant: inputs spend outgoing sat"
     );
 
+    assert!(
+      self.required_inputs.iter().all(|outpoint| transaction
+        .input
+        .iter()
+        .any(|tx_in| tx_in.previous_output == *outpoint)),
+      "invariant: required inputs are spent",
+    );
+
+    let mut input_cunes: BTreeMap<CuneId, u128> = BTreeMap::new();
+    for tx_in in &transaction.input {
+      for (id, balance) in self
+        .cune_balances
+        .get(&tx_in.previous_output)
+        .into_iter()
+        .flatten()
+      {
+        *input_cunes.entry(*id).or_default() += balance;
+      }
+    }
+
+    assert_eq!(
+      transaction
+        .input
+        .iter()
+        .filter(
+          |tx_in| self.cune_balances.contains_key(&tx_in.previous_output)
+            && tx_in.previous_output != self.outgoing.outpoint
+        )
+        .count(),
+      0,
+      "invariant: only the outgoing input may carry cunes",
+    );
+
+    if !input_cunes.is_empty() {
+      assert!(
+        transaction
+          .output
+          .iter()
+          .all(|tx_out| !tx_out.script_pubkey.is_op_return()),
+        "invariant: cunes are not burned",
+      );
+
+      assert!(
+        self.change_addresses.iter().any(
+          |change_address| change_address.script_pubkey() == transaction.output[0].script_pubkey
+        ),
+        "invariant: cunes are sent to change output",
+      );
+    }
+
     let mut sat_offset = 0;
     let mut found = false;
     for tx_in in &transaction.input {
@@ -542,6 +740,13 @@ impl TransactionBuilder {
       "invariant: recipient address appears exactly once in outputs",
     );
 
+    // A single change address receives both alignment and excess change
+    let max_change_outputs = if self.change_addresses.len() == 1 {
+      2
+    } else {
+      1
+    };
+
     assert!(
       self
         .change_addresses
@@ -551,7 +756,7 @@ impl TransactionBuilder {
           .iter()
           .filter(|tx_out| tx_out.script_pubkey == change_address.script_pubkey())
           .count())
-        .all(|count| count <= 1),
+        .all(|count| count <= max_change_outputs),
       "invariant: change addresses appear at most once in outputs",
     );
 
@@ -561,9 +766,9 @@ impl TransactionBuilder {
         let slop = self.fee_rate.fee(Self::ADDITIONAL_OUTPUT_VBYTES);
 
         match self.target {
-          Target::Postage => {
+          Target::Postage(postage) => {
             assert!(
-              Amount::from_sat(output.value) <= Self::MAX_POSTAGE + slop,
+              Amount::from_sat(output.value) <= postage * 2 + slop,
               "invariant: excess postage is stripped"
             );
           }
@@ -573,7 +778,7 @@ impl TransactionBuilder {
                 <= self
                   .change_addresses
                   .iter()
-                  .map(|address| address.script_pubkey().dust_value())
+                  .map(|address| self.chain.dust_value(&address.script_pubkey()))
                   .max()
                   .unwrap_or_default()
                   + slop,
@@ -608,7 +813,7 @@ impl TransactionBuilder {
 
     let mut modified_tx = transaction.clone();
     for input in &mut modified_tx.input {
-      input.witness = Witness::from_vec(vec![vec![0; 64]]);
+      input.script_sig = self.script_sig.clone();
     }
     let expected_fee = self.fee_rate.fee(modified_tx.vsize());
 
@@ -619,7 +824,7 @@ impl TransactionBuilder {
 
     for tx_out in &transaction.output {
       assert!(
-        Amount::from_sat(tx_out.value) >= tx_out.script_pubkey.dust_value(),
+        Amount::from_sat(tx_out.value) >= self.chain.dust_value(&tx_out.script_pubkey),
         "invariant: all outputs are above dust limit",
       );
     }
@@ -641,22 +846,37 @@ impl TransactionBuilder {
   }
 
   fn select_cardinal_utxo(&mut self, minimum_value: Amount) -> Result<(OutPoint, Amount)> {
+    Self::select_cardinal_utxo_from(
+      &mut self.utxos,
+      &self.amounts,
+      &self.inscriptions,
+      &self.cune_balances,
+      minimum_value,
+    )
+  }
+
+  /// Remove and return the first UTXO in `utxos` worth at least
+  /// `minimum_value` which carries neither inscriptions nor cunes.
+  fn select_cardinal_utxo_from(
+    utxos: &mut BTreeSet<OutPoint>,
+    amounts: &BTreeMap<OutPoint, Amount>,
+    inscriptions: &BTreeMap<SatPoint, InscriptionId>,
+    cune_balances: &BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
+    minimum_value: Amount,
+  ) -> Result<(OutPoint, Amount)> {
     let mut found = None;
 
-    let inscribed_utxos = self
-      .inscriptions
+    let inscribed_utxos = inscriptions
       .keys()
       .map(|satpoint| satpoint.outpoint)
       .collect::<BTreeSet<OutPoint>>();
 
-    let dunic_utxos = self.dunic_utxos.clone();
-
-    for utxo in &self.utxos {
-      if inscribed_utxos.contains(utxo) || dunic_utxos.contains(utxo) {
+    for utxo in utxos.iter() {
+      if inscribed_utxos.contains(utxo) || cune_balances.contains_key(utxo) {
         continue;
       }
 
-      let value = self.amounts[utxo];
+      let value = amounts[utxo];
 
       if value >= minimum_value {
         found = Some((*utxo, value));
@@ -666,7 +886,7 @@ impl TransactionBuilder {
 
     let (utxo, value) = found.ok_or(Error::NotEnoughCardinalUtxos)?;
 
-    self.utxos.remove(&utxo);
+    utxos.remove(&utxo);
 
     Ok((utxo, value))
   }
@@ -689,10 +909,12 @@ mod tests {
       satpoint(2, 0),
       BTreeMap::new(),
       utxos.clone().into_iter().collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .select_outgoing()
@@ -722,6 +944,7 @@ mod tests {
 
     let tx_builder = TransactionBuilder {
       amounts,
+      chain: Chain::Regtest,
       fee_rate: FeeRate::try_from(1.0).unwrap(),
       utxos: BTreeSet::new(),
       outgoing: satpoint(1, 0),
@@ -729,13 +952,16 @@ mod tests {
       recipient: recipient(),
       unused_change_addresses: vec![change(0), change(1)],
       change_addresses: vec![change(0), change(1)].into_iter().collect(),
+      cune_balances: BTreeMap::new(),
+      required_inputs: Vec::new(),
+      script_sig: TransactionBuilder::dummy_script_sig(),
       inputs: vec![outpoint(1), outpoint(2), outpoint(3)],
       outputs: vec![
         (recipient(), Amount::from_sat(5_000)),
         (change(0), Amount::from_sat(5_000)),
-        (change(1), Amount::from_sat(1_724)),
+        (change(1), Amount::from_sat(1_453)),
       ],
-      target: Target::Postage,
+      target: Target::Postage(Chain::Regtest.default_postage()),
     };
 
     pretty_assert_eq!(
@@ -747,7 +973,7 @@ mod tests {
         output: vec![
           tx_out(5_000, recipient()),
           tx_out(5_000, change(0)),
-          tx_out(1_724, change(1))
+          tx_out(1_453, change(1))
         ],
       })
     )
@@ -761,9 +987,12 @@ mod tests {
       satpoint(1, 0),
       BTreeMap::new(),
       utxos.into_iter().collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest,
+      Chain::Regtest.default_postage(),
     )
     .unwrap()
     .is_explicitly_rbf())
@@ -778,15 +1007,18 @@ mod tests {
         satpoint(1, 0),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Ok(Transaction {
         version: 1,
         lock_time: PackedLockTime::ZERO,
         input: vec![tx_in(outpoint(1))],
-        output: vec![tx_out(4901, recipient())],
+        output: vec![tx_out(4811, recipient())],
       })
     )
   }
@@ -800,10 +1032,12 @@ mod tests {
       satpoint(1, 4_950),
       BTreeMap::new(),
       utxos.into_iter().collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .select_outgoing()
@@ -825,15 +1059,18 @@ mod tests {
         satpoint(1, 4_950),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Ok(Transaction {
         version: 1,
         lock_time: PackedLockTime::ZERO,
         input: vec![tx_in(outpoint(1)), tx_in(outpoint(2))],
-        output: vec![tx_out(4_950, change(1)), tx_out(4_862, recipient())],
+        output: vec![tx_out(4_950, change(1)), tx_out(4_682, recipient())],
       })
     )
   }
@@ -847,9 +1084,12 @@ mod tests {
         satpoint(1, 4_950),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Err(Error::NotEnoughCardinalUtxos),
     )
@@ -867,9 +1107,12 @@ mod tests {
         satpoint(1, 4_950),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Err(Error::NotEnoughCardinalUtxos),
     )
@@ -887,9 +1130,12 @@ mod tests {
         satpoint(1, 4_950),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Ok(Transaction {
         version: 1,
@@ -897,8 +1143,8 @@ mod tests {
         input: vec![tx_in(outpoint(1)), tx_in(outpoint(2))],
         output: vec![
           tx_out(4_950, change(1)),
-          tx_out(TransactionBuilder::TARGET_POSTAGE.to_sat(), recipient()),
-          tx_out(14_831, change(0)),
+          tx_out(Chain::Regtest.default_postage().to_sat(), recipient()),
+          tx_out(14_651, change(0)),
         ],
       })
     )
@@ -913,10 +1159,12 @@ mod tests {
       vec![(outpoint(1), Amount::from_sat(4))]
         .into_iter()
         .collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .build()
@@ -932,10 +1180,12 @@ mod tests {
       vec![(outpoint(1), Amount::from_sat(4))]
         .into_iter()
         .collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .build()
@@ -951,10 +1201,12 @@ mod tests {
       vec![(outpoint(1), Amount::from_sat(5))]
         .into_iter()
         .collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .build()
@@ -970,10 +1222,12 @@ mod tests {
       vec![(outpoint(1), Amount::from_sat(5))]
         .into_iter()
         .collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .select_outgoing()
@@ -995,10 +1249,12 @@ mod tests {
       vec![(outpoint(1), Amount::from_sat(5))]
         .into_iter()
         .collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .select_outgoing()
@@ -1018,17 +1274,20 @@ mod tests {
         satpoint(1, 0),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Ok(Transaction {
         version: 1,
         lock_time: PackedLockTime::ZERO,
         input: vec![tx_in(outpoint(1))],
         output: vec![
-          tx_out(TransactionBuilder::TARGET_POSTAGE.to_sat(), recipient()),
-          tx_out(989_870, change(1))
+          tx_out(Chain::Regtest.default_postage().to_sat(), recipient()),
+          tx_out(989_780, change(1))
         ],
       })
     )
@@ -1043,10 +1302,12 @@ mod tests {
       satpoint(1, 0),
       BTreeMap::new(),
       utxos.into_iter().collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .select_outgoing()
@@ -1064,15 +1325,18 @@ mod tests {
         satpoint(1, 3_333),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Ok(Transaction {
         version: 1,
         lock_time: PackedLockTime::ZERO,
         input: vec![tx_in(outpoint(1))],
-        output: vec![tx_out(3_333, change(1)), tx_out(6_537, recipient())],
+        output: vec![tx_out(3_333, change(1)), tx_out(6_447, recipient())],
       })
     )
   }
@@ -1089,15 +1353,18 @@ mod tests {
         satpoint(1, 1),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Ok(Transaction {
         version: 1,
         lock_time: PackedLockTime::ZERO,
         input: vec![tx_in(outpoint(2)), tx_in(outpoint(1))],
-        output: vec![tx_out(10_001, change(1)), tx_out(9_811, recipient())],
+        output: vec![tx_out(10_001, change(1)), tx_out(9_631, recipient())],
       })
     )
   }
@@ -1111,10 +1378,12 @@ mod tests {
       satpoint(1, 3_333),
       BTreeMap::new(),
       utxos.into_iter().collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .select_outgoing()
@@ -1139,10 +1408,12 @@ mod tests {
       satpoint(1, 1),
       BTreeMap::new(),
       utxos.into_iter().collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .select_outgoing()
@@ -1165,10 +1436,12 @@ mod tests {
       satpoint(1, 3_333),
       BTreeMap::new(),
       utxos.into_iter().collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .select_outgoing()
@@ -1188,10 +1461,12 @@ mod tests {
       satpoint(1, 0),
       BTreeMap::new(),
       utxos.into_iter().collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       FeeRate::try_from(1.0).unwrap(),
-      Target::Postage,
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
     )
     .unwrap()
     .select_outgoing()
@@ -1211,6 +1486,7 @@ mod tests {
 
     TransactionBuilder {
       amounts,
+      chain: Chain::Regtest,
       fee_rate: FeeRate::try_from(1.0).unwrap(),
       utxos: BTreeSet::new(),
       outgoing: satpoint(1, 0),
@@ -1218,13 +1494,16 @@ mod tests {
       recipient: recipient(),
       unused_change_addresses: vec![change(0), change(1)],
       change_addresses: vec![change(0), change(1)].into_iter().collect(),
+      cune_balances: BTreeMap::new(),
+      required_inputs: Vec::new(),
+      script_sig: TransactionBuilder::dummy_script_sig(),
       inputs: vec![outpoint(1), outpoint(2), outpoint(3)],
       outputs: vec![
         (recipient(), Amount::from_sat(5_000)),
         (recipient(), Amount::from_sat(5_000)),
         (change(1), Amount::from_sat(1_774)),
       ],
-      target: Target::Postage,
+      target: Target::Postage(Chain::Regtest.default_postage()),
     }
     .build()
     .unwrap();
@@ -1240,6 +1519,7 @@ mod tests {
 
     TransactionBuilder {
       amounts,
+      chain: Chain::Regtest,
       fee_rate: FeeRate::try_from(1.0).unwrap(),
       utxos: BTreeSet::new(),
       outgoing: satpoint(1, 0),
@@ -1247,13 +1527,16 @@ mod tests {
       recipient: recipient(),
       unused_change_addresses: vec![change(0), change(1)],
       change_addresses: vec![change(0), change(1)].into_iter().collect(),
+      cune_balances: BTreeMap::new(),
+      required_inputs: Vec::new(),
+      script_sig: TransactionBuilder::dummy_script_sig(),
       inputs: vec![outpoint(1), outpoint(2), outpoint(3)],
       outputs: vec![
         (recipient(), Amount::from_sat(5_000)),
         (change(0), Amount::from_sat(5_000)),
         (change(0), Amount::from_sat(1_774)),
       ],
-      target: Target::Postage,
+      target: Target::Postage(Chain::Regtest.default_postage()),
     }
     .build()
     .unwrap();
@@ -1271,9 +1554,12 @@ mod tests {
         satpoint(1, 0),
         BTreeMap::from([(satpoint(2, 10 * COIN_VALUE), inscription_id(1))]),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Err(Error::NotEnoughCardinalUtxos)
     )
@@ -1288,9 +1574,12 @@ mod tests {
         satpoint(1, 0),
         BTreeMap::from([(satpoint(1, 500), inscription_id(1))]),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Err(Error::UtxoContainsAdditionalInscription {
         outgoing_satpoint: satpoint(1, 0),
@@ -1310,14 +1599,16 @@ mod tests {
       satpoint(1, 0),
       BTreeMap::from([(satpoint(1, 0), inscription_id(1))]),
       utxos.into_iter().collect(),
+      BTreeMap::new(),
       recipient(),
       [change(0), change(1)],
       fee_rate,
+      Chain::Regtest,
+      Chain::Regtest.default_postage(),
     )
     .unwrap();
 
-    let fee =
-      fee_rate.fee(transaction.vsize() + TransactionBuilder::SCHNORR_SIGNATURE_SIZE / 4 + 1);
+    let fee = fee_rate.fee(transaction.vsize() + TransactionBuilder::dummy_script_sig().len());
 
     pretty_assert_eq!(
       transaction,
@@ -1339,16 +1630,18 @@ mod tests {
         satpoint(1, 0),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
         Amount::from_sat(1000)
       ),
       Ok(Transaction {
         version: 1,
         lock_time: PackedLockTime::ZERO,
         input: vec![tx_in(outpoint(1))],
-        output: vec![tx_out(1000, recipient()), tx_out(3870, change(1))],
+        output: vec![tx_out(1000, recipient()), tx_out(3780, change(1))],
       })
     )
   }
@@ -1357,7 +1650,7 @@ mod tests {
   fn exact_transaction_adds_output_to_cover_value() {
     let utxos = vec![
       (outpoint(1), Amount::from_sat(1_000)),
-      (outpoint(2), Amount::from_sat(1_000)),
+      (outpoint(2), Amount::from_sat(1_200)),
     ];
 
     pretty_assert_eq!(
@@ -1365,16 +1658,18 @@ mod tests {
         satpoint(1, 0),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
         Amount::from_sat(1500)
       ),
       Ok(Transaction {
         version: 1,
         lock_time: PackedLockTime::ZERO,
         input: vec![tx_in(outpoint(1)), tx_in(outpoint(2))],
-        output: vec![tx_out(1500, recipient()), tx_out(312, change(1))],
+        output: vec![tx_out(1500, recipient()), tx_out(332, change(1))],
       })
     )
   }
@@ -1388,9 +1683,11 @@ mod tests {
         satpoint(1, 0),
         BTreeMap::from([(satpoint(1, 500), inscription_id(1))]),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
         Amount::from_sat(1)
       ),
       Err(Error::Dust {
@@ -1412,9 +1709,11 @@ mod tests {
         satpoint(1, 0),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
         Amount::from_sat(1000)
       ),
       Err(Error::NotEnoughCardinalUtxos),
@@ -1433,9 +1732,11 @@ mod tests {
         satpoint(1, 0),
         BTreeMap::new(),
         utxos.into_iter().collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(4.0).unwrap(),
+        Chain::Regtest,
         Amount::from_sat(1000)
       ),
       Err(Error::NotEnoughCardinalUtxos),
@@ -1444,9 +1745,10 @@ mod tests {
 
   #[test]
   fn additional_input_size_is_correct() {
-    let before = TransactionBuilder::estimate_vbytes_with(0, Vec::new());
-    let after = TransactionBuilder::estimate_vbytes_with(1, Vec::new());
-    assert_eq!(after - before, TransactionBuilder::ADDITIONAL_INPUT_VBYTES);
+    assert_eq!(
+      TransactionBuilder::additional_input_vbytes(&TransactionBuilder::dummy_script_sig()),
+      148
+    );
   }
 
   #[test]
@@ -1472,16 +1774,18 @@ mod tests {
         vec![(outpoint(1), Amount::from_sat(1_000))]
           .into_iter()
           .collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
         Amount::from_sat(707)
       ),
       Ok(Transaction {
         version: 1,
         lock_time: PackedLockTime::ZERO,
         input: vec![tx_in(outpoint(1))],
-        output: vec![tx_out(901, recipient())],
+        output: vec![tx_out(811, recipient())],
       }),
     );
   }
@@ -1492,12 +1796,15 @@ mod tests {
       TransactionBuilder::build_transaction_with_postage(
         satpoint(1, 0),
         BTreeMap::new(),
-        vec![(outpoint(1), Amount::from_sat(20_099))]
+        vec![(outpoint(1), Amount::from_sat(20_189))]
           .into_iter()
           .collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Ok(Transaction {
         version: 1,
@@ -1514,12 +1821,14 @@ mod tests {
       TransactionBuilder::build_transaction_with_value(
         satpoint(1, 0),
         BTreeMap::new(),
-        vec![(outpoint(1), Amount::from_sat(1_500))]
+        vec![(outpoint(1), Amount::from_sat(1_950))]
           .into_iter()
           .collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(5.0).unwrap(),
+        Chain::Regtest,
         Amount::from_sat(1000)
       ),
       Ok(Transaction {
@@ -1540,9 +1849,11 @@ mod tests {
         vec![(outpoint(1), Amount::from_sat(1_500))]
           .into_iter()
           .collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(6.0).unwrap(),
+        Chain::Regtest,
         Amount::from_sat(1000)
       ),
       Err(Error::NotEnoughCardinalUtxos)
@@ -1558,9 +1869,11 @@ mod tests {
         vec![(outpoint(1), Amount::from_sat(1000))]
           .into_iter()
           .collect(),
+        BTreeMap::new(),
         recipient(),
         [recipient(), change(1)],
         FeeRate::try_from(0.0).unwrap(),
+        Chain::Regtest,
         Amount::from_sat(1000)
       ),
       Err(Error::DuplicateAddress(recipient()))
@@ -1576,15 +1889,110 @@ mod tests {
         vec![(outpoint(1), Amount::from_sat(1000))]
           .into_iter()
           .collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(0)],
         FeeRate::try_from(0.0).unwrap(),
+        Chain::Regtest,
         Amount::from_sat(1000)
       ),
       Err(Error::DuplicateAddress(change(0)))
     );
   }
 
+  #[test]
+  fn single_change_address_receives_alignment_and_excess_value() {
+    let utxos = vec![
+      (outpoint(1), Amount::from_sat(5_000)),
+      (outpoint(2), Amount::from_sat(25_000)),
+    ];
+
+    pretty_assert_eq!(
+      TransactionBuilder::new_with_change_address(
+        satpoint(1, 4_950),
+        BTreeMap::new(),
+        utxos.into_iter().collect(),
+        BTreeMap::new(),
+        recipient(),
+        change(0),
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Target::Postage(Chain::Regtest.default_postage()),
+      )
+      .unwrap()
+      .build_transaction(),
+      Ok(Transaction {
+        version: 1,
+        lock_time: PackedLockTime::ZERO,
+        input: vec![tx_in(outpoint(1)), tx_in(outpoint(2))],
+        output: vec![
+          tx_out(4_950, change(0)),
+          tx_out(Chain::Regtest.default_postage().to_sat(), recipient()),
+          tx_out(14_651, change(0)),
+        ],
+      })
+    )
+  }
+
+  #[test]
+  fn single_change_address_must_not_be_recipient() {
+    pretty_assert_eq!(
+      TransactionBuilder::new_with_change_address(
+        satpoint(1, 0),
+        BTreeMap::new(),
+        vec![(outpoint(1), Amount::from_sat(1000))]
+          .into_iter()
+          .collect(),
+        BTreeMap::new(),
+        recipient(),
+        recipient(),
+        FeeRate::try_from(0.0).unwrap(),
+        Chain::Regtest,
+        Target::Value(Amount::from_sat(1000)),
+      )
+      .unwrap_err(),
+      Error::DuplicateAddress(recipient())
+    );
+  }
+
+  #[test]
+  fn fee_is_estimated_with_given_script_sig() {
+    let utxos = vec![(outpoint(1), Amount::from_sat(1_000_000))];
+
+    let script_sig = script::Builder::new().push_slice(&[0; 200]).into_script();
+
+    let additional_vbytes = script_sig.len() - TransactionBuilder::dummy_script_sig().len();
+
+    pretty_assert_eq!(
+      TransactionBuilder::new(
+        satpoint(1, 0),
+        BTreeMap::new(),
+        utxos.into_iter().collect(),
+        BTreeMap::new(),
+        recipient(),
+        [change(0), change(1)],
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Target::Postage(Chain::Regtest.default_postage()),
+      )
+      .unwrap()
+      .with_script_sig(script_sig)
+      .build_transaction(),
+      Ok(Transaction {
+        version: 1,
+        lock_time: PackedLockTime::ZERO,
+        input: vec![tx_in(outpoint(1))],
+        output: vec![
+          tx_out(Chain::Regtest.default_postage().to_sat(), recipient()),
+          tx_out(
+            989_780 - u64::try_from(additional_vbytes).unwrap(),
+            change(1)
+          )
+        ],
+      })
+    )
+  }
+
   #[test]
   fn output_over_value_because_fees_prevent_excess_value_stripping() {
     pretty_assert_eq!(
@@ -1594,16 +2002,18 @@ mod tests {
         vec![(outpoint(1), Amount::from_sat(2000))]
           .into_iter()
           .collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(2.0).unwrap(),
+        Chain::Regtest,
         Amount::from_sat(1500)
       ),
       Ok(Transaction {
         version: 1,
         lock_time: PackedLockTime::ZERO,
         input: vec![tx_in(outpoint(1))],
-        output: vec![tx_out(1802, recipient())],
+        output: vec![tx_out(1622, recipient())],
       }),
     );
   }
@@ -1614,12 +2024,15 @@ mod tests {
       TransactionBuilder::build_transaction_with_postage(
         satpoint(1, 0),
         BTreeMap::new(),
-        vec![(outpoint(1), Amount::from_sat(45000))]
+        vec![(outpoint(1), Amount::from_sat(67_500))]
           .into_iter()
           .collect(),
+        BTreeMap::new(),
         recipient(),
         [change(0), change(1)],
         FeeRate::try_from(250.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
       ),
       Ok(Transaction {
         version: 1,
@@ -1629,4 +2042,265 @@ mod tests {
       }),
     );
   }
+
+  fn cune_balances(outpoint: OutPoint) -> BTreeMap<OutPoint, BTreeMap<CuneId, u128>> {
+    BTreeMap::from([(
+      outpoint,
+      BTreeMap::from([(
+        CuneId {
+          height: 1,
+          index: 0,
+        },
+        1_000,
+      )]),
+    )])
+  }
+
+  #[test]
+  fn cunes_on_outgoing_utxo_are_sent_to_change_output() {
+    let utxos = vec![
+      (outpoint(1), Amount::from_sat(10_000)),
+      (outpoint(2), Amount::from_sat(10_000)),
+    ];
+
+    pretty_assert_eq!(
+      TransactionBuilder::build_transaction_with_postage(
+        satpoint(1, 0),
+        BTreeMap::new(),
+        utxos.into_iter().collect(),
+        cune_balances(outpoint(1)),
+        recipient(),
+        [change(0), change(1)],
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
+      ),
+      Ok(Transaction {
+        version: 1,
+        lock_time: PackedLockTime::ZERO,
+        input: vec![tx_in(outpoint(2)), tx_in(outpoint(1))],
+        output: vec![tx_out(10_000, change(1)), tx_out(9_632, recipient())],
+      })
+    )
+  }
+
+  #[test]
+  fn do_not_select_dunic_utxos_for_cardinal_utxos() {
+    let utxos = vec![
+      (outpoint(1), Amount::from_sat(100)),
+      (outpoint(2), Amount::from_sat(49 * COIN_VALUE)),
+    ];
+
+    pretty_assert_eq!(
+      TransactionBuilder::build_transaction_with_postage(
+        satpoint(1, 0),
+        BTreeMap::new(),
+        utxos.into_iter().collect(),
+        cune_balances(outpoint(2)),
+        recipient(),
+        [change(0), change(1)],
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
+      ),
+      Err(Error::NotEnoughCardinalUtxos)
+    )
+  }
+
+  #[test]
+  #[should_panic(expected = "invariant: cunes are sent to change output")]
+  fn invariant_cunes_are_sent_to_change_output() {
+    let utxos = vec![(outpoint(1), Amount::from_sat(10_000))];
+
+    TransactionBuilder::new(
+      satpoint(1, 0),
+      BTreeMap::new(),
+      utxos.into_iter().collect(),
+      cune_balances(outpoint(1)),
+      recipient(),
+      [change(0), change(1)],
+      FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
+    )
+    .unwrap()
+    .select_outgoing()
+    .unwrap()
+    .add_value()
+    .unwrap()
+    .strip_value()
+    .deduct_fee()
+    .build()
+    .unwrap();
+  }
+
+  #[test]
+  #[should_panic(expected = "invariant: only the outgoing input may carry cunes")]
+  fn invariant_only_outgoing_input_carries_cunes() {
+    let utxos = vec![
+      (outpoint(1), Amount::from_sat(10_000)),
+      (outpoint(2), Amount::from_sat(10_000)),
+    ];
+
+    let mut builder = TransactionBuilder::new(
+      satpoint(1, 0),
+      BTreeMap::new(),
+      utxos.into_iter().collect(),
+      BTreeMap::new(),
+      recipient(),
+      [change(0), change(1)],
+      FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
+    )
+    .unwrap()
+    .select_outgoing()
+    .unwrap()
+    .align_outgoing()
+    .pad_alignment_output()
+    .unwrap()
+    .add_value()
+    .unwrap()
+    .strip_value()
+    .deduct_fee();
+
+    builder.inputs.push(outpoint(2));
+    builder.cune_balances = cune_balances(outpoint(2));
+
+    builder.build().unwrap();
+  }
+
+  #[test]
+  fn required_inputs_are_spent() {
+    let builder = || {
+      TransactionBuilder::new(
+        satpoint(1, 0),
+        BTreeMap::new(),
+        vec![
+          (outpoint(1), Amount::from_sat(10_000)),
+          (outpoint(2), Amount::from_sat(20_000)),
+          (outpoint(3), Amount::from_sat(20_000)),
+        ]
+        .into_iter()
+        .collect(),
+        BTreeMap::new(),
+        recipient(),
+        [change(0), change(1)],
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Target::Value(Amount::from_sat(15_000)),
+      )
+      .unwrap()
+    };
+
+    pretty_assert_eq!(
+      builder().build_transaction().unwrap().input,
+      vec![tx_in(outpoint(1)), tx_in(outpoint(2))],
+    );
+
+    pretty_assert_eq!(
+      builder()
+        .with_required_inputs(vec![outpoint(3)])
+        .build_transaction()
+        .unwrap()
+        .input,
+      vec![tx_in(outpoint(1)), tx_in(outpoint(3))],
+    );
+  }
+
+  #[test]
+  fn required_inputs_must_be_cardinal() {
+    let utxos = vec![
+      (outpoint(1), Amount::from_sat(10_000)),
+      (outpoint(2), Amount::from_sat(10_000)),
+    ];
+
+    pretty_assert_eq!(
+      TransactionBuilder::new(
+        satpoint(1, 0),
+        BTreeMap::from([(satpoint(2, 0), inscription_id(1))]),
+        utxos.clone().into_iter().collect(),
+        BTreeMap::new(),
+        recipient(),
+        [change(0), change(1)],
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Target::Postage(Chain::Regtest.default_postage()),
+      )
+      .unwrap()
+      .with_required_inputs(vec![outpoint(2)])
+      .build_transaction(),
+      Err(Error::RequiredInputNotCardinal(outpoint(2))),
+    );
+
+    pretty_assert_eq!(
+      TransactionBuilder::new(
+        satpoint(1, 0),
+        BTreeMap::new(),
+        utxos.clone().into_iter().collect(),
+        cune_balances(outpoint(2)),
+        recipient(),
+        [change(0), change(1)],
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Target::Postage(Chain::Regtest.default_postage()),
+      )
+      .unwrap()
+      .with_required_inputs(vec![outpoint(2)])
+      .build_transaction(),
+      Err(Error::RequiredInputNotCardinal(outpoint(2))),
+    );
+
+    pretty_assert_eq!(
+      TransactionBuilder::new(
+        satpoint(1, 0),
+        BTreeMap::new(),
+        utxos.into_iter().collect(),
+        BTreeMap::new(),
+        recipient(),
+        [change(0), change(1)],
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Target::Postage(Chain::Regtest.default_postage()),
+      )
+      .unwrap()
+      .with_required_inputs(vec![outpoint(3)])
+      .build_transaction(),
+      Err(Error::RequiredInputNotInWallet(outpoint(3))),
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "invariant: required inputs are spent")]
+  fn invariant_required_inputs_are_spent() {
+    let utxos = vec![
+      (outpoint(1), Amount::from_sat(10_000)),
+      (outpoint(2), Amount::from_sat(10_000)),
+    ];
+
+    TransactionBuilder::new(
+      satpoint(1, 0),
+      BTreeMap::new(),
+      utxos.into_iter().collect(),
+      BTreeMap::new(),
+      recipient(),
+      [change(0), change(1)],
+      FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest,
+      Target::Postage(Chain::Regtest.default_postage()),
+    )
+    .unwrap()
+    .with_required_inputs(vec![outpoint(2)])
+    .select_outgoing()
+    .unwrap()
+    .align_outgoing()
+    .pad_alignment_output()
+    .unwrap()
+    .add_value()
+    .unwrap()
+    .strip_value()
+    .deduct_fee()
+    .build()
+    .unwrap();
+  }
 }
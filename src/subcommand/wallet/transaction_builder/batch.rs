@@ -0,0 +1,627 @@
+//! Sending many inscriptions in one transaction.
+//!
+//! Each transfer gets its own segment of inputs and outputs, built by a
+//! `TransactionBuilder` sending that inscription alone without paying a fee,
+//! so that the segment is laid out, padded, and stripped of excess postage
+//! exactly as a single send would be, and checked against the same
+//! invariants. Since the inputs and outputs of every segment have equal
+//! value, each inscription ends up on the first sat of its recipient's
+//! output. The fee is paid by a final cardinal input and change output.
+//!
+//! The combined transaction is then checked against the invariants which
+//! span segments before it is returned.
+
+use super::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Transfer {
+  pub(crate) outgoing: SatPoint,
+  pub(crate) recipient: Address,
+}
+
+#[derive(Debug)]
+pub(crate) struct BatchTransactionBuilder {
+  amounts: BTreeMap<OutPoint, Amount>,
+  chain: Chain,
+  change_addresses: BTreeSet<Address>,
+  cune_balances: BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
+  fee_rate: FeeRate,
+  inputs: Vec<OutPoint>,
+  inscriptions: BTreeMap<SatPoint, InscriptionId>,
+  outputs: Vec<TxOut>,
+  postage: Amount,
+  transfers: Vec<Transfer>,
+  unused_change_addresses: Vec<Address>,
+  utxos: BTreeSet<OutPoint>,
+}
+
+impl BatchTransactionBuilder {
+  /// Number of change addresses needed to build a transaction sending
+  /// `transfers` inscriptions: an alignment and a stripped postage output for
+  /// each, and a final change output paying the fee.
+  pub(crate) fn change_addresses_required(transfers: usize) -> usize {
+    transfers * 2 + 1
+  }
+
+  pub(crate) fn build_transaction(
+    transfers: Vec<Transfer>,
+    inscriptions: BTreeMap<SatPoint, InscriptionId>,
+    amounts: BTreeMap<OutPoint, Amount>,
+    cune_balances: BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
+    change: Vec<Address>,
+    fee_rate: FeeRate,
+    chain: Chain,
+    postage: Amount,
+  ) -> Result<Transaction> {
+    Self::new(
+      transfers,
+      inscriptions,
+      amounts,
+      cune_balances,
+      change,
+      fee_rate,
+      chain,
+      postage,
+    )?
+    .add_transfers()?
+    .pay_fee()?
+    .build()
+  }
+
+  fn new(
+    mut transfers: Vec<Transfer>,
+    inscriptions: BTreeMap<SatPoint, InscriptionId>,
+    amounts: BTreeMap<OutPoint, Amount>,
+    cune_balances: BTreeMap<OutPoint, BTreeMap<CuneId, u128>>,
+    change: Vec<Address>,
+    fee_rate: FeeRate,
+    chain: Chain,
+    postage: Amount,
+  ) -> Result<Self> {
+    assert!(
+      change.len() >= Self::change_addresses_required(transfers.len()),
+      "not enough change addresses"
+    );
+
+    let mut change_addresses = BTreeSet::new();
+    for address in &change {
+      if !change_addresses.insert(address.clone()) {
+        return Err(Error::DuplicateAddress(address.clone()));
+      }
+    }
+
+    for transfer in &transfers {
+      if change_addresses.contains(&transfer.recipient) {
+        return Err(Error::DuplicateAddress(transfer.recipient.clone()));
+      }
+    }
+
+    let mut outgoing = BTreeSet::new();
+    for transfer in &transfers {
+      if !outgoing.insert(transfer.outgoing.outpoint) {
+        return Err(Error::DuplicateOutgoing(transfer.outgoing));
+      }
+    }
+
+    // Cunes without a cunestone are assigned to the first output, so a
+    // transfer whose outgoing UTXO carries cunes is laid out first, where its
+    // segment starts with an alignment output paying to change
+    if let Some(i) = transfers
+      .iter()
+      .position(|transfer| cune_balances.contains_key(&transfer.outgoing.outpoint))
+    {
+      let transfer = transfers.remove(i);
+      transfers.insert(0, transfer);
+    }
+
+    Ok(Self {
+      utxos: amounts
+        .keys()
+        .filter(|outpoint| !outgoing.contains(outpoint))
+        .cloned()
+        .collect(),
+      amounts,
+      chain,
+      change_addresses,
+      cune_balances,
+      fee_rate,
+      inputs: Vec::new(),
+      inscriptions,
+      outputs: Vec::new(),
+      postage,
+      transfers,
+      unused_change_addresses: change,
+    })
+  }
+
+  fn add_transfers(mut self) -> Result<Self> {
+    for transfer in self.transfers.clone() {
+      let first = self.next_change_address();
+      let second = self.next_change_address();
+
+      let amounts = self
+        .amounts
+        .iter()
+        .filter(|(outpoint, _amount)| {
+          **outpoint == transfer.outgoing.outpoint || self.utxos.contains(outpoint)
+        })
+        .map(|(outpoint, amount)| (*outpoint, *amount))
+        .collect();
+
+      let segment = TransactionBuilder::new(
+        transfer.outgoing,
+        self.inscriptions.clone(),
+        amounts,
+        self.cune_balances.clone(),
+        transfer.recipient,
+        [second.clone(), first.clone()],
+        FeeRate::ZERO,
+        self.chain,
+        Target::Postage(self.postage),
+      )?
+      .build_transaction()?;
+
+      // Change addresses the segment didn't use are left for later segments
+      for address in [second, first] {
+        if !segment
+          .output
+          .iter()
+          .any(|tx_out| tx_out.script_pubkey == address.script_pubkey())
+        {
+          self.unused_change_addresses.push(address);
+        }
+      }
+
+      for tx_in in &segment.input {
+        self.utxos.remove(&tx_in.previous_output);
+        self.inputs.push(tx_in.previous_output);
+      }
+
+      self.outputs.extend(segment.output);
+    }
+
+    Ok(self)
+  }
+
+  fn pay_fee(mut self) -> Result<Self> {
+    let change = self.next_change_address();
+
+    let mut estimate = self.transaction();
+    estimate.input.push(TxIn {
+      previous_output: OutPoint::null(),
+      script_sig: Script::new(),
+      sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+      witness: Witness::new(),
+    });
+    estimate.output.push(TxOut {
+      value: 0,
+      script_pubkey: change.script_pubkey(),
+    });
+
+    let fee = self.fee_rate.fee(Self::estimate_vbytes(estimate));
+
+    let needed = fee
+      .checked_add(self.chain.dust_value(&change.script_pubkey()))
+      .ok_or(Error::ValueOverflow)?;
+
+    let (utxo, value) = self.select_cardinal_utxo(needed)?;
+
+    self.inputs.push(utxo);
+    self.outputs.push(TxOut {
+      value: (value - fee).to_sat(),
+      script_pubkey: change.script_pubkey(),
+    });
+
+    tprintln!("paid {fee} sat fee with {value} sat input");
+
+    Ok(self)
+  }
+
+  fn transaction(&self) -> Transaction {
+    Transaction {
+      version: 1,
+      lock_time: PackedLockTime::ZERO,
+      input: self
+        .inputs
+        .iter()
+        .map(|outpoint| TxIn {
+          previous_output: *outpoint,
+          script_sig: Script::new(),
+          sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+          witness: Witness::new(),
+        })
+        .collect(),
+      output: self.outputs.clone(),
+    }
+  }
+
+  /// Size of `transaction` once each of its inputs is signed, estimated as
+  /// `TransactionBuilder` does.
+  fn estimate_vbytes(mut transaction: Transaction) -> usize {
+    for tx_in in &mut transaction.input {
+      tx_in.script_sig = TransactionBuilder::dummy_script_sig();
+    }
+
+    transaction.vsize()
+  }
+
+  fn build(self) -> Result<Transaction> {
+    let transaction = self.transaction();
+
+    if transaction
+      .input
+      .iter()
+      .any(|tx_in| self.cune_balances.contains_key(&tx_in.previous_output))
+    {
+      assert!(
+        self.change_addresses.iter().any(
+          |change_address| change_address.script_pubkey() == transaction.output[0].script_pubkey
+        ),
+        "invariant: cunes are sent to change output",
+      );
+    }
+
+    for transfer in &self.transfers {
+      let mut sat_offset = 0;
+      for tx_in in &transaction.input {
+        if tx_in.previous_output == transfer.outgoing.outpoint {
+          sat_offset += transfer.outgoing.offset;
+          break;
+        }
+        sat_offset += self.amounts[&tx_in.previous_output].to_sat();
+      }
+
+      let mut output_start = 0;
+      let mut found = false;
+      for tx_out in &transaction.output {
+        if output_start + tx_out.value > sat_offset {
+          assert_eq!(
+            tx_out.script_pubkey,
+            transfer.recipient.script_pubkey(),
+            "invariant: outgoing sat is sent to recipient"
+          );
+          assert_eq!(
+            output_start, sat_offset,
+            "invariant: sat is at first position in recipient output"
+          );
+          found = true;
+          break;
+        }
+        output_start += tx_out.value;
+      }
+      assert!(found, "invariant: outgoing sat is found in outputs");
+    }
+
+    assert!(
+      self
+        .change_addresses
+        .iter()
+        .map(|change_address| transaction
+          .output
+          .iter()
+          .filter(|tx_out| tx_out.script_pubkey == change_address.script_pubkey())
+          .count())
+        .all(|count| count <= 1),
+      "invariant: change addresses appear at most once in outputs",
+    );
+
+    let mut actual_fee = Amount::ZERO;
+    for input in &transaction.input {
+      actual_fee += self.amounts[&input.previous_output];
+    }
+    for output in &transaction.output {
+      actual_fee -= Amount::from_sat(output.value);
+    }
+
+    assert_eq!(
+      actual_fee,
+      self
+        .fee_rate
+        .fee(Self::estimate_vbytes(transaction.clone())),
+      "invariant: fee estimation is correct",
+    );
+
+    Ok(transaction)
+  }
+
+  fn next_change_address(&mut self) -> Address {
+    self
+      .unused_change_addresses
+      .pop()
+      .expect("not enough change addresses")
+  }
+
+  fn select_cardinal_utxo(&mut self, minimum_value: Amount) -> Result<(OutPoint, Amount)> {
+    TransactionBuilder::select_cardinal_utxo_from(
+      &mut self.utxos,
+      &self.amounts,
+      &self.inscriptions,
+      &self.cune_balances,
+      minimum_value,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn transfer(n: u64, offset: u64, recipient: Address) -> Transfer {
+    Transfer {
+      outgoing: satpoint(n, offset),
+      recipient,
+    }
+  }
+
+  fn change_addresses() -> Vec<Address> {
+    vec![change(2), change(1), change(0)]
+  }
+
+  #[test]
+  fn aligned_inscriptions_are_sent_in_one_transaction() {
+    let amounts = [
+      (outpoint(1), Amount::from_sat(10_000)),
+      (outpoint(2), Amount::from_sat(10_000)),
+      (outpoint(3), Amount::from_sat(20_000)),
+    ];
+
+    let transaction = BatchTransactionBuilder::build_transaction(
+      vec![transfer(1, 0, recipient()), transfer(2, 0, address())],
+      BTreeMap::from([
+        (satpoint(1, 0), inscription_id(1)),
+        (satpoint(2, 0), inscription_id(2)),
+      ]),
+      amounts.into_iter().collect(),
+      BTreeMap::new(),
+      vec![change(4), change(3), change(2), change(1), change(0)],
+      FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest,
+      Chain::Regtest.default_postage(),
+    )
+    .unwrap();
+
+    assert_eq!(
+      transaction
+        .input
+        .iter()
+        .map(|tx_in| tx_in.previous_output)
+        .collect::<Vec<OutPoint>>(),
+      [outpoint(1), outpoint(2), outpoint(3)],
+    );
+
+    assert_eq!(transaction.output[0], tx_out(10_000, recipient()));
+    assert_eq!(transaction.output[1], tx_out(10_000, address()));
+    assert_eq!(transaction.output.len(), 3);
+  }
+
+  #[test]
+  fn unaligned_inscription_gets_alignment_output() {
+    let amounts = [
+      (outpoint(1), Amount::from_sat(20_000)),
+      (outpoint(2), Amount::from_sat(20_000)),
+    ];
+
+    let transaction = BatchTransactionBuilder::build_transaction(
+      vec![transfer(1, 5_000, recipient())],
+      BTreeMap::from([(satpoint(1, 5_000), inscription_id(1))]),
+      amounts.into_iter().collect(),
+      BTreeMap::new(),
+      change_addresses(),
+      FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest,
+      Chain::Regtest.default_postage(),
+    )
+    .unwrap();
+
+    assert_eq!(transaction.input, [tx_in(outpoint(1)), tx_in(outpoint(2))]);
+    assert_eq!(transaction.output[0], tx_out(5_000, change(0)));
+    assert_eq!(transaction.output[1], tx_out(15_000, recipient()));
+    assert_eq!(
+      transaction.output[2].script_pubkey,
+      change(1).script_pubkey()
+    );
+  }
+
+  #[test]
+  fn excess_postage_is_stripped() {
+    let amounts = [
+      (outpoint(1), Amount::from_sat(1_000_000)),
+      (outpoint(2), Amount::from_sat(20_000)),
+    ];
+
+    let transaction = BatchTransactionBuilder::build_transaction(
+      vec![transfer(1, 0, recipient())],
+      BTreeMap::from([(satpoint(1, 0), inscription_id(1))]),
+      amounts.into_iter().collect(),
+      BTreeMap::new(),
+      change_addresses(),
+      FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest,
+      Chain::Regtest.default_postage(),
+    )
+    .unwrap();
+
+    assert_eq!(transaction.output[0], tx_out(10_000, recipient()));
+    assert_eq!(transaction.output[1], tx_out(990_000, change(0)));
+    assert_eq!(
+      transaction.output[2].script_pubkey,
+      change(1).script_pubkey()
+    );
+  }
+
+  #[test]
+  fn cunes_on_outgoing_utxo_are_sent_to_change_output() {
+    let amounts = [
+      (outpoint(1), Amount::from_sat(10_000)),
+      (outpoint(2), Amount::from_sat(10_000)),
+      (outpoint(3), Amount::from_sat(10_000)),
+    ];
+
+    let transaction = BatchTransactionBuilder::build_transaction(
+      vec![transfer(1, 0, recipient())],
+      BTreeMap::new(),
+      amounts.into_iter().collect(),
+      cune_balances(outpoint(1)),
+      change_addresses(),
+      FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest,
+      Chain::Regtest.default_postage(),
+    )
+    .unwrap();
+
+    assert_eq!(
+      transaction.input,
+      [tx_in(outpoint(2)), tx_in(outpoint(1)), tx_in(outpoint(3))]
+    );
+    assert_eq!(transaction.output[0], tx_out(10_000, change(0)));
+    assert_eq!(transaction.output[1], tx_out(10_000, recipient()));
+  }
+
+  #[test]
+  fn transfer_carrying_cunes_is_laid_out_first() {
+    let amounts = [
+      (outpoint(1), Amount::from_sat(10_000)),
+      (outpoint(2), Amount::from_sat(10_000)),
+      (outpoint(3), Amount::from_sat(10_000)),
+      (outpoint(4), Amount::from_sat(10_000)),
+    ];
+
+    let transaction = BatchTransactionBuilder::build_transaction(
+      vec![transfer(1, 0, recipient()), transfer(2, 0, address())],
+      BTreeMap::new(),
+      amounts.into_iter().collect(),
+      cune_balances(outpoint(2)),
+      vec![change(4), change(3), change(2), change(1), change(0)],
+      FeeRate::try_from(1.0).unwrap(),
+      Chain::Regtest,
+      Chain::Regtest.default_postage(),
+    )
+    .unwrap();
+
+    assert_eq!(
+      transaction.input,
+      [
+        tx_in(outpoint(3)),
+        tx_in(outpoint(2)),
+        tx_in(outpoint(1)),
+        tx_in(outpoint(4))
+      ]
+    );
+    assert_eq!(transaction.output[0], tx_out(10_000, change(0)));
+    assert_eq!(transaction.output[1], tx_out(10_000, address()));
+    assert_eq!(transaction.output[2], tx_out(10_000, recipient()));
+  }
+
+  #[test]
+  fn other_inscriptions_are_not_spent() {
+    let amounts = [
+      (outpoint(1), Amount::from_sat(10_000)),
+      (outpoint(2), Amount::from_sat(1_000_000)),
+    ];
+
+    assert_eq!(
+      BatchTransactionBuilder::build_transaction(
+        vec![transfer(1, 0, recipient())],
+        BTreeMap::from([
+          (satpoint(1, 0), inscription_id(1)),
+          (satpoint(2, 0), inscription_id(2)),
+        ]),
+        amounts.into_iter().collect(),
+        BTreeMap::new(),
+        change_addresses(),
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
+      ),
+      Err(Error::NotEnoughCardinalUtxos),
+    );
+  }
+
+  #[test]
+  fn utxo_containing_additional_inscription_is_refused() {
+    assert_eq!(
+      BatchTransactionBuilder::build_transaction(
+        vec![transfer(1, 0, recipient())],
+        BTreeMap::from([
+          (satpoint(1, 0), inscription_id(1)),
+          (satpoint(1, 5_000), inscription_id(2)),
+        ]),
+        [(outpoint(1), Amount::from_sat(10_000))].into(),
+        BTreeMap::new(),
+        change_addresses(),
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
+      ),
+      Err(Error::UtxoContainsAdditionalInscription {
+        outgoing_satpoint: satpoint(1, 0),
+        inscribed_satpoint: satpoint(1, 5_000),
+        inscription_id: inscription_id(2),
+      }),
+    );
+  }
+
+  #[test]
+  fn outgoing_must_be_in_wallet() {
+    assert_eq!(
+      BatchTransactionBuilder::build_transaction(
+        vec![transfer(1, 0, recipient())],
+        BTreeMap::new(),
+        [(outpoint(2), Amount::from_sat(10_000))].into(),
+        BTreeMap::new(),
+        change_addresses(),
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
+      ),
+      Err(Error::NotInWallet(satpoint(1, 0))),
+    );
+  }
+
+  #[test]
+  fn outgoing_utxo_may_only_be_sent_once() {
+    assert_eq!(
+      BatchTransactionBuilder::build_transaction(
+        vec![transfer(1, 0, recipient()), transfer(1, 0, address())],
+        BTreeMap::new(),
+        [(outpoint(1), Amount::from_sat(10_000))].into(),
+        BTreeMap::new(),
+        vec![change(4), change(3), change(2), change(1), change(0)],
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
+      ),
+      Err(Error::DuplicateOutgoing(satpoint(1, 0))),
+    );
+  }
+
+  #[test]
+  fn recipient_may_not_be_change_address() {
+    assert_eq!(
+      BatchTransactionBuilder::build_transaction(
+        vec![transfer(1, 0, change(0))],
+        BTreeMap::new(),
+        [(outpoint(1), Amount::from_sat(10_000))].into(),
+        BTreeMap::new(),
+        change_addresses(),
+        FeeRate::try_from(1.0).unwrap(),
+        Chain::Regtest,
+        Chain::Regtest.default_postage(),
+      ),
+      Err(Error::DuplicateAddress(change(0))),
+    );
+  }
+
+  fn cune_balances(outpoint: OutPoint) -> BTreeMap<OutPoint, BTreeMap<CuneId, u128>> {
+    BTreeMap::from([(
+      outpoint,
+      BTreeMap::from([(
+        CuneId {
+          height: 1,
+          index: 0,
+        },
+        1_000,
+      )]),
+    )])
+  }
+}
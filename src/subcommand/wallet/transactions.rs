@@ -1,35 +1,697 @@
-use super::*;
+use {
+  super::*,
+  crate::{
+    crc20::{Event, OperationType, Receipt},
+    index::entry::InscriptionMove,
+  },
+};
 
 #[derive(Debug, Parser)]
 pub(crate) struct Transactions {
   #[clap(long, help = "Fetch at most <LIMIT> transactions.")]
   limit: Option<u16>,
+  #[clap(
+    long,
+    help = "Only list transactions confirmed at or after block <HEIGHT>. Unconfirmed transactions are always listed."
+  )]
+  since: Option<u32>,
+  #[clap(long, help = "Also write transactions to <CSV>, one per line.")]
+  csv: Option<PathBuf>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CuneMovement {
+  pub divisibility: u8,
+  pub received: u128,
+  pub sent: u128,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Crc20Operation {
+  pub inscription: InscriptionId,
+  pub operation: String,
+  pub from: String,
+  pub to: String,
+  pub tick: Option<String>,
+  pub amount: Option<String>,
+  pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct Output {
   pub transaction: Txid,
   pub confirmations: i32,
+  pub height: Option<u32>,
+  pub cardinal: Option<i64>,
+  pub fee: Option<u64>,
+  pub inscriptions_created: Vec<InscriptionId>,
+  pub inscriptions_received: Vec<InscriptionId>,
+  pub inscriptions_sent: Vec<InscriptionId>,
+  pub cunes: BTreeMap<SpacedCune, CuneMovement>,
+  pub crc20: Vec<Crc20Operation>,
+}
+
+#[derive(Deserialize)]
+struct AddressInfo {
+  ismine: bool,
 }
 
 impl Transactions {
   pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let index = Index::open(&options)?;
+
+    let history = index.has_history_index();
+
+    if history {
+      index.update()?;
+    }
+
+    let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
+
+    let chain = options.chain();
+
+    let tip = u32::try_from(client.get_block_count()?)?;
+
+    let mut ownership = HashMap::new();
     let mut output = Vec::new();
-    for tx in options
-      .craftcoin_rpc_client_for_wallet_command(false)?
-      .list_transactions(
-        None,
-        Some(self.limit.unwrap_or(u16::MAX).into()),
-        None,
-        None,
-      )?
-    {
-      output.push(Output {
-        transaction: tx.info.txid,
-        confirmations: tx.info.confirmations,
+
+    for (txid, confirmations, height) in self.list_transactions(&client, tip)? {
+      output.push(if history {
+        Self::annotate(
+          &index,
+          &client,
+          chain,
+          &mut ownership,
+          txid,
+          confirmations,
+          height,
+        )?
+      } else {
+        Output {
+          transaction: txid,
+          confirmations,
+          height,
+          cardinal: None,
+          fee: None,
+          inscriptions_created: Vec::new(),
+          inscriptions_received: Vec::new(),
+          inscriptions_sent: Vec::new(),
+          cunes: BTreeMap::new(),
+          crc20: Vec::new(),
+        }
       });
     }
 
+    if let Some(csv) = &self.csv {
+      fs::write(csv, Self::csv(&output))
+        .with_context(|| format!("failed to write transactions to {}", csv.display()))?;
+    }
+
     Ok(Box::new(output))
   }
+
+  /// The txid, confirmations, and height of the most recent `--limit` wallet
+  /// transactions confirmed at or after `--since`, oldest first. craftcoind
+  /// lists a transaction once for every wallet output it pays or spends, so
+  /// pages of `listtransactions`, which go back in time, are fetched until
+  /// enough distinct transactions are found.
+  fn list_transactions(&self, client: &Client, tip: u32) -> Result<Vec<(Txid, i32, Option<u32>)>> {
+    let limit = self.limit.map(usize::from);
+    let page_size = usize::from(self.limit.unwrap_or(u16::MAX));
+
+    let mut seen = HashSet::new();
+    let mut pages = Vec::new();
+    let mut found = 0;
+    let mut skip = 0;
+
+    loop {
+      let page = client.list_transactions(None, Some(page_size), Some(skip), None)?;
+
+      skip += page.len();
+
+      let mut transactions = Vec::new();
+
+      for tx in &page {
+        if !seen.insert(tx.info.txid) {
+          continue;
+        }
+
+        let height = tx.info.blockheight.or_else(|| {
+          u32::try_from(tx.info.confirmations)
+            .ok()
+            .filter(|confirmations| *confirmations > 0)
+            .and_then(|confirmations| (tip + 1).checked_sub(confirmations))
+        });
+
+        if let (Some(since), Some(height)) = (self.since, height) {
+          if height < since {
+            continue;
+          }
+        }
+
+        transactions.push((tx.info.txid, tx.info.confirmations, height));
+      }
+
+      found += transactions.len();
+      pages.push(transactions);
+
+      if page.len() < page_size || limit.map_or(false, |limit| found >= limit) {
+        break;
+      }
+    }
+
+    let transactions = pages.into_iter().rev().flatten().collect::<Vec<_>>();
+
+    let excess = limit.map_or(0, |limit| transactions.len().saturating_sub(limit));
+
+    Ok(transactions.into_iter().skip(excess).collect())
+  }
+
+  fn annotate(
+    index: &Index,
+    client: &Client,
+    chain: Chain,
+    ownership: &mut HashMap<Script, bool>,
+    txid: Txid,
+    confirmations: i32,
+    height: Option<u32>,
+  ) -> Result<Output> {
+    let transaction = index
+      .get_transaction(txid)?
+      .ok_or_else(|| anyhow!("transaction {txid} not found"))?;
+
+    let mut inputs = Vec::new();
+    for input in &transaction.input {
+      if input.previous_output.is_null() {
+        continue;
+      }
+
+      let prevout = index
+        .get_transaction(input.previous_output.txid)?
+        .and_then(|tx| {
+          tx.output
+            .into_iter()
+            .nth(usize::try_from(input.previous_output.vout).unwrap())
+        })
+        .ok_or_else(|| anyhow!("output {} not found", input.previous_output))?;
+
+      inputs.push((input.previous_output, prevout));
+    }
+
+    let mut owned_inputs = BTreeSet::new();
+    for (outpoint, prevout) in &inputs {
+      if Self::is_mine(client, chain, ownership, &prevout.script_pubkey)? {
+        owned_inputs.insert(*outpoint);
+      }
+    }
+
+    let mut owned_outputs = BTreeSet::new();
+    for (vout, tx_out) in transaction.output.iter().enumerate() {
+      if Self::is_mine(client, chain, ownership, &tx_out.script_pubkey)? {
+        owned_outputs.insert(OutPoint {
+          txid,
+          vout: vout.try_into().unwrap(),
+        });
+      }
+    }
+
+    let confirmed = confirmations > 0;
+
+    let moves = if confirmed {
+      index.get_inscription_moves(txid)?
+    } else {
+      let mut pending = Vec::new();
+      for (outpoint, prevout) in &inputs {
+        let mut inscriptions = Vec::new();
+        for inscription_id in index.get_inscriptions_on_output(*outpoint)? {
+          if let Some(satpoint) = index.get_inscription_satpoint_by_id(inscription_id)? {
+            inscriptions.push((satpoint, inscription_id));
+          }
+        }
+        pending.push((prevout.value, inscriptions));
+      }
+      Self::pending_moves(&transaction, &pending)
+    };
+
+    let mut non_cardinal = BTreeSet::new();
+    let mut inscriptions_created = Vec::new();
+    let mut inscriptions_received = Vec::new();
+    let mut inscriptions_sent = Vec::new();
+
+    for InscriptionMove {
+      created,
+      inscription_id,
+      new_satpoint,
+      old_satpoint,
+    } in moves
+    {
+      let to_wallet = owned_outputs.contains(&new_satpoint.outpoint);
+
+      non_cardinal.insert(new_satpoint.outpoint);
+
+      if created {
+        inscriptions_created.push(inscription_id);
+        continue;
+      }
+
+      non_cardinal.insert(old_satpoint.outpoint);
+
+      match (owned_inputs.contains(&old_satpoint.outpoint), to_wallet) {
+        (true, false) => inscriptions_sent.push(inscription_id),
+        (false, true) => inscriptions_received.push(inscription_id),
+        _ => {}
+      }
+    }
+
+    let mut cunes = BTreeMap::<SpacedCune, CuneMovement>::new();
+
+    for outpoint in &owned_inputs {
+      let balances = index.get_historical_cune_balances_for_outpoint(*outpoint)?;
+
+      if !balances.is_empty() {
+        non_cardinal.insert(*outpoint);
+      }
+
+      // The index only knows which cunes the outputs of confirmed
+      // transactions hold
+      if !confirmed {
+        continue;
+      }
+
+      for (spaced_cune, pile) in balances {
+        cunes
+          .entry(spaced_cune)
+          .or_insert(CuneMovement {
+            divisibility: pile.divisibility,
+            received: 0,
+            sent: 0,
+          })
+          .sent += pile.amount;
+      }
+    }
+
+    if confirmed {
+      for outpoint in &owned_outputs {
+        let balances = index.get_historical_cune_balances_for_outpoint(*outpoint)?;
+
+        if !balances.is_empty() {
+          non_cardinal.insert(*outpoint);
+        }
+
+        for (spaced_cune, pile) in balances {
+          cunes
+            .entry(spaced_cune)
+            .or_insert(CuneMovement {
+              divisibility: pile.divisibility,
+              received: 0,
+              sent: 0,
+            })
+            .received += pile.amount;
+        }
+      }
+    }
+
+    let spent = inputs
+      .iter()
+      .filter(|(outpoint, _prevout)| {
+        owned_inputs.contains(outpoint) && !non_cardinal.contains(outpoint)
+      })
+      .map(|(_outpoint, prevout)| prevout.value)
+      .sum::<u64>();
+
+    let received = owned_outputs
+      .iter()
+      .filter(|outpoint| !non_cardinal.contains(outpoint))
+      .map(|outpoint| transaction.output[usize::try_from(outpoint.vout).unwrap()].value)
+      .sum::<u64>();
+
+    let fee = (!owned_inputs.is_empty()).then(|| {
+      inputs
+        .iter()
+        .map(|(_outpoint, prevout)| prevout.value)
+        .sum::<u64>()
+        .saturating_sub(transaction.output.iter().map(|tx_out| tx_out.value).sum())
+    });
+
+    let mut crc20 = Vec::new();
+    for receipt in index.get_crc20_receipts(txid)? {
+      crc20.push(Self::crc20_operation(index, receipt)?);
+    }
+
+    Ok(Output {
+      transaction: txid,
+      confirmations,
+      height,
+      cardinal: Some(i64::try_from(received)? - i64::try_from(spent)?),
+      fee,
+      inscriptions_created,
+      inscriptions_received,
+      inscriptions_sent,
+      cunes,
+      crc20,
+    })
+  }
+
+  fn is_mine(
+    client: &Client,
+    chain: Chain,
+    ownership: &mut HashMap<Script, bool>,
+    script_pubkey: &Script,
+  ) -> Result<bool> {
+    if let Some(mine) = ownership.get(script_pubkey) {
+      return Ok(*mine);
+    }
+
+    let mine = match chain.address_from_script(script_pubkey) {
+      Ok(address) => {
+        client
+          .call::<AddressInfo>("getaddressinfo", &[address.to_string().into()])?
+          .ismine
+      }
+      Err(_) => false,
+    };
+
+    ownership.insert(script_pubkey.clone(), mine);
+
+    Ok(mine)
+  }
+
+  /// Where the inscriptions on the inputs of an unconfirmed transaction will
+  /// end up, found by walking input and output values, since the index only
+  /// records the moves of confirmed transactions. `inputs` holds the value
+  /// of each input and the inscriptions on it.
+  fn pending_moves(
+    transaction: &Transaction,
+    inputs: &[(u64, Vec<(SatPoint, InscriptionId)>)],
+  ) -> Vec<InscriptionMove> {
+    let mut moves = Vec::new();
+
+    let mut input_value = 0;
+    for (value, inscriptions) in inputs {
+      for (old_satpoint, inscription_id) in inscriptions {
+        let offset = input_value + old_satpoint.offset;
+
+        let mut output_value = 0;
+        let mut new_satpoint = SatPoint {
+          outpoint: OutPoint::null(),
+          offset: 0,
+        };
+
+        for (vout, tx_out) in transaction.output.iter().enumerate() {
+          if offset < output_value + tx_out.value {
+            new_satpoint = SatPoint {
+              outpoint: OutPoint {
+                txid: transaction.txid(),
+                vout: vout.try_into().unwrap(),
+              },
+              offset: offset - output_value,
+            };
+            break;
+          }
+          output_value += tx_out.value;
+        }
+
+        moves.push(InscriptionMove {
+          created: false,
+          inscription_id: *inscription_id,
+          new_satpoint,
+          old_satpoint: *old_satpoint,
+        });
+      }
+
+      input_value += value;
+    }
+
+    moves
+  }
+
+  fn crc20_operation(index: &Index, receipt: Receipt) -> Result<Crc20Operation> {
+    let operation = match receipt.op {
+      OperationType::Deploy => "deploy",
+      OperationType::Mint => "mint",
+      OperationType::InscribeTransfer => "inscribe-transfer",
+      OperationType::Transfer => "transfer",
+    };
+
+    let (tick, amount, error) = match receipt.result {
+      Ok(event) => {
+        let (tick, amount) = match event {
+          Event::Deploy(deploy) => (deploy.tick, deploy.supply),
+          Event::Mint(mint) => (mint.tick, mint.amount),
+          Event::InscribeTransfer(transfer) => (transfer.tick, transfer.amount),
+          Event::Transfer(transfer) => (transfer.tick, transfer.amount),
+        };
+
+        let decimal = index
+          .get_crc20_token_info(&tick)?
+          .map(|token_info| token_info.decimal)
+          .unwrap_or_default();
+
+        (
+          Some(tick.to_string()),
+          Some(super::crc20::format_amount(amount, decimal)),
+          None,
+        )
+      }
+      Err(error) => (None, None, Some(error.to_string())),
+    };
+
+    Ok(Crc20Operation {
+      inscription: receipt.inscription_id,
+      operation: operation.into(),
+      from: receipt.from.to_string(),
+      to: receipt.to.to_string(),
+      tick,
+      amount,
+      error,
+    })
+  }
+
+  fn csv(transactions: &[Output]) -> String {
+    fn field(value: String) -> String {
+      if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+      } else {
+        value
+      }
+    }
+
+    fn join<T: ToString>(values: &[T]) -> String {
+      values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(" ")
+    }
+
+    let mut csv = "transaction,height,confirmations,cardinal,fee,inscriptions_created,inscriptions_received,inscriptions_sent,cunes,crc20\n".to_string();
+
+    for transaction in transactions {
+      let cunes = transaction
+        .cunes
+        .iter()
+        .map(|(spaced_cune, movement)| {
+          let (sign, amount) = if movement.received >= movement.sent {
+            ('+', movement.received - movement.sent)
+          } else {
+            ('-', movement.sent - movement.received)
+          };
+
+          format!(
+            "{spaced_cune}:{sign}{}",
+            Decimal {
+              value: amount,
+              scale: movement.divisibility,
+            }
+          )
+        })
+        .collect::<Vec<String>>();
+
+      let crc20 = transaction
+        .crc20
+        .iter()
+        .map(|operation| match (&operation.tick, &operation.amount) {
+          (Some(tick), Some(amount)) => format!("{}:{tick}:{amount}", operation.operation),
+          _ => format!("{}:error", operation.operation),
+        })
+        .collect::<Vec<String>>();
+
+      csv.push_str(
+        &[
+          transaction.transaction.to_string(),
+          transaction
+            .height
+            .map(|height| height.to_string())
+            .unwrap_or_default(),
+          transaction.confirmations.to_string(),
+          transaction
+            .cardinal
+            .map(|cardinal| cardinal.to_string())
+            .unwrap_or_default(),
+          transaction
+            .fee
+            .map(|fee| fee.to_string())
+            .unwrap_or_default(),
+          join(&transaction.inscriptions_created),
+          join(&transaction.inscriptions_received),
+          join(&transaction.inscriptions_sent),
+          cunes.join(" "),
+          crc20.join(" "),
+        ]
+        .into_iter()
+        .map(field)
+        .collect::<Vec<String>>()
+        .join(","),
+      );
+
+      csv.push('\n');
+    }
+
+    csv
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn pending(outputs: &[u64], inputs: &[(u64, Vec<(SatPoint, InscriptionId)>)]) -> Transaction {
+    Transaction {
+      version: 1,
+      lock_time: bitcoin::PackedLockTime::ZERO,
+      input: inputs
+        .iter()
+        .map(|(_value, inscriptions)| tx_in(inscriptions[0].0.outpoint))
+        .collect(),
+      output: outputs
+        .iter()
+        .map(|value| tx_out(*value, recipient()))
+        .collect(),
+    }
+  }
+
+  #[test]
+  fn pending_moves_follow_sat_offsets() {
+    let inputs = [
+      (1_000, vec![(satpoint(1, 500), inscription_id(1))]),
+      (2_000, vec![(satpoint(2, 100), inscription_id(2))]),
+    ];
+
+    let transaction = pending(&[1_000, 2_000], &inputs);
+
+    assert_eq!(
+      Transactions::pending_moves(&transaction, &inputs),
+      [
+        InscriptionMove {
+          created: false,
+          inscription_id: inscription_id(1),
+          new_satpoint: SatPoint {
+            outpoint: OutPoint {
+              txid: transaction.txid(),
+              vout: 0,
+            },
+            offset: 500,
+          },
+          old_satpoint: satpoint(1, 500),
+        },
+        InscriptionMove {
+          created: false,
+          inscription_id: inscription_id(2),
+          new_satpoint: SatPoint {
+            outpoint: OutPoint {
+              txid: transaction.txid(),
+              vout: 1,
+            },
+            offset: 100,
+          },
+          old_satpoint: satpoint(2, 100),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn pending_moves_into_fee_have_no_output() {
+    let inputs = [(1_000, vec![(satpoint(1, 900), inscription_id(1))])];
+
+    assert_eq!(
+      Transactions::pending_moves(&pending(&[800], &inputs), &inputs)[0].new_satpoint,
+      SatPoint {
+        outpoint: OutPoint::null(),
+        offset: 0,
+      }
+    );
+  }
+
+  #[test]
+  fn csv() {
+    let mut cunes = BTreeMap::new();
+    cunes.insert(
+      SpacedCune::from_str("AAAA").unwrap(),
+      CuneMovement {
+        divisibility: 1,
+        received: 5,
+        sent: 20,
+      },
+    );
+
+    let transactions = [
+      Output {
+        transaction: txid(1),
+        confirmations: 2,
+        height: Some(10),
+        cardinal: Some(-1_500),
+        fee: Some(300),
+        inscriptions_created: Vec::new(),
+        inscriptions_received: Vec::new(),
+        inscriptions_sent: vec![inscription_id(1), inscription_id(2)],
+        cunes,
+        crc20: vec![
+          Crc20Operation {
+            inscription: inscription_id(3),
+            operation: "transfer".into(),
+            from: "a".into(),
+            to: "b".into(),
+            tick: Some("or,d".into()),
+            amount: Some("1.5".into()),
+            error: None,
+          },
+          Crc20Operation {
+            inscription: inscription_id(4),
+            operation: "mint".into(),
+            from: "a".into(),
+            to: "a".into(),
+            tick: None,
+            amount: None,
+            error: Some("tick: ordi has been minted".into()),
+          },
+        ],
+      },
+      Output {
+        transaction: txid(2),
+        confirmations: 0,
+        height: None,
+        cardinal: Some(1_000),
+        fee: None,
+        inscriptions_created: Vec::new(),
+        inscriptions_received: Vec::new(),
+        inscriptions_sent: Vec::new(),
+        cunes: BTreeMap::new(),
+        crc20: Vec::new(),
+      },
+    ];
+
+    assert_eq!(
+      Transactions::csv(&transactions),
+      format!(
+        "transaction,height,confirmations,cardinal,fee,inscriptions_created,inscriptions_received,inscriptions_sent,cunes,crc20\n\
+        {},10,2,-1500,300,,,{} {},AAAA:-1.5,\"transfer:or,d:1.5 mint:error\"\n\
+        {},,0,1000,,,,,,\n",
+        txid(1),
+        inscription_id(1),
+        inscription_id(2),
+        txid(2),
+      )
+    );
+  }
 }
@@ -0,0 +1,36 @@
+use {super::*, crate::wallet::Wallet, freeze::Output};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Unfreeze {
+  #[arg(required = true, help = "Unfreeze <OUTPOINTS>.")]
+  outpoints: Vec<OutPoint>,
+}
+
+impl Unfreeze {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let mut frozen = Wallet::frozen_outputs(&options)?;
+
+    for outpoint in &self.outpoints {
+      ensure!(frozen.remove(outpoint), "output {outpoint} is not frozen");
+    }
+
+    Wallet::save_frozen_outputs(&options, &frozen)?;
+
+    // Release any node wallet locks on the unfrozen outputs, so that the node
+    // may select them again
+    let client = options.craftcoin_rpc_client_for_wallet_command(false)?;
+
+    let locked = LockedOutputs::list(&client)?
+      .into_iter()
+      .filter(|outpoint| self.outpoints.contains(outpoint))
+      .collect::<Vec<OutPoint>>();
+
+    if !locked.is_empty() && !client.unlock_unspent(&locked)? {
+      bail!("failed to unlock outputs");
+    }
+
+    Ok(Box::new(Output {
+      frozen: frozen.into_iter().collect(),
+    }))
+  }
+}
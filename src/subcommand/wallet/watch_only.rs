@@ -0,0 +1,115 @@
+use {
+  super::*,
+  crate::wallet::{Wallet, WatchOnlyGroup},
+  bitcoin::util::bip32::ExtendedPubKey,
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct WatchOnly {
+  #[arg(help = "Edit watch-only address group <GROUP>. Lists the group if no changes are given.")]
+  group: String,
+  #[arg(long, help = "Add <ADDRESS> to group.")]
+  address: Vec<Address>,
+  #[arg(
+    long,
+    help = "Add addresses derived from extended public key <XPUB> to group."
+  )]
+  xpub: Vec<ExtendedPubKey>,
+  #[arg(
+    long,
+    help = "Remove address or extended public key <REMOVE> from group."
+  )]
+  remove: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Output {
+  pub addresses: Vec<Address>,
+  pub xpubs: Vec<String>,
+}
+
+impl WatchOnly {
+  pub(crate) fn run(self, options: Options) -> SubcommandResult {
+    let mut group = Wallet::watch_only_group(&options, &self.group)?;
+
+    if !self.address.is_empty() || !self.xpub.is_empty() || !self.remove.is_empty() {
+      let network = options.chain().network();
+
+      for address in self.address {
+        ensure!(
+          address.network == network,
+          "address {address} is not valid for {}",
+          options.chain()
+        );
+
+        group.addresses.insert(address);
+      }
+
+      for xpub in self.xpub {
+        ensure!(
+          xpub.network == network,
+          "extended public key {xpub} is not valid for {}",
+          options.chain()
+        );
+
+        group.xpubs.insert(xpub);
+      }
+
+      for entry in &self.remove {
+        Self::remove(&mut group, entry)?;
+      }
+
+      Wallet::save_watch_only_group(&options, &self.group, &group)?;
+    }
+
+    Ok(Box::new(Output {
+      addresses: group.addresses.into_iter().collect(),
+      xpubs: group.xpubs.iter().map(ToString::to_string).collect(),
+    }))
+  }
+
+  fn remove(group: &mut WatchOnlyGroup, entry: &str) -> Result {
+    let removed = if let Ok(address) = entry.parse::<Address>() {
+      group.addresses.remove(&address)
+    } else if let Ok(xpub) = entry.parse::<ExtendedPubKey>() {
+      group.xpubs.remove(&xpub)
+    } else {
+      bail!("`{entry}` is neither an address nor an extended public key");
+    };
+
+    ensure!(removed, "`{entry}` is not in watch-only group");
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn remove_entries() {
+    let mut group = WatchOnlyGroup {
+      addresses: [address(), recipient()].into(),
+      xpubs: BTreeSet::new(),
+    };
+
+    WatchOnly::remove(&mut group, &address().to_string()).unwrap();
+
+    assert_eq!(group.addresses, [recipient()].into());
+
+    assert_eq!(
+      WatchOnly::remove(&mut group, &address().to_string())
+        .unwrap_err()
+        .to_string(),
+      format!("`{}` is not in watch-only group", address())
+    );
+
+    assert_eq!(
+      WatchOnly::remove(&mut group, "foo")
+        .unwrap_err()
+        .to_string(),
+      "`foo` is neither an address nor an extended public key"
+    );
+  }
+}
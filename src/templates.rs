@@ -1,5 +1,6 @@
-use {super::*, boilerplate::Boilerplate};
+use {super::*, bitcoin::hashes::sha256, boilerplate::Boilerplate};
 pub(crate) use {
+  batch::{BatchCrc20BalanceJson, BatchInscriptionJson, BatchJson},
   block::BlockHashAndConfirmations,
   block::BlockHtml,
   block::BlockJson,
@@ -8,12 +9,14 @@ pub(crate) use {
     CuneAddressJson, CuneBalance, CuneEntryJson, CuneHtml, CuneJson, CuneOutput, CuneOutputJson,
   },
   cune_balances::CuneBalancesHtml,
-  cunes::CunesHtml,
+  cunes::{CunesEntryJson, CunesHtml},
   home::HomeHtml,
   iframe::Iframe,
-  input::InputHtml,
+  input::{InputHtml, InputJson},
   inscription::{CraftscriptionJson, InscriptionByAddressJson, InscriptionHtml, InscriptionJson},
-  inscriptions::InscriptionsHtml,
+  inscriptions::{
+    InscriptionSearchJson, InscriptionsByHashJson, InscriptionsHtml, InscriptionsPageJson,
+  },
   output::AddressOutputJson,
   output::OutputHtml,
   output::OutputJson,
@@ -22,13 +25,15 @@ pub(crate) use {
     PreviewAudioHtml, PreviewImageHtml, PreviewModelHtml, PreviewPdfHtml, PreviewTextHtml,
     PreviewUnknownHtml, PreviewVideoHtml,
   },
-  range::RangeHtml,
+  range::{RangeHtml, RangeJson},
   rare::RareTxt,
-  sat::SatHtml,
+  sat::{SatHtml, SatJson},
+  search::{SearchHtml, SearchJson, SearchResult},
   transaction::TransactionHtml,
   utxo::Utxo,
 };
 
+mod batch;
 mod block;
 mod crc20;
 mod cune;
@@ -44,6 +49,7 @@ mod preview;
 mod range;
 mod rare;
 mod sat;
+mod search;
 mod transaction;
 mod utxo;
 
@@ -0,0 +1,27 @@
+use super::*;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BatchJson<T> {
+  pub(crate) height: Option<u32>,
+  pub(crate) results: Vec<T>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BatchInscriptionJson {
+  pub(crate) content_length: Option<usize>,
+  pub(crate) content_type: Option<String>,
+  pub(crate) genesis_fee: u64,
+  pub(crate) genesis_height: u32,
+  pub(crate) inscription_id: InscriptionId,
+  pub(crate) inscription_number: u64,
+  pub(crate) satpoint: SatPoint,
+  pub(crate) timestamp: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct BatchCrc20BalanceJson {
+  pub(crate) tick: String,
+  pub(crate) available: String,
+  pub(crate) transferable: String,
+  pub(crate) overall: String,
+}
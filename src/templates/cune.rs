@@ -22,6 +22,23 @@ pub(crate) struct CuneEntryJson {
   pub(crate) timestamp: u64,
 }
 
+impl CuneEntryJson {
+  pub(crate) fn new(entry: &CuneEntry) -> Self {
+    Self {
+      burned: entry.burned,
+      divisibility: entry.divisibility,
+      etching: entry.etching,
+      mint: entry.terms,
+      mints: entry.mints,
+      number: entry.number,
+      cune: entry.spaced_cune(),
+      supply: entry.supply,
+      symbol: entry.symbol,
+      timestamp: entry.timestamp,
+    }
+  }
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct CuneJson {
   pub(crate) entry: CuneEntryJson,
@@ -41,6 +58,7 @@ pub(crate) struct CuneAddressJson {
   pub(crate) cunes: Vec<CuneBalance>,
   pub(crate) total_cunes: usize,
   pub(crate) total_elements: u32,
+  pub(crate) next: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -5,6 +5,12 @@ pub(crate) struct CunesHtml {
   pub(crate) entries: Vec<(CuneId, CuneEntry)>,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CunesEntryJson {
+  pub(crate) id: CuneId,
+  pub(crate) entry: CuneEntryJson,
+}
+
 impl PageContent for CunesHtml {
   fn title(&self) -> String {
     "Cunes".to_string()
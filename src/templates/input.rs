@@ -6,6 +6,25 @@ pub(crate) struct InputHtml {
   pub(crate) input: TxIn,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct InputJson {
+  pub(crate) previous_output: OutPoint,
+  pub(crate) script_sig: String,
+  pub(crate) sequence: u32,
+  pub(crate) witness: Vec<String>,
+}
+
+impl InputJson {
+  pub(crate) fn new(input: &TxIn) -> Self {
+    Self {
+      previous_output: input.previous_output,
+      script_sig: input.script_sig.asm(),
+      sequence: input.sequence.0,
+      witness: input.witness.iter().map(hex::encode).collect(),
+    }
+  }
+}
+
 impl PageContent for InputHtml {
   fn title(&self) -> String {
     format!("Input /{}/{}/{}", self.path.0, self.path.1, self.path.2)
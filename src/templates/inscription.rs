@@ -5,6 +5,9 @@ use crate::sat_point::SatPoint;
 #[derive(Boilerplate, Default)]
 pub(crate) struct InscriptionHtml {
   pub(crate) chain: Chain,
+  pub(crate) content_sha256: Option<sha256::Hash>,
+  pub(crate) duplicates: usize,
+  pub(crate) first_inscription_number: Option<u64>,
   pub(crate) genesis_fee: u64,
   pub(crate) genesis_height: u32,
   pub(crate) inscription: Inscription,
@@ -22,6 +25,9 @@ pub(crate) struct InscriptionHtml {
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub(crate) struct CraftscriptionJson {
   pub(crate) chain: Chain,
+  pub(crate) content_sha256: Option<sha256::Hash>,
+  pub(crate) duplicates: usize,
+  pub(crate) first_inscription_number: Option<u64>,
   pub(crate) genesis_fee: u64,
   pub(crate) genesis_height: u32,
   pub(crate) inscription: Inscription,
@@ -212,4 +218,37 @@ mod tests {
       .unindent()
     );
   }
+
+  #[test]
+  fn with_duplicates() {
+    assert_regex_match!(
+      InscriptionHtml {
+        content_sha256: Some(sha256::Hash::hash(b"HELLOWORLD")),
+        duplicates: 2,
+        first_inscription_number: Some(0),
+        genesis_fee: 1,
+        inscription: inscription("text/plain;charset=utf-8", "HELLOWORLD"),
+        inscription_id: inscription_id(1),
+        inscription_number: 1,
+        output: tx_out(1, address()),
+        satpoint: satpoint(1, 0),
+        ..Default::default()
+      },
+      "
+        <h1>Craftscription 1</h1>
+        .*
+          <dt>content type</dt>
+          <dd>text/plain;charset=utf-8</dd>
+          <dt>content hash</dt>
+          <dd><a class=monospace href=/inscriptions/by-hash/[[:xdigit:]]{64}>[[:xdigit:]]{64}</a></dd>
+          <dt>first inscribed as</dt>
+          <dd>#0</dd>
+          <dt>duplicates</dt>
+          <dd>2</dd>
+          <dt>timestamp</dt>
+        .*
+      "
+      .unindent()
+    );
+  }
 }
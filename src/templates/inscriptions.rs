@@ -12,6 +12,25 @@ pub struct InscriptionsJson {
   pub inscriptions: Vec<InscriptionId>,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct InscriptionsPageJson {
+  pub(crate) inscriptions: Vec<InscriptionId>,
+  pub(crate) prev: Option<u64>,
+  pub(crate) next: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct InscriptionSearchJson {
+  pub(crate) inscriptions: Vec<InscriptionId>,
+  pub(crate) next: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct InscriptionsByHashJson {
+  pub(crate) sha256: sha256::Hash,
+  pub(crate) inscriptions: Vec<InscriptionId>,
+}
+
 impl PageContent for InscriptionsHtml {
   fn title(&self) -> String {
     "Craftscription".into()
@@ -6,6 +6,13 @@ pub(crate) struct RangeHtml {
   pub(crate) end: Sat,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RangeJson {
+  pub(crate) start: Sat,
+  pub(crate) end: Sat,
+  pub(crate) size: u64,
+}
+
 impl PageContent for RangeHtml {
   fn title(&self) -> String {
     format!("Sat range {}–{}", self.start, self.end)
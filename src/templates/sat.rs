@@ -10,6 +10,17 @@ pub(crate) struct SatHtml {
   pub(crate) inscription: Option<InscriptionId>,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SatJson {
+  pub(crate) number: u64,
+  pub(crate) decimal: String,
+  pub(crate) height: u32,
+  pub(crate) rarity: Rarity,
+  pub(crate) satpoint: Option<SatPoint>,
+  pub(crate) timestamp: i64,
+  pub(crate) inscription: Option<InscriptionId>,
+}
+
 impl PageContent for SatHtml {
   fn title(&self) -> String {
     format!("Sat {}", self.sat)
@@ -0,0 +1,100 @@
+use super::*;
+
+#[derive(Boilerplate)]
+pub(crate) struct SearchHtml {
+  pub(crate) content_type: Option<String>,
+  pub(crate) limit: usize,
+  pub(crate) next: Option<u64>,
+  pub(crate) query: String,
+  pub(crate) results: Vec<SearchResult>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SearchJson {
+  pub(crate) results: Vec<SearchResult>,
+  pub(crate) next: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SearchResult {
+  pub(crate) inscription_id: InscriptionId,
+  pub(crate) inscription_number: u64,
+  pub(crate) content_type: Option<String>,
+  pub(crate) snippet: String,
+}
+
+impl SearchHtml {
+  fn next_url(&self, next: u64) -> String {
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+
+    query.append_pair("q", &self.query);
+
+    if let Some(content_type) = &self.content_type {
+      query.append_pair("content_type", content_type);
+    }
+
+    query
+      .append_pair("from", &next.to_string())
+      .append_pair("limit", &self.limit.to_string());
+
+    format!("/search?{}", query.finish())
+  }
+}
+
+impl PageContent for SearchHtml {
+  fn title(&self) -> String {
+    format!("Search: {}", self.query)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn without_results() {
+    assert_regex_match!(
+      SearchHtml {
+        content_type: None,
+        limit: 100,
+        next: None,
+        query: "foo".into(),
+        results: Vec::new(),
+      },
+      "
+        <h1>Search results for “foo”</h1>
+        <p>No matching craftscriptions.</p>
+      "
+      .unindent()
+    );
+  }
+
+  #[test]
+  fn with_results_and_next() {
+    assert_regex_match!(
+      SearchHtml {
+        content_type: Some("text/*".into()),
+        limit: 1,
+        next: Some(0),
+        query: "hello world".into(),
+        results: vec![SearchResult {
+          inscription_id: inscription_id(1),
+          inscription_number: 1,
+          content_type: Some("text/plain".into()),
+          snippet: "<b>hello</b> world".into(),
+        }],
+      },
+      "
+        <h1>Search results for “hello world”</h1>
+        <dl>
+          <dt><a href=/craftscription/1{64}i1>Craftscription 1</a></dt>
+          <dd>&lt;b&gt;hello&lt;/b&gt; world</dd>
+        </dl>
+        <div class=center>
+          <a class=next href=/search\\?q=hello\\+world&amp;content_type=text%2F\\*&amp;from=0&amp;limit=1>next</a>
+        </div>
+      "
+      .unindent()
+    );
+  }
+}
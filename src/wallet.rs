@@ -1,14 +1,263 @@
-use super::*;
+use {
+  super::*,
+  crate::multisig::Multisig,
+  bitcoin::{
+    secp256k1::Secp256k1,
+    util::bip32::{ChildNumber, ExtendedPubKey},
+  },
+};
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub(crate) struct Wallet {
-  _private: (),
+  watch_only: Option<BTreeSet<Address>>,
+}
+
+/// A named group of addresses and extended public keys, stored in the data
+/// directory, whose outputs can be queried like those of a wallet without
+/// being imported into craftcoind.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub(crate) struct WatchOnlyGroup {
+  #[serde(default)]
+  pub(crate) addresses: BTreeSet<Address>,
+  #[serde(default)]
+  pub(crate) xpubs: BTreeSet<ExtendedPubKey>,
+}
+
+impl WatchOnlyGroup {
+  /// Number of addresses derived from each of the receive and change chains
+  /// of an extended public key.
+  pub(crate) const XPUB_ADDRESSES: u32 = 200;
+
+  /// The group's addresses, along with the pay-to-pubkey-hash addresses
+  /// derived from its extended public keys at `m/0/i` and `m/1/i`.
+  pub(crate) fn addresses(&self, network: Network) -> Result<BTreeSet<Address>> {
+    let secp = Secp256k1::verification_only();
+
+    let mut addresses = self.addresses.clone();
+
+    for xpub in &self.xpubs {
+      for chain in 0..2 {
+        for index in 0..Self::XPUB_ADDRESSES {
+          let child = xpub.derive_pub(
+            &secp,
+            &[
+              ChildNumber::from_normal_idx(chain)?,
+              ChildNumber::from_normal_idx(index)?,
+            ],
+          )?;
+
+          addresses.insert(Address::p2pkh(&child.to_pub(), network));
+        }
+      }
+    }
+
+    Ok(addresses)
+  }
 }
 
 impl Wallet {
   pub(crate) fn load(options: &Options) -> Result<Self> {
+    if let Some(group) = &options.watch_only {
+      bail!("watch-only group `{group}` can be queried but not spent from");
+    }
+
     options.craftcoin_rpc_client_for_wallet_command(false)?;
 
-    Ok(Self { _private: () })
+    Ok(Self { watch_only: None })
+  }
+
+  /// Loads the watch-only group given with `--watch-only`, or the craftcoind
+  /// wallet if there is none, for commands which only list wallet outputs.
+  pub(crate) fn load_for_query(options: &Options) -> Result<Self> {
+    let Some(group) = &options.watch_only else {
+      return Self::load(options);
+    };
+
+    let path = Self::watch_only_group_path(options, group)?;
+
+    ensure!(path.exists(), "watch-only group `{group}` does not exist");
+
+    Ok(Self {
+      watch_only: Some(
+        Self::watch_only_group(options, group)?.addresses(options.chain().network())?,
+      ),
+    })
+  }
+
+  /// Addresses whose outputs make up the wallet, if it is a watch-only group
+  /// rather than a craftcoind wallet.
+  pub(crate) fn watch_only(&self) -> Option<&BTreeSet<Address>> {
+    self.watch_only.as_ref()
+  }
+
+  pub(crate) fn watch_only_group(options: &Options, group: &str) -> Result<WatchOnlyGroup> {
+    let path = Self::watch_only_group_path(options, group)?;
+
+    if !path.exists() {
+      return Ok(WatchOnlyGroup::default());
+    }
+
+    serde_json::from_slice(&fs::read(&path)?)
+      .with_context(|| format!("failed to parse watch-only group at {}", path.display()))
+  }
+
+  pub(crate) fn save_watch_only_group(
+    options: &Options,
+    group: &str,
+    watch_only_group: &WatchOnlyGroup,
+  ) -> Result {
+    let path = Self::watch_only_group_path(options, group)?;
+
+    fs::create_dir_all(path.parent().unwrap())?;
+
+    fs::write(&path, serde_json::to_vec_pretty(watch_only_group)?)
+      .with_context(|| format!("failed to write watch-only group to {}", path.display()))
+  }
+
+  fn watch_only_group_path(options: &Options, group: &str) -> Result<PathBuf> {
+    Self::named_path(options, "watch-only", "watch-only group", group)
+  }
+
+  /// Multisig treasuries are stored in the data directory under their name,
+  /// so that cosigners can build and sign spends from them.
+  pub(crate) fn multisig(options: &Options, name: &str) -> Result<Multisig> {
+    let path = Self::multisig_path(options, name)?;
+
+    ensure!(path.exists(), "multisig `{name}` is not registered");
+
+    serde_json::from_slice(&fs::read(&path)?)
+      .with_context(|| format!("failed to parse multisig at {}", path.display()))
+  }
+
+  /// Saves `multisig` under `name`, replacing a multisig already registered
+  /// under that name only if `overwrite` is set.
+  pub(crate) fn save_multisig(
+    options: &Options,
+    name: &str,
+    multisig: &Multisig,
+    overwrite: bool,
+  ) -> Result {
+    let path = Self::multisig_path(options, name)?;
+
+    ensure!(
+      overwrite || !path.exists(),
+      "multisig `{name}` is already registered, use `--force` to replace it"
+    );
+
+    fs::create_dir_all(path.parent().unwrap())?;
+
+    fs::write(&path, serde_json::to_vec_pretty(multisig)?)
+      .with_context(|| format!("failed to write multisig to {}", path.display()))
+  }
+
+  fn multisig_path(options: &Options, name: &str) -> Result<PathBuf> {
+    Self::named_path(options, "multisig", "multisig", name)
+  }
+
+  fn named_path(options: &Options, directory: &str, kind: &str, name: &str) -> Result<PathBuf> {
+    ensure!(
+      !name.is_empty()
+        && name
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+      "{kind} name `{name}` may only contain letters, digits, `-`, and `_`"
+    );
+
+    Ok(
+      options
+        .data_dir()?
+        .join(directory)
+        .join(format!("{name}.json")),
+    )
+  }
+
+  /// Outputs which no wallet command may spend, stored in the data directory
+  /// under the wallet's name.
+  pub(crate) fn frozen_outputs(options: &Options) -> Result<BTreeSet<OutPoint>> {
+    let path = Self::frozen_outputs_path(options)?;
+
+    if !path.exists() {
+      return Ok(BTreeSet::new());
+    }
+
+    serde_json::from_slice(&fs::read(&path)?)
+      .with_context(|| format!("failed to parse frozen outputs at {}", path.display()))
+  }
+
+  pub(crate) fn save_frozen_outputs(options: &Options, frozen: &BTreeSet<OutPoint>) -> Result {
+    let path = Self::frozen_outputs_path(options)?;
+
+    fs::create_dir_all(path.parent().unwrap())?;
+
+    fs::write(&path, serde_json::to_vec_pretty(frozen)?)
+      .with_context(|| format!("failed to write frozen outputs to {}", path.display()))
+  }
+
+  fn frozen_outputs_path(options: &Options) -> Result<PathBuf> {
+    Ok(
+      options
+        .data_dir()?
+        .join("wallets")
+        .join(&options.wallet)
+        .join("frozen.json"),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, bitcoin::util::bip32::ExtendedPrivKey};
+
+  fn xpub() -> ExtendedPubKey {
+    ExtendedPubKey::from_priv(
+      &Secp256k1::new(),
+      &ExtendedPrivKey::new_master(Network::Bitcoin, &[1; 32]).unwrap(),
+    )
+  }
+
+  #[test]
+  fn xpub_addresses_are_derived_from_receive_and_change_chains() {
+    let group = WatchOnlyGroup {
+      addresses: [address()].into(),
+      xpubs: [xpub()].into(),
+    };
+
+    let addresses = group.addresses(Network::Bitcoin).unwrap();
+
+    assert_eq!(
+      addresses.len(),
+      1 + 2 * usize::try_from(WatchOnlyGroup::XPUB_ADDRESSES).unwrap()
+    );
+
+    assert!(addresses.contains(&address()));
+
+    for chain in 0..2 {
+      let child = xpub()
+        .derive_pub(
+          &Secp256k1::new(),
+          &[
+            ChildNumber::from_normal_idx(chain).unwrap(),
+            ChildNumber::from_normal_idx(0).unwrap(),
+          ],
+        )
+        .unwrap();
+
+      assert!(addresses.contains(&Address::p2pkh(&child.to_pub(), Network::Bitcoin)));
+    }
+  }
+
+  #[test]
+  fn group_names_cannot_escape_data_dir() {
+    let options = Options {
+      data_dir: Some("data".into()),
+      ..Default::default()
+    };
+
+    assert_eq!(
+      Wallet::watch_only_group(&options, "../cold")
+        .unwrap_err()
+        .to_string(),
+      "watch-only group name `../cold` may only contain letters, digits, `-`, and `_`"
+    );
   }
 }
@@ -28,6 +28,16 @@ pub trait Api {
   #[rpc(name = "getblockcount")]
   fn get_block_count(&self) -> Result<u64, jsonrpc_core::Error>;
 
+  #[rpc(name = "estimatesmartfee")]
+  fn estimate_smart_fee(
+    &self,
+    conf_target: u16,
+    estimate_mode: Option<String>,
+  ) -> Result<Value, jsonrpc_core::Error>;
+
+  #[rpc(name = "estimatefee")]
+  fn estimate_fee(&self, nblocks: u16) -> Result<f64, jsonrpc_core::Error>;
+
   #[rpc(name = "getwalletinfo")]
   fn get_wallet_info(&self) -> Result<GetWalletInfoResult, jsonrpc_core::Error>;
 
@@ -54,8 +64,16 @@ pub trait Api {
   fn sign_raw_transaction_with_wallet(
     &self,
     tx: String,
-    utxos: Option<()>,
-    sighash_type: Option<()>,
+    utxos: Option<Vec<Value>>,
+    sighash_type: Option<String>,
+  ) -> Result<Value, jsonrpc_core::Error>;
+
+  #[rpc(name = "fundrawtransaction")]
+  fn fund_raw_transaction(
+    &self,
+    tx: String,
+    options: Option<Value>,
+    is_witness: Option<bool>,
   ) -> Result<Value, jsonrpc_core::Error>;
 
   #[rpc(name = "sendrawtransaction")]
@@ -99,6 +117,9 @@ pub trait Api {
     query_options: Option<String>,
   ) -> Result<Vec<ListUnspentResultEntry>, jsonrpc_core::Error>;
 
+  #[rpc(name = "getmempoolentry")]
+  fn get_mempool_entry(&self, txid: Txid) -> Result<Value, jsonrpc_core::Error>;
+
   #[rpc(name = "listlockunspent")]
   fn list_lock_unspent(&self) -> Result<Vec<JsonOutPoint>, jsonrpc_core::Error>;
 
@@ -108,6 +129,12 @@ pub trait Api {
     address_type: Option<bitcoincore_rpc::json::AddressType>,
   ) -> Result<bitcoin::Address, jsonrpc_core::Error>;
 
+  #[rpc(name = "getaddressinfo")]
+  fn get_address_info(&self, address: String) -> Result<Value, jsonrpc_core::Error>;
+
+  #[rpc(name = "signmessage")]
+  fn sign_message(&self, address: String, message: String) -> Result<String, jsonrpc_core::Error>;
+
   #[rpc(name = "getdescriptorinfo")]
   fn get_descriptor_info(
     &self,
@@ -143,6 +170,9 @@ pub trait Api {
     outputs: Vec<JsonOutPoint>,
   ) -> Result<bool, jsonrpc_core::Error>;
 
+  #[rpc(name = "listaddressgroupings")]
+  fn list_address_groupings(&self) -> Result<Value, jsonrpc_core::Error>;
+
   #[rpc(name = "listdescriptors")]
   fn list_descriptors(&self) -> Result<ListDescriptorsResult, jsonrpc_core::Error>;
 
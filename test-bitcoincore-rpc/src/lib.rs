@@ -8,9 +8,10 @@ use {
     consensus::encode::{deserialize, serialize},
     hash_types::BlockHash,
     hashes::Hash,
+    secp256k1::{Secp256k1, SecretKey},
     util::amount::SignedAmount,
-    Address, Amount, Block, BlockHeader, Network, OutPoint, PackedLockTime, Script, Sequence,
-    Transaction, TxIn, TxMerkleNode, TxOut, Txid, Witness, Wtxid,
+    Address, Amount, Block, BlockHeader, Network, OutPoint, PackedLockTime, PrivateKey, Script,
+    Sequence, Transaction, TxIn, TxMerkleNode, TxOut, Txid, Witness, Wtxid,
   },
   bitcoincore_rpc::json::{
     Bip125Replaceable, CreateRawTransactionInput, Descriptor, EstimateMode, GetBalancesResult,
@@ -39,19 +40,32 @@ mod state;
 
 pub fn builder() -> Builder {
   Builder {
+    estimate_smart_fee: true,
     fail_lock_unspent: false,
+    fee_estimate: None,
     network: Network::Bitcoin,
+    relay_fee: Amount::ZERO,
     version: 1146000,
   }
 }
 
 pub struct Builder {
+  estimate_smart_fee: bool,
   fail_lock_unspent: bool,
+  fee_estimate: Option<Amount>,
   network: Network,
+  relay_fee: Amount,
   version: usize,
 }
 
 impl Builder {
+  pub fn estimate_smart_fee(self, estimate_smart_fee: bool) -> Self {
+    Self {
+      estimate_smart_fee,
+      ..self
+    }
+  }
+
   pub fn fail_lock_unspent(self, fail_lock_unspent: bool) -> Self {
     Self {
       fail_lock_unspent,
@@ -59,10 +73,21 @@ impl Builder {
     }
   }
 
+  pub fn fee_estimate(self, fee_estimate: Amount) -> Self {
+    Self {
+      fee_estimate: Some(fee_estimate),
+      ..self
+    }
+  }
+
   pub fn network(self, network: Network) -> Self {
     Self { network, ..self }
   }
 
+  pub fn relay_fee(self, relay_fee: Amount) -> Self {
+    Self { relay_fee, ..self }
+  }
+
   pub fn version(self, version: usize) -> Self {
     Self { version, ..self }
   }
@@ -71,7 +96,10 @@ impl Builder {
     let state = Arc::new(Mutex::new(State::new(
       self.network,
       self.version,
+      self.estimate_smart_fee,
       self.fail_lock_unspent,
+      self.fee_estimate,
+      self.relay_fee,
     )));
     let server = Server::new(state.clone());
     let mut io = IoHandler::default();
@@ -186,7 +214,12 @@ impl Handle {
   }
 
   pub fn broadcast_tx(&self, template: TransactionTemplate) -> Txid {
-    self.state().broadcast_tx(template)
+    self.state().broadcast_tx(template, Script::new())
+  }
+
+  /// Like `broadcast_tx`, but with outputs paying to `address`.
+  pub fn broadcast_tx_to(&self, template: TransactionTemplate, address: &Address) -> Txid {
+    self.state().broadcast_tx(template, address.script_pubkey())
   }
 
   pub fn invalidate_tip(&self) -> BlockHash {
@@ -222,6 +255,18 @@ impl Handle {
     self.state().locked.insert(output);
   }
 
+  pub fn locked(&self) -> BTreeSet<OutPoint> {
+    self.state().locked.clone()
+  }
+
+  pub fn legacy_address(&self) -> Address {
+    self.state().new_legacy_address()
+  }
+
+  pub fn import_private_key(&self, key: PrivateKey) {
+    self.state().import_private_key(key);
+  }
+
   pub fn network(&self) -> String {
     match self.state().network {
       Network::Bitcoin => "mainnet".to_string(),
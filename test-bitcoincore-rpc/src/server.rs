@@ -2,8 +2,8 @@ use {
   super::*,
   bitcoin::{
     psbt::serialize::Deserialize,
-    secp256k1::{rand, KeyPair, Secp256k1, XOnlyPublicKey},
-    Address, Witness,
+    secp256k1::{rand, KeyPair, Message, Secp256k1, XOnlyPublicKey},
+    Address, EcdsaSig, EcdsaSighashType, Witness,
   },
   bitcoincore_rpc::RawTx,
 };
@@ -83,7 +83,7 @@ impl Api for Server {
       connections_out: None,
       network_active: true,
       networks: Vec::new(),
-      relay_fee: Amount::from_sat(0),
+      relay_fee: self.state().relay_fee,
       incremental_fee: Amount::from_sat(0),
       local_addresses: Vec::new(),
       warnings: String::new(),
@@ -161,6 +161,39 @@ impl Api for Server {
     )
   }
 
+  fn estimate_smart_fee(
+    &self,
+    conf_target: u16,
+    estimate_mode: Option<String>,
+  ) -> Result<Value, jsonrpc_core::Error> {
+    assert_eq!(estimate_mode, None, "estimate_mode param not supported");
+
+    if !self.state().estimate_smart_fee {
+      return Err(jsonrpc_core::Error::method_not_found());
+    }
+
+    Ok(match self.state().fee_estimate {
+      Some(fee_estimate) => serde_json::json!({
+        "feerate": fee_estimate.to_btc(),
+        "blocks": conf_target,
+      }),
+      None => serde_json::json!({
+        "errors": ["Insufficient data or no feerate found"],
+        "blocks": 0,
+      }),
+    })
+  }
+
+  fn estimate_fee(&self, _nblocks: u16) -> Result<f64, jsonrpc_core::Error> {
+    Ok(
+      self
+        .state()
+        .fee_estimate
+        .map(|fee_estimate| fee_estimate.to_btc())
+        .unwrap_or(-1.0),
+    )
+  }
+
   fn get_wallet_info(&self) -> Result<GetWalletInfoResult, jsonrpc_core::Error> {
     if let Some(wallet_name) = self.state().loaded_wallets.first().cloned() {
       Ok(GetWalletInfoResult {
@@ -237,15 +270,129 @@ impl Api for Server {
   fn sign_raw_transaction_with_wallet(
     &self,
     tx: String,
-    utxos: Option<()>,
-    sighash_type: Option<()>,
+    utxos: Option<Vec<Value>>,
+    sighash_type: Option<String>,
   ) -> Result<Value, jsonrpc_core::Error> {
-    assert_eq!(utxos, None, "utxos param not supported");
-    assert_eq!(sighash_type, None, "sighash_type param not supported");
+    // Only the redeem scripts of pay-to-script-hash outputs are read from the
+    // utxos param, the rest is looked up in the chain
+    let redeem_scripts = utxos
+      .unwrap_or_default()
+      .into_iter()
+      .filter_map(|utxo| {
+        let outpoint = OutPoint {
+          txid: utxo["txid"].as_str().unwrap().parse().unwrap(),
+          vout: utxo["vout"].as_u64().unwrap().try_into().unwrap(),
+        };
+
+        let redeem_script = Script::from(hex::decode(utxo["redeemScript"].as_str()?).unwrap());
+
+        Some((outpoint, redeem_script))
+      })
+      .collect::<BTreeMap<OutPoint, Script>>();
+
+    // The last byte of the dummy signature is the sighash type, so callers
+    // can tell which one was requested. It is left at zero when none is
+    // given, to keep the txids of existing transactions stable.
+    let sighash_type = match sighash_type.as_deref() {
+      None => 0,
+      Some("ALL") => 0x01,
+      Some("NONE") => 0x02,
+      Some("SINGLE") => 0x03,
+      Some("ALL|ANYONECANPAY") => 0x81,
+      Some("NONE|ANYONECANPAY") => 0x82,
+      Some("SINGLE|ANYONECANPAY") => 0x83,
+      Some(sighash_type) => panic!("unknown sighash type: {sighash_type}"),
+    };
+
+    let mut signature = [0; 72];
+    signature[71] = sighash_type;
+
+    let state = self.state();
 
     let mut transaction = Transaction::deserialize(&hex::decode(tx).unwrap()).unwrap();
-    for input in &mut transaction.input {
-      input.witness = Witness::from_vec(vec![vec![0; 64]]);
+    for i in 0..transaction.input.len() {
+      let input = &transaction.input[i];
+
+      // Inputs which are already signed, or which spend nothing, cannot be
+      // signed by the wallet
+      if !input.script_sig.is_empty() || input.previous_output.is_null() {
+        continue;
+      }
+
+      // Multisig inputs are signed with whichever of the redeem script's keys
+      // are in the wallet, leaving them partially signed if that is fewer
+      // than the threshold
+      if let Some(redeem_script) = redeem_scripts.get(&input.previous_output) {
+        let hash_ty = EcdsaSighashType::from_consensus(u32::from(sighash_type.max(0x01)));
+
+        let sighash = transaction.signature_hash(i, redeem_script, hash_ty.to_u32());
+
+        let secp256k1 = Secp256k1::new();
+
+        let mut builder = script::Builder::new().push_int(0);
+
+        for instruction in redeem_script.instructions() {
+          let Ok(script::Instruction::PushBytes(public_key)) = instruction else {
+            continue;
+          };
+
+          let Some(key) = state
+            .keys
+            .values()
+            .find(|key| key.public_key(&secp256k1).to_bytes() == public_key)
+          else {
+            continue;
+          };
+
+          let signature = EcdsaSig {
+            sig: secp256k1.sign_ecdsa(&Message::from_slice(&sighash[..]).unwrap(), &key.inner),
+            hash_ty,
+          };
+
+          builder = builder.push_slice(&signature.to_vec());
+        }
+
+        transaction.input[i].script_sig =
+          builder.push_slice(redeem_script.as_bytes()).into_script();
+
+        continue;
+      }
+
+      let script_pubkey = state
+        .transaction(input.previous_output.txid)
+        .and_then(|tx| {
+          tx.output
+            .get(usize::try_from(input.previous_output.vout).unwrap())
+        })
+        .map(|output| output.script_pubkey.clone())
+        .unwrap_or_default();
+
+      // Inputs spending legacy addresses get a real signature, so that it
+      // can be verified
+      let script_sig = if let Some(key) = state.keys.get(&script_pubkey) {
+        let hash_ty = EcdsaSighashType::from_consensus(u32::from(sighash_type.max(0x01)));
+
+        let sighash = transaction.signature_hash(i, &script_pubkey, hash_ty.to_u32());
+
+        let secp256k1 = Secp256k1::new();
+
+        let signature = EcdsaSig {
+          sig: secp256k1.sign_ecdsa(&Message::from_slice(&sighash[..]).unwrap(), &key.inner),
+          hash_ty,
+        };
+
+        script::Builder::new()
+          .push_slice(&signature.to_vec())
+          .push_key(&key.public_key(&secp256k1))
+          .into_script()
+      } else {
+        script::Builder::new()
+          .push_slice(&signature)
+          .push_slice(&[0; 33])
+          .into_script()
+      };
+
+      transaction.input[i].script_sig = script_sig;
     }
 
     Ok(
@@ -258,9 +405,121 @@ impl Api for Server {
     )
   }
 
+  fn fund_raw_transaction(
+    &self,
+    tx: String,
+    options: Option<Value>,
+    _is_witness: Option<bool>,
+  ) -> Result<Value, jsonrpc_core::Error> {
+    let mut transaction: Transaction = deserialize(&hex::decode(tx).unwrap()).unwrap();
+
+    let options = options.unwrap_or_default();
+
+    let fee_rate = options
+      .get("feeRate")
+      .and_then(Value::as_f64)
+      .map(|fee_rate| Amount::from_btc(fee_rate).unwrap().to_sat())
+      .unwrap_or(1000);
+
+    let mut state = self.state();
+
+    let spent = state
+      .mempool
+      .iter()
+      .flat_map(|tx| tx.input.iter().map(|txin| txin.previous_output))
+      .collect::<BTreeSet<OutPoint>>();
+
+    let value = |state: &State, outpoint: OutPoint| {
+      state.transaction(outpoint.txid).unwrap().output[usize::try_from(outpoint.vout).unwrap()]
+        .value
+    };
+
+    // Signed inputs carry a 72 byte signature and a 33 byte public key
+    let fee = |transaction: &Transaction| {
+      (u64::try_from(transaction.vsize() + transaction.input.len() * 107).unwrap() + 43) * fee_rate
+        / 1000
+    };
+
+    let mut input_value = transaction
+      .input
+      .iter()
+      .map(|txin| value(&state, txin.previous_output))
+      .sum::<u64>();
+
+    let output_value = transaction
+      .output
+      .iter()
+      .map(|txout| txout.value)
+      .sum::<u64>();
+
+    let candidates = state
+      .utxos
+      .keys()
+      .filter(|outpoint| {
+        !state.locked.contains(outpoint)
+          && !spent.contains(outpoint)
+          && !transaction
+            .input
+            .iter()
+            .any(|txin| txin.previous_output == **outpoint)
+      })
+      .copied()
+      .collect::<Vec<OutPoint>>();
+
+    for outpoint in candidates {
+      if input_value >= output_value + fee(&transaction) {
+        break;
+      }
+
+      transaction.input.push(TxIn {
+        previous_output: outpoint,
+        script_sig: Script::new(),
+        sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+        witness: Witness::new(),
+      });
+
+      input_value += value(&state, outpoint);
+    }
+
+    let fee = fee(&transaction);
+
+    if input_value < output_value + fee {
+      return Err(jsonrpc_core::Error::new(
+        jsonrpc_core::types::error::ErrorCode::ServerError(-4),
+      ));
+    }
+
+    let change_position = options
+      .get("changePosition")
+      .and_then(Value::as_u64)
+      .map(|position| usize::try_from(position).unwrap())
+      .unwrap_or(transaction.output.len());
+
+    let secp256k1 = Secp256k1::new();
+    let key_pair = KeyPair::new(&secp256k1, &mut rand::thread_rng());
+    let (public_key, _parity) = XOnlyPublicKey::from_keypair(&key_pair);
+    let change = Address::p2tr(&secp256k1, public_key, None, self.network).script_pubkey();
+
+    state.addresses.insert(change.clone());
+
+    transaction.output.insert(
+      change_position,
+      TxOut {
+        value: input_value - output_value - fee,
+        script_pubkey: change,
+      },
+    );
+
+    Ok(serde_json::json!({
+      "hex": transaction.raw_hex(),
+      "fee": Amount::from_sat(fee).to_btc(),
+      "changepos": change_position,
+    }))
+  }
+
   fn send_raw_transaction(&self, tx: String) -> Result<String, jsonrpc_core::Error> {
     let tx: Transaction = deserialize(&hex::decode(tx).unwrap()).unwrap();
-    self.state.lock().unwrap().mempool.push(tx.clone());
+    self.state.lock().unwrap().accept_to_mempool(tx.clone());
 
     Ok(tx.txid().to_string())
   }
@@ -363,7 +622,7 @@ impl Api for Server {
         None => Err(Self::not_found()),
       }
     } else {
-      match self.state().transactions.get(&txid) {
+      match self.state().transaction(txid) {
         Some(tx) => Ok(Value::String(hex::encode(serialize(tx)))),
         None => Err(Self::not_found()),
       }
@@ -378,7 +637,10 @@ impl Api for Server {
     include_unsafe: Option<bool>,
     query_options: Option<String>,
   ) -> Result<Vec<ListUnspentResultEntry>, jsonrpc_core::Error> {
-    assert_eq!(minconf, None, "minconf param not supported");
+    assert!(
+      matches!(minconf, None | Some(0)),
+      "minconf param only supported with value 0"
+    );
     assert_eq!(maxconf, None, "maxconf param not supported");
     assert_eq!(address, None, "address param not supported");
     assert_eq!(include_unsafe, None, "include_unsafe param not supported");
@@ -386,9 +648,28 @@ impl Api for Server {
 
     let state = self.state();
 
+    let mut utxos = state.utxos.clone();
+
+    if minconf == Some(0) {
+      for tx in &state.mempool {
+        for txin in &tx.input {
+          utxos.remove(&txin.previous_output);
+        }
+
+        for (vout, txout) in tx.output.iter().enumerate() {
+          utxos.insert(
+            OutPoint {
+              txid: tx.txid(),
+              vout: vout.try_into().unwrap(),
+            },
+            Amount::from_sat(txout.value),
+          );
+        }
+      }
+    }
+
     Ok(
-      state
-        .utxos
+      utxos
         .iter()
         .filter(|(outpoint, _amount)| !state.locked.contains(outpoint))
         .map(|(outpoint, &amount)| ListUnspentResultEntry {
@@ -410,6 +691,23 @@ impl Api for Server {
     )
   }
 
+  fn get_mempool_entry(&self, txid: Txid) -> Result<Value, jsonrpc_core::Error> {
+    let state = self.state();
+
+    let Some(tx) = state.mempool.iter().find(|tx| tx.txid() == txid) else {
+      return Err(jsonrpc_core::Error::new(
+        jsonrpc_core::types::error::ErrorCode::ServerError(-5),
+      ));
+    };
+
+    Ok(serde_json::json!({
+      "size": tx.vsize(),
+      "vsize": tx.vsize(),
+      "fee": Amount::from_sat(state.fee(tx)).to_btc(),
+      "descendantcount": state.mempool_descendants([txid].into()).len(),
+    }))
+  }
+
   fn list_lock_unspent(&self) -> Result<Vec<JsonOutPoint>, jsonrpc_core::Error> {
     Ok(
       self
@@ -430,9 +728,30 @@ impl Api for Server {
     let (public_key, _parity) = XOnlyPublicKey::from_keypair(&key_pair);
     let address = Address::p2tr(&secp256k1, public_key, None, self.network);
 
+    self.state().addresses.insert(address.script_pubkey());
+
     Ok(address)
   }
 
+  fn get_address_info(&self, address: String) -> Result<Value, jsonrpc_core::Error> {
+    let script_pubkey = address
+      .parse::<Address>()
+      .map_err(|_| jsonrpc_core::Error::invalid_params("invalid address"))?
+      .script_pubkey();
+
+    Ok(serde_json::json!({
+      "address": address,
+      "scriptPubKey": hex::encode(script_pubkey.as_bytes()),
+      "ismine": self.state().addresses.contains(&script_pubkey),
+    }))
+  }
+
+  fn sign_message(&self, address: String, message: String) -> Result<String, jsonrpc_core::Error> {
+    // Not a real signature, but enough to tell which address and message
+    // were signed
+    Ok(format!("{address}:{message}"))
+  }
+
   fn get_descriptor_info(
     &self,
     desc: String,
@@ -465,13 +784,19 @@ impl Api for Server {
   fn get_new_address(
     &self,
     _label: Option<String>,
-    _address_type: Option<bitcoincore_rpc::json::AddressType>,
+    address_type: Option<bitcoincore_rpc::json::AddressType>,
   ) -> Result<bitcoin::Address, jsonrpc_core::Error> {
+    if address_type == Some(bitcoincore_rpc::json::AddressType::Legacy) {
+      return Ok(self.state().new_legacy_address());
+    }
+
     let secp256k1 = Secp256k1::new();
     let key_pair = KeyPair::new(&secp256k1, &mut rand::thread_rng());
     let (public_key, _parity) = XOnlyPublicKey::from_keypair(&key_pair);
     let address = Address::p2tr(&secp256k1, public_key, None, self.network);
 
+    self.state().addresses.insert(address.script_pubkey());
+
     Ok(address)
   }
 
@@ -479,7 +804,7 @@ impl Api for Server {
     &self,
     _label: Option<String>,
     count: Option<u16>,
-    _skip: Option<usize>,
+    skip: Option<usize>,
     _include_watchonly: Option<bool>,
   ) -> Result<Vec<ListTransactionResult>, jsonrpc_core::Error> {
     let state = self.state();
@@ -487,9 +812,10 @@ impl Api for Server {
       state
         .transactions
         .iter()
-        .take(count.unwrap_or(u16::MAX).into())
         .map(|(txid, tx)| (*txid, tx))
         .chain(state.mempool.iter().map(|tx| (tx.txid(), tx)))
+        .skip(skip.unwrap_or_default())
+        .take(count.unwrap_or(u16::MAX).into())
         .map(|(txid, tx)| ListTransactionResult {
           info: WalletTxInfo {
             confirmations: state.get_confirmations(tx),
@@ -524,8 +850,6 @@ impl Api for Server {
     unlock: bool,
     outputs: Vec<JsonOutPoint>,
   ) -> Result<bool, jsonrpc_core::Error> {
-    assert!(!unlock);
-
     let mut state = self.state();
 
     if state.fail_lock_unspent {
@@ -537,13 +861,56 @@ impl Api for Server {
         vout: output.vout,
         txid: output.txid,
       };
-      assert!(state.utxos.contains_key(&output));
-      state.locked.insert(output);
+
+      if unlock {
+        assert!(state.locked.remove(&output));
+      } else {
+        assert!(state.utxos.contains_key(&output) || state.transaction(output.txid).is_some());
+        state.locked.insert(output);
+      }
     }
 
     Ok(true)
   }
 
+  fn list_address_groupings(&self) -> Result<Value, jsonrpc_core::Error> {
+    let state = self.state();
+
+    let mut balances = BTreeMap::<Script, Amount>::new();
+
+    for tx in state.transactions.values().chain(state.mempool()) {
+      for tx_out in &tx.output {
+        if state.addresses.contains(&tx_out.script_pubkey) {
+          balances.entry(tx_out.script_pubkey.clone()).or_default();
+        }
+      }
+    }
+
+    for (outpoint, amount) in &state.utxos {
+      let Some(tx) = state.transaction(outpoint.txid) else {
+        continue;
+      };
+
+      if let Some(balance) =
+        balances.get_mut(&tx.output[usize::try_from(outpoint.vout).unwrap()].script_pubkey)
+      {
+        *balance += *amount;
+      }
+    }
+
+    // Every address which has received an output is reported, in a single
+    // grouping
+    Ok(serde_json::json!([balances
+      .into_iter()
+      .map(|(script_pubkey, balance)| serde_json::json!([
+        Address::from_script(&script_pubkey, self.network)
+          .unwrap()
+          .to_string(),
+        balance.to_btc(),
+      ]))
+      .collect::<Vec<Value>>()]))
+  }
+
   fn list_descriptors(&self) -> Result<ListDescriptorsResult, jsonrpc_core::Error> {
     Ok(ListDescriptorsResult {
       wallet_name: "ord".into(),
@@ -1,14 +1,19 @@
 use super::*;
 
 pub(crate) struct State {
+  pub(crate) addresses: BTreeSet<Script>,
   pub(crate) blocks: BTreeMap<BlockHash, Block>,
   pub(crate) descriptors: Vec<String>,
+  pub(crate) estimate_smart_fee: bool,
   pub(crate) fail_lock_unspent: bool,
+  pub(crate) fee_estimate: Option<Amount>,
   pub(crate) hashes: Vec<BlockHash>,
+  pub(crate) keys: BTreeMap<Script, PrivateKey>,
   pub(crate) locked: BTreeSet<OutPoint>,
   pub(crate) mempool: Vec<Transaction>,
   pub(crate) network: Network,
   pub(crate) nonce: u32,
+  pub(crate) relay_fee: Amount,
   pub(crate) sent: Vec<Sent>,
   pub(crate) transactions: BTreeMap<Txid, Transaction>,
   pub(crate) utxos: BTreeMap<OutPoint, Amount>,
@@ -18,7 +23,14 @@ pub(crate) struct State {
 }
 
 impl State {
-  pub(crate) fn new(network: Network, version: usize, fail_lock_unspent: bool) -> Self {
+  pub(crate) fn new(
+    network: Network,
+    version: usize,
+    estimate_smart_fee: bool,
+    fail_lock_unspent: bool,
+    fee_estimate: Option<Amount>,
+    relay_fee: Amount,
+  ) -> Self {
     let mut hashes = Vec::new();
     let mut blocks = BTreeMap::new();
 
@@ -36,14 +48,19 @@ impl State {
     blocks.insert(genesis_block_hash, genesis_block);
 
     Self {
+      addresses: BTreeSet::new(),
       blocks,
       descriptors: Vec::new(),
+      estimate_smart_fee,
       fail_lock_unspent,
+      fee_estimate,
       hashes,
+      keys: BTreeMap::new(),
       locked: BTreeSet::new(),
       mempool: Vec::new(),
       network,
       nonce: 0,
+      relay_fee,
       sent: Vec::new(),
       transactions: BTreeMap::new(),
       utxos: BTreeMap::new(),
@@ -136,7 +153,11 @@ impl State {
     blockhash
   }
 
-  pub(crate) fn broadcast_tx(&mut self, template: TransactionTemplate) -> Txid {
+  pub(crate) fn broadcast_tx(
+    &mut self,
+    template: TransactionTemplate,
+    script_pubkey: Script,
+  ) -> Txid {
     let mut total_value = 0;
     let mut input = Vec::new();
     for (i, (height, tx, vout)) in template.inputs.iter().enumerate() {
@@ -171,7 +192,7 @@ impl State {
             .get(i)
             .cloned()
             .unwrap_or(value_per_output),
-          script_pubkey: script::Builder::new().into_script(),
+          script_pubkey: script_pubkey.clone(),
         })
         .collect(),
     };
@@ -184,6 +205,97 @@ impl State {
     &self.mempool
   }
 
+  /// A new p2pkh address, whose key is kept so that inputs spending it can
+  /// be signed for real.
+  pub(crate) fn new_legacy_address(&mut self) -> Address {
+    let secp256k1 = Secp256k1::new();
+    let key = PrivateKey::new(
+      SecretKey::new(&mut bitcoin::secp256k1::rand::thread_rng()),
+      self.network,
+    );
+    let address = Address::p2pkh(&key.public_key(&secp256k1), self.network);
+
+    self.addresses.insert(address.script_pubkey());
+    self.keys.insert(address.script_pubkey(), key);
+
+    address
+  }
+
+  /// Adds `key` to the wallet, so that it can sign inputs spending its p2pkh
+  /// address, or multisig inputs whose redeem scripts include it.
+  pub(crate) fn import_private_key(&mut self, key: PrivateKey) {
+    let address = Address::p2pkh(&key.public_key(&Secp256k1::new()), self.network);
+
+    self.keys.insert(address.script_pubkey(), key);
+  }
+
+  pub(crate) fn transaction(&self, txid: Txid) -> Option<&Transaction> {
+    self
+      .transactions
+      .get(&txid)
+      .or_else(|| self.mempool.iter().find(|tx| tx.txid() == txid))
+  }
+
+  pub(crate) fn fee(&self, tx: &Transaction) -> u64 {
+    tx.input
+      .iter()
+      .map(|txin| {
+        self.transaction(txin.previous_output.txid).unwrap().output
+          [txin.previous_output.vout as usize]
+          .value
+      })
+      .sum::<u64>()
+      - tx.output.iter().map(|txout| txout.value).sum::<u64>()
+  }
+
+  /// Mempool transactions which spend outputs of `txids`, directly or
+  /// indirectly, including `txids` themselves.
+  pub(crate) fn mempool_descendants(&self, txids: BTreeSet<Txid>) -> BTreeSet<Txid> {
+    let mut descendants = txids;
+
+    loop {
+      let before = descendants.len();
+
+      for tx in &self.mempool {
+        if tx
+          .input
+          .iter()
+          .any(|txin| descendants.contains(&txin.previous_output.txid))
+        {
+          descendants.insert(tx.txid());
+        }
+      }
+
+      if descendants.len() == before {
+        return descendants;
+      }
+    }
+  }
+
+  /// Adds `tx` to the mempool, evicting any transactions it replaces along
+  /// with their descendants.
+  pub(crate) fn accept_to_mempool(&mut self, tx: Transaction) {
+    let conflicts = self
+      .mempool
+      .iter()
+      .filter(|mempool_tx| {
+        mempool_tx.input.iter().any(|mempool_txin| {
+          tx.input
+            .iter()
+            .any(|txin| txin.previous_output == mempool_txin.previous_output)
+        })
+      })
+      .map(Transaction::txid)
+      .collect();
+
+    let evicted = self.mempool_descendants(conflicts);
+
+    self
+      .mempool
+      .retain(|mempool_tx| !evicted.contains(&mempool_tx.txid()));
+    self.mempool.push(tx);
+  }
+
   pub(crate) fn get_confirmations(&self, tx: &Transaction) -> i32 {
     for (confirmations, hash) in self.hashes.iter().rev().enumerate() {
       if self.blocks.get(hash).unwrap().txdata.contains(tx) {
@@ -50,7 +50,9 @@ impl CommandBuilder {
   }
 
   pub(crate) fn write(self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Self {
-    fs::write(self.tempdir.path().join(path), contents).unwrap();
+    let path = self.tempdir.path().join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
     self
   }
 
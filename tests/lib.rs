@@ -82,5 +82,6 @@ mod server;
 mod subsidy;
 mod test_server;
 mod traits;
+mod verify_message;
 mod version;
 mod wallet;
@@ -60,7 +60,7 @@ fn inscription_page() {
   <dt>address</dt>
   <dd class=monospace>bc1.*</dd>
   <dt>output value</dt>
-  <dd>10000</dd>
+  <dd>100000</dd>
   <dt>preview</dt>
   <dd><a href=/preview/{inscription}>link</a></dd>
   <dt>content</dt>
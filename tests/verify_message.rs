@@ -0,0 +1,52 @@
+use {super::*, ord::subcommand::verify_message::Output};
+
+// Signature over "hello world" by the key of `PUBLIC_KEY`, in the format
+// produced by craftcoind's `signmessage`
+const PUBLIC_KEY: &str = "0372dba3238bc653f749cf19b94aa7310e1c87fe89211fa93d747d53de5fd1bba5";
+const SIGNATURE: &str =
+  "IM6c+vGEf80QvsolvIbs8C4g7881EVSEyTVTh+Rv+dy+YaNuRdSMuS3nToU14GghIwOFOXFREbg1gv5Tz/Ymids=";
+
+fn signer() -> bitcoin::Address {
+  bitcoin::Address::p2pkh(&PUBLIC_KEY.parse().unwrap(), Network::Bitcoin)
+}
+
+fn verify_message(message: &str) -> CommandBuilder {
+  CommandBuilder::new(vec![
+    "verify-message".into(),
+    "--address".into(),
+    signer().to_string(),
+    "--signature".into(),
+    SIGNATURE.into(),
+    message.into(),
+  ])
+}
+
+#[test]
+fn valid_signature() {
+  assert_eq!(
+    verify_message("hello world").output::<Output>(),
+    Output {
+      address: signer(),
+      public_key: PUBLIC_KEY.into(),
+    }
+  );
+}
+
+#[test]
+fn signature_over_other_message() {
+  verify_message("goodbye world")
+    .expected_stderr(format!("error: message was not signed by {}\n", signer()))
+    .expected_exit_code(1)
+    .run();
+}
+
+#[test]
+fn malformed_signature() {
+  CommandBuilder::new(format!(
+    "verify-message --address {} --signature Zm9v hello",
+    signer()
+  ))
+  .expected_stderr("error: signature is malformed\n")
+  .expected_exit_code(1)
+  .run();
+}
@@ -1,12 +1,23 @@
 use super::*;
 
 mod balance;
+mod bump;
+mod consolidate;
+mod crc20;
 mod create;
+mod freeze;
 mod inscribe;
 mod inscriptions;
+mod multisig;
+mod offer;
 mod outputs;
 mod receive;
 mod restore;
 mod sats;
 mod send;
+mod send_cunes;
+mod send_inscriptions;
+mod sign_message;
+mod split;
 mod transactions;
+mod watch_only;
@@ -0,0 +1,192 @@
+use {
+  super::*,
+  ord::subcommand::wallet::{bump::Output, inscriptions, split},
+};
+
+#[test]
+fn bump_replaces_transaction() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let split = CommandBuilder::new("wallet split --fee-rate 1 --outputs 2 --value 1btc")
+    .rpc_server(&rpc_server)
+    .output::<split::Output>();
+
+  let output = CommandBuilder::new(format!("wallet bump {} --fee-rate 10", split.transaction))
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert!(!output.cpfp);
+  assert_eq!(output.original, split.transaction);
+  assert!(output.fee > split.fee);
+
+  let mempool = rpc_server.mempool();
+  assert_eq!(mempool.len(), 1);
+  assert_eq!(mempool[0].txid(), output.transaction);
+  assert_eq!(mempool[0].input.len(), 1);
+  assert_eq!(mempool[0].output.len(), 3);
+  assert!(mempool[0].output[..2]
+    .iter()
+    .all(|tx_out| tx_out.value == COIN_VALUE));
+  assert_eq!(
+    mempool[0].output[2].value + output.fee,
+    split.change.unwrap() + split.fee
+  );
+}
+
+#[test]
+fn bump_refuses_lower_fee_rate() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let split = CommandBuilder::new("wallet split --fee-rate 10 --outputs 2 --value 1btc")
+    .rpc_server(&rpc_server)
+    .output::<split::Output>();
+
+  CommandBuilder::new(format!("wallet bump {} --fee-rate 5", split.transaction))
+    .rpc_server(&rpc_server)
+    .stderr_regex(
+      "error: fee rate of 5 sats/vB does not exceed the original fee rate of .* sats/vB\n",
+    )
+    .expected_exit_code(1)
+    .run();
+
+  assert_eq!(rpc_server.mempool()[0].txid(), split.transaction);
+}
+
+#[test]
+fn bump_requires_unconfirmed_transaction() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let split = CommandBuilder::new("wallet split --fee-rate 1 --outputs 2 --value 1btc")
+    .rpc_server(&rpc_server)
+    .output::<split::Output>();
+
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new(format!("wallet bump {} --fee-rate 10", split.transaction))
+    .rpc_server(&rpc_server)
+    .expected_stderr(format!(
+      "error: transaction {} is not in the mempool\n",
+      split.transaction
+    ))
+    .expected_exit_code(1)
+    .run();
+}
+
+#[test]
+fn bump_refuses_to_replace_commit_with_pending_reveal() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let Inscribe { commit, .. } = CommandBuilder::new("wallet inscribe foo.txt --fee-rate 1")
+    .write("foo.txt", "FOO")
+    .rpc_server(&rpc_server)
+    .output::<Inscribe>();
+
+  CommandBuilder::new(format!("wallet bump {commit} --fee-rate 10"))
+    .rpc_server(&rpc_server)
+    .expected_stderr(format!(
+      "error: transaction {commit} has unconfirmed descendants which would be evicted by a replacement, use `--cpfp` to bump it with a child transaction\n"
+    ))
+    .expected_exit_code(1)
+    .run();
+
+  assert_eq!(rpc_server.mempool().len(), 2);
+}
+
+#[test]
+fn bump_reveal_with_child_transaction() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+  rpc_server.mine_blocks_with_subsidy(1, 100 * COIN_VALUE);
+
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = CommandBuilder::new(format!(
+    "wallet inscribe foo.txt --satpoint {coinbase}:0:0 --fee-rate 1"
+  ))
+  .write("foo.txt", "FOO")
+  .rpc_server(&rpc_server)
+  .output::<Inscribe>();
+
+  let output = CommandBuilder::new(format!("wallet bump {reveal} --fee-rate 10 --cpfp"))
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert!(output.cpfp);
+  assert_eq!(output.original, reveal);
+
+  let mempool = rpc_server.mempool();
+  assert_eq!(mempool.len(), 3);
+
+  let child = &mempool[2];
+  assert_eq!(child.txid(), output.transaction);
+  assert_eq!(child.input[0].previous_output, OutPoint::new(reveal, 0));
+  assert_eq!(child.output[0], mempool[1].output[0]);
+  assert_eq!(child.output[1].value + output.fee, 100 * COIN_VALUE);
+
+  rpc_server.mine_blocks(1);
+
+  let inscriptions = CommandBuilder::new("wallet inscriptions")
+    .rpc_server(&rpc_server)
+    .output::<Vec<inscriptions::Output>>();
+
+  assert_eq!(inscriptions.len(), 1);
+  assert_eq!(inscriptions[0].inscription, inscription.parse().unwrap());
+  assert_eq!(
+    inscriptions[0].location,
+    format!("{}:0:0", output.transaction).parse().unwrap()
+  );
+}
+
+#[test]
+fn bump_with_child_transaction_refuses_sufficient_fee_rate() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let split = CommandBuilder::new("wallet split --fee-rate 10 --outputs 2 --value 1btc")
+    .rpc_server(&rpc_server)
+    .output::<split::Output>();
+
+  CommandBuilder::new(format!(
+    "wallet bump {} --fee-rate 5 --cpfp",
+    split.transaction
+  ))
+  .rpc_server(&rpc_server)
+  .expected_stderr(format!(
+    "error: transaction {} and its unconfirmed ancestors already pay a fee rate of at least 5 sats/vB\n",
+    split.transaction
+  ))
+  .expected_exit_code(1)
+  .run();
+}
+
+#[test]
+fn bump_refuses_to_replace_reveal() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let Inscribe { reveal, .. } = CommandBuilder::new("wallet inscribe foo.txt --fee-rate 1")
+    .write("foo.txt", "FOO")
+    .rpc_server(&rpc_server)
+    .output::<Inscribe>();
+
+  CommandBuilder::new(format!("wallet bump {reveal} --fee-rate 10"))
+    .rpc_server(&rpc_server)
+    .expected_stderr(format!(
+      "error: transaction {reveal} reveals an inscription and cannot be re-signed by the wallet, use `--cpfp` to bump it with a child transaction\n"
+    ))
+    .expected_exit_code(1)
+    .run();
+}
@@ -0,0 +1,71 @@
+use {super::*, ord::subcommand::wallet::consolidate::Output};
+
+#[test]
+fn consolidate_cardinal_utxos() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(3);
+
+  let output = CommandBuilder::new("wallet consolidate --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert_eq!(output.inputs, 3);
+  assert_eq!(output.outputs, 1);
+  assert_eq!(output.consolidated + output.fee, 150 * COIN_VALUE);
+  assert!(output.separated.is_empty());
+
+  let mempool = rpc_server.mempool();
+  assert_eq!(mempool.len(), 1);
+  assert_eq!(mempool[0].txid(), output.transaction);
+  assert_eq!(mempool[0].input.len(), 3);
+  assert_eq!(mempool[0].output.len(), 1);
+}
+
+#[test]
+fn consolidate_respects_max_inputs() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(3);
+
+  let output = CommandBuilder::new("wallet consolidate --fee-rate 1 --max-inputs 2")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert_eq!(output.inputs, 2);
+}
+
+#[test]
+fn consolidate_does_not_spend_inscribed_utxos() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let Inscribe { reveal, .. } = inscribe(&rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new("wallet consolidate --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  let mempool = rpc_server.mempool();
+  assert_eq!(mempool.len(), 1);
+  assert!(mempool[0]
+    .input
+    .iter()
+    .all(|tx_in| tx_in.previous_output.txid != reveal));
+}
+
+#[test]
+fn consolidate_requires_two_cardinal_utxos() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new("wallet consolidate --fee-rate 1")
+    .rpc_server(&rpc_server)
+    .expected_stderr("error: wallet contains fewer than two cardinal UTXOs, nothing to consolidate\n")
+    .expected_exit_code(1)
+    .run();
+}
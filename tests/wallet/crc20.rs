@@ -0,0 +1,144 @@
+use {
+  super::*,
+  ord::subcommand::wallet::crc20::{Output, TickBalance},
+};
+
+#[test]
+fn crc20_balances_of_empty_wallet() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  assert_eq!(
+    CommandBuilder::new("wallet crc20 balances")
+      .rpc_server(&rpc_server)
+      .output::<Output>(),
+    Output {
+      balances: Vec::new(),
+      transferable: Vec::new(),
+    }
+  );
+}
+
+#[test]
+fn crc20_balances_ignores_plain_inscriptions() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  inscribe(&rpc_server);
+
+  let output = CommandBuilder::new("wallet crc20 balances")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert!(output.balances.is_empty());
+  assert!(output.transferable.is_empty());
+}
+
+#[test]
+fn crc20_balances_include_addresses_without_outputs() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let holder = CommandBuilder::new("--chain regtest wallet receive")
+    .rpc_server(&rpc_server)
+    .output::<ord::subcommand::wallet::receive::Output>()
+    .address;
+
+  let inscribe = |name: &str, operation: &str| {
+    let inscription = CommandBuilder::new(format!(
+      "--chain regtest --index-cunes wallet inscribe --fee-rate 1 --destination {holder} {name}.json"
+    ))
+    .write(format!("{name}.json"), operation)
+    .rpc_server(&rpc_server)
+    .output::<Inscribe>()
+    .inscription;
+
+    rpc_server.mine_blocks(1);
+
+    inscription
+  };
+
+  let send_away = |inscription: &str| {
+    CommandBuilder::new(format!(
+      "--chain regtest --index-cunes wallet send --fee-rate 1 bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw {inscription}"
+    ))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run();
+
+    rpc_server.mine_blocks(1);
+  };
+
+  let balances = || {
+    CommandBuilder::new("--chain regtest --index-cunes wallet crc20 balances")
+      .rpc_server(&rpc_server)
+      .output::<Output>()
+  };
+
+  inscribe(
+    "deploy",
+    r#"{"p":"crc-20","op":"deploy","tick":"abcd","max":"1000","lim":"1000"}"#,
+  );
+  let mint = inscribe(
+    "mint",
+    r#"{"p":"crc-20","op":"mint","tick":"abcd","amt":"1000"}"#,
+  );
+
+  send_away(&mint);
+
+  let output = balances();
+
+  assert_eq!(
+    output.balances,
+    [TickBalance {
+      tick: "abcd".into(),
+      overall: "1000".into(),
+      transferable: "0".into(),
+      available: "1000".into(),
+    }]
+  );
+  assert!(output.transferable.is_empty());
+
+  let transfer = inscribe(
+    "transfer",
+    r#"{"p":"crc-20","op":"transfer","tick":"abcd","amt":"400"}"#,
+  );
+
+  let output = balances();
+
+  assert_eq!(
+    output.balances,
+    [TickBalance {
+      tick: "abcd".into(),
+      overall: "1000".into(),
+      transferable: "400".into(),
+      available: "600".into(),
+    }]
+  );
+  assert_eq!(output.transferable.len(), 1);
+  assert_eq!(output.transferable[0].inscription.to_string(), transfer);
+  assert_eq!(output.transferable[0].amount, "400");
+  assert_eq!(output.transferable[0].owner, holder.to_string());
+
+  send_away(&transfer);
+
+  let output = balances();
+
+  assert_eq!(
+    output.balances,
+    [TickBalance {
+      tick: "abcd".into(),
+      overall: "600".into(),
+      transferable: "0".into(),
+      available: "600".into(),
+    }]
+  );
+  assert!(output.transferable.is_empty());
+}
@@ -0,0 +1,267 @@
+use {super::*, ord::subcommand::wallet::freeze::Output};
+
+#[test]
+fn freeze_output() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  assert_eq!(
+    CommandBuilder::new(format!("wallet freeze {coinbase}:0"))
+      .rpc_server(&rpc_server)
+      .output::<Output>(),
+    Output {
+      frozen: vec![OutPoint::new(coinbase, 0)],
+    }
+  );
+}
+
+#[test]
+fn freeze_lists_frozen_outputs() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  assert_eq!(
+    CommandBuilder::new("wallet freeze")
+      .write("wallets/ord/frozen.json", format!("[\"{coinbase}:0\"]"))
+      .rpc_server(&rpc_server)
+      .output::<Output>(),
+    Output {
+      frozen: vec![OutPoint::new(coinbase, 0)],
+    }
+  );
+}
+
+#[test]
+fn freeze_requires_output_in_wallet() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new(
+    "wallet freeze 0000000000000000000000000000000000000000000000000000000000000000:0",
+  )
+  .rpc_server(&rpc_server)
+  .expected_stderr(
+    "error: output 0000000000000000000000000000000000000000000000000000000000000000:0 is not in wallet\n",
+  )
+  .expected_exit_code(1)
+  .run();
+}
+
+#[test]
+fn unfreeze_output() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  assert_eq!(
+    CommandBuilder::new(format!("wallet unfreeze {coinbase}:0"))
+      .write("wallets/ord/frozen.json", format!("[\"{coinbase}:0\"]"))
+      .rpc_server(&rpc_server)
+      .output::<Output>(),
+    Output { frozen: Vec::new() }
+  );
+}
+
+#[test]
+fn unfreeze_releases_locked_output() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  rpc_server.lock(OutPoint::new(coinbase, 0));
+
+  CommandBuilder::new(format!("wallet unfreeze {coinbase}:0"))
+    .write("wallets/ord/frozen.json", format!("[\"{coinbase}:0\"]"))
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert!(rpc_server.locked().is_empty());
+}
+
+#[test]
+fn unfreeze_requires_frozen_output() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  CommandBuilder::new(format!("wallet unfreeze {coinbase}:0"))
+    .rpc_server(&rpc_server)
+    .expected_stderr(format!("error: output {coinbase}:0 is not frozen\n"))
+    .expected_exit_code(1)
+    .run();
+}
+
+#[test]
+fn split_does_not_spend_frozen_outputs() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  CommandBuilder::new("wallet split --fee-rate 1 --outputs 2 --value 1btc")
+    .write("wallets/ord/frozen.json", format!("[\"{coinbase}:0\"]"))
+    .rpc_server(&rpc_server)
+    .expected_stderr(
+      "error: wallet does not contain enough cardinal UTXOs, please add additional funds\n",
+    )
+    .expected_exit_code(1)
+    .run();
+
+  assert!(rpc_server.mempool().is_empty());
+}
+
+#[test]
+fn send_amount_locks_frozen_outputs() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new("wallet send --fee-rate 1 bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 1btc")
+    .write("wallets/ord/frozen.json", format!("[\"{coinbase}:0\"]"))
+    .rpc_server(&rpc_server)
+    .run();
+
+  assert_eq!(
+    rpc_server.sent(),
+    &[Sent {
+      amount: 1.0,
+      address: "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        .parse()
+        .unwrap(),
+      locked: vec![OutPoint::new(coinbase, 0)],
+    }]
+  );
+
+  assert!(rpc_server.locked().is_empty());
+}
+
+#[test]
+fn inscribe_with_utxo() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  let Inscribe { commit, .. } = CommandBuilder::new(format!(
+    "wallet inscribe foo.txt --fee-rate 1 --utxo {coinbase}:0"
+  ))
+  .write("foo.txt", "FOO")
+  .rpc_server(&rpc_server)
+  .output::<Inscribe>();
+
+  let mempool = rpc_server.mempool();
+  assert_eq!(mempool[0].txid(), commit);
+  assert_eq!(
+    mempool[0]
+      .input
+      .iter()
+      .map(|input| input.previous_output)
+      .collect::<Vec<OutPoint>>(),
+    [OutPoint::new(coinbase, 0)]
+  );
+}
+
+#[test]
+fn send_inscription_with_utxo() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = inscribe(&rpc_server);
+
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  CommandBuilder::new(format!(
+    "wallet send --fee-rate 1 bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 {inscription} --utxo {coinbase}:0"
+  ))
+  .rpc_server(&rpc_server)
+  .stdout_regex(".*")
+  .run();
+
+  assert_eq!(
+    rpc_server.mempool()[0]
+      .input
+      .iter()
+      .map(|input| input.previous_output)
+      .collect::<Vec<OutPoint>>(),
+    [OutPoint::new(reveal, 0), OutPoint::new(coinbase, 0)]
+  );
+}
+
+#[test]
+fn send_amount_with_utxo() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  CommandBuilder::new(format!(
+    "wallet send --fee-rate 1 bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 1btc --utxo {coinbase}:0"
+  ))
+  .rpc_server(&rpc_server)
+  .stdout_regex(".*")
+  .run();
+
+  assert!(rpc_server.sent().is_empty());
+
+  assert_eq!(
+    rpc_server.mempool()[0]
+      .input
+      .iter()
+      .map(|input| input.previous_output)
+      .collect::<Vec<OutPoint>>(),
+    [OutPoint::new(coinbase, 0)]
+  );
+
+  assert!(rpc_server.locked().is_empty());
+}
+
+#[test]
+fn inscribe_with_excluded_utxo() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let first = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+  let second = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  let excluded = OutPoint::new(first.min(second), 0);
+
+  let Inscribe { commit, .. } = CommandBuilder::new(format!(
+    "wallet inscribe foo.txt --fee-rate 1 --exclude-utxo {excluded}"
+  ))
+  .write("foo.txt", "FOO")
+  .rpc_server(&rpc_server)
+  .output::<Inscribe>();
+
+  let mempool = rpc_server.mempool();
+  assert_eq!(mempool[0].txid(), commit);
+  assert!(mempool[0]
+    .input
+    .iter()
+    .all(|input| input.previous_output != excluded));
+}
+
+#[test]
+fn inscribe_refuses_frozen_utxo() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  CommandBuilder::new(format!(
+    "wallet inscribe foo.txt --fee-rate 1 --utxo {coinbase}:0"
+  ))
+  .write("foo.txt", "FOO")
+  .write("wallets/ord/frozen.json", format!("[\"{coinbase}:0\"]"))
+  .rpc_server(&rpc_server)
+  .expected_stderr(format!("error: output {coinbase}:0 is frozen\n"))
+  .expected_exit_code(1)
+  .run();
+
+  assert!(rpc_server.mempool().is_empty());
+}
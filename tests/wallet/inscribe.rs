@@ -154,7 +154,11 @@ fn refuse_to_reinscribe_sats() {
 
   rpc_server.mine_blocks(1);
 
-  let Inscribe { reveal, .. } = inscribe(&rpc_server);
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = inscribe(&rpc_server);
 
   rpc_server.mine_blocks_with_subsidy(1, 100);
 
@@ -162,10 +166,59 @@ fn refuse_to_reinscribe_sats() {
     .write("hello.txt", "HELLOWORLD")
     .rpc_server(&rpc_server)
     .expected_exit_code(1)
-    .expected_stderr(format!("error: sat at {reveal}:0:0 already inscribed\n"))
+    .expected_stderr(format!(
+      "error: sat at {reveal}:0:0 already inscribed with inscription {inscription}, use --reinscribe to inscribe it again\n"
+    ))
     .run();
 }
 
+#[test]
+fn reinscribe_sats_with_opt_in() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = inscribe(&rpc_server);
+
+  rpc_server.mine_blocks_with_subsidy(1, 100);
+
+  let output = CommandBuilder::new(format!(
+    "wallet inscribe --satpoint {reveal}:0:0 --reinscribe hello.txt"
+  ))
+  .write("hello.txt", "HELLOWORLD")
+  .rpc_server(&rpc_server)
+  .expected_stderr(format!(
+    "warning: reinscribing sat at {reveal}:0:0 which carries inscription {inscription}\n"
+  ))
+  .output::<serde_json::Value>();
+
+  assert_eq!(output["reinscribed"], inscription);
+}
+
+#[test]
+fn reinscribe_requires_inscribed_sat() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let txid = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  CommandBuilder::new(format!(
+    "wallet inscribe --satpoint {txid}:0:0 --reinscribe hello.txt"
+  ))
+  .write("hello.txt", "HELLOWORLD")
+  .rpc_server(&rpc_server)
+  .expected_exit_code(1)
+  .expected_stderr(format!(
+    "error: sat at {txid}:0:0 is not inscribed and cannot be reinscribed\n"
+  ))
+  .run();
+}
+
 #[test]
 fn refuse_to_inscribe_already_inscribed_utxo() {
   let rpc_server = test_bitcoincore_rpc::spawn();
@@ -299,6 +352,36 @@ fn inscribe_with_commit_fee_rate() {
   pretty_assert_eq!(fee_rate, 1.0);
 }
 
+#[test]
+fn inscribe_default_fee_rate_is_raised_to_minimum_relay_fee() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .relay_fee(bitcoin::Amount::from_sat(2_000))
+    .build();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new("wallet inscribe degenerate.png")
+    .write("degenerate.png", [1; 520])
+    .rpc_server(&rpc_server)
+    .output::<Inscribe>();
+
+  let tx1 = &rpc_server.mempool()[0];
+  let mut fee = 0;
+  for input in &tx1.input {
+    fee += rpc_server
+      .get_utxo_amount(&input.previous_output)
+      .unwrap()
+      .to_sat();
+  }
+  for output in &tx1.output {
+    fee -= output.value;
+  }
+
+  let fee_rate = fee as f64 / tx1.vsize() as f64;
+
+  pretty_assert_eq!(fee_rate, 2.0);
+}
+
 #[test]
 fn inscribe_with_wallet_named_foo() {
   let rpc_server = test_bitcoincore_rpc::spawn();
@@ -396,3 +479,20 @@ fn inscribe_with_no_limit() {
     .write("degenerate.png", four_megger)
     .rpc_server(&rpc_server);
 }
+
+#[test]
+fn inscribe_with_custom_postage() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let txid = CommandBuilder::new("wallet inscribe --postage 250000sat degenerate.png")
+    .write("degenerate.png", [1; 520])
+    .rpc_server(&rpc_server)
+    .output::<Inscribe>()
+    .reveal;
+
+  let reveal_tx = &rpc_server.mempool()[1];
+  assert_eq!(reveal_tx.txid(), txid);
+  assert_eq!(reveal_tx.output[0].value, 250_000);
+}
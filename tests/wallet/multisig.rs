@@ -0,0 +1,206 @@
+use {
+  super::*,
+  bitcoin::{secp256k1::Secp256k1, Address, PrivateKey, PublicKey},
+  ord::subcommand::wallet::multisig::{broadcast, combine, register, send, sign},
+};
+
+fn private_key(n: u8) -> PrivateKey {
+  PrivateKey::from_slice(&[n; 32], Network::Bitcoin).unwrap()
+}
+
+fn public_keys() -> Vec<PublicKey> {
+  (1..=3)
+    .map(|n| private_key(n).public_key(&Secp256k1::new()))
+    .collect()
+}
+
+fn multisig() -> String {
+  format!(
+    r#"{{"threshold":2,"public_keys":[{}]}}"#,
+    public_keys()
+      .iter()
+      .map(|public_key| format!("\"{public_key}\""))
+      .collect::<Vec<String>>()
+      .join(",")
+  )
+}
+
+#[test]
+fn register_multisig() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  let keys = public_keys()
+    .iter()
+    .map(|public_key| format!("--public-key {public_key}"))
+    .collect::<Vec<String>>()
+    .join(" ");
+
+  let output = CommandBuilder::new(format!("wallet multisig register dao --threshold 2 {keys}"))
+    .rpc_server(&rpc_server)
+    .output::<register::Output>();
+
+  assert_eq!(output.threshold, 2);
+  assert_eq!(output.public_keys, public_keys());
+
+  let imported = CommandBuilder::new(format!(
+    "wallet multisig register dao --redeem-script {}",
+    output.redeem_script
+  ))
+  .rpc_server(&rpc_server)
+  .output::<register::Output>();
+
+  assert_eq!(imported, output);
+}
+
+#[test]
+fn register_requires_force_to_replace_multisig() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  let command = format!(
+    "wallet multisig register dao --threshold 1 --public-key {} --public-key {}",
+    public_keys()[0],
+    public_keys()[1]
+  );
+
+  CommandBuilder::new(command.clone())
+    .rpc_server(&rpc_server)
+    .write("multisig/dao.json", multisig())
+    .expected_stderr("error: multisig `dao` is already registered, use `--force` to replace it\n")
+    .expected_exit_code(1)
+    .run();
+
+  let output = CommandBuilder::new(format!("{command} --force"))
+    .rpc_server(&rpc_server)
+    .write("multisig/dao.json", multisig())
+    .output::<register::Output>();
+
+  assert_eq!(output.threshold, 1);
+}
+
+#[test]
+fn register_requires_valid_threshold() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  CommandBuilder::new(format!(
+    "wallet multisig register dao --threshold 3 --public-key {} --public-key {}",
+    public_keys()[0],
+    public_keys()[1]
+  ))
+  .rpc_server(&rpc_server)
+  .expected_stderr("error: multisig threshold must be between 1 and the number of public keys\n")
+  .expected_exit_code(1)
+  .run();
+}
+
+#[test]
+fn inscription_can_be_sent_from_multisig_once_signed() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let address = CommandBuilder::new(format!(
+    "wallet multisig register dao --threshold 2 --public-key {} --public-key {} --public-key {}",
+    public_keys()[0],
+    public_keys()[1],
+    public_keys()[2],
+  ))
+  .rpc_server(&rpc_server)
+  .output::<register::Output>()
+  .address;
+
+  let Inscribe { inscription, .. } = inscribe(&rpc_server);
+
+  CommandBuilder::new(format!("wallet send --fee-rate 1 {address} {inscription}"))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run();
+
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new(format!(
+    "wallet multisig send dao --fee-rate 1 bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 {inscription}"
+  ))
+  .rpc_server(&rpc_server)
+  .write("multisig/dao.json", multisig())
+  .output::<send::Output>();
+
+  assert!(output.fee > 0);
+
+  let combined = CommandBuilder::new(format!("wallet multisig combine {}", output.psbt))
+    .rpc_server(&rpc_server)
+    .output::<combine::Output>();
+
+  assert_eq!(combined.missing_signatures, [2]);
+  assert!(!combined.complete);
+
+  CommandBuilder::new(format!("wallet multisig broadcast {}", output.psbt))
+    .rpc_server(&rpc_server)
+    .expected_stderr("error: PSBT input 0 needs 2 more signatures\n")
+    .expected_exit_code(1)
+    .run();
+
+  assert!(rpc_server.mempool().is_empty());
+
+  let signed = [1, 3]
+    .into_iter()
+    .map(|n| {
+      let cosigner = test_bitcoincore_rpc::spawn();
+      create_wallet(&cosigner);
+      cosigner.import_private_key(private_key(n));
+
+      let signed = CommandBuilder::new(format!("wallet multisig sign {}", output.psbt))
+        .rpc_server(&cosigner)
+        .output::<sign::Output>();
+
+      assert_eq!(signed.signatures, 1);
+      assert!(!signed.complete);
+
+      signed.psbt
+    })
+    .collect::<Vec<String>>();
+
+  let combined = CommandBuilder::new(format!("wallet multisig combine {}", signed.join(" ")))
+    .rpc_server(&rpc_server)
+    .output::<combine::Output>();
+
+  assert_eq!(combined.missing_signatures, [0]);
+  assert!(combined.complete);
+
+  let broadcast = CommandBuilder::new(format!("wallet multisig broadcast {}", signed.join(" ")))
+    .rpc_server(&rpc_server)
+    .output::<broadcast::Output>();
+
+  let mempool = rpc_server.mempool();
+
+  assert_eq!(mempool.len(), 1);
+
+  let transaction = &mempool[0];
+
+  assert_eq!(transaction.txid(), broadcast.transaction);
+  assert_eq!(transaction.input.len(), 1);
+  assert_eq!(
+    transaction.input[0].script_sig.instructions().count(),
+    4,
+    "script sig should hold OP_0, two signatures, and the redeem script"
+  );
+  assert_eq!(
+    transaction.output[0].script_pubkey,
+    "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+      .parse::<Address>()
+      .unwrap()
+      .script_pubkey()
+  );
+}
+
+#[test]
+fn unregistered_multisig() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  CommandBuilder::new(
+    "wallet multisig send dao --fee-rate 1 bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 1000sat",
+  )
+  .rpc_server(&rpc_server)
+  .expected_stderr("error: multisig `dao` is not registered\n")
+  .expected_exit_code(1)
+  .run();
+}
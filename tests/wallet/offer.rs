@@ -0,0 +1,248 @@
+use {
+  super::*,
+  ord::subcommand::wallet::{
+    inscriptions,
+    offer::{accept, create},
+  },
+};
+
+#[test]
+fn create_offer_for_inscription() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = inscribe(&rpc_server);
+
+  let output = CommandBuilder::new(format!(
+    "wallet offer create --inscription {inscription} --price 1btc"
+  ))
+  .rpc_server(&rpc_server)
+  .output::<create::Output>();
+
+  assert_eq!(output.outgoing, OutPoint::new(reveal, 0));
+  assert_eq!(output.price, 100_000_000);
+  assert_eq!(output.inscriptions, [inscription.parse().unwrap()]);
+
+  assert!(rpc_server.mempool().is_empty());
+}
+
+#[test]
+fn create_offer_refuses_price_below_dust() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe { inscription, .. } = inscribe(&rpc_server);
+
+  CommandBuilder::new(format!(
+    "wallet offer create --inscription {inscription} --price 1000sat"
+  ))
+  .rpc_server(&rpc_server)
+  .expected_stderr("error: price of 1000 sats is below the dust limit of 100000 sats\n")
+  .expected_exit_code(1)
+  .run();
+}
+
+#[test]
+fn create_offer_refuses_frozen_output() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = inscribe(&rpc_server);
+
+  CommandBuilder::new(format!(
+    "wallet offer create --inscription {inscription} --price 1btc"
+  ))
+  .write("wallets/ord/frozen.json", format!("[\"{reveal}:0\"]"))
+  .rpc_server(&rpc_server)
+  .expected_stderr(format!("error: output {reveal}:0 is frozen\n"))
+  .expected_exit_code(1)
+  .run();
+}
+
+#[test]
+fn create_offer_requires_cunes_in_outpoint() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  let coinbase = rpc_server.mine_blocks(1)[0].txdata[0].txid();
+
+  CommandBuilder::new(format!(
+    "wallet offer create --outpoint {coinbase}:0 --price 1btc"
+  ))
+  .rpc_server(&rpc_server)
+  .expected_stderr(format!("error: output {coinbase}:0 contains no cunes\n"))
+  .expected_exit_code(1)
+  .run();
+}
+
+/// Inscribes and sends the inscription to a legacy address of the wallet,
+/// since offers can only be made for outputs paying to a public key hash.
+fn inscribe_to_legacy_address(rpc_server: &test_bitcoincore_rpc::Handle) -> (Txid, String) {
+  let Inscribe { inscription, .. } = inscribe(rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  let address = rpc_server.legacy_address();
+
+  let txid = CommandBuilder::new(format!("wallet send --fee-rate 1 {address} {inscription}"))
+    .rpc_server(rpc_server)
+    .stdout_regex(r".*")
+    .run()
+    .trim()
+    .parse()
+    .unwrap();
+
+  rpc_server.mine_blocks(1);
+
+  (txid, inscription)
+}
+
+#[test]
+fn accept_offer() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let (outgoing, inscription) = inscribe_to_legacy_address(&rpc_server);
+
+  let create = CommandBuilder::new(format!(
+    "wallet offer create --inscription {inscription} --price 1btc"
+  ))
+  .rpc_server(&rpc_server)
+  .output::<create::Output>();
+
+  let accept = CommandBuilder::new(format!(
+    "wallet offer accept --offer {} --inscription {inscription} --max-price 1btc --fee-rate 1",
+    create.offer
+  ))
+  .rpc_server(&rpc_server)
+  .output::<accept::Output>();
+
+  assert_eq!(accept.outgoing, OutPoint::new(outgoing, 0));
+  assert_eq!(accept.received, OutPoint::new(accept.transaction, 0));
+  assert_eq!(accept.price, 100_000_000);
+
+  let mempool = rpc_server.mempool();
+  assert_eq!(mempool.len(), 1);
+
+  let transaction = &mempool[0];
+  assert_eq!(transaction.txid(), accept.transaction);
+  assert_eq!(
+    transaction.input[1].previous_output,
+    OutPoint::new(outgoing, 0)
+  );
+  assert_eq!(transaction.output.len(), 3);
+  assert_eq!(transaction.output[1].value, 100_000_000);
+
+  // The seller signature is kept as is
+  let offer = bitcoin::consensus::deserialize::<bitcoin::Transaction>(
+    &hex::decode(create.offer.to_string()).unwrap(),
+  )
+  .unwrap();
+  assert_eq!(transaction.input[1].script_sig, offer.input[1].script_sig);
+
+  rpc_server.mine_blocks(1);
+
+  let inscriptions = CommandBuilder::new("wallet inscriptions")
+    .rpc_server(&rpc_server)
+    .output::<Vec<inscriptions::Output>>();
+
+  assert_eq!(inscriptions.len(), 1);
+  assert!(inscriptions[0]
+    .location
+    .to_string()
+    .starts_with(&format!("{}:", accept.received)));
+}
+
+#[test]
+fn accept_offer_refuses_price_above_maximum() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let (_, inscription) = inscribe_to_legacy_address(&rpc_server);
+
+  let create = CommandBuilder::new(format!(
+    "wallet offer create --inscription {inscription} --price 1btc"
+  ))
+  .rpc_server(&rpc_server)
+  .output::<create::Output>();
+
+  CommandBuilder::new(format!(
+    "wallet offer accept --offer {} --inscription {inscription} --max-price 0.5btc --fee-rate 1",
+    create.offer
+  ))
+  .rpc_server(&rpc_server)
+  .expected_stderr("error: offer price of 100000000 sats exceeds maximum price of 50000000 sats\n")
+  .expected_exit_code(1)
+  .run();
+
+  assert!(rpc_server.mempool().is_empty());
+}
+
+#[test]
+fn accept_offer_verifies_seller_signature() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe { inscription, .. } = inscribe(&rpc_server);
+
+  let create = CommandBuilder::new(format!(
+    "wallet offer create --inscription {inscription} --price 1btc"
+  ))
+  .rpc_server(&rpc_server)
+  .output::<create::Output>();
+
+  CommandBuilder::new(format!(
+    "wallet offer accept --offer {} --inscription {inscription} --max-price 1btc --fee-rate 1",
+    create.offer
+  ))
+  .rpc_server(&rpc_server)
+  .stderr_regex("error: offer signature is malformed\nbecause: .*")
+  .expected_exit_code(1)
+  .run();
+
+  assert!(rpc_server.mempool().is_empty());
+}
+
+#[test]
+fn accept_offer_checks_inscription_location() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = inscribe(&rpc_server);
+
+  let Inscribe {
+    reveal: other_reveal,
+    inscription: other,
+    ..
+  } = inscribe(&rpc_server);
+
+  let create = CommandBuilder::new(format!(
+    "wallet offer create --inscription {inscription} --price 1btc"
+  ))
+  .rpc_server(&rpc_server)
+  .output::<create::Output>();
+
+  CommandBuilder::new(format!(
+    "wallet offer accept --offer {} --inscription {other} --max-price 1btc --fee-rate 1",
+    create.offer
+  ))
+  .rpc_server(&rpc_server)
+  .expected_stderr(format!(
+    "error: offer spends {reveal}:0, but inscription {other} is at {other_reveal}:0:0\n"
+  ))
+  .expected_exit_code(1)
+  .run();
+
+  assert!(rpc_server.mempool().is_empty());
+}
@@ -0,0 +1,56 @@
+use {
+  super::*,
+  ord::subcommand::wallet::{etch, send_cunes::Output},
+};
+
+#[test]
+fn send_cunes_does_not_pay_fees_with_inscribed_or_dunic_outputs() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .network(Network::Regtest)
+    .build();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let etch = CommandBuilder::new(
+    "--chain regtest --index-cunes wallet etch --cune AAAAAAAAAAAAA --divisibility 0 --fee-rate 1 --supply 1000 --symbol ¢",
+  )
+  .rpc_server(&rpc_server)
+  .output::<etch::Output>();
+
+  rpc_server.mine_blocks(1);
+
+  let Inscribe { reveal, .. } =
+    CommandBuilder::new("--chain regtest --index-cunes wallet inscribe --fee-rate 1 foo.txt")
+      .write("foo.txt", "FOO")
+      .rpc_server(&rpc_server)
+      .output::<Inscribe>();
+
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new(
+    "--chain regtest --index-cunes wallet send-cunes --fee-rate 1 --csv recipients.csv",
+  )
+  .write(
+    "recipients.csv",
+    "bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw,400,AAAAAAAAAAAAA\n",
+  )
+  .rpc_server(&rpc_server)
+  .output::<Output>();
+
+  assert_eq!(output.batches.len(), 1);
+
+  let mempool = rpc_server.mempool();
+  assert_eq!(mempool.len(), 1);
+  assert_eq!(mempool[0].txid(), output.batches[0].transaction);
+
+  let inputs = mempool[0]
+    .input
+    .iter()
+    .map(|txin| txin.previous_output)
+    .collect::<Vec<OutPoint>>();
+
+  assert!(inputs.contains(&OutPoint::new(etch.transaction, 1)));
+  assert!(!inputs.contains(&OutPoint::new(reveal, 0)));
+
+  assert!(rpc_server.locked().is_empty());
+}
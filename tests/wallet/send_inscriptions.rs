@@ -0,0 +1,156 @@
+use {
+  super::*,
+  ord::subcommand::wallet::{inscriptions, send_inscriptions::Output},
+};
+
+const FIRST: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+const SECOND: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+
+#[test]
+fn inscriptions_are_sent_in_one_transaction() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe {
+    reveal: first,
+    inscription: first_id,
+    ..
+  } = inscribe(&rpc_server);
+
+  let Inscribe {
+    reveal: second,
+    inscription: second_id,
+    ..
+  } = inscribe(&rpc_server);
+
+  let output = CommandBuilder::new("wallet send-inscriptions --fee-rate 1 --csv recipients.csv")
+    .write(
+      "recipients.csv",
+      format!("id,address\n{first_id},{FIRST}\n{second_id},{SECOND}\n"),
+    )
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert_eq!(output.batches.len(), 1);
+
+  let batch = &output.batches[0];
+
+  let mempool = rpc_server.mempool();
+  assert_eq!(mempool.len(), 1);
+
+  let transaction = &mempool[0];
+  assert_eq!(transaction.txid(), batch.transaction);
+  assert_eq!(
+    transaction.input[0].previous_output,
+    OutPoint::new(first, 0)
+  );
+  assert_eq!(
+    transaction.input[1].previous_output,
+    OutPoint::new(second, 0)
+  );
+
+  assert_eq!(batch.recipients.len(), 2);
+  assert_eq!(batch.recipients[0].inscription, first_id.parse().unwrap());
+  assert_eq!(batch.recipients[1].inscription, second_id.parse().unwrap());
+
+  for (vout, address) in [FIRST, SECOND].into_iter().enumerate() {
+    let address = address.parse::<bitcoin::Address>().unwrap();
+    assert_eq!(
+      batch.recipients[vout].output,
+      format!("{}:{vout}:0", batch.transaction).parse().unwrap()
+    );
+    assert_eq!(
+      transaction.output[vout].script_pubkey,
+      address.script_pubkey()
+    );
+    assert_eq!(transaction.output[vout].value, 100_000);
+  }
+
+  rpc_server.mine_blocks(1);
+
+  let mut locations = CommandBuilder::new("wallet inscriptions")
+    .rpc_server(&rpc_server)
+    .output::<Vec<inscriptions::Output>>()
+    .into_iter()
+    .map(|output| (output.inscription, output.location))
+    .collect::<Vec<_>>();
+
+  locations.sort();
+
+  let mut expected = batch
+    .recipients
+    .iter()
+    .map(|recipient| (recipient.inscription, recipient.output))
+    .collect::<Vec<_>>();
+
+  expected.sort();
+
+  assert_eq!(locations, expected);
+}
+
+#[test]
+fn dry_run_prints_table_without_sending() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = inscribe(&rpc_server);
+
+  let output =
+    CommandBuilder::new("wallet send-inscriptions --fee-rate 1 --csv recipients.csv --dry-run")
+      .write("recipients.csv", format!("{inscription},{FIRST}\n"))
+      .rpc_server(&rpc_server)
+      .stderr_regex(format!(
+        "transaction [[:xdigit:]]{{64}} fee [0-9]+ sats\n  {inscription}  {reveal}:0:0 -> 0  100000 sats  {FIRST}\n"
+      ))
+      .output::<Output>();
+
+  assert_eq!(output.batches.len(), 1);
+  assert_eq!(
+    output.batches[0].recipients[0].inscription.to_string(),
+    inscription
+  );
+
+  assert!(rpc_server.mempool().is_empty());
+}
+
+#[test]
+fn unknown_inscription_is_an_error() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let inscription = "0000000000000000000000000000000000000000000000000000000000000000i0";
+
+  CommandBuilder::new("wallet send-inscriptions --fee-rate 1 --csv recipients.csv")
+    .write("recipients.csv", format!("{inscription},{FIRST}\n"))
+    .rpc_server(&rpc_server)
+    .expected_stderr(format!("error: inscription {inscription} not found\n"))
+    .expected_exit_code(1)
+    .run();
+}
+
+#[test]
+fn inscription_may_only_be_listed_once() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe { inscription, .. } = inscribe(&rpc_server);
+
+  CommandBuilder::new("wallet send-inscriptions --fee-rate 1 --csv recipients.csv")
+    .write(
+      "recipients.csv",
+      format!("{inscription},{FIRST}\n{inscription},{SECOND}\n"),
+    )
+    .rpc_server(&rpc_server)
+    .expected_stderr(format!(
+      "error: line 2: inscription {inscription} is listed more than once\n"
+    ))
+    .expected_exit_code(1)
+    .run();
+
+  assert!(rpc_server.mempool().is_empty());
+}
@@ -0,0 +1,53 @@
+use {super::*, ord::subcommand::wallet::sign_message::Output};
+
+#[test]
+fn sign_message_with_address() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let address = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+  assert_eq!(
+    CommandBuilder::new(format!("wallet sign-message --address {address} foo"))
+      .rpc_server(&rpc_server)
+      .output::<Output>(),
+    Output {
+      address: address.parse().unwrap(),
+      message: "foo".into(),
+      signature: format!("{address}:foo"),
+    }
+  );
+}
+
+#[test]
+fn sign_message_with_inscription_holder() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe { inscription, .. } = inscribe(&rpc_server);
+
+  let output = CommandBuilder::new(format!(
+    "wallet sign-message --inscription {inscription} foo"
+  ))
+  .rpc_server(&rpc_server)
+  .output::<Output>();
+
+  assert_eq!(output.signature, format!("{}:foo", output.address));
+}
+
+#[test]
+fn sign_message_with_unknown_inscription() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let inscription = "0000000000000000000000000000000000000000000000000000000000000000i0";
+
+  CommandBuilder::new(format!(
+    "wallet sign-message --inscription {inscription} foo"
+  ))
+  .rpc_server(&rpc_server)
+  .expected_stderr(format!("error: inscription {inscription} not found\n"))
+  .expected_exit_code(1)
+  .run();
+}
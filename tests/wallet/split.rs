@@ -0,0 +1,117 @@
+use {super::*, ord::subcommand::wallet::split::Output};
+
+#[test]
+fn split_cardinal_utxo() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new("wallet split --fee-rate 1 --outputs 5 --value 200000sat")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert_eq!(output.inputs, 1);
+  assert_eq!(output.outputs.len(), 5);
+  assert_eq!(
+    output.change.unwrap() + output.fee + 5 * 200_000,
+    50 * COIN_VALUE
+  );
+
+  let mempool = rpc_server.mempool();
+  assert_eq!(mempool.len(), 1);
+  assert_eq!(mempool[0].output.len(), 6);
+  assert!(mempool[0].output[..5]
+    .iter()
+    .all(|tx_out| tx_out.value == 200_000));
+}
+
+#[test]
+fn split_fails_without_enough_cardinal_value() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new("wallet split --fee-rate 1 --outputs 2 --value 30btc")
+    .rpc_server(&rpc_server)
+    .expected_stderr(
+      "error: wallet does not contain enough cardinal UTXOs, please add additional funds\n",
+    )
+    .expected_exit_code(1)
+    .run();
+}
+
+#[test]
+fn split_with_estimated_fee_rate() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .fee_estimate(bitcoin::Amount::from_sat(3_000))
+    .build();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new("wallet split --fee-rate auto:2 --outputs 2 --value 1btc")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert_eq!(output.fee_rate, 3.0);
+}
+
+#[test]
+fn split_with_estimated_fee_rate_falls_back_to_estimatefee() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .estimate_smart_fee(false)
+    .fee_estimate(bitcoin::Amount::from_sat(3_000))
+    .build();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new("wallet split --fee-rate auto:2 --outputs 2 --value 1btc")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert_eq!(output.fee_rate, 3.0);
+}
+
+#[test]
+fn split_with_estimated_fee_rate_uses_minimum_relay_fee_without_estimate() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .relay_fee(bitcoin::Amount::from_sat(100_000))
+    .build();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new("wallet split --fee-rate auto --outputs 2 --value 1btc")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert_eq!(output.fee_rate, 100.0);
+}
+
+#[test]
+fn split_with_fee_rate_in_coins_per_kb() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new("wallet split --fee-rate 0.01CRAFT/kB --outputs 2 --value 1btc")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert_eq!(output.fee_rate, 1000.0);
+}
+
+#[test]
+fn split_refuses_fee_rate_below_minimum_relay_fee() {
+  let rpc_server = test_bitcoincore_rpc::builder()
+    .relay_fee(bitcoin::Amount::from_sat(100_000))
+    .build();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  CommandBuilder::new("wallet split --fee-rate 1 --outputs 2 --value 1btc")
+    .rpc_server(&rpc_server)
+    .expected_stderr(
+      "error: fee rate of 1 sats/vB is below the node's minimum relay fee rate of 100 sats/vB\n",
+    )
+    .expected_exit_code(1)
+    .run();
+}
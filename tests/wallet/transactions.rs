@@ -59,3 +59,136 @@ fn transactions_with_limit() {
   assert_regex_match!(output[0].transaction.to_string(), "[[:xdigit:]]{64}");
   assert_eq!(output[0].confirmations, 1);
 }
+
+#[test]
+fn transactions_show_inscriptions_created_and_sent() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let Inscribe {
+    reveal,
+    inscription,
+    ..
+  } = inscribe(&rpc_server);
+
+  let output = CommandBuilder::new("--index-history wallet transactions")
+    .rpc_server(&rpc_server)
+    .output::<Vec<Output>>();
+
+  let created = output.iter().find(|tx| tx.transaction == reveal).unwrap();
+
+  assert_eq!(
+    created
+      .inscriptions_created
+      .iter()
+      .map(ToString::to_string)
+      .collect::<Vec<String>>(),
+    [inscription.clone()]
+  );
+
+  let send = CommandBuilder::new(format!(
+    "wallet send --fee-rate 1 bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 {inscription}",
+  ))
+  .rpc_server(&rpc_server)
+  .stdout_regex(r".*")
+  .run();
+
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new("--index-history wallet transactions")
+    .rpc_server(&rpc_server)
+    .output::<Vec<Output>>();
+
+  let sent = output
+    .iter()
+    .find(|tx| tx.transaction.to_string() == send.trim())
+    .unwrap();
+
+  assert_eq!(
+    sent
+      .inscriptions_sent
+      .iter()
+      .map(ToString::to_string)
+      .collect::<Vec<String>>(),
+    [inscription]
+  );
+  assert!(sent.fee.is_some());
+}
+
+#[test]
+fn transactions_since() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(3);
+
+  let output = CommandBuilder::new("--index-history wallet transactions --since 2")
+    .rpc_server(&rpc_server)
+    .output::<Vec<Output>>();
+
+  assert_eq!(output.len(), 2);
+  assert!(output.iter().all(|tx| tx.height >= Some(2)));
+}
+
+#[test]
+fn transactions_csv() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let tempdir = TempDir::new().unwrap();
+  let csv = tempdir.path().join("history.csv");
+
+  let output = CommandBuilder::new(format!(
+    "--index-history wallet transactions --csv {}",
+    csv.display()
+  ))
+  .rpc_server(&rpc_server)
+  .output::<Vec<Output>>();
+
+  let contents = fs::read_to_string(csv).unwrap();
+  let mut lines = contents.lines();
+
+  assert_eq!(
+    lines.next().unwrap(),
+    "transaction,height,confirmations,cardinal,fee,inscriptions_created,inscriptions_received,inscriptions_sent,cunes,crc20"
+  );
+  assert!(lines
+    .next()
+    .unwrap()
+    .starts_with(&format!("{},", output[0].transaction)));
+}
+
+#[test]
+fn transactions_without_history_index_are_not_annotated() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new("wallet transactions")
+    .rpc_server(&rpc_server)
+    .output::<Vec<Output>>();
+
+  assert_eq!(output.len(), 1);
+  assert_eq!(output[0].confirmations, 1);
+  assert_eq!(output[0].height, Some(1));
+  assert_eq!(output[0].cardinal, None);
+  assert_eq!(output[0].fee, None);
+  assert!(output[0].inscriptions_created.is_empty());
+  assert!(output[0].cunes.is_empty());
+  assert!(output[0].crc20.is_empty());
+}
+
+#[test]
+fn transactions_limit_is_applied_after_since() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(4);
+
+  let output = CommandBuilder::new("wallet transactions --since 3 --limit 2")
+    .rpc_server(&rpc_server)
+    .output::<Vec<Output>>();
+
+  assert_eq!(output.len(), 2);
+  assert!(output.iter().all(|tx| tx.height >= Some(3)));
+}
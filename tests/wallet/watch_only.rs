@@ -0,0 +1,99 @@
+use {
+  super::*,
+  bitcoin::{hashes::Hash, util::address::Payload, Address, PubkeyHash},
+  ord::subcommand::wallet::{inscriptions, watch_only::Output},
+};
+
+fn cold_storage() -> Address {
+  Address {
+    payload: Payload::PubkeyHash(PubkeyHash::from_inner([0xff; 20])),
+    network: Network::Bitcoin,
+  }
+}
+
+fn group() -> String {
+  format!(r#"{{"addresses":["{}"]}}"#, cold_storage())
+}
+
+#[test]
+fn addresses_can_be_added_and_removed() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  let output = CommandBuilder::new(format!(
+    "wallet watch-only cold --address {}",
+    cold_storage()
+  ))
+  .rpc_server(&rpc_server)
+  .output::<Output>();
+
+  assert_eq!(output.addresses, [cold_storage()]);
+
+  let output = CommandBuilder::new(format!(
+    "wallet watch-only cold --address {} --remove {}",
+    cold_storage(),
+    cold_storage()
+  ))
+  .rpc_server(&rpc_server)
+  .output::<Output>();
+
+  assert!(output.addresses.is_empty());
+
+  let output = CommandBuilder::new("wallet watch-only cold")
+    .rpc_server(&rpc_server)
+    .write("watch-only/cold.json", group())
+    .output::<Output>();
+
+  assert_eq!(output.addresses, [cold_storage()]);
+}
+
+#[test]
+fn inscriptions_of_watch_only_group() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+  rpc_server.mine_blocks(1);
+
+  let Inscribe { inscription, .. } = inscribe(&rpc_server);
+
+  CommandBuilder::new(format!(
+    "wallet send --fee-rate 1 {} {inscription}",
+    cold_storage()
+  ))
+  .rpc_server(&rpc_server)
+  .stdout_regex(".*")
+  .run();
+
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new("--watch-only cold wallet inscriptions")
+    .rpc_server(&rpc_server)
+    .write("watch-only/cold.json", group())
+    .output::<Vec<inscriptions::Output>>();
+
+  assert_eq!(output.len(), 1);
+  assert_eq!(output[0].inscription.to_string(), inscription);
+}
+
+#[test]
+fn watch_only_group_cannot_be_spent_from() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  CommandBuilder::new(
+    "--watch-only cold wallet send --fee-rate 1 bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4 1000sat",
+  )
+  .rpc_server(&rpc_server)
+  .expected_stderr("error: watch-only group `cold` can be queried but not spent from\n")
+  .expected_exit_code(1)
+  .run();
+}
+
+#[test]
+fn unknown_watch_only_group() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  CommandBuilder::new("--watch-only cold wallet outputs")
+    .rpc_server(&rpc_server)
+    .expected_stderr("error: watch-only group `cold` does not exist\n")
+    .expected_exit_code(1)
+    .run();
+}